@@ -0,0 +1,224 @@
+use super::{
+    decoder, merkle_root,
+    opcodes::{FlowOps, UserOps},
+    stack, u32_table, BaseElement, FieldElement, ProcessorAir, ProofOptions, PublicInputs,
+    StarkField, CF_OP_BITS_RANGE, HD_OP_BITS_RANGE, LD_OP_BITS_RANGE,
+};
+use core::{convert::TryFrom, fmt, ops::Range};
+use winter_air::{Air, Assertion, EvaluationFrame};
+use winter_prover::ExecutionTrace;
+
+// CONSTRAINT CHECKER
+// ================================================================================================
+
+/// Checks the specified execution trace against all constraints of the processor AIR, and
+/// returns an error describing the first constraint which is not satisfied.
+///
+/// Transition constraints are evaluated for every pair of consecutive rows of the trace, and
+/// boundary assertions are checked against the rows to which they apply; assertions against the
+/// first row are checked before any transition, and all other assertions are checked after all
+/// transitions. This is much faster than generating a proof, and unlike the prover, pinpoints
+/// the failing constraint by name together with the step and the operation at which it failed.
+pub fn check_constraints(
+    trace: &ExecutionTrace<BaseElement>,
+    pub_inputs: PublicInputs,
+) -> Result<(), ConstraintError> {
+    let air = ProcessorAir::new(
+        trace.get_info(),
+        pub_inputs,
+        ProofOptions::default().into_inner(),
+    );
+    let trace_length = trace.length();
+
+    // check assertions against the first row
+    let assertions = air.get_assertions();
+    let (first_assertions, last_assertions): (Vec<_>, Vec<_>) = assertions
+        .iter()
+        .partition(|assertion| assertion.first_step() == 0);
+    for assertion in first_assertions {
+        check_assertion(trace, assertion, trace_length)?;
+    }
+
+    // evaluate transition constraints for every step of the trace
+    let names = get_constraint_names(&air);
+    debug_assert_eq!(
+        air.decoder_constraint_count
+            + air.stack_constraint_count
+            + air.storage_constraint_count
+            + air.u32_constraint_count
+            + air.memory_constraint_count,
+        names.len(),
+        "every transition constraint must have a name"
+    );
+    let periodic_columns = air.get_periodic_column_values();
+    let mut evaluations = vec![BaseElement::ZERO; names.len()];
+    for step in 0..trace_length - 1 {
+        let frame = EvaluationFrame::from_rows(read_row(trace, step), read_row(trace, step + 1));
+        let periodic_values: Vec<BaseElement> = periodic_columns
+            .iter()
+            .map(|column| column[step % column.len()])
+            .collect();
+
+        air.evaluate_transition(&frame, &periodic_values, &mut evaluations);
+        if let Some(i) = evaluations
+            .iter()
+            .position(|&value| value != BaseElement::ZERO)
+        {
+            return Err(ConstraintError::TransitionConstraint {
+                constraint: names[i].clone(),
+                step,
+                operation: get_operation(frame.current()),
+                value: evaluations[i].as_int(),
+            });
+        }
+    }
+
+    // check all other assertions
+    for assertion in last_assertions {
+        check_assertion(trace, assertion, trace_length)?;
+    }
+
+    Ok(())
+}
+
+// CONSTRAINT ERROR
+// ================================================================================================
+
+/// A constraint of the processor AIR which is not satisfied by an execution trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstraintError {
+    /// A transition constraint evaluated to a non-zero `value` on the transition from the
+    /// specified `step` to the next one; `operation` is the operation executed at this step.
+    TransitionConstraint {
+        constraint: String,
+        step: usize,
+        operation: String,
+        value: u128,
+    },
+    /// The value of the specified register at the specified step differs from the asserted one.
+    Assertion {
+        register: usize,
+        step: usize,
+        expected: u128,
+        actual: u128,
+    },
+}
+
+impl fmt::Display for ConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstraintError::TransitionConstraint {
+                constraint,
+                step,
+                operation,
+                value,
+            } => write!(
+                f,
+                "transition constraint '{}' failed at step {} ({}): evaluated to {}",
+                constraint, step, operation, value
+            ),
+            ConstraintError::Assertion {
+                register,
+                step,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "assertion against register {} failed at step {}: expected {}, but was {}",
+                register, step, expected, actual
+            ),
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns names of all transition constraints of the `air` in the order in which they are
+/// evaluated.
+fn get_constraint_names(air: &ProcessorAir) -> Vec<String> {
+    let mut result = decoder::get_constraint_names(air.ctx_depth, air.loop_depth);
+    result.append(&mut stack::get_constraint_names(air.stack_depth));
+    result.append(&mut merkle_root::get_constraint_names(
+        "storage",
+        UserOps::SRoot,
+        air.has_storage,
+    ));
+    result.append(&mut u32_table::get_constraint_names(air.has_u32_table));
+    result.append(&mut merkle_root::get_constraint_names(
+        "memory",
+        UserOps::MRoot,
+        air.has_memory,
+    ));
+    result
+}
+
+/// Makes sure all values asserted by the `assertion` match the values in the trace.
+fn check_assertion(
+    trace: &ExecutionTrace<BaseElement>,
+    assertion: &Assertion<BaseElement>,
+    trace_length: usize,
+) -> Result<(), ConstraintError> {
+    let register = assertion.register();
+    let mut result = Ok(());
+    assertion.apply(trace_length, |step, expected| {
+        let actual = trace.get(register, step);
+        if result.is_ok() && actual != expected {
+            result = Err(ConstraintError::Assertion {
+                register,
+                step,
+                expected: expected.as_int(),
+                actual: actual.as_int(),
+            });
+        }
+    });
+    result
+}
+
+/// Reads the row of the trace at the specified step.
+fn read_row(trace: &ExecutionTrace<BaseElement>, step: usize) -> Vec<BaseElement> {
+    let mut row = vec![BaseElement::ZERO; trace.width()];
+    trace.read_row_into(step, &mut row);
+    row
+}
+
+/// Returns the name of the operation encoded by op bits of the specified row: this is the flow
+/// control operation if control flow op bits are not all zeros, and the user operation otherwise.
+fn get_operation(row: &[BaseElement]) -> String {
+    let cf_op = read_op_bits(row, CF_OP_BITS_RANGE);
+    let ld_op = read_op_bits(row, LD_OP_BITS_RANGE);
+    let hd_op = read_op_bits(row, HD_OP_BITS_RANGE);
+
+    match (cf_op, ld_op, hd_op) {
+        (Some(0), Some(ld_op), Some(hd_op)) => match UserOps::try_from((hd_op << 5) | ld_op) {
+            Ok(op) => op.to_string(),
+            Err(_) => format!("unknown operation {:#09b}", (hd_op << 5) | ld_op),
+        },
+        (Some(cf_op), _, _) => FLOW_OPS[cf_op as usize].to_string(),
+        _ => "invalid op bits".to_string(),
+    }
+}
+
+/// Reads the value encoded by the binary op bits in the specified range of the row; the first
+/// bit is the least significant one. Returns None if any of the bits is not binary.
+fn read_op_bits(row: &[BaseElement], range: Range<usize>) -> Option<u8> {
+    row[range]
+        .iter()
+        .rev()
+        .try_fold(0, |value, bit| match bit.as_int() {
+            bit @ 0..=1 => Some((value << 1) | bit as u8),
+            _ => None,
+        })
+}
+
+/// Flow control operations indexed by their op codes.
+const FLOW_OPS: [FlowOps; 8] = [
+    FlowOps::Hacc,
+    FlowOps::Begin,
+    FlowOps::Tend,
+    FlowOps::Fend,
+    FlowOps::Loop,
+    FlowOps::Wrap,
+    FlowOps::Break,
+    FlowOps::Void,
+];
@@ -32,7 +32,7 @@ where
     );
 
     // make sure loop stack didn't change
-    let loop_result = &mut result[ctx_stack_end..ctx_stack_end + current.loop_stack().len()];
+    let loop_result = &mut result[ctx_stack_end..];
     enforce_stack_copy(
         loop_result,
         current.loop_stack(),
@@ -69,7 +69,7 @@ where
     );
 
     // make sure loop stack didn't change
-    let loop_result = &mut result[ctx_stack_end..ctx_stack_end + current.loop_stack().len()];
+    let loop_result = &mut result[ctx_stack_end..];
     enforce_stack_copy(
         loop_result,
         current.loop_stack(),
@@ -106,7 +106,7 @@ where
     );
 
     // make sure loop stack didn't change
-    let loop_result = &mut result[ctx_stack_end..ctx_stack_end + current.loop_stack().len()];
+    let loop_result = &mut result[ctx_stack_end..];
     enforce_stack_copy(
         loop_result,
         current.loop_stack(),
@@ -143,7 +143,7 @@ where
 
     // make sure loop stack was shifted by 1 item to the right, but don't enforce constraints
     // on the first item of the stack (which will contain loop image)
-    let loop_result = &mut result[ctx_stack_end..ctx_stack_end + current.loop_stack().len()];
+    let loop_result = &mut result[ctx_stack_end..];
     enforce_right_shift(
         loop_result,
         current.loop_stack(),
@@ -185,7 +185,7 @@ where
     );
 
     // make sure loop stack didn't change
-    let loop_result = &mut result[ctx_stack_end..ctx_stack_end + current.loop_stack().len()];
+    let loop_result = &mut result[ctx_stack_end..];
     enforce_stack_copy(
         loop_result,
         current.loop_stack(),
@@ -199,13 +199,22 @@ pub fn enforce_break<E>(result: &mut [E], current: &TraceState<E>, next: &TraceS
 where
     E: FieldElement<BaseField = BaseElement>,
 {
-    // make sure sponge state didn't change
+    // make sure the first 3 elements of the sponge state didn't change
     let old_sponge = current.op_sponge();
     let new_sponge = next.op_sponge();
-    for i in 0..OP_SPONGE_WIDTH {
+    for i in 0..(OP_SPONGE_WIDTH - 1) {
         result.agg_constraint(i, op_flag, are_equal(old_sponge[i], new_sponge[i]));
     }
 
+    // make sure the final value of the loop counter was merged into the last element of the
+    // sponge state; the counter of a Loop block is 1, and thus, the element doesn't change
+    let counter_offset = current.loop_counters()[0] - E::ONE;
+    result.agg_constraint(
+        OP_SPONGE_WIDTH - 1,
+        op_flag,
+        are_equal(old_sponge[3] + counter_offset, new_sponge[3]),
+    );
+
     // make sure item at the top of loop stack is equal to loop image
     let loop_image = old_sponge[0];
     result.agg_constraint(
@@ -226,16 +235,19 @@ where
         op_flag,
     );
 
-    // make loop image was popped from loop stack
-    let loop_result = &mut result[ctx_stack_end..ctx_stack_end + current.loop_stack().len()];
-    enforce_left_shift(
-        loop_result,
-        current.loop_stack(),
-        next.loop_stack(),
-        1,
-        1,
-        op_flag,
-    );
+    // make loop image was popped from loop stack; there are no loop stack constraints if the
+    // execution trace does not include loop stack registers
+    let loop_result = &mut result[ctx_stack_end..];
+    if !loop_result.is_empty() {
+        enforce_left_shift(
+            loop_result,
+            current.loop_stack(),
+            next.loop_stack(),
+            1,
+            1,
+            op_flag,
+        );
+    }
 }
 
 pub fn enforce_void<E>(result: &mut [E], current: &TraceState<E>, next: &TraceState<E>, op_flag: E)
@@ -262,7 +274,7 @@ where
     );
 
     // make sure loop stack didn't change
-    let loop_result = &mut result[ctx_stack_end..ctx_stack_end + current.loop_stack().len()];
+    let loop_result = &mut result[ctx_stack_end..];
     enforce_stack_copy(
         loop_result,
         current.loop_stack(),
@@ -547,6 +559,21 @@ mod tests {
         super::enforce_break(&mut evaluations, &state1, &state2, BaseElement::ONE);
         assert_eq!([0, 0, 0, 0, 0, 0, 0].to_elements(), evaluations);
 
+        // correct transition (counter merged into sponge), context depth = 1, loop depth = 1
+        let state1 = new_counted_state(15, FlowOps::Break, &[3, 5, 7, 9], &[11], &[3], &[4]);
+        let state2 = new_counted_state(16, FlowOps::Void, &[3, 5, 7, 12], &[11], &[0], &[0]);
+
+        let mut evaluations = vec![BaseElement::ZERO; 7];
+        super::enforce_break(&mut evaluations, &state1, &state2, BaseElement::ONE);
+        assert_eq!([0, 0, 0, 0, 0, 0, 0].to_elements(), evaluations);
+
+        // incorrect transition (counter not merged into sponge), context depth = 1, loop depth = 1
+        let state2 = new_counted_state(16, FlowOps::Void, &[3, 5, 7, 9], &[11], &[0], &[0]);
+
+        let mut evaluations = vec![BaseElement::ZERO; 7];
+        super::enforce_break(&mut evaluations, &state1, &state2, BaseElement::ONE);
+        assert_eq!([0, 0, 0, 3, 0, 0, 0].to_elements(), evaluations);
+
         // incorrect transition (loop image mismatch), context depth = 1, loop depth = 1
         let state1 = new_state(15, FlowOps::Wrap, &[3, 5, 7, 9], &[11], &[5]);
         let state2 = new_state(16, FlowOps::Void, &[3, 5, 7, 9], &[11], &[0]);
@@ -651,6 +678,19 @@ mod tests {
         sponge: &[u128; 4],
         ctx_stack: &[u128],
         loop_stack: &[u128],
+    ) -> TraceState<BaseElement> {
+        // counters of Loop blocks are always 1
+        let loop_counters = vec![1; loop_stack.len()];
+        new_counted_state(step, flow_op, sponge, ctx_stack, loop_stack, &loop_counters)
+    }
+
+    fn new_counted_state(
+        step: usize,
+        flow_op: FlowOps,
+        sponge: &[u128; 4],
+        ctx_stack: &[u128],
+        loop_stack: &[u128],
+        loop_counters: &[u128],
     ) -> TraceState<BaseElement> {
         let ctx_depth = ctx_stack.len();
         let loop_depth = loop_stack.len();
@@ -667,6 +707,7 @@ mod tests {
 
         state.extend_from_slice(ctx_stack);
         state.extend_from_slice(loop_stack);
+        state.extend_from_slice(loop_counters);
         state.push(101); // single value for user stack
 
         TraceState::from_slice(ctx_depth, loop_depth, 1, &state.to_elements())
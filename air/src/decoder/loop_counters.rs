@@ -0,0 +1,195 @@
+use super::{
+    are_equal, enforce_left_shift, enforce_right_shift, enforce_stack_copy, is_binary, BaseElement,
+    EvaluationResult, FieldElement, FlowOps, TraceState, LOOP_COUNTER_BASE, NUM_CF_OPS,
+};
+
+// CONSTRAINT EVALUATOR
+// ================================================================================================
+
+/// Enforces constraints on loop counter registers; the `result` slice must contain a constraint
+/// for every loop counter register present in the execution trace.
+///
+/// LOOP pushes a binary value onto the stack of counters (1 for Loop blocks, and 0 for Repeat
+/// blocks); WRAP updates the counter of the innermost loop as ctr' = g * ctr + (1 - g); BREAK
+/// pops the counter from the stack. All other operations leave the counters unchanged.
+pub fn enforce_loop_counters<E: FieldElement<BaseField = BaseElement>>(
+    result: &mut [E],
+    current: &TraceState<E>,
+    next: &TraceState<E>,
+    op_flags: &[E; NUM_CF_OPS],
+) {
+    if result.is_empty() {
+        return;
+    }
+
+    let old_counters = current.loop_counters();
+    let new_counters = next.loop_counters();
+
+    // LOOP: the counters are shifted by 1 item to the right, and the new counter is binary
+    let op_flag = op_flags[FlowOps::Loop as usize];
+    result.agg_constraint(0, op_flag, is_binary(new_counters[0]));
+    enforce_right_shift(result, old_counters, new_counters, 1, op_flag);
+
+    // WRAP: the counter of the innermost loop is updated, and all other counters are copied
+    let op_flag = op_flags[FlowOps::Wrap as usize];
+    let base = E::from(LOOP_COUNTER_BASE);
+    let expected = base * old_counters[0] + (E::ONE - base);
+    result.agg_constraint(0, op_flag, are_equal(expected, new_counters[0]));
+    enforce_stack_copy(result, old_counters, new_counters, 1, op_flag);
+
+    // BREAK: the counter of the innermost loop is popped from the stack
+    let op_flag = op_flags[FlowOps::Break as usize];
+    enforce_left_shift(result, old_counters, new_counters, 1, 1, op_flag);
+
+    // all other operations copy the counters; flags of these operations are mutually exclusive,
+    // and thus, their sum is a flag which is set when any of the operations is executed
+    let op_flag = op_flags[FlowOps::Hacc as usize]
+        + op_flags[FlowOps::Begin as usize]
+        + op_flags[FlowOps::Tend as usize]
+        + op_flags[FlowOps::Fend as usize]
+        + op_flags[FlowOps::Void as usize];
+    enforce_stack_copy(result, old_counters, new_counters, 0, op_flag);
+}
+
+// TESTS
+// ================================================================================================
+
+#[cfg(test)]
+mod tests {
+
+    use super::{are_equal, TraceState};
+    use crate::ToElements;
+    use vm_core::{
+        opcodes::{FlowOps, UserOps},
+        BaseElement, FieldElement, StarkField, LOOP_COUNTER_BASE, NUM_CF_OPS,
+    };
+
+    #[test]
+    fn op_loop() {
+        // a Repeat block starts with the counter set to 0
+        let state1 = new_state(FlowOps::Loop, &[7, 0]);
+        let state2 = new_state(FlowOps::Void, &[0, 7]);
+        assert_eq!(
+            [0, 0].to_elements(),
+            evaluate(FlowOps::Loop, &state1, &state2)
+        );
+
+        // a Loop block starts with the counter set to 1
+        let state2 = new_state(FlowOps::Void, &[1, 7]);
+        assert_eq!(
+            [0, 0].to_elements(),
+            evaluate(FlowOps::Loop, &state1, &state2)
+        );
+
+        // the counter must be binary, and the counters must be shifted to the right
+        let state2 = new_state(FlowOps::Void, &[2, 0]);
+        assert_eq!(
+            [2, 7].to_elements(),
+            evaluate(FlowOps::Loop, &state1, &state2)
+        );
+    }
+
+    #[test]
+    fn op_wrap() {
+        let g = LOOP_COUNTER_BASE;
+
+        // the counter of a Loop block does not change
+        let state1 = new_state(FlowOps::Wrap, &[1, 7]);
+        let state2 = new_state(FlowOps::Void, &[1, 7]);
+        assert_eq!(
+            [0, 0].to_elements(),
+            evaluate(FlowOps::Wrap, &state1, &state2)
+        );
+
+        // the counter of a Repeat block is updated
+        let counter = g * BaseElement::new(5) + BaseElement::ONE - g;
+        let state1 = new_state(FlowOps::Wrap, &[5, 7]);
+        let state2 = new_state(FlowOps::Void, &[counter.as_int(), 7]);
+        assert_eq!(
+            [0, 0].to_elements(),
+            evaluate(FlowOps::Wrap, &state1, &state2)
+        );
+
+        // the counter is not updated, and the outer counter changes
+        let state2 = new_state(FlowOps::Void, &[5, 8]);
+        let expected = vec![
+            are_equal(counter, BaseElement::new(5)),
+            are_equal(BaseElement::new(7), BaseElement::new(8)),
+        ];
+        assert_eq!(expected, evaluate(FlowOps::Wrap, &state1, &state2));
+    }
+
+    #[test]
+    fn op_break() {
+        // the counter is popped from the stack, and the stack is filled with zeros
+        let state1 = new_state(FlowOps::Break, &[5, 7]);
+        let state2 = new_state(FlowOps::Void, &[7, 0]);
+        assert_eq!(
+            [0, 0].to_elements(),
+            evaluate(FlowOps::Break, &state1, &state2)
+        );
+
+        let state2 = new_state(FlowOps::Void, &[5, 7]);
+        assert_eq!(
+            [2, 7].to_elements(),
+            evaluate(FlowOps::Break, &state1, &state2)
+        );
+    }
+
+    #[test]
+    fn other_ops() {
+        // all other operations copy the counters
+        for &op in [
+            FlowOps::Hacc,
+            FlowOps::Begin,
+            FlowOps::Tend,
+            FlowOps::Fend,
+            FlowOps::Void,
+        ]
+        .iter()
+        {
+            let state1 = new_state(op, &[5, 7]);
+            let state2 = new_state(FlowOps::Void, &[5, 7]);
+            assert_eq!([0, 0].to_elements(), evaluate(op, &state1, &state2));
+
+            let state2 = new_state(FlowOps::Void, &[4, 5]);
+            assert_eq!([1, 2].to_elements(), evaluate(op, &state1, &state2));
+        }
+    }
+
+    // HELPER FUNCTIONS
+    // --------------------------------------------------------------------------------------------
+    fn evaluate(
+        flow_op: FlowOps,
+        state1: &TraceState<BaseElement>,
+        state2: &TraceState<BaseElement>,
+    ) -> Vec<BaseElement> {
+        let mut op_flags = [BaseElement::ZERO; NUM_CF_OPS];
+        op_flags[flow_op as usize] = BaseElement::ONE;
+
+        let mut evaluations = vec![BaseElement::ZERO; state1.loop_depth()];
+        super::enforce_loop_counters(&mut evaluations, state1, state2, &op_flags);
+        evaluations
+    }
+
+    fn new_state(flow_op: FlowOps, loop_counters: &[u128]) -> TraceState<BaseElement> {
+        let loop_depth = loop_counters.len();
+
+        let mut state = vec![15, 0, 0, 0, 0];
+
+        for i in 0..3 {
+            state.push(((flow_op as u128) >> i) & 1);
+        }
+
+        for i in 0..7 {
+            state.push(((UserOps::Noop as u128) >> i) & 1);
+        }
+
+        state.push(0); // single value for context stack
+        state.resize(state.len() + loop_depth, 0); // loop stack
+        state.extend_from_slice(loop_counters);
+        state.push(101); // single value for user stack
+
+        TraceState::from_slice(1, loop_depth, 1, &state.to_elements())
+    }
+}
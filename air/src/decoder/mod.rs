@@ -6,7 +6,8 @@ use crate::{
         is_binary, is_zero, EvaluationResult,
     },
     BaseElement, FieldElement, TraceState, TransitionConstraintDegree, VmTransition,
-    BASE_CYCLE_LENGTH, MIN_CONTEXT_DEPTH, MIN_LOOP_DEPTH,
+    BASE_CYCLE_LENGTH, LOOP_COUNTER_BASE, MIN_CONTEXT_DEPTH, NUM_CF_OPS, NUM_CF_OP_BITS,
+    NUM_HD_OP_BITS, NUM_LD_OP_BITS,
 };
 use core::cmp;
 
@@ -22,6 +23,9 @@ use flow_ops::{
     enforce_wrap,
 };
 
+mod loop_counters;
+use loop_counters::enforce_loop_counters;
+
 #[cfg(test)]
 mod tests;
 
@@ -42,8 +46,15 @@ const SPONGE_CONSTRAINT_DEGREES: [usize; NUM_SPONGE_CONSTRAINTS] = [
     6, 7, 6, 6, // sponge transition constraints
 ];
 
+const LOOP_IMAGE_CONSTRAINT_DEGREE: usize = 4;
+
 const STACK_CONSTRAINT_DEGREE: usize = 4;
 
+const LOOP_COUNTER_CONSTRAINT_DEGREES: [usize; 2] = [
+    5, // the counter of the innermost loop
+    4, // counters of all other loops
+];
+
 const CYCLE_MASK_IDX: usize = 0;
 const PREFIX_MASK_IDX: usize = 1;
 const PUSH_MASK_IDX: usize = 2;
@@ -67,14 +78,65 @@ pub fn get_transition_constraint_degrees(
     for &degree in SPONGE_CONSTRAINT_DEGREES.iter() {
         result.push(TransitionConstraintDegree::new(degree));
     }
+    result.push(TransitionConstraintDegree::new(
+        LOOP_IMAGE_CONSTRAINT_DEGREE,
+    ));
 
+    // loop stack constraints are included only for loop stack registers which are present in
+    // the execution trace; otherwise, the constraints would be identically zero, and thus, their
+    // degree would not match the declared degree
     result.resize(
-        result.len()
-            + cmp::max(ctx_depth, MIN_CONTEXT_DEPTH)
-            + cmp::max(loop_depth, MIN_LOOP_DEPTH),
+        result.len() + cmp::max(ctx_depth, MIN_CONTEXT_DEPTH) + loop_depth,
         TransitionConstraintDegree::new(STACK_CONSTRAINT_DEGREE),
     );
 
+    // the same applies to loop counter constraints
+    for i in 0..loop_depth {
+        let degree = LOOP_COUNTER_CONSTRAINT_DEGREES[cmp::min(i, 1)];
+        result.push(TransitionConstraintDegree::new(degree));
+    }
+
+    result
+}
+
+// CONSTRAINT NAMES
+// ================================================================================================
+
+/// Returns names of decoder constraints in the same order in which the constraints are evaluated
+/// by [enforce_constraints()].
+pub fn get_constraint_names(ctx_depth: usize, loop_depth: usize) -> Vec<String> {
+    let mut result = Vec::new();
+
+    for i in 0..NUM_CF_OP_BITS {
+        result.push(format!("cf_op_bits[{}] is binary", i));
+    }
+    for i in 0..NUM_LD_OP_BITS {
+        result.push(format!("ld_op_bits[{}] is binary", i));
+    }
+    for i in 0..NUM_HD_OP_BITS {
+        result.push(format!("hd_op_bits[{}] is binary", i));
+    }
+    result.push("op_counter transition".to_string());
+    result.push("ld_ops and hd_ops are not all zeros".to_string());
+    result.push("cf_ops imply NOOP".to_string());
+    result.push("VOID is followed by VOID".to_string());
+    result.push("operation alignment".to_string());
+
+    for i in 0..OP_SPONGE_WIDTH {
+        result.push(format!("op_sponge[{}] transition", i));
+    }
+    result.push("loop image".to_string());
+
+    for i in 0..cmp::max(ctx_depth, MIN_CONTEXT_DEPTH) {
+        result.push(format!("ctx_stack[{}] transition", i));
+    }
+    for i in 0..loop_depth {
+        result.push(format!("loop_stack[{}] transition", i));
+    }
+    for i in 0..loop_depth {
+        result.push(format!("loop_counters[{}] transition", i));
+    }
+
     result
 }
 
@@ -90,13 +152,15 @@ pub fn enforce_constraints<E: FieldElement<BaseField = BaseElement>>(
     // evaluate constraints for decoding op codes
     enforce_op_bits(&mut result[..NUM_OP_CONSTRAINTS], transition, masks);
 
-    // evaluate constraints for flow control operations
-    let result = &mut result[NUM_OP_CONSTRAINTS..];
-    let op_flags = transition.cf_op_flags();
-
+    // evaluate constraints for flow control operations; constraints for loop counters are
+    // evaluated separately as they are the last constraints of the decoder
     let current = transition.current();
     let next = transition.next();
 
+    let result = &mut result[NUM_OP_CONSTRAINTS..];
+    let (result, counter_result) = result.split_at_mut(result.len() - current.loop_depth());
+    let op_flags = transition.cf_op_flags();
+
     enforce_hacc(result, transition, ark, op_flags[FlowOps::Hacc as usize]);
     enforce_begin(result, current, next, op_flags[FlowOps::Begin as usize]);
     enforce_tend(result, current, next, op_flags[FlowOps::Tend as usize]);
@@ -105,6 +169,8 @@ pub fn enforce_constraints<E: FieldElement<BaseField = BaseElement>>(
     enforce_wrap(result, current, next, op_flags[FlowOps::Wrap as usize]);
     enforce_break(result, current, next, op_flags[FlowOps::Break as usize]);
     enforce_void(result, current, next, op_flags[FlowOps::Void as usize]);
+
+    enforce_loop_counters(counter_result, current, next, &op_flags);
 }
 
 // CYCLE MASKS
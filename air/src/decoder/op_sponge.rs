@@ -1,5 +1,8 @@
 use super::{are_equal, BaseElement, EvaluationResult, FieldElement, UserOps, VmTransition};
-use vm_core::op_sponge::{apply_inv_mds, apply_mds, apply_sbox, STATE_WIDTH};
+use vm_core::{
+    op_sponge::{apply_inv_mds, apply_mds, apply_sbox, STATE_WIDTH},
+    U32_SELECTORS_RANGE,
+};
 
 // CONSTRAINT EVALUATOR
 // ================================================================================================
@@ -10,10 +13,19 @@ pub fn enforce_hacc<E: FieldElement<BaseField = BaseElement>>(
     ark: &[E],
     op_flag: E,
 ) {
-    // determine current op_value
+    // determine current op_value; for PUSH operation, this is the value pushed onto the stack,
+    // and for U32 operation, this is the code of the selected u32 operation (the selectors are
+    // zeros at all other steps)
     let stack_top = transition.next().user_stack()[0];
     let push_flag = transition.hd_op_flags()[UserOps::Push.hd_index()];
-    let op_value = stack_top * push_flag;
+    let mut op_value = stack_top * push_flag;
+
+    let u32_table = transition.current().u32_table();
+    if !u32_table.is_empty() {
+        for (i, &selector) in u32_table[U32_SELECTORS_RANGE].iter().enumerate() {
+            op_value += selector * E::from((i + 1) as u32);
+        }
+    }
 
     // evaluate the first half of Rescue round
     let mut old_sponge = [E::ZERO; STATE_WIDTH];
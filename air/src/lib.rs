@@ -1,29 +1,37 @@
-use core::convert::{TryFrom, TryInto};
+use core::{convert::TryInto, fmt};
 use vm_core::{
-    hasher, op_sponge, opcodes, BASE_CYCLE_LENGTH, CF_OP_BITS_RANGE, HD_OP_BITS_RANGE,
-    LD_OP_BITS_RANGE, MIN_CONTEXT_DEPTH, MIN_LOOP_DEPTH, NUM_CF_OPS, NUM_HD_OPS, NUM_LD_OPS,
-    OP_COUNTER_IDX, OP_SPONGE_RANGE,
+    hasher, op_sponge, opcodes,
+    storage::{root_from_bytes, StorageNode},
+    BASE_CYCLE_LENGTH, CF_OP_BITS_RANGE, HD_OP_BITS_RANGE, LD_OP_BITS_RANGE, LOOP_COUNTER_BASE,
+    MEMORY_TRACE_WIDTH, MIN_CONTEXT_DEPTH, NUM_CF_OPS, NUM_CF_OP_BITS, NUM_HD_OPS, NUM_HD_OP_BITS,
+    NUM_LD_OPS, NUM_LD_OP_BITS, OP_COUNTER_IDX, OP_SPONGE_RANGE, STORAGE_TRACE_WIDTH,
+    U32_TRACE_WIDTH,
 };
 use winter_air::{
     Air, AirContext, Assertion, EvaluationFrame, ProofOptions as WinterProofOptions, TraceInfo,
     TransitionConstraintDegree,
 };
-use winter_utils::{group_slice_elements, ByteWriter, Serializable};
+use winter_utils::group_slice_elements;
 
+mod checker;
 mod decoder;
+mod merkle_root;
 mod options;
 mod stack;
 mod transition;
+mod u32_table;
 mod utils;
 
 // EXPORTS
 // ================================================================================================
 
+pub use checker::{check_constraints, ConstraintError};
 pub use options::ProofOptions;
 pub use transition::VmTransition;
 pub use vm_core::{
-    utils::ToElements, BaseElement, FieldElement, StarkField, TraceState, MAX_OUTPUTS,
-    MIN_TRACE_LENGTH,
+    storage::Storage, utils::ToElements, BaseElement, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, FieldElement, Serializable, SliceReader, StarkField, TraceState,
+    MAX_OUTPUTS, MAX_PUBLIC_INPUTS, MIN_TRACE_LENGTH,
 };
 pub use winter_air::{FieldExtension, HashFunction};
 
@@ -39,7 +47,16 @@ pub struct ProcessorAir {
     ctx_depth: usize,
     loop_depth: usize,
     stack_depth: usize,
+    has_memory: bool,
+    has_storage: bool,
+    has_u32_table: bool,
+    init_storage_root: StorageNode,
+    final_storage_root: StorageNode,
     decoder_constraint_count: usize,
+    stack_constraint_count: usize,
+    storage_constraint_count: usize,
+    u32_constraint_count: usize,
+    memory_constraint_count: usize,
 }
 
 impl Air for ProcessorAir {
@@ -47,13 +64,34 @@ impl Air for ProcessorAir {
     type PublicInputs = PublicInputs;
 
     fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: WinterProofOptions) -> Self {
-        let meta = TraceMetadata::from_trace_info(&trace_info);
+        let mut meta = TraceMetadata::from_trace_info(&trace_info);
+
+        // the storage root can change only if the execution trace includes the storage root
+        // registers; so, the registers must be there if the roots differ
+        if !meta.has_storage && pub_inputs.init_storage_root != pub_inputs.final_storage_root {
+            meta.has_storage = true;
+            meta.stack_depth = meta.stack_depth.saturating_sub(STORAGE_TRACE_WIDTH);
+        }
 
         let mut tcd = decoder::get_transition_constraint_degrees(meta.ctx_depth, meta.loop_depth);
         let decoder_constraint_count = tcd.len();
         tcd.append(&mut stack::get_transition_constraint_degrees(
             meta.stack_depth,
         ));
+        let stack_constraint_count = tcd.len() - decoder_constraint_count;
+        tcd.append(&mut merkle_root::get_transition_constraint_degrees(
+            meta.has_storage,
+        ));
+        let storage_constraint_count =
+            tcd.len() - decoder_constraint_count - stack_constraint_count;
+        tcd.append(&mut u32_table::get_transition_constraint_degrees(&meta));
+        let u32_constraint_count = tcd.len()
+            - decoder_constraint_count
+            - stack_constraint_count
+            - storage_constraint_count;
+        let mut memory_tcd = merkle_root::get_transition_constraint_degrees(meta.has_memory);
+        let memory_constraint_count = memory_tcd.len();
+        tcd.append(&mut memory_tcd);
 
         Self {
             context: AirContext::new(trace_info, tcd, options),
@@ -64,7 +102,16 @@ impl Air for ProcessorAir {
             ctx_depth: meta.ctx_depth,
             loop_depth: meta.loop_depth,
             stack_depth: meta.stack_depth,
+            has_memory: meta.has_memory,
+            has_storage: meta.has_storage,
+            has_u32_table: meta.has_u32_table,
+            init_storage_root: pub_inputs.init_storage_root,
+            final_storage_root: pub_inputs.final_storage_root,
             decoder_constraint_count,
+            stack_constraint_count,
+            storage_constraint_count,
+            u32_constraint_count,
+            memory_constraint_count,
         }
     }
 
@@ -117,9 +164,9 @@ impl Air for ProcessorAir {
             result.push(Assertion::single(i, 0, BaseElement::ZERO));
         }
 
-        // make sure all loop stack registers are 0s
+        // make sure all loop stack and loop counter registers are 0s
         let loop_stack_start = ctx_stack_end;
-        let loop_stack_end = loop_stack_start + self.loop_depth;
+        let loop_stack_end = loop_stack_start + 2 * self.loop_depth;
         for i in loop_stack_start..loop_stack_end {
             result.push(Assertion::single(i, 0, BaseElement::ZERO));
         }
@@ -166,7 +213,7 @@ impl Air for ProcessorAir {
             result.push(Assertion::single(i, last_step, BaseElement::ZERO));
         }
 
-        // make sure all loop stack registers are 0s
+        // make sure all loop stack and loop counter registers are 0s
         for i in loop_stack_start..loop_stack_end {
             result.push(Assertion::single(i, last_step, BaseElement::ZERO));
         }
@@ -180,6 +227,35 @@ impl Air for ProcessorAir {
             ));
         }
 
+        // --- set assertions for the memory trace ------------------------------------------------
+        let memory_start = user_stack_start + self.stack_depth;
+        let mut storage_start = memory_start;
+        if self.has_memory {
+            let empty_root = Storage::new().root_node();
+            // every program starts with empty memory; the final root is not constrained as
+            // memory does not outlive the program
+            result.append(&mut merkle_root::get_assertions(
+                memory_start,
+                0,
+                &empty_root,
+            ));
+            storage_start += MEMORY_TRACE_WIDTH;
+        }
+
+        // --- set assertions for the storage root ------------------------------------------------
+        if self.has_storage {
+            result.append(&mut merkle_root::get_assertions(
+                storage_start,
+                0,
+                &self.init_storage_root,
+            ));
+            result.append(&mut merkle_root::get_assertions(
+                storage_start,
+                last_step,
+                &self.final_storage_root,
+            ));
+        }
+
         result
     }
 
@@ -189,16 +265,41 @@ impl Air for ProcessorAir {
         periodic_values: &[E],
         result: &mut [E],
     ) {
-        let mut transition = VmTransition::new(self.ctx_depth, self.loop_depth, self.stack_depth);
+        let mut transition = VmTransition::new(
+            self.ctx_depth,
+            self.loop_depth,
+            self.stack_depth,
+            self.has_memory,
+            self.has_storage,
+            self.has_u32_table,
+        );
         transition.update(frame);
 
         let (masks, ark) = periodic_values.split_at(decoder::MASKS.len());
 
-        decoder::enforce_constraints(&transition, masks, ark, result);
-        stack::enforce_constraints(
+        // split constraint evaluation result into decoder, stack, storage, u32 operations, and
+        // memory constraints
+        let (decoder_result, result) = result.split_at_mut(self.decoder_constraint_count);
+        let (stack_result, result) = result.split_at_mut(self.stack_constraint_count);
+        let (storage_result, result) = result.split_at_mut(self.storage_constraint_count);
+        let (u32_result, memory_result) = result.split_at_mut(self.u32_constraint_count);
+
+        decoder::enforce_constraints(&transition, masks, ark, decoder_result);
+        stack::enforce_constraints(&transition, ark, stack_result);
+        merkle_root::enforce_constraints(
             &transition,
-            ark,
-            &mut result[self.decoder_constraint_count..],
+            opcodes::UserOps::SRoot,
+            transition.current().storage(),
+            transition.next().storage(),
+            storage_result,
+        );
+        u32_table::enforce_constraints(&transition, u32_result);
+        merkle_root::enforce_constraints(
+            &transition,
+            opcodes::UserOps::MRoot,
+            transition.current().memory(),
+            transition.next().memory(),
+            memory_result,
         );
     }
 
@@ -214,27 +315,68 @@ pub struct PublicInputs {
     program_hash: [BaseElement; op_sponge::DIGEST_SIZE],
     inputs: Vec<BaseElement>,
     outputs: Vec<BaseElement>,
+    init_storage_root: StorageNode,
+    final_storage_root: StorageNode,
 }
 
 impl PublicInputs {
-    pub fn new(program_hash: [u8; 32], inputs: &[u128], outputs: &[u128]) -> Self {
+    /// Returns public inputs for a program with the specified hash which was executed against
+    /// the specified `inputs` and produced the specified `outputs`; the program is assumed to
+    /// start and to end with empty storage.
+    ///
+    /// # Errors
+    /// Returns an error if the program hash does not consist of two valid field elements, or if
+    /// any of the inputs or outputs is not a valid field element.
+    pub fn new(
+        program_hash: [u8; 32],
+        inputs: &[u128],
+        outputs: &[u128],
+    ) -> Result<Self, PublicInputsError> {
         let program_hash: &[[u8; 16]] = group_slice_elements(&program_hash);
         let program_hash = [
-            BaseElement::try_from(program_hash[0]).unwrap(),
-            BaseElement::try_from(program_hash[1]).unwrap(),
+            to_element(u128::from_le_bytes(program_hash[0]))
+                .ok_or(PublicInputsError::InvalidProgramHash)?,
+            to_element(u128::from_le_bytes(program_hash[1]))
+                .ok_or(PublicInputsError::InvalidProgramHash)?,
         ];
 
-        Self {
+        let inputs = inputs
+            .iter()
+            .map(|&v| to_element(v).ok_or(PublicInputsError::InvalidInput(v)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let outputs = outputs
+            .iter()
+            .map(|&v| to_element(v).ok_or(PublicInputsError::InvalidOutput(v)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // unless specified otherwise, the program is assumed to start and end with empty storage
+        let empty_storage_root = Storage::new().root_node();
+
+        Ok(Self {
             program_hash,
-            inputs: inputs
-                .iter()
-                .map(|&v| BaseElement::try_from(v).unwrap())
-                .collect(),
-            outputs: outputs
-                .iter()
-                .map(|&v| BaseElement::try_from(v).unwrap())
-                .collect(),
-        }
+            inputs,
+            outputs,
+            init_storage_root: empty_storage_root,
+            final_storage_root: empty_storage_root,
+        })
+    }
+
+    /// Returns these public inputs with the roots of the storage before and after the execution
+    /// of the program set to the specified values.
+    ///
+    /// # Errors
+    /// Returns an error if either of the roots does not consist of two valid field elements.
+    pub fn with_storage_roots(
+        mut self,
+        init_root: [u8; 32],
+        final_root: [u8; 32],
+    ) -> Result<Self, PublicInputsError> {
+        self.init_storage_root =
+            root_from_bytes(&init_root).ok_or(PublicInputsError::InvalidStorageRoot)?;
+        self.final_storage_root =
+            root_from_bytes(&final_root).ok_or(PublicInputsError::InvalidStorageRoot)?;
+        Ok(self)
     }
 }
 
@@ -243,6 +385,43 @@ impl Serializable for PublicInputs {
         target.write(&self.program_hash[..]);
         target.write(&self.inputs);
         target.write(&self.outputs);
+        target.write(&self.init_storage_root[..]);
+        target.write(&self.final_storage_root[..]);
+    }
+}
+
+// PUBLIC INPUTS ERROR
+// ================================================================================================
+
+/// A value provided as a public input of the processor AIR which is not a valid field element.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PublicInputsError {
+    /// The program hash does not consist of two valid field elements.
+    InvalidProgramHash,
+    /// The specified input is not a valid field element.
+    InvalidInput(u128),
+    /// The specified output is not a valid field element.
+    InvalidOutput(u128),
+    /// A storage root does not consist of two valid field elements.
+    InvalidStorageRoot,
+}
+
+impl fmt::Display for PublicInputsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PublicInputsError::InvalidProgramHash => {
+                write!(f, "program hash is not a valid field element")
+            }
+            PublicInputsError::InvalidInput(value) => {
+                write!(f, "input {} is not a valid field element", value)
+            }
+            PublicInputsError::InvalidOutput(value) => {
+                write!(f, "output {} is not a valid field element", value)
+            }
+            PublicInputsError::InvalidStorageRoot => {
+                write!(f, "storage root is not a valid field element")
+            }
+        }
     }
 }
 
@@ -254,6 +433,9 @@ pub struct TraceMetadata {
     pub ctx_depth: usize,
     pub loop_depth: usize,
     pub stack_depth: usize,
+    pub has_memory: bool,
+    pub has_storage: bool,
+    pub has_u32_table: bool,
 }
 
 impl TraceMetadata {
@@ -261,12 +443,38 @@ impl TraceMetadata {
         let op_count = u64::from_le_bytes(trace_info.meta()[..8].try_into().unwrap()) as usize;
         let ctx_depth = trace_info.meta()[8] as usize;
         let loop_depth = trace_info.meta()[9] as usize;
+        let has_memory = trace_info.meta()[10] != 0;
+        let has_u32_table = trace_info.meta()[11] != 0;
+        let has_storage = trace_info.meta()[12] != 0;
         let decoder_width = TraceState::<BaseElement>::compute_decoder_width(ctx_depth, loop_depth);
+        let memory_width = if has_memory { MEMORY_TRACE_WIDTH } else { 0 };
+        let storage_width = if has_storage { STORAGE_TRACE_WIDTH } else { 0 };
+        let u32_table_width = if has_u32_table { U32_TRACE_WIDTH } else { 0 };
         TraceMetadata {
             op_count,
             ctx_depth,
             loop_depth,
-            stack_depth: trace_info.width() - decoder_width,
+            stack_depth: trace_info.width()
+                - decoder_width
+                - memory_width
+                - storage_width
+                - u32_table_width,
+            has_memory,
+            has_storage,
+            has_u32_table,
         }
     }
 }
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the field element with the specified value, or None if the value is not smaller than
+/// the field modulus.
+fn to_element(value: u128) -> Option<BaseElement> {
+    if value < BaseElement::MODULUS {
+        Some(BaseElement::new(value))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{
+    opcodes::UserOps as OpCode, utils::are_equal, BaseElement, FieldElement,
+    TransitionConstraintDegree, VmTransition,
+};
+use vm_core::storage::StorageNode;
+use winter_air::Assertion;
+
+// CONSTANTS
+// ================================================================================================
+const NUM_CONSTRAINTS: usize = 4;
+const CONSTRAINT_DEGREES: [usize; NUM_CONSTRAINTS] = [
+    6, 6, // the root is replaced with the top two items of the stack by the root operation
+    6, 6, // the old root is placed at the top of the stack by the root operation
+];
+
+/// Degree of the constraint which prohibits the root operation when the execution trace does not
+/// include the root registers.
+const NO_ROOT_CONSTRAINT_DEGREE: usize = 5;
+
+// CONSTRAINT DEGREES
+// ================================================================================================
+
+/// Returns degrees of the constraints of a set of Merkle root registers; if the execution trace
+/// does not include the registers, a single constraint prohibits the root operation instead.
+pub fn get_transition_constraint_degrees(has_registers: bool) -> Vec<TransitionConstraintDegree> {
+    if !has_registers {
+        return vec![TransitionConstraintDegree::new(NO_ROOT_CONSTRAINT_DEGREE)];
+    }
+
+    CONSTRAINT_DEGREES
+        .iter()
+        .map(|&degree| TransitionConstraintDegree::new(degree))
+        .collect()
+}
+
+// CONSTRAINT NAMES
+// ================================================================================================
+
+/// Returns names of the constraints of a set of Merkle root registers in the same order in which
+/// the constraints are evaluated by [enforce_constraints()]; `section` names the root (e.g.,
+/// "memory") and `op_code` is the operation which swaps it with the top of the stack.
+pub fn get_constraint_names(section: &str, op_code: OpCode, has_registers: bool) -> Vec<String> {
+    let op_code = op_code.to_string().to_uppercase();
+    if !has_registers {
+        return vec![format!(
+            "{} is not executed without {} root registers",
+            op_code, section
+        )];
+    }
+
+    let width = NUM_CONSTRAINTS / 2;
+    let mut result = Vec::with_capacity(NUM_CONSTRAINTS);
+    for i in 0..width {
+        result.push(format!(
+            "{} root[{}] is replaced only by {}",
+            section, i, op_code
+        ));
+    }
+    for i in 0..width {
+        result.push(format!(
+            "{} moves {} root[{}] onto stack[{}]",
+            op_code, section, i, i
+        ));
+    }
+    result
+}
+
+// BOUNDARY CONSTRAINTS
+// ================================================================================================
+
+/// Returns assertions that the root registers which start at `first_register` hold the specified
+/// `root` at the specified step.
+pub fn get_assertions(
+    first_register: usize,
+    step: usize,
+    root: &StorageNode,
+) -> Vec<Assertion<BaseElement>> {
+    root.iter()
+        .enumerate()
+        .map(|(i, &value)| Assertion::single(first_register + i, step, value))
+        .collect()
+}
+
+// TRANSITION CONSTRAINTS
+// ================================================================================================
+
+/// Enforces constraints of a set of Merkle root registers; `current` and `next` are the values of
+/// the registers at the current and the next steps.
+///
+/// Memory and storage are both sparse Merkle trees of which the VM keeps only the root. The root
+/// registers can change only when the specified root operation (MROOT or SROOT) is executed; the
+/// operation swaps the root with the top two items of the stack. Together with the boundary
+/// constraints, this ties every root computed and verified by the program to the root the
+/// registers start with.
+///
+/// If the execution trace does not include the registers, the constraints enforce that the root
+/// operation was not executed.
+pub fn enforce_constraints<E: FieldElement<BaseField = BaseElement>>(
+    transition: &VmTransition<E>,
+    op_code: OpCode,
+    current: &[E],
+    next: &[E],
+    result: &mut [E],
+) {
+    let op_flag = transition.ld_op_flags()[op_code.ld_index()];
+
+    if current.is_empty() {
+        result[0] = op_flag;
+        return;
+    }
+
+    let width = current.len();
+    let old_stack = transition.current().user_stack();
+    let new_stack = transition.next().user_stack();
+    for i in 0..width {
+        result[i] = are_equal(next[i], current[i] + op_flag * (old_stack[i] - current[i]));
+        result[width + i] = op_flag * are_equal(new_stack[i], current[i]);
+    }
+}
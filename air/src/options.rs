@@ -1,6 +1,8 @@
 use core::ops::Deref;
 use winter_air::{FieldExtension, HashFunction, ProofOptions as WinterProofOptions};
+use winter_utils::{ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable};
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ProofOptions(WinterProofOptions);
 
 impl ProofOptions {
@@ -59,6 +61,24 @@ impl Default for ProofOptions {
     }
 }
 
+impl From<WinterProofOptions> for ProofOptions {
+    fn from(options: WinterProofOptions) -> Self {
+        Self(options)
+    }
+}
+
+impl Serializable for ProofOptions {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.0.write_into(target);
+    }
+}
+
+impl Deserializable for ProofOptions {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        WinterProofOptions::read_from(source).map(Self)
+    }
+}
+
 impl Deref for ProofOptions {
     type Target = WinterProofOptions;
 
@@ -36,6 +36,16 @@ where
     enforce_left_shift(result, old_stack, new_stack, 2, 1, op_flag);
 }
 
+/// Enforces constraints for U32 operation. No constraints are placed on the first element of
+/// the stack here; the result of the operation is tied to the u32 operations table by the
+/// constraints of the table. The rest of the stack is shifted left by 1 element.
+pub fn enforce_u32<E>(result: &mut [E], old_stack: &[E], new_stack: &[E], op_flag: E)
+where
+    E: FieldElement,
+{
+    enforce_left_shift(result, old_stack, new_stack, 2, 1, op_flag);
+}
+
 /// Enforces constraints for INV operation. The constraints are based on the first element of
 /// the stack; the rest of the stack is unaffected.
 pub fn enforce_inv<E>(result: &mut [E], old_stack: &[E], new_stack: &[E], op_flag: E)
@@ -0,0 +1,14 @@
+use super::{enforce_stack_copy, FieldElement};
+
+// CONSTRAINT EVALUATORS
+// ================================================================================================
+
+/// Enforces constraints for MROOT and SROOT operations. No constraints are placed on the top two
+/// elements of the stack here; these are tied to the memory or storage root registers by the
+/// constraints of the root registers. The rest of the stack remains unchanged.
+pub fn enforce_root_swap<E>(result: &mut [E], old_stack: &[E], new_stack: &[E], op_flag: E)
+where
+    E: FieldElement,
+{
+    enforce_stack_copy(result, old_stack, new_stack, 2, op_flag);
+}
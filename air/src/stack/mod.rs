@@ -14,6 +14,7 @@ use input::{enforce_push, enforce_read, enforce_read2};
 mod arithmetic;
 use arithmetic::{
     enforce_add, enforce_and, enforce_inv, enforce_mul, enforce_neg, enforce_not, enforce_or,
+    enforce_u32,
 };
 
 mod manipulation;
@@ -31,17 +32,49 @@ use conditional::{enforce_choose, enforce_choose2, enforce_cswap2};
 mod hash;
 use hash::enforce_rescr;
 
+mod merkle_root;
+use merkle_root::enforce_root_swap;
+
 // CONSTANTS
 // ================================================================================================
 pub const NUM_AUX_CONSTRAINTS: usize = 2;
+const AUX_CONSTRAINT_DEGREES: [usize; NUM_AUX_CONSTRAINTS] = [
+    7, // the first aux constraint is set by assertion, comparison, and conditional operations
+    6, // the second aux constraint is set only by AND and OR operations; their flags differ
+       // in the lowest op bit only, and thus, the sum of the flags has degree 4
+];
 
 // CONSTRAINT DEGREES
 // ================================================================================================
 
 pub fn get_transition_constraint_degrees(stack_depth: usize) -> Vec<TransitionConstraintDegree> {
+    let mut result = AUX_CONSTRAINT_DEGREES
+        .iter()
+        .map(|&degree| TransitionConstraintDegree::new(degree))
+        .collect::<Vec<_>>();
+
     // all stack transition constraints have degree 7
-    let degree = TransitionConstraintDegree::new(7);
-    vec![degree; stack_depth + NUM_AUX_CONSTRAINTS]
+    result.resize(
+        NUM_AUX_CONSTRAINTS + stack_depth,
+        TransitionConstraintDegree::new(7),
+    );
+    result
+}
+
+// CONSTRAINT NAMES
+// ================================================================================================
+
+/// Returns names of stack constraints in the same order in which the constraints are evaluated
+/// by [enforce_constraints()].
+pub fn get_constraint_names(stack_depth: usize) -> Vec<String> {
+    let mut result = vec![
+        "operand of assertion, comparison, or conditional operation is valid".to_string(),
+        "second operand of AND or OR is binary".to_string(),
+    ];
+    for i in 0..stack_depth {
+        result.push(format!("stack[{}] is updated by the executed operation", i));
+    }
+    result
 }
 
 // HELPER FUNCTIONS
@@ -215,6 +248,14 @@ pub fn enforce_constraints<E: FieldElement<BaseField = BaseElement>>(
         ld_flags[OpCode::Or.ld_index()],
     );
 
+    // u32 operations
+    enforce_u32(
+        &mut evaluations,
+        old_stack,
+        new_stack,
+        ld_flags[OpCode::U32.ld_index()],
+    );
+
     // comparison operations
     enforce_eq(
         &mut evaluations,
@@ -253,6 +294,20 @@ pub fn enforce_constraints<E: FieldElement<BaseField = BaseElement>>(
         ld_flags[OpCode::CSwap2.ld_index()],
     );
 
+    // memory and storage operations
+    enforce_root_swap(
+        &mut evaluations,
+        old_stack,
+        new_stack,
+        ld_flags[OpCode::MRoot.ld_index()],
+    );
+    enforce_root_swap(
+        &mut evaluations,
+        old_stack,
+        new_stack,
+        ld_flags[OpCode::SRoot.ld_index()],
+    );
+
     // 2 ----- enforce constraints for high-degree operations --------------------------------------
     let hd_flags = transition.hd_op_flags();
 
@@ -21,10 +21,32 @@ impl<E: FieldElement<BaseField = BaseElement>> VmTransition<E> {
     // CONSTRUCTOR
     // --------------------------------------------------------------------------------------------
 
-    pub fn new(ctx_depth: usize, loop_depth: usize, stack_depth: usize) -> Self {
+    pub fn new(
+        ctx_depth: usize,
+        loop_depth: usize,
+        stack_depth: usize,
+        has_memory: bool,
+        has_storage: bool,
+        has_u32_table: bool,
+    ) -> Self {
+        let mut current = TraceState::new(ctx_depth, loop_depth, stack_depth);
+        let mut next = TraceState::new(ctx_depth, loop_depth, stack_depth);
+        if has_memory {
+            current = current.with_memory();
+            next = next.with_memory();
+        }
+        if has_storage {
+            current = current.with_storage();
+            next = next.with_storage();
+        }
+        if has_u32_table {
+            current = current.with_u32_table();
+            next = next.with_u32_table();
+        }
+
         Self {
-            current: TraceState::new(ctx_depth, loop_depth, stack_depth),
-            next: TraceState::new(ctx_depth, loop_depth, stack_depth),
+            current,
+            next,
             cf_op_flags: [E::ZERO; NUM_CF_OPS],
             ld_op_flags: [E::ZERO; NUM_LD_OPS],
             hd_op_flags: [E::ZERO; NUM_HD_OPS],
@@ -116,7 +138,7 @@ impl<E: FieldElement<BaseField = BaseElement>> VmTransition<E> {
         let not_1 = binary_not(self.current.ld_op_bits()[1]);
         self.ld_op_flags[0] = not_0 * not_1;
         self.ld_op_flags[1] = self.current.ld_op_bits()[0] * not_1;
-        self.ld_op_flags[2] = not_0 * self.current.cf_op_bits()[1];
+        self.ld_op_flags[2] = not_0 * self.current.ld_op_bits()[1];
         self.ld_op_flags[3] = self.current.ld_op_bits()[0] * self.current.ld_op_bits()[1];
         self.ld_op_flags.copy_within(0..4, 4);
 
@@ -265,13 +287,32 @@ mod tests {
             transition.ld_op_flags()
         );
         assert_eq!([0, 0, 1, 0].to_elements(), transition.hd_op_flags());
+
+        // mixed 3: low-degree flags do not depend on control flow op bits
+        let transition = vm_transition_from_current(&[
+            101, 1, 2, 3, 4, 0, 0, 0, 0, 1, 1, 0, 1, 1, 1, 15, 16, 17,
+        ]);
+
+        assert_eq!(
+            [1, 0, 0, 0, 0, 0, 0, 0].to_elements(),
+            transition.cf_op_flags()
+        );
+        assert_eq!(
+            [
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0,
+                0, 0, 0, 0,
+            ]
+            .to_elements(),
+            transition.ld_op_flags()
+        );
+        assert_eq!([0, 0, 0, 1].to_elements(), transition.hd_op_flags());
     }
 
     // HELPER FUNCTIONS
     // --------------------------------------------------------------------------------------------
 
     fn vm_transition_from_current(current_row: &[u128]) -> VmTransition<BaseElement> {
-        let mut result = VmTransition::new(1, 0, 2);
+        let mut result = VmTransition::new(1, 0, 2, false, false, false);
         let current = current_row.iter().map(|&v| BaseElement::new(v)).collect();
         let frame = EvaluationFrame::from_rows(current, vec![BaseElement::ZERO; current_row.len()]);
         result.update(&frame);
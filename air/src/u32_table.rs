@@ -0,0 +1,214 @@
+use crate::{
+    opcodes::{U32Op, UserOps as OpCode},
+    utils::{are_equal, is_binary},
+    BaseElement, FieldElement, TraceMetadata, TransitionConstraintDegree, VmTransition,
+};
+use vm_core::{
+    NUM_U32_OPS, U32_A_BITS_RANGE, U32_BITS, U32_B_BITS_RANGE, U32_C_BITS_RANGE, U32_D_BITS_RANGE,
+    U32_E_BITS_RANGE, U32_POWERS_RANGE, U32_SELECTORS_RANGE,
+};
+
+// CONSTANTS
+// ================================================================================================
+const NUM_OP_CONSTRAINTS: usize = 18;
+const OP_CONSTRAINT_DEGREES: [usize; NUM_OP_CONSTRAINTS] = [
+    2, 2, 2, 2, 2, 2, 2, 2, // selectors are binary
+    5, // exactly one selector is set when U32 operation is executed
+    2, 3, 2, // powers of two are selected by the lower 5 bits of a
+    2, 2, 2, // operands and the result match the top of the stack
+    3, // the result is computed according to the selected operation
+    3, // auxiliary values are bounded according to the selected operation
+    2, // shift amount is smaller than 32
+];
+
+/// Number of registers which hold binary decompositions of values in the u32 operations table.
+const NUM_BIT_REGISTERS: usize = U32_E_BITS_RANGE.end - U32_A_BITS_RANGE.start;
+
+/// Degree of the constraint which prohibits U32 operations when the execution trace does not
+/// include the u32 operations table.
+const NO_TABLE_CONSTRAINT_DEGREE: usize = 5;
+
+// CONSTRAINT DEGREES
+// ================================================================================================
+
+pub fn get_transition_constraint_degrees(meta: &TraceMetadata) -> Vec<TransitionConstraintDegree> {
+    if !meta.has_u32_table {
+        return vec![TransitionConstraintDegree::new(NO_TABLE_CONSTRAINT_DEGREE)];
+    }
+
+    let mut result = OP_CONSTRAINT_DEGREES
+        .iter()
+        .map(|&degree| TransitionConstraintDegree::new(degree))
+        .collect::<Vec<_>>();
+
+    // all bits are binary
+    for _ in 0..NUM_BIT_REGISTERS {
+        result.push(TransitionConstraintDegree::new(2));
+    }
+
+    result
+}
+
+// CONSTRAINT NAMES
+// ================================================================================================
+
+/// Returns names of the constraints of the u32 operations table in the same order in which the
+/// constraints are evaluated by [enforce_constraints()].
+pub fn get_constraint_names(has_u32_table: bool) -> Vec<String> {
+    if !has_u32_table {
+        return vec!["U32 is not executed without the u32 table".to_string()];
+    }
+
+    let mut result = Vec::with_capacity(NUM_OP_CONSTRAINTS + NUM_BIT_REGISTERS);
+    for i in 0..NUM_U32_OPS {
+        result.push(format!("u32 selectors[{}] is binary", i));
+    }
+    result.push("exactly one u32 selector is set by U32".to_string());
+    result.push("u32 powers[0] is computed from a_bits[0..2]".to_string());
+    result.push("u32 powers[1] is computed from a_bits[2..4]".to_string());
+    result.push("u32 powers[2] is computed from a_bits[4]".to_string());
+    result.push("u32 operand a is stack[0]".to_string());
+    result.push("u32 operand b is stack[1]".to_string());
+    result.push("u32 result c is placed at stack[0]".to_string());
+    result.push("u32 result c is computed by the selected operation".to_string());
+    result.push("u32 carry, borrow, or remainder is in range".to_string());
+    result.push("u32 shift amount is smaller than 32".to_string());
+
+    let bit_ranges = [
+        ("a", U32_A_BITS_RANGE),
+        ("b", U32_B_BITS_RANGE),
+        ("c", U32_C_BITS_RANGE),
+        ("d", U32_D_BITS_RANGE),
+        ("e", U32_E_BITS_RANGE),
+    ];
+    for (name, range) in bit_ranges.iter() {
+        for i in 0..range.len() {
+            result.push(format!("u32 {}_bits[{}] is binary", name, i));
+        }
+    }
+    result
+}
+
+// TRANSITION CONSTRAINTS
+// ================================================================================================
+
+/// Enforces constraints of the u32 operations table.
+///
+/// When U32 operation is executed, exactly one of the selectors of the table is set; the
+/// selected operation is applied to the top two items of the stack (a and b), and its result
+/// (c) is placed at the top of the stack. All of these values, as well as auxiliary values d and
+/// e, are decomposed into 32 bits, and thus, are 32-bit integers. With this, the results of
+/// operations are enforced as follows:
+/// * ADD: a + b = c + d * 2^32, where d is binary;
+/// * SUB: b - a = c - d * 2^32, where d is binary;
+/// * MUL: a * b = c + d * 2^32;
+/// * DIV: b = c * a + d, where d < a (i.e., a - d - 1 = e);
+/// * AND and XOR: c is computed from the bits of a and b;
+/// * SHL: b * 2^a = c + d * 2^32, where a < 32;
+/// * SHR: b = c * 2^a + d, where a < 32 and d < 2^a (i.e., 2^a - d - 1 = e).
+///
+/// Since all values are smaller than 2^64, none of the above relations can wrap around the field
+/// modulus. The selectors are also injected into the program hash, and thus, the executed
+/// operations are bound to the program.
+///
+/// If the execution trace does not include the table, the constraints enforce that U32
+/// operations were not executed.
+pub fn enforce_constraints<E: FieldElement<BaseField = BaseElement>>(
+    transition: &VmTransition<E>,
+    result: &mut [E],
+) {
+    let u32_flag = transition.ld_op_flags()[OpCode::U32.ld_index()];
+
+    let current = transition.current().u32_table();
+    if current.is_empty() {
+        result[0] = u32_flag;
+        return;
+    }
+
+    // selectors are binary, and exactly one of them is set when U32 operation is executed
+    let selectors = &current[U32_SELECTORS_RANGE];
+    let mut selector_sum = E::ZERO;
+    for (i, &selector) in selectors.iter().enumerate() {
+        result[i] = is_binary(selector);
+        selector_sum += selector;
+    }
+    result[NUM_U32_OPS] = are_equal(selector_sum, u32_flag);
+
+    // all bits are binary
+    for (i, &bit) in current[U32_A_BITS_RANGE.start..U32_E_BITS_RANGE.end]
+        .iter()
+        .enumerate()
+    {
+        result[NUM_OP_CONSTRAINTS + i] = is_binary(bit);
+    }
+
+    let a_bits = &current[U32_A_BITS_RANGE];
+    let b_bits = &current[U32_B_BITS_RANGE];
+    let a = combine_bits(a_bits);
+    let b = combine_bits(b_bits);
+    let c = combine_bits(&current[U32_C_BITS_RANGE]);
+    let d = combine_bits(&current[U32_D_BITS_RANGE]);
+    let e = combine_bits(&current[U32_E_BITS_RANGE]);
+
+    // 2^a is computed from the lower 5 bits of a in three steps: bits 0 and 1, then bits 2 and 3,
+    // and then bit 4
+    let powers = &current[U32_POWERS_RANGE];
+    let power = powers[2];
+    result[9] = are_equal(
+        powers[0],
+        (E::ONE + a_bits[0]) * (E::ONE + E::from(3u32) * a_bits[1]),
+    );
+    result[10] = are_equal(
+        powers[1],
+        powers[0] * (E::ONE + E::from(15u32) * a_bits[2]) * (E::ONE + E::from(255u32) * a_bits[3]),
+    );
+    result[11] = are_equal(power, powers[1] * (E::ONE + E::from(65535u32) * a_bits[4]));
+
+    // operands are the top two items of the stack, and the result is at the top of the stack
+    // after the operation
+    let old_stack = transition.current().user_stack();
+    let new_stack = transition.next().user_stack();
+    result[12] = selector_sum * are_equal(a, old_stack[0]);
+    result[13] = selector_sum * are_equal(b, old_stack[1]);
+    result[14] = selector_sum * are_equal(c, new_stack[0]);
+
+    // results of operations; at most one of the selectors is set, and thus, the relations of all
+    // operations can be combined into a single constraint
+    let s = |op: U32Op| selectors[op.selector_index()];
+    let two_32 = E::from(1u128 << U32_BITS);
+    let mut and = E::ZERO;
+    let mut xor = E::ZERO;
+    let mut power_of_two = E::ONE;
+    for (&a_bit, &b_bit) in a_bits.iter().zip(b_bits.iter()) {
+        let and_bit = a_bit * b_bit;
+        and += and_bit * power_of_two;
+        xor += (a_bit + b_bit - and_bit.double()) * power_of_two;
+        power_of_two = power_of_two.double();
+    }
+    result[15] = s(U32Op::Add) * are_equal(a + b, c + d * two_32)
+        + s(U32Op::Sub) * are_equal(b + d * two_32, a + c)
+        + s(U32Op::Mul) * are_equal(a * b, c + d * two_32)
+        + s(U32Op::Div) * are_equal(b, c * a + d)
+        + s(U32Op::And) * are_equal(c, and)
+        + s(U32Op::Xor) * are_equal(c, xor)
+        + s(U32Op::Shl) * are_equal(b * power, c + d * two_32)
+        + s(U32Op::Shr) * are_equal(b, c * power + d);
+
+    // carries and borrows are binary, and remainders are smaller than divisors
+    result[16] = (s(U32Op::Add) + s(U32Op::Sub)) * is_binary(d)
+        + s(U32Op::Div) * are_equal(a, d + e + E::ONE)
+        + s(U32Op::Shr) * are_equal(power, d + e + E::ONE);
+
+    // shift amounts are smaller than 32, and thus, 2^a is computed from all bits of a
+    result[17] = (s(U32Op::Shl) + s(U32Op::Shr)) * combine_bits(&a_bits[5..]);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the value of the binary decomposition held in `bits`, least significant bit first.
+fn combine_bits<E: FieldElement>(bits: &[E]) -> E {
+    bits.iter()
+        .rev()
+        .fold(E::ZERO, |value, &bit| value.double() + bit)
+}
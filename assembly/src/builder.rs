@@ -0,0 +1,498 @@
+use super::{
+    add_span, build_empty_false_branch, parse_op_token, AssemblyError, HintMap, LocationMap, OpCode,
+};
+use vm_core::{
+    program::{
+        blocks::{Call, Group, Loop, ProgramBlock, Repeat, Switch},
+        Program,
+    },
+    BaseElement,
+};
+use winter_utils::collections::BTreeMap;
+
+// PROGRAM BUILDER
+// ================================================================================================
+
+/// Builds programs from typed instructions instead of assembly source code.
+///
+/// Every method of the builder corresponds to an instruction of Miden assembly, and is compiled
+/// by the same parsers as the instruction in the source code. Thus, the resulting program has
+/// exactly the same structure (including alignment padding) and hash as the program compiled
+/// from equivalent source code. For example, the program below is the same as the program
+/// compiled from `begin push.3 push.5 add while.true dup.1 add end end`:
+/// ```
+/// use miden_assembly::ProgramBuilder;
+///
+/// let program = ProgramBuilder::new()
+///     .push(3)
+///     .push(5)
+///     .add()
+///     .while_true(|body| body.dup(1).add())
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// Invalid instructions (e.g. `dup(7)`) and empty blocks do not panic; instead, the first error
+/// encountered while building the program is returned by [ProgramBuilder::build()]. The step of
+/// the error is the position of the instruction in the program, with `begin` at position 0, and
+/// every block taking up positions for its head and its `end` (and `else`, if present).
+pub struct ProgramBuilder {
+    body: Vec<ProgramBlock>,
+    op_codes: Vec<OpCode>,
+    op_hints: HintMap,
+    step: usize,
+    error: Option<AssemblyError>,
+}
+
+impl ProgramBuilder {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a builder for a new program without any instructions.
+    pub fn new() -> Self {
+        Self::with_head(vec![OpCode::Begin], 1)
+    }
+
+    /// Returns a builder for a block body which starts with the specified operations; `step` is
+    /// the position of the first instruction of the body.
+    fn with_head(op_codes: Vec<OpCode>, step: usize) -> Self {
+        ProgramBuilder {
+            body: Vec::new(),
+            op_codes,
+            op_hints: BTreeMap::new(),
+            step,
+            error: None,
+        }
+    }
+
+    // STACK MANIPULATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Appends a `noop` instruction.
+    pub fn noop(self) -> Self {
+        self.instruction(&["noop"])
+    }
+
+    /// Appends an `assert` instruction.
+    pub fn assert(self) -> Self {
+        self.instruction(&["assert"])
+    }
+
+    /// Appends an `assert.eq` instruction.
+    pub fn assert_eq(self) -> Self {
+        self.instruction(&["assert", "eq"])
+    }
+
+    /// Appends a `push.value` instruction.
+    pub fn push(self, value: u128) -> Self {
+        self.instruction(&["push", &value.to_string()])
+    }
+
+    /// Appends a `read` instruction which reads a value from tape A.
+    pub fn read(self) -> Self {
+        self.instruction(&["read"])
+    }
+
+    /// Appends a `read.ab` instruction which reads a value from each of tapes A and B.
+    pub fn read_ab(self) -> Self {
+        self.instruction(&["read", "ab"])
+    }
+
+    /// Appends a `dup.n` instruction.
+    pub fn dup(self, n: usize) -> Self {
+        self.instruction(&["dup", &n.to_string()])
+    }
+
+    /// Appends a `pad.n` instruction.
+    pub fn pad(self, n: usize) -> Self {
+        self.instruction(&["pad", &n.to_string()])
+    }
+
+    /// Appends a `pick.n` instruction.
+    pub fn pick(self, n: usize) -> Self {
+        self.instruction(&["pick", &n.to_string()])
+    }
+
+    /// Appends a `drop.n` instruction.
+    pub fn drop(self, n: usize) -> Self {
+        self.instruction(&["drop", &n.to_string()])
+    }
+
+    /// Appends a `swap.n` instruction.
+    pub fn swap(self, n: usize) -> Self {
+        self.instruction(&["swap", &n.to_string()])
+    }
+
+    /// Appends a `roll.n` instruction.
+    pub fn roll(self, n: usize) -> Self {
+        self.instruction(&["roll", &n.to_string()])
+    }
+
+    /// Appends a `choose.n` instruction.
+    pub fn choose(self, n: usize) -> Self {
+        self.instruction(&["choose", &n.to_string()])
+    }
+
+    // ARITHMETIC AND BOOLEAN OPERATIONS
+    // --------------------------------------------------------------------------------------------
+
+    /// Appends an `add` instruction.
+    pub fn add(self) -> Self {
+        self.instruction(&["add"])
+    }
+
+    /// Appends a `sub` instruction.
+    pub fn sub(self) -> Self {
+        self.instruction(&["sub"])
+    }
+
+    /// Appends a `mul` instruction.
+    pub fn mul(self) -> Self {
+        self.instruction(&["mul"])
+    }
+
+    /// Appends a `div` instruction.
+    pub fn div(self) -> Self {
+        self.instruction(&["div"])
+    }
+
+    /// Appends a `neg` instruction.
+    #[allow(clippy::should_implement_trait)]
+    pub fn neg(self) -> Self {
+        self.instruction(&["neg"])
+    }
+
+    /// Appends an `inv` instruction.
+    pub fn inv(self) -> Self {
+        self.instruction(&["inv"])
+    }
+
+    /// Appends a `not` instruction.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        self.instruction(&["not"])
+    }
+
+    /// Appends an `and` instruction.
+    pub fn and(self) -> Self {
+        self.instruction(&["and"])
+    }
+
+    /// Appends an `or` instruction.
+    pub fn or(self) -> Self {
+        self.instruction(&["or"])
+    }
+
+    // COMPARISON OPERATIONS
+    // --------------------------------------------------------------------------------------------
+
+    /// Appends an `eq` instruction.
+    pub fn eq(self) -> Self {
+        self.instruction(&["eq"])
+    }
+
+    /// Appends a `ne` instruction.
+    pub fn ne(self) -> Self {
+        self.instruction(&["ne"])
+    }
+
+    /// Appends a `gt.n` instruction which compares values of at most `n` bits.
+    pub fn gt(self, n: u32) -> Self {
+        self.instruction(&["gt", &n.to_string()])
+    }
+
+    /// Appends an `lt.n` instruction which compares values of at most `n` bits.
+    pub fn lt(self, n: u32) -> Self {
+        self.instruction(&["lt", &n.to_string()])
+    }
+
+    /// Appends an `rc.n` instruction which checks that the top value fits into `n` bits.
+    pub fn rc(self, n: u32) -> Self {
+        self.instruction(&["rc", &n.to_string()])
+    }
+
+    /// Appends an `isodd.n` instruction for a value of at most `n` bits.
+    pub fn isodd(self, n: u32) -> Self {
+        self.instruction(&["isodd", &n.to_string()])
+    }
+
+    // U32 OPERATIONS
+    // --------------------------------------------------------------------------------------------
+
+    /// Appends a `u32add` instruction.
+    pub fn u32add(self) -> Self {
+        self.instruction(&["u32add"])
+    }
+
+    /// Appends a `u32sub` instruction.
+    pub fn u32sub(self) -> Self {
+        self.instruction(&["u32sub"])
+    }
+
+    /// Appends a `u32mul` instruction.
+    pub fn u32mul(self) -> Self {
+        self.instruction(&["u32mul"])
+    }
+
+    /// Appends a `u32div` instruction.
+    pub fn u32div(self) -> Self {
+        self.instruction(&["u32div"])
+    }
+
+    /// Appends a `u32and` instruction.
+    pub fn u32and(self) -> Self {
+        self.instruction(&["u32and"])
+    }
+
+    /// Appends a `u32xor` instruction.
+    pub fn u32xor(self) -> Self {
+        self.instruction(&["u32xor"])
+    }
+
+    /// Appends a `u32shl` instruction.
+    pub fn u32shl(self) -> Self {
+        self.instruction(&["u32shl"])
+    }
+
+    /// Appends a `u32shr` instruction.
+    pub fn u32shr(self) -> Self {
+        self.instruction(&["u32shr"])
+    }
+
+    // MEMORY AND STORAGE OPERATIONS
+    // --------------------------------------------------------------------------------------------
+
+    /// Appends a `mem.load` instruction.
+    pub fn mem_load(self) -> Self {
+        self.instruction(&["mem", "load"])
+    }
+
+    /// Appends a `mem.store` instruction.
+    pub fn mem_store(self) -> Self {
+        self.instruction(&["mem", "store"])
+    }
+
+    /// Appends a `storage.read` instruction.
+    pub fn storage_read(self) -> Self {
+        self.instruction(&["storage", "read"])
+    }
+
+    /// Appends a `storage.write` instruction.
+    pub fn storage_write(self) -> Self {
+        self.instruction(&["storage", "write"])
+    }
+
+    // CRYPTOGRAPHIC OPERATIONS
+    // --------------------------------------------------------------------------------------------
+
+    /// Appends a `hash.n` instruction which hashes the top `n` values of the stack.
+    pub fn hash(self, n: usize) -> Self {
+        self.instruction(&["hash", &n.to_string()])
+    }
+
+    /// Appends an `smpath.n` instruction which computes the root of a Merkle tree of depth `n`.
+    pub fn smpath(self, n: usize) -> Self {
+        self.instruction(&["smpath", &n.to_string()])
+    }
+
+    /// Appends a `pmpath.n` instruction which computes the root of a Merkle tree of depth `n`
+    /// for a node at a position which is not known to the verifier.
+    pub fn pmpath(self, n: usize) -> Self {
+        self.instruction(&["pmpath", &n.to_string()])
+    }
+
+    // CONTROL FLOW
+    // --------------------------------------------------------------------------------------------
+
+    /// Appends a `block` statement; the body of the block is built by `body`.
+    pub fn block<B>(mut self, body: B) -> Self
+    where
+        B: FnOnce(ProgramBuilder) -> ProgramBuilder,
+    {
+        self.close_span();
+        if let Some(body) = self.build_branch(&["block"], vec![], body) {
+            self.body.push(Group::new_block(body));
+        }
+        self
+    }
+
+    /// Appends an `if.true` statement without an `else` branch; the body of the true branch is
+    /// built by `t_branch`.
+    pub fn if_true<T>(mut self, t_branch: T) -> Self
+    where
+        T: FnOnce(ProgramBuilder) -> ProgramBuilder,
+    {
+        self.close_span();
+        if let Some(t_branch) = self.build_branch(&["if", "true"], vec![OpCode::Assert], t_branch) {
+            let f_branch = vec![build_empty_false_branch()];
+            self.body.push(Switch::new_block(t_branch, f_branch));
+        }
+        self
+    }
+
+    /// Appends an `if.true` statement with an `else` branch; bodies of the true and the false
+    /// branches are built by `t_branch` and `f_branch` respectively.
+    pub fn if_else<T, F>(mut self, t_branch: T, f_branch: F) -> Self
+    where
+        T: FnOnce(ProgramBuilder) -> ProgramBuilder,
+        F: FnOnce(ProgramBuilder) -> ProgramBuilder,
+    {
+        self.close_span();
+        let t_branch = self.build_branch(&["if", "true"], vec![OpCode::Assert], t_branch);
+        // `else` terminates the true branch and starts the false branch at the same step
+        self.step -= 1;
+        let f_branch = self.build_branch(&["else"], vec![OpCode::Not, OpCode::Assert], f_branch);
+        if let (Some(t_branch), Some(f_branch)) = (t_branch, f_branch) {
+            self.body.push(Switch::new_block(t_branch, f_branch));
+        }
+        self
+    }
+
+    /// Appends a `repeat.n` statement; the body which is repeated `num_iterations` times is built
+    /// by `body`. The number of iterations must be between 2 and [u32::MAX].
+    pub fn repeat<B>(mut self, num_iterations: usize, body: B) -> Self
+    where
+        B: FnOnce(ProgramBuilder) -> ProgramBuilder,
+    {
+        let num_iterations_str = num_iterations.to_string();
+        let head = ["repeat", num_iterations_str.as_str()];
+        if num_iterations < 2 || num_iterations > u32::MAX as usize {
+            self.set_error(AssemblyError::invalid_num_iterations(&head, self.step));
+        }
+
+        self.close_span();
+        if let Some(body) = self.build_branch(&head, vec![], body) {
+            if self.error.is_none() {
+                self.body.push(Repeat::new_block(body, num_iterations));
+            }
+        }
+        self
+    }
+
+    /// Appends a `while.true` statement; the body of the loop is built by `body`.
+    pub fn while_true<B>(mut self, body: B) -> Self
+    where
+        B: FnOnce(ProgramBuilder) -> ProgramBuilder,
+    {
+        self.close_span();
+        if let Some(body) = self.build_branch(&["while", "true"], vec![OpCode::Assert], body) {
+            self.body.push(Loop::new_block(body));
+        }
+        self
+    }
+
+    /// Appends a `call` instruction which executes the library block with the specified hash.
+    pub fn call(mut self, hash: BaseElement) -> Self {
+        self.close_span();
+        self.body.push(Call::new_block(hash));
+        self.step += 1;
+        self
+    }
+
+    // PROGRAM BUILDER
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the program built by this builder, or the first error encountered while building
+    /// it.
+    pub fn build(mut self) -> Result<Program, AssemblyError> {
+        if self.step == 1 {
+            self.set_error(AssemblyError::empty_block(&["begin"], 0));
+        }
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        add_span(
+            &mut self.body,
+            &mut self.op_codes,
+            &mut self.op_hints,
+            &mut LocationMap::new(),
+            false,
+        );
+        Ok(Program::new(Group::new(self.body)))
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Compiles the specified instruction and appends the resulting operations to the current
+    /// Span block; once an error has been encountered, all other instructions are ignored.
+    fn instruction(mut self, op: &[&str]) -> Self {
+        if self.error.is_none() {
+            if let Err(error) = parse_op_token(
+                op.to_vec(),
+                &mut self.op_codes,
+                &mut self.op_hints,
+                self.step,
+            ) {
+                self.error = Some(error);
+            }
+        }
+        self.step += 1;
+        self
+    }
+
+    /// Closes the current Span block before a nested block is appended to the body; if the
+    /// nested block is the first block of the body, an empty Span block is added before it.
+    fn close_span(&mut self) {
+        let force_span = self.body.is_empty();
+        add_span(
+            &mut self.body,
+            &mut self.op_codes,
+            &mut self.op_hints,
+            &mut LocationMap::new(),
+            force_span,
+        );
+    }
+
+    /// Builds the body of a nested block with the specified `head` instruction; the body starts
+    /// with `head_ops` and continues with instructions appended by `build`.
+    ///
+    /// Returns None if an error has been encountered while building the body; the error is
+    /// recorded in this builder unless this builder has already encountered an error.
+    fn build_branch<B>(
+        &mut self,
+        head: &[&str],
+        head_ops: Vec<OpCode>,
+        build: B,
+    ) -> Option<Vec<ProgramBlock>>
+    where
+        B: FnOnce(ProgramBuilder) -> ProgramBuilder,
+    {
+        let first_step = self.step;
+        let mut branch = build(ProgramBuilder::with_head(head_ops, first_step + 1));
+
+        // the head and the terminating instruction (`end` or `else`) of the block take up a step
+        // each; a block must contain at least one other instruction
+        self.step = branch.step + 1;
+        if branch.step == first_step + 1 {
+            branch.set_error(AssemblyError::empty_block(head, first_step));
+        }
+        if let Some(error) = branch.error {
+            self.set_error(error);
+            return None;
+        }
+
+        add_span(
+            &mut branch.body,
+            &mut branch.op_codes,
+            &mut branch.op_hints,
+            &mut LocationMap::new(),
+            false,
+        );
+        Some(branch.body)
+    }
+
+    /// Records the specified error unless an error has already been recorded.
+    fn set_error(&mut self, error: AssemblyError) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+}
+
+impl Default for ProgramBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,321 @@
+use super::{
+    parse_op_token, HintMap, OpCode, OpHint, Program, ProgramBlock, Span, StarkField,
+    BASE_CYCLE_LENGTH,
+};
+
+// CONSTANTS
+// ================================================================================================
+
+const INDENT: &str = "    ";
+
+/// Instructions which either have no parameters, or have parameters with a small set of valid
+/// values.
+const FIXED_INSTRUCTIONS: [&str; 50] = [
+    "noop",
+    "assert",
+    "assert.eq",
+    "read",
+    "read.ab",
+    "dup",
+    "dup.2",
+    "dup.3",
+    "dup.4",
+    "pad",
+    "pad.2",
+    "pad.3",
+    "pad.4",
+    "pad.5",
+    "pad.6",
+    "pad.7",
+    "pad.8",
+    "pick",
+    "pick.2",
+    "pick.3",
+    "drop",
+    "drop.2",
+    "drop.3",
+    "drop.4",
+    "drop.5",
+    "drop.6",
+    "drop.7",
+    "drop.8",
+    "swap",
+    "swap.2",
+    "swap.4",
+    "roll.4",
+    "roll.8",
+    "add",
+    "sub",
+    "mul",
+    "div",
+    "neg",
+    "inv",
+    "not",
+    "and",
+    "or",
+    "eq",
+    "ne",
+    "choose",
+    "choose.2",
+    "hash",
+    "hash.2",
+    "hash.3",
+    "hash.4",
+];
+
+/// Parametrized instructions (e.g. `push` or `gt`) are identified by the hints which the
+/// assembler places within this many steps from the start of the instruction.
+const MAX_HINT_OFFSET: usize = 16;
+
+/// Maximum depth of a Merkle tree supported by `smpath` instruction.
+const MAX_SMPATH_DEPTH: usize = 256;
+
+// DISASSEMBLER
+// ================================================================================================
+
+/// Transforms a program back into Miden assembly source code.
+///
+/// Sequences of VM operations generated by the assembler for macro instructions (e.g. `gt`,
+/// `hash`, `smpath`) are collapsed back into these instructions, and `noop`'s inserted by the
+/// assembler to align instructions are removed. For programs produced by the assembler, the
+/// returned source code compiles into a program with the same hash as the original program.
+///
+/// Control blocks are transformed into `block`, `if.true`, `while.true`, `repeat`, and `call`
+/// statements. Operations which cannot be expressed in Miden assembly (this can happen only for
+/// programs which were not produced by the assembler) are emitted as comments; similarly, a
+/// Repeat block with a single iteration is emitted as a `repeat.1` statement, which the
+/// assembler does not accept.
+pub fn disassemble(program: &Program) -> String {
+    let mut source = String::new();
+    write_line(&mut source, 0, "begin");
+    write_blocks(&mut source, program.root().body(), &[OpCode::Begin], 1);
+    write_line(&mut source, 0, "end");
+    source
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Writes source code for a sequence of blocks; `prefix` is the sequence of operations which
+/// the assembler places at the start of the first block in the sequence.
+fn write_blocks(source: &mut String, blocks: &[ProgramBlock], prefix: &[OpCode], depth: usize) {
+    for (i, block) in blocks.iter().enumerate() {
+        match block {
+            ProgramBlock::Span(block) => {
+                let prefix = if i == 0 { prefix } else { &[] };
+                for instruction in disassemble_span(block, prefix, i == 0) {
+                    write_line(source, depth, &instruction);
+                }
+            }
+            ProgramBlock::Group(block) => {
+                write_line(source, depth, "block");
+                write_blocks(source, block.body(), &[], depth + 1);
+                write_line(source, depth, "end");
+            }
+            ProgramBlock::Switch(block) => {
+                write_line(source, depth, "if.true");
+                write_blocks(source, block.true_branch(), &[OpCode::Assert], depth + 1);
+                // the assembler creates a trivial false branch for if statements without else
+                if !is_empty_branch(block.false_branch()) {
+                    write_line(source, depth, "else");
+                    let prefix = [OpCode::Not, OpCode::Assert];
+                    write_blocks(source, block.false_branch(), &prefix, depth + 1);
+                }
+                write_line(source, depth, "end");
+            }
+            ProgramBlock::Loop(block) => {
+                write_line(source, depth, "while.true");
+                write_blocks(source, block.body(), &[OpCode::Assert], depth + 1);
+                write_line(source, depth, "end");
+            }
+            ProgramBlock::Repeat(block) => {
+                let head = format!("repeat.{}", block.num_iterations());
+                write_line(source, depth, &head);
+                write_blocks(source, block.body(), &[], depth + 1);
+                write_line(source, depth, "end");
+            }
+            ProgramBlock::Call(block) => {
+                let instruction = format!("call.{:#x}", block.callee_hash().as_int());
+                write_line(source, depth, &instruction);
+            }
+        }
+    }
+}
+
+/// Transforms operations of a Span block into a sequence of assembly instructions.
+///
+/// At every step, all instructions which could start at this step are expanded using the
+/// assembler itself, and the instruction with the longest expansion matching operations (and
+/// hints) of the span is selected. This guarantees that the selected instructions compile back
+/// into the same operations.
+fn disassemble_span(span: &Span, prefix: &[OpCode], is_first: bool) -> Vec<String> {
+    let ops = get_span_ops(span);
+    let hints = (0..span.length())
+        .filter_map(|i| match span.get_hint(i) {
+            OpHint::None => None,
+            hint => Some((i, hint)),
+        })
+        .collect::<HintMap>();
+
+    let mut instructions = Vec::new();
+    let mut step = if ops.starts_with(prefix) {
+        prefix.len()
+    } else {
+        0
+    };
+
+    // the assembler does not create Span blocks without instructions unless the span is the
+    // first block in a sequence; so, only the first span can end right after the prefix
+    while !is_span_end(&ops, step) || (step == 0 && !is_first) {
+        match match_instruction(&ops, &hints, step) {
+            Some((instruction, next_step)) => {
+                instructions.push(instruction);
+                step = next_step;
+            }
+            None => {
+                instructions.push(format!("# unsupported operation: {}", ops[step]));
+                step += 1;
+            }
+        }
+    }
+
+    instructions
+}
+
+/// Returns the instruction which expands into the longest sequence of operations and hints
+/// matching the span at the specified step, together with the step following the instruction.
+fn match_instruction(ops: &[OpCode], hints: &HintMap, step: usize) -> Option<(String, usize)> {
+    let mut result: Option<(String, usize)> = None;
+    let mut best_step = step;
+    for instruction in get_candidates(ops, hints, step) {
+        // instructions are aligned based on their position within a cycle; so, it is enough
+        // to expand an instruction after a sequence of operations of the same length modulo
+        // cycle length
+        let offset = step % BASE_CYCLE_LENGTH;
+        let mut expansion = vec![OpCode::Noop; offset];
+        let mut expansion_hints = HintMap::new();
+        let op = instruction.split('.').collect::<Vec<_>>();
+        if parse_op_token(op, &mut expansion, &mut expansion_hints, 0).is_err() {
+            continue;
+        }
+
+        let next_step = step + expansion.len() - offset;
+        if next_step > ops.len() || ops[step..next_step] != expansion[offset..] {
+            continue;
+        }
+
+        let expected_hints = hints.range(step..next_step).map(|(&i, &hint)| (i, hint));
+        let actual_hints = expansion_hints
+            .iter()
+            .map(|(&i, &hint)| (i - offset + step, hint));
+        if !expected_hints.eq(actual_hints) {
+            continue;
+        }
+
+        if result.is_none() || next_step > best_step {
+            result = Some((instruction, next_step));
+            best_step = next_step;
+        }
+    }
+
+    result
+}
+
+/// Returns a list of instructions which could start at the specified step of the span.
+fn get_candidates(ops: &[OpCode], hints: &HintMap, step: usize) -> Vec<String> {
+    let mut candidates = FIXED_INSTRUCTIONS
+        .iter()
+        .map(|instruction| instruction.to_string())
+        .collect::<Vec<_>>();
+
+    for (_, hint) in hints.range(step..step + MAX_HINT_OFFSET) {
+        match hint {
+            OpHint::PushValue(value) => candidates.push(format!("push.{}", value.as_int())),
+            OpHint::CmpStart(n) => {
+                candidates.push(format!("gt.{}", n));
+                candidates.push(format!("lt.{}", n));
+            }
+            OpHint::RcStart(n) => {
+                candidates.push(format!("rc.{}", n));
+                candidates.push(format!("isodd.{}", n));
+            }
+            OpHint::PmpathStart(n) => candidates.push(format!("pmpath.{}", n)),
+            OpHint::U32Op(op) => candidates.push(format!("u32{}", op)),
+            OpHint::StorageRead => candidates.push("storage.read".to_string()),
+            OpHint::StorageWrite => candidates.push("storage.write".to_string()),
+            OpHint::MemoryRead => candidates.push("mem.load".to_string()),
+            OpHint::MemoryWrite => candidates.push("mem.store".to_string()),
+            OpHint::EqStart | OpHint::None => (),
+        }
+    }
+
+    if let Some(depth) = get_smpath_depth(ops, step) {
+        candidates.push(format!("smpath.{}", depth));
+    }
+
+    candidates
+}
+
+/// Returns the depth of the Merkle tree if operations at the specified step look like an
+/// expansion of `smpath` instruction.
+fn get_smpath_depth(ops: &[OpCode], step: usize) -> Option<usize> {
+    let head = [
+        OpCode::Read2,
+        OpCode::Swap2,
+        OpCode::Read2,
+        OpCode::CSwap2,
+        OpCode::Pad2,
+    ];
+    if !ops[step..].starts_with(&head) {
+        return None;
+    }
+
+    // skip the head of the instruction and NOOPs aligning hashing to the cycle boundary; then,
+    // count the number of full hashing cycles
+    let mut i = step + head.len();
+    i += (BASE_CYCLE_LENGTH - i % BASE_CYCLE_LENGTH) % BASE_CYCLE_LENGTH;
+    let mut depth = 2;
+    while i + BASE_CYCLE_LENGTH <= ops.len()
+        && ops[i] == OpCode::RescR
+        && ops[i + BASE_CYCLE_LENGTH - 1] == OpCode::Pad2
+    {
+        depth += 1;
+        i += BASE_CYCLE_LENGTH;
+    }
+
+    if depth <= MAX_SMPATH_DEPTH {
+        Some(depth)
+    } else {
+        None
+    }
+}
+
+/// Returns true if all operations starting with the specified step are NOOPs which the
+/// assembler would add to align the span.
+fn is_span_end(ops: &[OpCode], step: usize) -> bool {
+    let pad_length = BASE_CYCLE_LENGTH - (step % BASE_CYCLE_LENGTH) - 1;
+    ops.len() == step + pad_length && ops[step..].iter().all(|&op| op == OpCode::Noop)
+}
+
+/// Returns true if the branch consists of a single Span block which the assembler creates for
+/// the false branch of an if statement without an else clause.
+fn is_empty_branch(blocks: &[ProgramBlock]) -> bool {
+    match blocks {
+        [ProgramBlock::Span(span)] => {
+            span.starts_with(&[OpCode::Not, OpCode::Assert]) && is_span_end(&get_span_ops(span), 2)
+        }
+        _ => false,
+    }
+}
+
+fn get_span_ops(span: &Span) -> Vec<OpCode> {
+    (0..span.length()).map(|i| span.get_op(i).0).collect()
+}
+
+fn write_line(source: &mut String, depth: usize, line: &str) {
+    for _ in 0..depth {
+        source.push_str(INDENT);
+    }
+    source.push_str(line);
+    source.push('\n');
+}
@@ -1,5 +1,59 @@
 use core::fmt;
 
+// SOURCE SPAN
+// ================================================================================================
+
+/// Location of a token in the assembly source code; lines and columns are 1-based, and columns
+/// are measured in characters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SourceSpan {
+    line: usize,
+    column: usize,
+    length: usize,
+}
+
+impl SourceSpan {
+    pub fn new(line: usize, column: usize, length: usize) -> Self {
+        SourceSpan {
+            line,
+            column,
+            length,
+        }
+    }
+
+    /// Returns the line on which the token is located.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the column at which the token starts.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns the number of characters in the token.
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Returns true if the span does not cover any characters.
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+}
+
+impl Default for SourceSpan {
+    fn default() -> Self {
+        SourceSpan::new(1, 1, 0)
+    }
+}
+
+impl fmt::Display for SourceSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
 // ASSEMBLY ERROR
 // ================================================================================================
 
@@ -7,6 +61,7 @@ pub struct AssemblyError {
     message: String,
     step: usize,
     op: String,
+    span: SourceSpan,
 }
 
 impl AssemblyError {
@@ -18,6 +73,7 @@ impl AssemblyError {
             message: String::from("a program must contain at least one instruction"),
             step: 0,
             op: String::from("begin"),
+            span: SourceSpan::default(),
         }
     }
 
@@ -26,30 +82,103 @@ impl AssemblyError {
             message: String::from("a program block must contain at least one instruction"),
             step,
             op: op.join("."),
+            span: SourceSpan::default(),
         }
     }
 
-    pub fn invalid_program_start(op: &str) -> AssemblyError {
+    pub fn invalid_program_start(op: &str, step: usize) -> AssemblyError {
         AssemblyError {
             message: String::from("a program must start with a 'being' instruction"),
-            step: 0,
+            step,
+            op: String::from(op),
+            span: SourceSpan::default(),
+        }
+    }
+
+    pub fn invalid_library_block_start(op: &str, step: usize) -> AssemblyError {
+        AssemblyError {
+            message: String::from("a library block must start with a 'block' instruction"),
+            step,
             op: String::from(op),
+            span: SourceSpan::default(),
         }
     }
 
-    pub fn invalid_program_end(op: &str) -> AssemblyError {
+    pub fn invalid_program_end(op: &str, step: usize) -> AssemblyError {
         AssemblyError {
             message: String::from("a program must end with an 'end' instruction"),
-            step: 0,
+            step,
             op: String::from(op),
+            span: SourceSpan::default(),
         }
     }
 
-    pub fn dangling_instructions(step: usize) -> AssemblyError {
+    pub fn missing_program_body(step: usize) -> AssemblyError {
         AssemblyError {
-            message: "dangling instructions after program end".to_string(),
+            message: String::from("procedure definitions must be followed by a program body"),
             step,
             op: String::from("end"),
+            span: SourceSpan::default(),
+        }
+    }
+
+    pub fn undefined_module(path: &str, step: usize) -> AssemblyError {
+        AssemblyError {
+            message: format!("module '{}' could not be found", path),
+            step,
+            op: format!("use.{}", path),
+            span: SourceSpan::default(),
+        }
+    }
+
+    pub fn duplicate_import(path: &str, step: usize) -> AssemblyError {
+        AssemblyError {
+            message: format!(
+                "module '{}' is already imported or has the same name as an imported module",
+                path
+            ),
+            step,
+            op: format!("use.{}", path),
+            span: SourceSpan::default(),
+        }
+    }
+
+    pub fn invalid_module(path: &str, step: usize, error: &AssemblyError) -> AssemblyError {
+        AssemblyError {
+            message: format!(
+                "module '{}' is invalid: {} at {}",
+                path, error.message, error.span
+            ),
+            step,
+            op: format!("use.{}", path),
+            span: SourceSpan::default(),
+        }
+    }
+
+    pub fn invalid_module_item(op: &str, step: usize) -> AssemblyError {
+        AssemblyError {
+            message: "a module can contain only procedure definitions".to_string(),
+            step,
+            op: String::from(op),
+            span: SourceSpan::default(),
+        }
+    }
+
+    pub fn unterminated_comment(step: usize) -> AssemblyError {
+        AssemblyError {
+            message: "block comment without matching */".to_string(),
+            step,
+            op: String::from("/*"),
+            span: SourceSpan::default(),
+        }
+    }
+
+    pub fn dangling_instructions(op: &str, step: usize) -> AssemblyError {
+        AssemblyError {
+            message: "dangling instructions after program end".to_string(),
+            step,
+            op: String::from(op),
+            span: SourceSpan::default(),
         }
     }
 
@@ -58,6 +187,7 @@ impl AssemblyError {
             message: format!("instruction {} is invalid", op.join(".")),
             step,
             op: op.join("."),
+            span: SourceSpan::default(),
         }
     }
 
@@ -66,6 +196,7 @@ impl AssemblyError {
             message: format!("malformed instruction {}: parameter is missing", op[0]),
             step,
             op: op.join("."),
+            span: SourceSpan::default(),
         }
     }
 
@@ -77,6 +208,7 @@ impl AssemblyError {
             ),
             step,
             op: op.join("."),
+            span: SourceSpan::default(),
         }
     }
 
@@ -88,6 +220,7 @@ impl AssemblyError {
             ),
             step,
             op: op.join("."),
+            span: SourceSpan::default(),
         }
     }
 
@@ -96,6 +229,7 @@ impl AssemblyError {
             message: format!("malformed instruction {}: {}", op[0], reason),
             step,
             op: op.join("."),
+            span: SourceSpan::default(),
         }
     }
 
@@ -104,17 +238,20 @@ impl AssemblyError {
             message: format!("invalid block head '{}'", op.join(".")),
             step,
             op: op.join("."),
+            span: SourceSpan::default(),
         }
     }
 
     pub fn invalid_num_iterations(op: &[&str], step: usize) -> AssemblyError {
         AssemblyError {
             message: format!(
-                "invalid repeat statement '{}': 2 or more iterations must be specified",
-                op.join(".")
+                "invalid repeat statement '{}': between 2 and {} iterations must be specified",
+                op.join("."),
+                u32::MAX
             ),
             step,
             op: op.join("."),
+            span: SourceSpan::default(),
         }
     }
 
@@ -123,6 +260,7 @@ impl AssemblyError {
             message: "else without matching if".to_string(),
             step,
             op: String::from("else"),
+            span: SourceSpan::default(),
         }
     }
 
@@ -131,6 +269,7 @@ impl AssemblyError {
             message: "block without matching end".to_string(),
             step,
             op: String::from("block"),
+            span: SourceSpan::default(),
         }
     }
 
@@ -139,6 +278,7 @@ impl AssemblyError {
             message: "if without matching else/end".to_string(),
             step,
             op: String::from("if.true"),
+            span: SourceSpan::default(),
         }
     }
 
@@ -147,6 +287,7 @@ impl AssemblyError {
             message: "while without matching end".to_string(),
             step,
             op: String::from("while.true"),
+            span: SourceSpan::default(),
         }
     }
 
@@ -155,6 +296,7 @@ impl AssemblyError {
             message: "repeat without matching end".to_string(),
             step,
             op: op.join("."),
+            span: SourceSpan::default(),
         }
     }
 
@@ -163,6 +305,52 @@ impl AssemblyError {
             message: "else without matching end".to_string(),
             step,
             op: String::from("else"),
+            span: SourceSpan::default(),
+        }
+    }
+
+    pub fn unmatched_proc(step: usize, op: &[&str]) -> AssemblyError {
+        AssemblyError {
+            message: "proc without matching end".to_string(),
+            step,
+            op: op.join("."),
+            span: SourceSpan::default(),
+        }
+    }
+
+    pub fn nested_proc(op: &[&str], step: usize) -> AssemblyError {
+        AssemblyError {
+            message: "procedures can be defined only before the program body".to_string(),
+            step,
+            op: op.join("."),
+            span: SourceSpan::default(),
+        }
+    }
+
+    pub fn duplicate_proc(op: &[&str], step: usize) -> AssemblyError {
+        AssemblyError {
+            message: format!("procedure '{}' is already defined", op[1]),
+            step,
+            op: op.join("."),
+            span: SourceSpan::default(),
+        }
+    }
+
+    pub fn undefined_proc(op: &[&str], step: usize) -> AssemblyError {
+        AssemblyError {
+            message: format!("procedure '{}' is not defined", op[1]),
+            step,
+            op: op.join("."),
+            span: SourceSpan::default(),
+        }
+    }
+
+    pub fn recursive_proc(op: &[&str], step: usize) -> AssemblyError {
+        AssemblyError {
+            message: format!("procedure '{}' cannot be executed recursively", op[1]),
+            step,
+            op: op.join("."),
+            span: SourceSpan::default(),
         }
     }
 
@@ -179,6 +367,53 @@ impl AssemblyError {
     pub fn step(&self) -> usize {
         self.step
     }
+
+    /// Returns location of the offending token in the source code.
+    pub fn span(&self) -> &SourceSpan {
+        &self.span
+    }
+
+    // DIAGNOSTICS
+    // --------------------------------------------------------------------------------------------
+
+    /// Renders this error as a human-readable diagnostic which includes the line of the `source`
+    /// code on which the error occurred, with the offending token underlined.
+    ///
+    /// The `source` must be the same source code from which this error was produced.
+    pub fn render(&self, source: &str) -> String {
+        let mut result = format!("error: {}\n", self.message);
+
+        let line = match source.lines().nth(self.span.line - 1) {
+            Some(line) => line,
+            None => {
+                result.push_str(&format!(" --> {}\n", self.span));
+                return result;
+            }
+        };
+
+        // the gutter must be wide enough to fit the line number
+        let line_number = self.span.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+
+        result.push_str(&format!("{} --> {}\n", gutter, self.span));
+        result.push_str(&format!("{} |\n", gutter));
+        result.push_str(&format!("{} | {}\n", line_number, line));
+        result.push_str(&format!(
+            "{} | {}{}\n",
+            gutter,
+            " ".repeat(self.span.column - 1),
+            "^".repeat(usize::max(self.span.length, 1))
+        ));
+
+        result
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    pub(crate) fn set_span(&mut self, span: SourceSpan) {
+        self.span = span;
+    }
 }
 
 // COMMON TRAIT IMPLEMENTATIONS
@@ -186,12 +421,12 @@ impl AssemblyError {
 
 impl fmt::Debug for AssemblyError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "assembly error at {}: {}", self.step, self.message)
+        write!(f, "assembly error at {}: {}", self.span, self.message)
     }
 }
 
 impl fmt::Display for AssemblyError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "assembly error at {}: {}", self.step, self.message)
+        write!(f, "assembly error at {}: {}", self.span, self.message)
     }
 }
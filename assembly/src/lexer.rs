@@ -0,0 +1,160 @@
+use super::{AssemblyError, SourceSpan};
+
+// CONSTANTS
+// ================================================================================================
+
+const LINE_COMMENT_START: char = '#';
+const BLOCK_COMMENT_START: &str = "/*";
+const BLOCK_COMMENT_END: &str = "*/";
+
+// TOKEN
+// ================================================================================================
+
+/// A single instruction or block delimiter of Miden assembly source code together with its
+/// location in the source code.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Token<'a> {
+    text: &'a str,
+    span: SourceSpan,
+}
+
+impl<'a> Token<'a> {
+    /// Returns the text of this token.
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Returns the location of this token in the source code.
+    pub fn span(&self) -> SourceSpan {
+        self.span
+    }
+
+    /// Returns the text of this token split into dot-separated parts; for example, for token
+    /// `push.7` this returns `["push", "7"]`.
+    pub fn parts(&self) -> Vec<&'a str> {
+        self.text.split('.').collect()
+    }
+}
+
+// LEXER
+// ================================================================================================
+
+/// Breaks Miden assembly source code into a sequence of tokens.
+///
+/// Tokens are separated by whitespace and comments. Two types of comments are supported:
+/// * Line comments which start with `#` and run until the end of the line.
+/// * Block comments which start with `/*` and end with `*/`; block comments can span multiple
+///   lines, but cannot be nested.
+///
+/// Returns an error if a block comment is not terminated.
+pub fn tokenize(source: &str) -> Result<Vec<Token<'_>>, AssemblyError> {
+    let mut tokens = Vec::new();
+    let mut cursor = Cursor::new(source);
+
+    while let Some(c) = cursor.peek() {
+        if c.is_whitespace() {
+            cursor.advance();
+        } else if c == LINE_COMMENT_START {
+            while let Some(c) = cursor.advance() {
+                if c == '\n' {
+                    break;
+                }
+            }
+        } else if cursor.starts_with(BLOCK_COMMENT_START) {
+            let span = SourceSpan::new(cursor.line, cursor.column, BLOCK_COMMENT_START.len());
+            cursor.skip(BLOCK_COMMENT_START);
+            if !cursor.skip_past(BLOCK_COMMENT_END) {
+                let mut error = AssemblyError::unterminated_comment(tokens.len());
+                error.set_span(span);
+                return Err(error);
+            }
+        } else {
+            // read the token until whitespace or the start of a comment is encountered
+            let (start, line, column) = (cursor.offset, cursor.line, cursor.column);
+            while let Some(c) = cursor.peek() {
+                if c.is_whitespace()
+                    || c == LINE_COMMENT_START
+                    || cursor.starts_with(BLOCK_COMMENT_START)
+                {
+                    break;
+                }
+                cursor.advance();
+            }
+
+            tokens.push(Token {
+                text: &source[start..cursor.offset],
+                span: SourceSpan::new(line, column, cursor.column - column),
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+// CURSOR
+// ================================================================================================
+
+/// Keeps track of the current position in the source code both as a byte offset, and as a
+/// line and column (measured in characters).
+struct Cursor<'a> {
+    source: &'a str,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(source: &'a str) -> Self {
+        Cursor {
+            source,
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// Returns the character at the current position without advancing the cursor.
+    fn peek(&self) -> Option<char> {
+        self.source[self.offset..].chars().next()
+    }
+
+    /// Returns true if the source code at the current position starts with `pattern`.
+    fn starts_with(&self, pattern: &str) -> bool {
+        self.source[self.offset..].starts_with(pattern)
+    }
+
+    /// Advances the cursor by one character and returns this character.
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    /// Advances the cursor past `pattern`; the source code at the current position must start
+    /// with `pattern`.
+    fn skip(&mut self, pattern: &str) {
+        debug_assert!(self.starts_with(pattern));
+        for _ in pattern.chars() {
+            self.advance();
+        }
+    }
+
+    /// Advances the cursor past the next occurrence of `pattern`. Returns false if the end of
+    /// the source code was reached before `pattern` was encountered.
+    fn skip_past(&mut self, pattern: &str) -> bool {
+        loop {
+            if self.starts_with(pattern) {
+                self.skip(pattern);
+                return true;
+            } else if self.advance().is_none() {
+                return false;
+            }
+        }
+    }
+}
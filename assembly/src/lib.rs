@@ -1,10 +1,10 @@
 use vm_core::{
-    opcodes::{OpHint, UserOps as OpCode},
+    opcodes::{OpHint, U32Op, UserOps as OpCode},
     program::{
-        blocks::{Group, Loop, ProgramBlock, Span, Switch},
+        blocks::{Call, Group, Loop, ProgramBlock, Repeat, SourceLocation, Span, Switch},
         Program,
     },
-    BaseElement, FieldElement, StarkField, BASE_CYCLE_LENGTH,
+    BaseElement, FieldElement, StarkField, BASE_CYCLE_LENGTH, STORAGE_TREE_DEPTH,
 };
 use winter_utils::collections::BTreeMap;
 
@@ -12,7 +12,19 @@ mod parsers;
 use parsers::*;
 
 mod errors;
-pub use errors::AssemblyError;
+pub use errors::{AssemblyError, SourceSpan};
+
+mod lexer;
+pub use lexer::{tokenize, Token};
+
+mod stdlib;
+pub use stdlib::StdLibrary;
+
+mod disassembler;
+pub use disassembler::disassemble;
+
+mod builder;
+pub use builder::ProgramBuilder;
 
 #[cfg(test)]
 mod tests;
@@ -21,50 +33,349 @@ mod tests;
 // ================================================================================================
 
 type HintMap = BTreeMap<usize, OpHint>;
+type LocationMap = BTreeMap<usize, SourceLocation>;
 
 // ASSEMBLY COMPILER
 // ================================================================================================
 
-/// Compiles provided assembly code into a program.
+/// Compiles provided assembly code into a program. Modules of the standard library can be
+/// imported by the source code; to import other modules, use [Assembler].
+///
+/// If the source code contains errors, only the first error is returned; use [compile_all()] to
+/// get all errors encountered during compilation.
 pub fn compile(source: &str) -> Result<Program, AssemblyError> {
-    // break assembly string into tokens
-    let tokens: Vec<&str> = source.split_whitespace().collect();
+    Assembler::new().compile(source)
+}
+
+/// Compiles provided assembly code into a program, collecting all errors encountered during
+/// compilation.
+///
+/// Invalid instructions do not stop compilation, and thus, all of them are reported at once;
+/// however, a malformed block structure (e.g. a block without a matching `end`) terminates
+/// compilation. The returned errors are ordered by their location in the source code.
+pub fn compile_all(source: &str) -> Result<Program, Vec<AssemblyError>> {
+    Assembler::new().compile_all(source)
+}
+
+/// Compiles provided assembly code into a block which can be added to a program library and
+/// executed by other programs via `call` instructions. Modules of the standard library can be
+/// imported by the source code; to import other modules, use [Assembler].
+pub fn compile_block(source: &str) -> Result<Group, AssemblyError> {
+    Assembler::new().compile_block(source)
+}
+
+// MODULE PROVIDER
+// ================================================================================================
+
+/// Supplies source code of modules imported by assembly programs via `use` instructions.
+pub trait ModuleProvider {
+    /// Returns source code of the module located at the specified `path` (e.g. `crypto::hashes`),
+    /// or None if this provider does not contain such a module.
+    fn get_module(&self, path: &str) -> Option<&str>;
+}
+
+// ASSEMBLER
+// ================================================================================================
+
+/// Compiles assembly programs which may import procedures from external modules.
+///
+/// Modules under the `std` namespace are always resolved against the standard library bundled
+/// with this crate; all other modules are resolved by the [ModuleProvider] supplied to the
+/// assembler (if any).
+#[derive(Default)]
+pub struct Assembler {
+    provider: Option<Box<dyn ModuleProvider>>,
+}
+
+impl Assembler {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a new assembler which can import only modules of the standard library.
+    pub fn new() -> Self {
+        Assembler { provider: None }
+    }
+
+    /// Returns a new assembler which resolves modules outside of the standard library using the
+    /// specified `provider`.
+    pub fn with_module_provider<P: ModuleProvider + 'static>(provider: P) -> Self {
+        Assembler {
+            provider: Some(Box::new(provider)),
+        }
+    }
+
+    // COMPILER
+    // --------------------------------------------------------------------------------------------
+
+    /// Compiles provided assembly code into a program.
+    ///
+    /// If the source code contains errors, only the first error is returned; use
+    /// [Assembler::compile_all()] to get all errors encountered during compilation.
+    pub fn compile(&self, source: &str) -> Result<Program, AssemblyError> {
+        self.compile_all(source)
+            .map_err(|mut errors| errors.remove(0))
+    }
+
+    /// Compiles provided assembly code into a program, collecting all errors encountered during
+    /// compilation.
+    ///
+    /// Invalid instructions do not stop compilation, and thus, all of them are reported at
+    /// once; however, a malformed block structure (e.g. a block without a matching `end`) or an
+    /// invalid import terminates compilation. The returned errors are ordered by their location
+    /// in the source code.
+    pub fn compile_all(&self, source: &str) -> Result<Program, Vec<AssemblyError>> {
+        self.compile_root(source, "begin").map(Program::new)
+    }
+
+    /// Compiles provided assembly code into a block which can be added to a program library and
+    /// executed by other programs via `call` instructions.
+    ///
+    /// The code has the same structure as the code of a program, but its body must be enclosed
+    /// in `block` and `end` instructions instead of `begin` and `end`. If the source code
+    /// contains errors, only the first error is returned.
+    pub fn compile_block(&self, source: &str) -> Result<Group, AssemblyError> {
+        self.compile_root(source, "block")
+            .map_err(|mut errors| errors.remove(0))
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Compiles provided assembly code into a root block of a program (if `head` is `begin`) or
+    /// of a library block (if `head` is `block`), collecting all errors encountered during
+    /// compilation.
+    fn compile_root(&self, source: &str, head: &str) -> Result<Group, Vec<AssemblyError>> {
+        // break assembly string into tokens
+        let tokens = tokenize(source).map_err(|error| vec![error])?;
+
+        // read the imported modules; these must be kept alive until the program is compiled
+        // because procedures of imported modules are parsed every time they are executed
+        let mut modules = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() && tokens[i].parts()[0] == "use" {
+            let module = self
+                .read_module(&tokens[i], i)
+                .map_err(|error| locate_errors(vec![error], &tokens))?;
+            modules.push(module);
+            i += 1;
+        }
 
+        let mut context = ParserContext::new();
+        let result = modules
+            .iter()
+            .try_for_each(|module| load_module(module, &mut context))
+            .and_then(|_| parse_root(&tokens, i, head, &mut context));
+
+        match result {
+            Ok(root) if context.errors.is_empty() => return Ok(root),
+            Ok(_) => (),
+            Err(error) => context.errors.push(error),
+        }
+
+        Err(locate_errors(context.errors, &tokens))
+    }
+
+    /// Reads the path of the module imported by the `use` instruction in the specified `token`,
+    /// and breaks source code of this module into tokens.
+    fn read_module<'a>(
+        &'a self,
+        token: &Token<'a>,
+        step: usize,
+    ) -> Result<Module<'a>, AssemblyError> {
+        let op = token.parts();
+        let path = read_module_path(&op, step)?;
+
+        let source = if path.starts_with("std::") {
+            stdlib::get_module_source(path)
+        } else {
+            self.provider
+                .as_ref()
+                .and_then(|provider| provider.get_module(path))
+        }
+        .ok_or_else(|| AssemblyError::undefined_module(path, step))?;
+
+        let tokens =
+            tokenize(source).map_err(|error| AssemblyError::invalid_module(path, step, &error))?;
+
+        Ok(Module {
+            path,
+            step,
+            source_tokens: tokens,
+        })
+    }
+}
+
+/// Attaches source location to each error, sorts the errors by their location in the source
+/// code, and removes duplicates.
+fn locate_errors(mut errors: Vec<AssemblyError>, tokens: &[Token]) -> Vec<AssemblyError> {
+    for error in errors.iter_mut() {
+        let span = match tokens.get(error.step()) {
+            Some(token) => token.span(),
+            None => tokens.last().map(|token| token.span()).unwrap_or_default(),
+        };
+        error.set_span(span);
+    }
+
+    // the body of a procedure is parsed every time the procedure is executed; so, errors in
+    // procedure bodies may be reported more than once
+    errors.sort_by_key(|error| error.step());
+    errors.dedup_by(|a, b| a.step() == b.step() && a.message() == b.message());
+
+    errors
+}
+
+// PARSER CONTEXT
+// ================================================================================================
+
+/// A module imported by a program.
+struct Module<'a> {
+    /// Full path of the module (e.g. `std::math`).
+    path: &'a str,
+    /// Position of the `use` instruction which imported the module.
+    step: usize,
+    /// Source code of the module broken into tokens.
+    source_tokens: Vec<Token<'a>>,
+}
+
+/// Location of a procedure definition.
+#[derive(Copy, Clone)]
+struct Procedure<'a> {
+    /// Token stream of the program or module containing the procedure.
+    tokens: &'a [Token<'a>],
+    /// Position of the procedure header in the token stream.
+    start: usize,
+    /// Path of the module containing the procedure; empty for procedures defined in a program.
+    module: &'a str,
+}
+
+/// Holds the state shared by all parser functions while a program is being compiled.
+struct ParserContext<'a> {
+    /// Maps aliases of imported modules to full module paths; for example, `math` is an alias of
+    /// module `std::math`.
+    imports: BTreeMap<&'a str, &'a str>,
+    /// Maps fully qualified names of defined procedures to locations of their definitions.
+    procs: BTreeMap<String, Procedure<'a>>,
+    /// Fully qualified names and module paths of the procedures currently being parsed; used to
+    /// resolve procedure names and to detect recursive calls.
+    call_stack: Vec<(String, &'a str)>,
+    /// Errors in individual instructions encountered so far.
+    errors: Vec<AssemblyError>,
+}
+
+impl<'a> ParserContext<'a> {
+    fn new() -> Self {
+        ParserContext {
+            imports: BTreeMap::new(),
+            procs: BTreeMap::new(),
+            call_stack: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Returns the path of the module containing the code currently being parsed; the path is
+    /// empty when the code belongs to the program itself.
+    fn current_module(&self) -> &'a str {
+        self.call_stack
+            .last()
+            .map(|(_, module)| *module)
+            .unwrap_or("")
+    }
+
+    /// Returns the fully qualified name of the procedure referenced as `name` from the code
+    /// currently being parsed, or None if `name` refers to a module which was not imported.
+    fn resolve_proc_name(&self, name: &str) -> Option<String> {
+        let module = self.current_module();
+        match name.find("::") {
+            Some(pos) if module.is_empty() => {
+                let path = self.imports.get(&name[..pos])?;
+                Some(format!("{}{}", path, &name[pos..]))
+            }
+            Some(_) => None,
+            None => Some(qualify_proc_name(module, name)),
+        }
+    }
+}
+
+/// Returns the name of procedure `name` qualified with the `module` path.
+fn qualify_proc_name(module: &str, name: &str) -> String {
+    if module.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}::{}", module, name)
+    }
+}
+
+// PARSER FUNCTIONS
+// ================================================================================================
+
+/// Parses a root block of a program from the `token` stream starting at position `i`; all tokens
+/// preceding this position must be imports which have already been processed. The body of the
+/// root block must start with the specified `head` instruction (`begin` for programs, and
+/// `block` for library blocks).
+///
+/// Errors in individual instructions are appended to the list of errors in the `context`; all
+/// other errors terminate parsing and are returned immediately.
+fn parse_root<'a>(
+    tokens: &'a [Token<'a>],
+    mut i: usize,
+    head: &str,
+    context: &mut ParserContext<'a>,
+) -> Result<Group, AssemblyError> {
     // perform basic validation
     if tokens.is_empty() {
         return Err(AssemblyError::empty_program());
-    } else if tokens[0] != "begin" {
-        return Err(AssemblyError::invalid_program_start(tokens[0]));
-    } else if tokens[tokens.len() - 1] != "end" {
-        return Err(AssemblyError::invalid_program_end(tokens[tokens.len() - 1]));
+    } else if tokens[tokens.len() - 1].text() != "end" {
+        let last = tokens.len() - 1;
+        return Err(AssemblyError::invalid_program_end(
+            tokens[last].text(),
+            last,
+        ));
+    }
+
+    // read procedure definitions; these must precede the main body of the program
+    while tokens[i].parts()[0] == "proc" {
+        i = parse_proc(tokens, i, "", context)?;
+        if i == tokens.len() {
+            return Err(AssemblyError::missing_program_body(i - 1));
+        }
+    }
+
+    if tokens[i].text() != head {
+        return match head {
+            "begin" => Err(AssemblyError::invalid_program_start(tokens[i].text(), i)),
+            _ => Err(AssemblyError::invalid_library_block_start(
+                tokens[i].text(),
+                i,
+            )),
+        };
     }
 
     // read the program from the token stream
     let mut root_blocks = Vec::new();
-    let i = parse_branch(&mut root_blocks, &tokens, 0)?;
+    let i = parse_branch(&mut root_blocks, tokens, i, context)?;
     let root = Group::new(root_blocks);
 
     // make sure there is nothing left after the last token
     if i < tokens.len() - 1 {
-        return Err(AssemblyError::dangling_instructions(i));
+        return Err(AssemblyError::dangling_instructions(
+            tokens[i + 1].text(),
+            i + 1,
+        ));
     }
 
-    // build and return the program
-    Ok(Program::new(root))
+    Ok(root)
 }
 
-// PARSER FUNCTIONS
-// ================================================================================================
-
 /// Parses a single program block from the `token` stream, and appends this block to the `parent`
 /// list of blocks.
-fn parse_block(
+fn parse_block<'a>(
     parent: &mut Vec<ProgramBlock>,
-    tokens: &[&str],
+    tokens: &'a [Token<'a>],
     mut i: usize,
+    context: &mut ParserContext<'a>,
 ) -> Result<usize, AssemblyError> {
     // read the block header
-    let head: Vec<&str> = tokens[i].split('.').collect();
+    let head = tokens[i].parts();
 
     // based on the block header, figure out what type of a block we are dealing with
     match head[0] {
@@ -75,7 +386,7 @@ fn parse_block(
             }
             // then parse the body of the block, add the new block to the parent, and return
             let mut body = Vec::new();
-            i = parse_branch(&mut body, tokens, i)?;
+            i = parse_branch(&mut body, tokens, i, context)?;
             parent.push(Group::new_block(body));
             Ok(i + 1)
         }
@@ -87,31 +398,15 @@ fn parse_block(
 
             // parse the body of the true branch
             let mut t_branch = Vec::new();
-            i = parse_branch(&mut t_branch, tokens, i)?;
+            i = parse_branch(&mut t_branch, tokens, i, context)?;
 
             // if the false branch is present, parse it as well; otherwise
             // create an empty false branch
             let mut f_branch = Vec::new();
-            if tokens[i] == "else" {
-                i = parse_branch(&mut f_branch, tokens, i)?;
+            if tokens[i].text() == "else" {
+                i = parse_branch(&mut f_branch, tokens, i, context)?;
             } else {
-                f_branch.push(Span::new_block(vec![
-                    OpCode::Not,
-                    OpCode::Assert,
-                    OpCode::Noop,
-                    OpCode::Noop,
-                    OpCode::Noop,
-                    OpCode::Noop,
-                    OpCode::Noop,
-                    OpCode::Noop,
-                    OpCode::Noop,
-                    OpCode::Noop,
-                    OpCode::Noop,
-                    OpCode::Noop,
-                    OpCode::Noop,
-                    OpCode::Noop,
-                    OpCode::Noop,
-                ]));
+                f_branch.push(build_empty_false_branch());
             }
 
             // create a Switch block, add it to the parent, and return
@@ -126,14 +421,13 @@ fn parse_block(
             }
 
             // parse loop body
-            let mut body_template = Vec::new();
-            i = parse_branch(&mut body_template, tokens, i)?;
-
-            // duplicate loop body as many times as needed
-            let body = repeat_block_sequence(body_template, num_iterations);
+            let mut body = Vec::new();
+            i = parse_branch(&mut body, tokens, i, context)?;
 
-            // create a Group block with all iterations expanded, and return
-            parent.push(Group::new_block(body));
+            // create a Repeat block which executes the body the specified number of times, and
+            // return; the body is not unrolled, and thus, the size of the block does not depend
+            // on the number of iterations
+            parent.push(Repeat::new_block(body, num_iterations));
             Ok(i + 1)
         }
         "while" => {
@@ -143,7 +437,7 @@ fn parse_block(
             }
             // then parse the body of the block, add the new block to the parent, and return
             let mut body = Vec::new();
-            i = parse_branch(&mut body, tokens, i)?;
+            i = parse_branch(&mut body, tokens, i, context)?;
             parent.push(Loop::new_block(body));
             Ok(i + 1)
         }
@@ -153,13 +447,14 @@ fn parse_block(
 
 /// Builds a body of a program block by parsing tokens from the stream and transforming
 /// them into program blocks.
-fn parse_branch(
+fn parse_branch<'a>(
     body: &mut Vec<ProgramBlock>,
-    tokens: &[&str],
+    tokens: &'a [Token<'a>],
     mut i: usize,
+    context: &mut ParserContext<'a>,
 ) -> Result<usize, AssemblyError> {
     // determine starting instructions of the branch based on branch head
-    let mut head: Vec<&str> = tokens[i].split('.').collect();
+    let mut head = tokens[i].parts();
     let mut op_codes: Vec<OpCode> = match head[0] {
         "begin" => {
             // this is a first block of a program
@@ -174,50 +469,278 @@ fn parse_branch(
         _ => return Err(AssemblyError::invalid_block_head(&head, i)),
     };
     let mut op_hints: HintMap = BTreeMap::new();
+    let mut op_locations: LocationMap = BTreeMap::new();
+    if !op_codes.is_empty() {
+        record_location(&mut op_locations, 0, &tokens[i], context);
+    }
 
     // save first step to check for empty branches
     let first_step = i;
-    i += 1;
 
-    // iterate over tokens and parse them one by one until the end of the block is reached;
-    // if a new block is encountered, parse it recursively
+    // parse the instructions until the end of the block is reached
+    i = parse_instructions(
+        body,
+        &mut op_codes,
+        &mut op_hints,
+        &mut op_locations,
+        tokens,
+        i,
+        context,
+    )?;
+    if i == tokens.len() {
+        // if all tokens were consumed by block end was not found, return an error
+        return match head[0] {
+            "block" => Err(AssemblyError::unmatched_block(first_step)),
+            "if" => Err(AssemblyError::unmatched_if(first_step)),
+            "else" => Err(AssemblyError::unmatched_else(first_step)),
+            "repeat" => Err(AssemblyError::unmatched_repeat(first_step, &head)),
+            "while" => Err(AssemblyError::unmatched_while(first_step)),
+            _ => Err(AssemblyError::invalid_block_head(&head, first_step)),
+        };
+    } else if tokens[i].text() == "else" && head[0] != "if" {
+        return Err(AssemblyError::dangling_else(i));
+    } else if i - first_step < 2 {
+        return Err(AssemblyError::empty_block(&head, first_step));
+    }
+
+    add_span(body, &mut op_codes, &mut op_hints, &mut op_locations, false);
+    Ok(i)
+}
+
+/// Parses instructions following the block head located at position `i` in the token stream
+/// until the end of the block (`else` or `end`) is reached; if a new block is encountered, it
+/// is parsed recursively, and if a procedure is executed, its body is inlined.
+///
+/// Returns the position of the token which terminated the block, or the number of tokens
+/// in the stream if all tokens were consumed without reaching the end of the block.
+fn parse_instructions<'a>(
+    body: &mut Vec<ProgramBlock>,
+    op_codes: &mut Vec<OpCode>,
+    op_hints: &mut HintMap,
+    op_locations: &mut LocationMap,
+    tokens: &'a [Token<'a>],
+    mut i: usize,
+    context: &mut ParserContext<'a>,
+) -> Result<usize, AssemblyError> {
+    i += 1;
     while i < tokens.len() {
-        let op: Vec<&str> = tokens[i].split('.').collect();
+        let op = tokens[i].parts();
         i = match op[0] {
             "block" | "if" | "repeat" | "while" => {
                 let force_span = body.is_empty();
-                add_span(body, &mut op_codes, &mut op_hints, force_span);
-                parse_block(body, tokens, i)?
+                add_span(body, op_codes, op_hints, op_locations, force_span);
+                parse_block(body, tokens, i, context)?
             }
-            "else" => {
-                if head[0] != "if" {
-                    return Err(AssemblyError::dangling_else(i));
-                } else if i - first_step < 2 {
-                    return Err(AssemblyError::empty_block(&head, first_step));
+            "else" | "end" => return Ok(i),
+            "proc" => return Err(AssemblyError::nested_proc(&op, i)),
+            "exec" => parse_exec(body, op_codes, op_hints, op_locations, tokens, i, context)?,
+            "call" => parse_call(body, op_codes, op_hints, op_locations, tokens, i, context),
+            _ => {
+                let op_index = op_codes.len();
+                match parse_op_token(op, op_codes, op_hints, i) {
+                    Ok(next) => {
+                        record_location(op_locations, op_index, &tokens[i], context);
+                        next
+                    }
+                    Err(error) => {
+                        // record the error and continue parsing from the next token
+                        context.errors.push(error);
+                        i + 1
+                    }
                 }
-                add_span(body, &mut op_codes, &mut op_hints, false);
-                return Ok(i);
             }
-            "end" => {
-                if i - first_step < 2 {
-                    return Err(AssemblyError::empty_block(&head, first_step));
-                }
-                add_span(body, &mut op_codes, &mut op_hints, false);
-                return Ok(i);
-            }
-            _ => parse_op_token(op, &mut op_codes, &mut op_hints, i)?,
         };
     }
 
-    // if all tokens were consumed by block end was not found, return an error
-    match head[0] {
-        "block" => Err(AssemblyError::unmatched_block(first_step)),
-        "if" => Err(AssemblyError::unmatched_if(first_step)),
-        "else" => Err(AssemblyError::unmatched_else(first_step)),
-        "repeat" => Err(AssemblyError::unmatched_repeat(first_step, &head)),
-        "while" => Err(AssemblyError::unmatched_while(first_step)),
-        _ => Err(AssemblyError::invalid_block_head(&head, first_step)),
+    Ok(i)
+}
+
+/// Reads procedure definitions from the specified `module` and adds them to the list of
+/// procedures defined in the `context`; the module is also added to the list of imports.
+///
+/// Errors in the module terminate parsing; they are reported as a single error located at the
+/// `use` instruction which imported the module.
+fn load_module<'a>(
+    module: &'a Module<'a>,
+    context: &mut ParserContext<'a>,
+) -> Result<(), AssemblyError> {
+    let tokens = &module.source_tokens;
+
+    // modules are referenced by the last component of their path
+    let alias = module.path.rsplit("::").next().unwrap_or(module.path);
+    if context.imports.insert(alias, module.path).is_some() {
+        return Err(AssemblyError::duplicate_import(module.path, module.step));
+    }
+
+    // a module can contain only procedure definitions
+    let mut i = 0;
+    let result = loop {
+        if i == tokens.len() {
+            break Ok(());
+        } else if tokens[i].parts()[0] != "proc" {
+            break Err(AssemblyError::invalid_module_item(tokens[i].text(), i));
+        }
+
+        match parse_proc(tokens, i, module.path, context) {
+            Ok(next) => i = next,
+            Err(error) => break Err(error),
+        }
+    };
+
+    // errors in instructions are collected in the context; since modules are loaded before
+    // the program is parsed, any such error must have come from this module
+    let mut errors = core::mem::take(&mut context.errors);
+    if let Err(error) = result {
+        errors.push(error);
+    }
+    match locate_errors(errors, tokens).first() {
+        Some(error) => Err(AssemblyError::invalid_module(
+            module.path,
+            module.step,
+            error,
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Parses a procedure definition located at position `i` in the token stream, and adds the
+/// procedure to the list of procedures defined in the `context`. The body of the procedure
+/// is parsed to make sure it is valid, but the result is discarded, as the body is parsed
+/// again every time the procedure is executed.
+///
+/// `module` is the path of the module containing the procedure; it is empty for procedures
+/// defined in the program itself.
+///
+/// Returns the position of the token immediately following the procedure definition.
+fn parse_proc<'a>(
+    tokens: &'a [Token<'a>],
+    i: usize,
+    module: &'a str,
+    context: &mut ParserContext<'a>,
+) -> Result<usize, AssemblyError> {
+    let head = tokens[i].parts();
+    let name = read_proc_name(&head, i)?;
+    if name.contains("::") {
+        return Err(AssemblyError::invalid_param_reason(
+            &head,
+            i,
+            format!("'{}' is not a valid procedure name", name),
+        ));
+    }
+
+    let qualified_name = qualify_proc_name(module, name);
+    let proc = Procedure {
+        tokens,
+        start: i,
+        module,
+    };
+    if context.procs.insert(qualified_name.clone(), proc).is_some() {
+        return Err(AssemblyError::duplicate_proc(&head, i));
+    }
+
+    // parse the body of the procedure; the procedure is put onto the call stack to detect
+    // procedures which execute themselves
+    let mut body = Vec::new();
+    let mut op_codes = Vec::new();
+    let mut op_hints = BTreeMap::new();
+    let mut op_locations = BTreeMap::new();
+    context.call_stack.push((qualified_name, module));
+    let end = parse_instructions(
+        &mut body,
+        &mut op_codes,
+        &mut op_hints,
+        &mut op_locations,
+        tokens,
+        i,
+        context,
+    )?;
+    context.call_stack.pop();
+
+    if end == tokens.len() {
+        return Err(AssemblyError::unmatched_proc(i, &head));
+    } else if tokens[end].text() == "else" {
+        return Err(AssemblyError::dangling_else(end));
+    } else if end - i < 2 {
+        return Err(AssemblyError::empty_block(&head, i));
+    }
+
+    Ok(end + 1)
+}
+
+/// Inlines the body of the procedure executed by the `exec` instruction at position `i` in the
+/// token stream; instructions of the procedure are appended to the instructions of the
+/// enclosing Span block.
+fn parse_exec<'a>(
+    body: &mut Vec<ProgramBlock>,
+    op_codes: &mut Vec<OpCode>,
+    op_hints: &mut HintMap,
+    op_locations: &mut LocationMap,
+    tokens: &'a [Token<'a>],
+    i: usize,
+    context: &mut ParserContext<'a>,
+) -> Result<usize, AssemblyError> {
+    let op = tokens[i].parts();
+    let name = match read_proc_name(&op, i) {
+        Ok(name) => name,
+        Err(error) => {
+            context.errors.push(error);
+            return Ok(i + 1);
+        }
+    };
+
+    // make sure the procedure has been defined and is not being executed recursively
+    let name = match context.resolve_proc_name(name) {
+        Some(name) if context.procs.contains_key(&name) => name,
+        _ => {
+            context.errors.push(AssemblyError::undefined_proc(&op, i));
+            return Ok(i + 1);
+        }
+    };
+    let proc = context.procs[&name];
+    if context.call_stack.iter().any(|(caller, _)| *caller == name) {
+        return Err(AssemblyError::recursive_proc(&op, i));
+    }
+
+    // parse the body of the procedure in place; the first operation of the procedure is
+    // attributed to the `exec` instruction
+    record_location(op_locations, op_codes.len(), &tokens[i], context);
+    context.call_stack.push((name, proc.module));
+    parse_instructions(
+        body,
+        op_codes,
+        op_hints,
+        op_locations,
+        proc.tokens,
+        proc.start,
+        context,
+    )?;
+    context.call_stack.pop();
+
+    Ok(i + 1)
+}
+
+/// Appends a Call block for the `call` instruction at position `i` in the token stream to the
+/// `body`; instructions preceding the call are moved into a Span block first.
+fn parse_call<'a>(
+    body: &mut Vec<ProgramBlock>,
+    op_codes: &mut Vec<OpCode>,
+    op_hints: &mut HintMap,
+    op_locations: &mut LocationMap,
+    tokens: &'a [Token<'a>],
+    i: usize,
+    context: &mut ParserContext<'a>,
+) -> usize {
+    let op = tokens[i].parts();
+    match read_value(&op, i) {
+        Ok(hash) => {
+            let force_span = body.is_empty();
+            add_span(body, op_codes, op_hints, op_locations, force_span);
+            body.push(Call::new_block(hash));
+        }
+        Err(error) => context.errors.push(error),
     }
+
+    i + 1
 }
 
 /// Transforms an assembly instruction into a sequence of one or more VM instructions.
@@ -261,6 +784,13 @@ fn parse_op_token(
 
         "choose" => parse_choose(op_codes, &op, step),
 
+        "u32add" | "u32sub" | "u32mul" | "u32div" | "u32and" | "u32xor" | "u32shl" | "u32shr" => {
+            parse_u32(op_codes, op_hints, &op, step)
+        }
+
+        "mem" => parse_mem(op_codes, op_hints, &op, step),
+        "storage" => parse_storage(op_codes, op_hints, &op, step),
+
         "hash" => parse_hash(op_codes, &op, step),
         "smpath" => parse_smpath(op_codes, &op, step),
         "pmpath" => parse_pmpath(op_codes, op_hints, &op, step),
@@ -280,6 +810,7 @@ fn add_span(
     body: &mut Vec<ProgramBlock>,
     op_codes: &mut Vec<OpCode>,
     op_hints: &mut HintMap,
+    op_locations: &mut LocationMap,
     force: bool,
 ) {
     // if there were no instructions in the current span, don't do anything
@@ -293,44 +824,90 @@ fn add_span(
     span_op_codes.resize(span_op_codes.len() + pad_length, OpCode::Noop);
 
     // add a new Span block to the body
-    body.push(ProgramBlock::Span(Span::new(
-        span_op_codes,
-        op_hints.clone(),
-    )));
+    let span = Span::new(span_op_codes, op_hints.clone()).with_locations(op_locations.clone());
+    body.push(ProgramBlock::Span(span));
 
-    // clear op_codes and op_hints for the next Span block
+    // clear op_codes, op_hints, and op_locations for the next Span block
     op_codes.clear();
     op_hints.clear();
+    op_locations.clear();
 }
 
-fn repeat_block_sequence(template: Vec<ProgramBlock>, num_iterations: usize) -> Vec<ProgramBlock> {
-    let mut body = Vec::with_capacity(template.len() * num_iterations);
+/// Records the location of the instruction in `token` as the location of the operation at
+/// position `op_index` in the current Span block; if another instruction starting at the same
+/// position has already been recorded (e.g. the `exec` instruction of an inlined procedure),
+/// the existing location is kept. Instructions of imported modules are not recorded since
+/// their locations do not refer to the source code of the program.
+fn record_location(
+    op_locations: &mut LocationMap,
+    op_index: usize,
+    token: &Token,
+    context: &ParserContext,
+) {
+    if context.current_module().is_empty() {
+        let span = token.span();
+        op_locations
+            .entry(op_index)
+            .or_insert_with(|| SourceLocation::new(span.line(), span.column()));
+    }
+}
 
-    let last_idx = template.len() - 1;
-    if !template[last_idx].is_span() {
-        for _ in 0..num_iterations {
-            body.extend_from_slice(&template);
-        }
-    } else {
-        body.extend_from_slice(&template);
-        for _ in 1..num_iterations {
-            let last_idx = body.len() - 1;
-            body[last_idx] = merge_spans(&body[last_idx], &template[0]);
-            body.extend_from_slice(&template[1..]);
-        }
+/// Returns the body of the false branch of an `if` statement without an `else` clause; the
+/// branch only makes sure that the condition was false.
+fn build_empty_false_branch() -> ProgramBlock {
+    let mut op_codes = vec![OpCode::Not, OpCode::Assert];
+    op_codes.resize(BASE_CYCLE_LENGTH - 1, OpCode::Noop);
+    Span::new_block(op_codes)
+}
+
+/// Reads the name of a procedure from a `proc` or `exec` instruction. A procedure name must
+/// start with a letter, and can contain only letters, digits, and underscores; names of
+/// procedures defined in imported modules are prefixed with the module alias (e.g. `math::inc`).
+fn read_proc_name<'a>(op: &[&'a str], step: usize) -> Result<&'a str, AssemblyError> {
+    if op.len() == 1 {
+        return Err(AssemblyError::missing_param(op, step));
+    } else if op.len() > 2 {
+        return Err(AssemblyError::extra_param(op, step));
     }
 
-    body
+    let name = op[1];
+    if name.split("::").count() > 2 || !name.split("::").all(is_identifier) {
+        return Err(AssemblyError::invalid_param_reason(
+            op,
+            step,
+            format!("'{}' is not a valid procedure name", name),
+        ));
+    }
+
+    Ok(name)
 }
 
-fn merge_spans(span1: &ProgramBlock, span2: &ProgramBlock) -> ProgramBlock {
-    match span1 {
-        ProgramBlock::Span(first_span) => match span2 {
-            ProgramBlock::Span(last_span) => ProgramBlock::Span(Span::merge(first_span, last_span)),
-            _ => panic!("span1 is not a Span block"),
-        },
-        _ => panic!("span2 is not a Span block"),
+/// Reads the path of a module from a `use` instruction. A module path consists of one or more
+/// identifiers separated by `::` (e.g. `std::math`).
+fn read_module_path<'a>(op: &[&'a str], step: usize) -> Result<&'a str, AssemblyError> {
+    if op.len() == 1 {
+        return Err(AssemblyError::missing_param(op, step));
+    } else if op.len() > 2 {
+        return Err(AssemblyError::extra_param(op, step));
+    }
+
+    let path = op[1];
+    if !path.split("::").all(is_identifier) {
+        return Err(AssemblyError::invalid_param_reason(
+            op,
+            step,
+            format!("'{}' is not a valid module path", path),
+        ));
     }
+
+    Ok(path)
+}
+
+/// Returns true if `name` starts with a letter, and contains only letters, digits, and
+/// underscores.
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
 fn read_param(op: &[&str], step: usize) -> Result<u32, AssemblyError> {
@@ -1,4 +1,7 @@
-use super::{AssemblyError, BaseElement, FieldElement, HintMap, OpCode, OpHint, StarkField};
+use super::{
+    AssemblyError, BaseElement, FieldElement, HintMap, OpCode, OpHint, StarkField, U32Op,
+    STORAGE_TREE_DEPTH,
+};
 
 // CONSTANTS
 // ================================================================================================
@@ -587,6 +590,275 @@ pub fn parse_choose(
     Ok(())
 }
 
+// U32 OPERATIONS
+// ================================================================================================
+
+/// Appends a U32 operation to the program; the operation to be executed is specified by the
+/// hint of the U32 operation.
+pub fn parse_u32(
+    program: &mut Vec<OpCode>,
+    hints: &mut HintMap,
+    op: &[&str],
+    step: usize,
+) -> Result<(), AssemblyError> {
+    if op.len() > 1 {
+        return Err(AssemblyError::extra_param(op, step));
+    }
+
+    let u32_op = match op[0] {
+        "u32add" => U32Op::Add,
+        "u32sub" => U32Op::Sub,
+        "u32mul" => U32Op::Mul,
+        "u32div" => U32Op::Div,
+        "u32and" => U32Op::And,
+        "u32xor" => U32Op::Xor,
+        "u32shl" => U32Op::Shl,
+        "u32shr" => U32Op::Shr,
+        _ => return Err(AssemblyError::invalid_op(op, step)),
+    };
+
+    hints.insert(program.len(), OpHint::U32Op(u32_op));
+    program.push(OpCode::U32);
+    Ok(())
+}
+
+// MEMORY OPERATIONS
+// ================================================================================================
+
+/// Appends a sequence of operations to the program to either load a value from memory or store
+/// a value into memory.
+///
+/// Memory is a sparse Merkle tree of the same shape as the storage, and thus, memory accesses
+/// are compiled into the same sequences of operations as storage accesses; the only differences
+/// are that memory root is accessed via MROOT operation, and that memory always starts out empty.
+pub fn parse_mem(
+    program: &mut Vec<OpCode>,
+    hints: &mut HintMap,
+    op: &[&str],
+    step: usize,
+) -> Result<(), AssemblyError> {
+    if op.len() == 1 {
+        return Err(AssemblyError::missing_param(op, step));
+    } else if op.len() > 2 {
+        return Err(AssemblyError::extra_param(op, step));
+    }
+
+    match op[1] {
+        "load" => append_tree_read(program, hints, OpHint::MemoryRead, OpCode::MRoot),
+        "store" => append_tree_write(program, hints, OpHint::MemoryWrite, OpCode::MRoot),
+        _ => {
+            return Err(AssemblyError::invalid_param_reason(
+                op,
+                step,
+                format!(
+                    "parameter {} is invalid; allowed values are: [load, store]",
+                    op[1]
+                ),
+            ))
+        }
+    }
+    Ok(())
+}
+
+// STORAGE OPERATIONS
+// ================================================================================================
+
+/// Appends a sequence of operations to the program to either read a value from the storage or
+/// write a value into the storage.
+pub fn parse_storage(
+    program: &mut Vec<OpCode>,
+    hints: &mut HintMap,
+    op: &[&str],
+    step: usize,
+) -> Result<(), AssemblyError> {
+    if op.len() == 1 {
+        return Err(AssemblyError::missing_param(op, step));
+    } else if op.len() > 2 {
+        return Err(AssemblyError::extra_param(op, step));
+    }
+
+    match op[1] {
+        "read" => append_tree_read(program, hints, OpHint::StorageRead, OpCode::SRoot),
+        "write" => append_tree_write(program, hints, OpHint::StorageWrite, OpCode::SRoot),
+        _ => {
+            return Err(AssemblyError::invalid_param_reason(
+                op,
+                step,
+                format!(
+                    "parameter {} is invalid; allowed values are: [read, write]",
+                    op[1]
+                ),
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Appends a sequence of operations which replaces the key at the top of the stack with the
+/// value stored under this key in the tree whose root is swapped with the stack by `root_op`
+/// operation (i.e., the storage or memory).
+///
+/// The value and the authentication path of its leaf are read from the input tapes; the path is
+/// hashed in the same way as by `pmpath` instruction, while binary decomposition of the key is
+/// accumulated to select the order of the nodes. At the end, the computed root is swapped with
+/// the root of the tree, and the operations make sure that the root did not change, and that
+/// the accumulated key is equal to the original key.
+fn append_tree_read(program: &mut Vec<OpCode>, hints: &mut HintMap, hint: OpHint, root_op: OpCode) {
+    // read the value and arrange the stack as [v, 0, 1, 0, k, v], where (v, 0) is the leaf, 1 is
+    // the initial power of two, 0 is the initial value of the accumulated key, and the key and
+    // the value are preserved below them
+    hints.insert(program.len(), hint);
+    program.extend_from_slice(&[
+        OpCode::Read,
+        OpCode::Swap,
+        OpCode::Dup2,
+        OpCode::Drop,
+        OpCode::Pad2,
+    ]);
+    append_push_op(program, hints, BaseElement::ONE);
+    program.extend_from_slice(&[OpCode::Swap, OpCode::Roll4]);
+
+    // read the first node of the path and the first bit of the key, and order the leaf and the
+    // node based on the bit
+    const READ_NODE: [OpCode; 9] = [
+        OpCode::Pad2,
+        OpCode::Swap2,
+        OpCode::Read2,
+        OpCode::Swap4,
+        OpCode::BinAcc,
+        OpCode::Swap4,
+        OpCode::Swap2,
+        OpCode::CSwap2,
+        OpCode::Pad2,
+    ];
+    program.extend_from_slice(&READ_NODE);
+
+    // pad with NOOPs to make sure hashing starts on a step which is a multiple of 16
+    let alignment = program.len() % HASH_OP_ALIGNMENT;
+    let pad_length = (HASH_OP_ALIGNMENT - alignment) % HASH_OP_ALIGNMENT;
+    program.resize(program.len() + pad_length, OpCode::Noop);
+
+    // repeat the following cycle of operations once for each remaining node: hash the 2 nodes
+    // on the stack, then read the next node and the next bit of the key, and order the nodes
+    let mut sub_cycle = [OpCode::Noop; 32];
+    sub_cycle[..10].copy_from_slice(&[OpCode::RescR; 10]);
+    sub_cycle[10] = OpCode::Drop4;
+    sub_cycle[11..20].copy_from_slice(&READ_NODE);
+
+    for _ in 1..STORAGE_TREE_DEPTH {
+        program.extend_from_slice(&sub_cycle);
+    }
+    program.extend_from_slice(&sub_cycle[..11]);
+
+    // the stack is now [r, R, p, a, k, v], where (r, R) is the computed root, p is the power of
+    // two, and a is the accumulated key; swap the computed root with the root of the tree,
+    // and make sure they are the same, and that a = k
+    program.extend_from_slice(&[
+        OpCode::Dup2,
+        root_op,
+        OpCode::Swap,
+        OpCode::Roll4,
+        OpCode::AssertEq,
+        OpCode::AssertEq,
+        OpCode::Drop,
+        OpCode::AssertEq,
+    ]);
+}
+
+/// Appends a sequence of operations which stores the value located right below the key at the
+/// top of the stack under this key in the tree whose root is swapped with the stack by `root_op`
+/// operation, and removes both the key and the value from the stack.
+///
+/// The old value and the authentication path of its leaf are read from the input tapes; since
+/// the path also authenticates the new value, the roots of the tree with the old and the new
+/// values are computed at the same time. At the end, the new root replaces the root of the
+/// tree, and the operations make sure that the old root is equal to the root of the tree, and
+/// that the key was decomposed correctly.
+fn append_tree_write(
+    program: &mut Vec<OpCode>,
+    hints: &mut HintMap,
+    hint: OpHint,
+    root_op: OpCode,
+) {
+    // read the old value and arrange the stack as [n, 0, o, 0, 1, 0, k], where (n, 0) is the
+    // new leaf, (o, 0) is the old leaf, 1 is the initial power of two, and 0 is the initial
+    // value of the accumulated key
+    hints.insert(program.len(), hint);
+    program.extend_from_slice(&[OpCode::Read, OpCode::Pad2]);
+    append_push_op(program, hints, BaseElement::ONE);
+    program.extend_from_slice(&[
+        OpCode::Swap,
+        OpCode::Roll4,
+        OpCode::Dup2,
+        OpCode::Roll8,
+        OpCode::Swap,
+        OpCode::Drop,
+    ]);
+
+    // read the next bit of the key and the next node of the path; then, keep a copy of the node
+    // and the bit for the new leaf, and order the old leaf and the node based on the bit
+    const READ_NODE: [OpCode; 17] = [
+        OpCode::Pad2,
+        OpCode::Roll8,
+        OpCode::Roll8,
+        OpCode::Swap2,
+        OpCode::BinAcc,
+        OpCode::Swap2,
+        OpCode::Swap4,
+        OpCode::Swap2,
+        OpCode::Roll8,
+        OpCode::Roll8,
+        OpCode::Read2,
+        OpCode::Dup2,
+        OpCode::Swap4,
+        OpCode::Dup2,
+        OpCode::Swap4,
+        OpCode::CSwap2,
+        OpCode::Pad2,
+    ];
+    program.extend_from_slice(&READ_NODE);
+
+    // pad with NOOPs to make sure hashing starts on a step which is a multiple of 16
+    let alignment = program.len() % HASH_OP_ALIGNMENT;
+    let pad_length = (HASH_OP_ALIGNMENT - alignment) % HASH_OP_ALIGNMENT;
+    program.resize(program.len() + pad_length, OpCode::Noop);
+
+    // repeat the following cycle of operations once for each remaining node: hash the old leaf
+    // and the node, order the new leaf and the node based on the same bit, hash them, and then
+    // read the next node and the next bit of the key
+    let mut sub_cycle = [OpCode::Noop; 48];
+    sub_cycle[..10].copy_from_slice(&[OpCode::RescR; 10]);
+    sub_cycle[10..16].copy_from_slice(&[
+        OpCode::Drop4,
+        OpCode::Swap2,
+        OpCode::Swap4,
+        OpCode::Swap2,
+        OpCode::CSwap2,
+        OpCode::Pad2,
+    ]);
+    sub_cycle[16..26].copy_from_slice(&[OpCode::RescR; 10]);
+    sub_cycle[26] = OpCode::Drop4;
+    sub_cycle[27..44].copy_from_slice(&READ_NODE);
+
+    for _ in 1..STORAGE_TREE_DEPTH {
+        program.extend_from_slice(&sub_cycle);
+    }
+    program.extend_from_slice(&sub_cycle[..27]);
+
+    // the stack is now [n, N, o, O, p, a, k], where (n, N) is the new root, and (o, O) is the
+    // old root; replace the root of the tree with the new root, and make sure the old root is
+    // the same as the root of the tree, and that a = k
+    program.extend_from_slice(&[
+        root_op,
+        OpCode::Swap,
+        OpCode::Roll4,
+        OpCode::AssertEq,
+        OpCode::AssertEq,
+        OpCode::Drop,
+        OpCode::AssertEq,
+    ]);
+}
+
 // CRYPTO OPERATIONS
 // ================================================================================================
 
@@ -832,7 +1104,7 @@ fn read_param(op: &[&str], step: usize) -> Result<u32, AssemblyError> {
     Ok(result)
 }
 
-fn read_value(op: &[&str], step: usize) -> Result<BaseElement, AssemblyError> {
+pub fn read_value(op: &[&str], step: usize) -> Result<BaseElement, AssemblyError> {
     // make sure exactly 1 parameter was supplied
     if op.len() == 1 {
         return Err(AssemblyError::missing_param(op, step));
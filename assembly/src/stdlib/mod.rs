@@ -0,0 +1,46 @@
+use super::ModuleProvider;
+
+// STANDARD LIBRARY MODULES
+// ================================================================================================
+
+const MODULES: [(&str, &str); 3] = [
+    ("std::math", include_str!("math.masm")),
+    ("std::cmp", include_str!("cmp.masm")),
+    ("std::merkle", include_str!("merkle.masm")),
+];
+
+// STANDARD LIBRARY
+// ================================================================================================
+
+/// Standard library of Miden assembly bundled with the assembler.
+///
+/// The library contains the following modules:
+/// * `std::math` - field arithmetic utilities.
+/// * `std::cmp` - comparison helpers for 32-bit and 64-bit integers.
+/// * `std::merkle` - verification of Merkle authentication paths.
+///
+/// Modules of the standard library are available to every program compiled by the assembler;
+/// a module can be imported into a program via a `use` instruction (e.g. `use.std::math`).
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StdLibrary;
+
+impl StdLibrary {
+    /// Returns paths of all modules contained in the standard library.
+    pub fn module_paths() -> impl Iterator<Item = &'static str> {
+        MODULES.iter().map(|(path, _)| *path)
+    }
+}
+
+impl ModuleProvider for StdLibrary {
+    fn get_module(&self, path: &str) -> Option<&str> {
+        get_module_source(path)
+    }
+}
+
+/// Returns source code of the standard library module located at the specified `path`.
+pub(crate) fn get_module_source(path: &str) -> Option<&'static str> {
+    MODULES
+        .iter()
+        .find(|(module_path, _)| *module_path == path)
+        .map(|(_, source)| *source)
+}
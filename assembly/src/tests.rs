@@ -1,3 +1,11 @@
+use vm_core::{
+    program::{
+        blocks::{ProgramBlock, SourceLocation},
+        Program,
+    },
+    BaseElement, SliceReader, StarkField,
+};
+
 // GROUP BLOCKS
 // ================================================================================================
 #[test]
@@ -269,9 +277,7 @@ fn repeat_2_spans() {
     begin \
         read read add read read::eq eq noop \
         noop noop noop noop noop noop noop \
-        block \
-            push(3) add noop noop noop noop noop noop \
-            noop noop noop noop noop noop noop noop \
+        repeat.2 \
             push(3) add noop noop noop noop noop noop \
             noop noop noop noop noop noop noop \
         end \
@@ -295,15 +301,7 @@ fn repeat_5_spans() {
     begin \
         read read add read read::eq eq noop \
         noop noop noop noop noop noop noop \
-        block \
-            push(3) add noop noop noop noop noop noop \
-            noop noop noop noop noop noop noop noop \
-            push(3) add noop noop noop noop noop noop \
-            noop noop noop noop noop noop noop noop \
-            push(3) add noop noop noop noop noop noop \
-            noop noop noop noop noop noop noop noop \
-            push(3) add noop noop noop noop noop noop \
-            noop noop noop noop noop noop noop noop \
+        repeat.5 \
             push(3) add noop noop noop noop noop noop \
             noop noop noop noop noop noop noop \
         end \
@@ -330,16 +328,7 @@ fn repeat_2_blocks() {
     begin \
         read read add read read::eq eq noop \
         noop noop noop noop noop noop noop \
-        block \
-            read noop noop noop noop noop noop noop \
-            noop noop noop noop noop noop noop \
-            if \
-                assert noop noop noop noop noop noop noop \
-                push(3) add mul noop noop noop noop \
-            else \
-                not assert noop noop noop noop noop noop \
-                noop noop noop noop noop noop noop \
-            end \
+        repeat.2 \
             read noop noop noop noop noop noop noop \
             noop noop noop noop noop noop noop \
             if \
@@ -374,18 +363,7 @@ fn repeat_2_blocks_with_suffix() {
     begin \
         read read add read read::eq eq noop \
         noop noop noop noop noop noop noop \
-        block \
-            read noop noop noop noop noop noop noop \
-            noop noop noop noop noop noop noop \
-            if \
-                assert noop noop noop noop noop noop noop \
-                push(3) add mul noop noop noop noop \
-            else \
-                not assert noop noop noop noop noop noop \
-                noop noop noop noop noop noop noop \
-            end \
-            neg add inv noop noop noop noop noop \
-            noop noop noop noop noop noop noop noop \
+        repeat.2 \
             read noop noop noop noop noop noop noop \
             noop noop noop noop noop noop noop \
             if \
@@ -402,3 +380,654 @@ fn repeat_2_blocks_with_suffix() {
 
     assert_eq!(expected, format!("{:?}", program));
 }
+
+#[test]
+fn repeat_many_iterations() {
+    use vm_core::Serializable;
+
+    // the size of a program does not depend on the number of iterations of its repeat blocks
+    let program = super::compile("begin repeat.10000 push.3 add end end").unwrap();
+    let small = super::compile("begin repeat.2 push.3 add end end").unwrap();
+    assert_eq!(small.to_bytes().len(), program.to_bytes().len());
+    assert_ne!(small.hash(), program.hash());
+
+    // the time needed to hash a repeat block does not depend on the number of iterations
+    let program = super::compile("begin repeat.4294967295 push.3 add end end").unwrap();
+    assert_eq!(small.to_bytes().len(), program.to_bytes().len());
+
+    let error = super::compile("begin repeat.4294967296 push.3 add end end").unwrap_err();
+    assert_eq!(
+        "malformed instruction repeat: parameter '4294967296' is invalid",
+        error.message()
+    );
+    let error = super::compile("begin repeat.1 push.3 add end end").unwrap_err();
+    assert_eq!(
+        "invalid repeat statement 'repeat.1': between 2 and 4294967295 iterations must be specified",
+        error.message()
+    );
+}
+
+// MEMORY INSTRUCTIONS
+// ================================================================================================
+
+#[test]
+fn memory_instructions() {
+    // memory accesses are compiled in the same way as storage accesses, except that the root of
+    // the memory is accessed via MROOT operation
+    let program = super::compile("begin push.3 mem.load end").unwrap();
+    let program = format!("{:?}", program);
+    assert_eq!(1, program.matches("mroot").count());
+    assert_eq!(0, program.matches("sroot").count());
+    assert_eq!(320, program.matches("rescr").count());
+
+    let program = super::compile("begin push.7 push.3 mem.store end").unwrap();
+    let program = format!("{:?}", program);
+    assert_eq!(1, program.matches("mroot").count());
+    assert_eq!(640, program.matches("rescr").count());
+
+    let error = super::compile("begin mem.read end").unwrap_err();
+    assert_eq!(
+        "malformed instruction mem: parameter read is invalid; allowed values are: [load, store]",
+        error.message()
+    );
+
+    let error = super::compile("begin mem end").unwrap_err();
+    assert_eq!(
+        "malformed instruction mem: parameter is missing",
+        error.message()
+    );
+}
+
+// STORAGE INSTRUCTIONS
+// ================================================================================================
+
+#[test]
+fn storage_instructions() {
+    // every instruction verifies the authentication path of the accessed leaf against the
+    // current root, and then swaps the root with the one it computed
+    let program = super::compile("begin push.3 storage.read end").unwrap();
+    let program = format!("{:?}", program);
+    assert_eq!(1, program.matches("sroot").count());
+    assert_eq!(320, program.matches("rescr").count());
+
+    let program = super::compile("begin push.9 push.3 storage.write end").unwrap();
+    let program = format!("{:?}", program);
+    assert_eq!(1, program.matches("sroot").count());
+    assert_eq!(640, program.matches("rescr").count());
+
+    let error = super::compile("begin storage.load end").unwrap_err();
+    assert_eq!(
+        "malformed instruction storage: parameter load is invalid; allowed values are: [read, write]",
+        error.message()
+    );
+
+    let error = super::compile("begin storage end").unwrap_err();
+    assert_eq!(
+        "malformed instruction storage: parameter is missing",
+        error.message()
+    );
+
+    let error = super::compile("begin storage.read.1 end").unwrap_err();
+    assert_eq!(
+        "malformed instruction storage: too many parameters provided",
+        error.message()
+    );
+}
+
+// U32 INSTRUCTIONS
+// ================================================================================================
+
+#[test]
+fn u32_instructions() {
+    let source = "begin push.7 push.3 u32add push.2 u32shl u32xor end";
+    let program = super::compile(source).unwrap();
+
+    let expected = "\
+        begin noop noop noop noop noop noop noop \
+        push(7) noop noop noop noop noop noop noop \
+        push(3) u32add noop noop noop noop noop noop \
+        push(2) u32shl u32xor noop noop noop noop end";
+
+    assert_eq!(expected, format!("{:?}", program));
+
+    let error = super::compile("begin u32add.2 end").unwrap_err();
+    assert_eq!(
+        "malformed instruction u32add: too many parameters provided",
+        error.message()
+    );
+
+    let error = super::compile("begin u32rol end").unwrap_err();
+    assert_eq!("instruction u32rol is invalid", error.message());
+}
+
+// CALL BLOCKS
+// ================================================================================================
+
+#[test]
+fn call_blocks() {
+    let block = super::compile_block("block push.2 mul end").unwrap();
+    let callee = block.body_hash().as_int();
+
+    let source = format!("begin push.3 call.{:#x} push.1 add end", callee);
+    let program = super::compile(&source).unwrap();
+
+    let expected = format!(
+        "\
+        begin noop noop noop noop noop noop noop \
+        push(3) noop noop noop noop noop noop \
+        call.{:#x} \
+        push(1) add noop noop noop noop noop noop \
+        noop noop noop noop noop noop noop end",
+        callee
+    );
+    assert_eq!(expected, format!("{:?}", program));
+
+    // calling a block results in the same program hash as inlining it
+    let inlined = super::compile("begin push.3 block push.2 mul end push.1 add end").unwrap();
+    assert_eq!(inlined.hash(), program.hash());
+
+    // hashes can also be specified as decimal values
+    let source = format!("begin push.3 call.{} push.1 add end", callee);
+    let result = super::compile(&source).unwrap();
+    assert_eq!(program.hash(), result.hash());
+}
+
+#[test]
+fn call_block_errors() {
+    let error = super::compile("begin call end").unwrap_err();
+    assert_eq!(
+        "malformed instruction call: parameter is missing",
+        error.message()
+    );
+
+    let error = super::compile_block("begin push.1 end").unwrap_err();
+    assert_eq!(
+        "a library block must start with a 'block' instruction",
+        error.message()
+    );
+}
+
+// ERRORS
+// ================================================================================================
+
+#[test]
+fn error_location() {
+    let source = "begin\n    push.1 pushh.2\n    add\nend";
+    let error = super::compile(source).unwrap_err();
+
+    assert_eq!("instruction pushh.2 is invalid", error.message());
+    assert_eq!(2, error.step());
+    assert_eq!(&super::SourceSpan::new(2, 12, 7), error.span());
+    assert_eq!(
+        "assembly error at 2:12: instruction pushh.2 is invalid",
+        format!("{}", error)
+    );
+}
+
+#[test]
+fn error_rendering() {
+    let source = "begin\n    push.1 pushh.2\n    add\nend";
+    let error = super::compile(source).unwrap_err();
+
+    let expected = "\
+error: instruction pushh.2 is invalid
+  --> 2:12
+  |
+2 |     push.1 pushh.2
+  |            ^^^^^^^
+";
+    assert_eq!(expected, error.render(source));
+}
+
+#[test]
+fn multiple_errors() {
+    let source = "begin push.x add foo\nif.true mul bar end end";
+    let errors = super::compile_all(source).unwrap_err();
+
+    assert_eq!(3, errors.len());
+    assert_eq!(&super::SourceSpan::new(1, 7, 6), errors[0].span());
+    assert_eq!(&super::SourceSpan::new(1, 18, 3), errors[1].span());
+    assert_eq!(&super::SourceSpan::new(2, 13, 3), errors[2].span());
+    assert_eq!("instruction bar is invalid", errors[2].message());
+}
+
+#[test]
+fn unmatched_block_error() {
+    let source = "begin push.1 while.true add end";
+    let errors = super::compile_all(source).unwrap_err();
+
+    assert_eq!(1, errors.len());
+    assert_eq!("block without matching end", errors[0].message());
+    assert_eq!(&super::SourceSpan::new(1, 1, 5), errors[0].span());
+}
+
+// COMMENTS
+// ================================================================================================
+
+#[test]
+fn comments() {
+    let source = "\
+    # program comment
+    begin
+        push.1 push.2 # push two values
+        /* add them
+           together */
+        add/*inline*/end";
+    let program = super::compile(source).unwrap();
+
+    let expected = "\
+        begin noop noop noop noop noop noop noop \
+        push(1) noop noop noop noop noop noop noop \
+        push(2) add noop noop noop noop noop noop \
+        noop noop noop noop noop noop noop end";
+
+    assert_eq!(expected, format!("{:?}", program));
+}
+
+#[test]
+fn token_spans() {
+    let source = "begin # comment\n  push.1 /* x */ add\nend";
+    let tokens = super::tokenize(source).unwrap();
+
+    let texts = tokens.iter().map(|t| t.text()).collect::<Vec<_>>();
+    assert_eq!(vec!["begin", "push.1", "add", "end"], texts);
+    assert_eq!(super::SourceSpan::new(2, 3, 6), tokens[1].span());
+    assert_eq!(super::SourceSpan::new(2, 18, 3), tokens[2].span());
+    assert_eq!(super::SourceSpan::new(3, 1, 3), tokens[3].span());
+}
+
+#[test]
+fn source_locations() {
+    let source = "\
+proc.inc push.1 add end
+begin
+    push.3 exec.inc
+    if.true
+        dup add
+    end
+end";
+    let program = super::compile(source).unwrap();
+    let blocks = program.root().body();
+
+    // instructions of a procedure are attributed to the exec instruction which inlined them
+    let first = match &blocks[0] {
+        ProgramBlock::Span(span) => span,
+        _ => panic!("expected a Span block"),
+    };
+    assert_eq!(Some(SourceLocation::new(2, 1)), first.get_location(0));
+    assert_eq!(Some(SourceLocation::new(3, 5)), first.get_location(1));
+    assert_eq!(Some(SourceLocation::new(3, 12)), first.get_location(9));
+    assert_eq!(None, first.get_location(10));
+    assert_eq!(
+        Some(SourceLocation::new(3, 12)),
+        first.get_instruction_location(10)
+    );
+
+    // the head of a branch is attributed to the first operation of the branch
+    let branch = match &blocks[1] {
+        ProgramBlock::Switch(block) => match &block.true_branch()[0] {
+            ProgramBlock::Span(span) => span.clone(),
+            _ => panic!("expected a Span block"),
+        },
+        _ => panic!("expected a Switch block"),
+    };
+    assert_eq!(Some(SourceLocation::new(4, 5)), branch.get_location(0));
+    assert_eq!(Some(SourceLocation::new(5, 9)), branch.get_location(1));
+    assert_eq!(Some(SourceLocation::new(5, 13)), branch.get_location(2));
+
+    // source locations do not affect the hash of the program
+    let expected = super::compile("begin push.3 push.1 add if.true dup add end end").unwrap();
+    assert_eq!(expected.hash(), program.hash());
+}
+
+#[test]
+fn unterminated_comment() {
+    let source = "begin push.1 /* add\nend";
+    let error = super::compile(source).unwrap_err();
+
+    assert_eq!("block comment without matching */", error.message());
+    assert_eq!(&super::SourceSpan::new(1, 14, 2), error.span());
+}
+
+// PROCEDURES
+// ================================================================================================
+
+#[test]
+fn procs_inlined_into_span() {
+    let source = "proc.inc push.1 add end begin push.2 exec.inc exec.inc end";
+    let program = super::compile(source).unwrap();
+    let expected = super::compile("begin push.2 push.1 add push.1 add end").unwrap();
+
+    assert_eq!(format!("{:?}", expected), format!("{:?}", program));
+    assert_eq!(expected.hash(), program.hash());
+}
+
+#[test]
+fn nested_procs() {
+    let source = "\
+    proc.double dup add end
+    proc.quad exec.double exec.double end
+    proc.check read if.true exec.quad else exec.double end end
+    begin exec.check mul end";
+    let program = super::compile(source).unwrap();
+    let expected =
+        super::compile("begin read if.true dup add dup add else dup add end mul end").unwrap();
+
+    assert_eq!(format!("{:?}", expected), format!("{:?}", program));
+    assert_eq!(expected.hash(), program.hash());
+}
+
+#[test]
+fn proc_errors() {
+    // recursive procedure
+    let error = super::compile("proc.foo add exec.foo end begin exec.foo end").unwrap_err();
+    assert_eq!(
+        "procedure 'foo' cannot be executed recursively",
+        error.message()
+    );
+    assert_eq!(2, error.step());
+
+    // undefined procedure; procedures must be defined before they are executed
+    let source = "proc.foo exec.bar end proc.bar add end begin exec.foo end";
+    let errors = super::compile_all(source).unwrap_err();
+    assert_eq!(1, errors.len());
+    assert_eq!("procedure 'bar' is not defined", errors[0].message());
+    assert_eq!(&super::SourceSpan::new(1, 10, 8), errors[0].span());
+
+    // duplicate procedure
+    let error = super::compile("proc.foo add end proc.foo mul end begin exec.foo end").unwrap_err();
+    assert_eq!("procedure 'foo' is already defined", error.message());
+    assert_eq!(3, error.step());
+
+    // procedure defined inside the program body
+    let error = super::compile("begin add proc.foo mul end end").unwrap_err();
+    assert_eq!(2, error.step());
+
+    // invalid procedure name
+    let error = super::compile("proc.1foo add end begin exec.1foo end").unwrap_err();
+    assert_eq!(0, error.step());
+}
+
+// MODULES
+// ================================================================================================
+
+#[test]
+fn stdlib_imports() {
+    let source = "\
+    use.std::math
+    use.std::cmp
+    begin push.3 exec.math::square exec.math::inc push.7 exec.cmp::max_u32 end";
+    let program = super::compile(source).unwrap();
+    let expected = super::compile(
+        "begin push.3 dup mul push.1 add push.7 \
+        dup.2 gt.32 pick.2 pick.2 choose swap drop swap drop end",
+    )
+    .unwrap();
+
+    assert_eq!(format!("{:?}", expected), format!("{:?}", program));
+}
+
+#[test]
+fn stdlib_modules_are_valid() {
+    for path in super::StdLibrary::module_paths() {
+        let source = format!("use.{} begin noop end", path);
+        assert!(
+            super::compile(&source).is_ok(),
+            "module {} is invalid",
+            path
+        );
+    }
+
+    // procedures of a module can execute other procedures of the same module
+    let source = "use.std::merkle begin exec.merkle::verify_smpath_8 end";
+    let program = super::compile(source).unwrap();
+    let expected = super::compile("begin smpath.8 swap roll.4 assert.eq assert.eq end").unwrap();
+    assert_eq!(format!("{:?}", expected), format!("{:?}", program));
+}
+
+#[test]
+fn custom_module_provider() {
+    let assembler = super::Assembler::with_module_provider(TestModules);
+
+    // local procedures do not conflict with procedures of imported modules, and procedures
+    // within a module are resolved against the module itself
+    let source = "\
+    use.crypto::utils
+    proc.double push.2 mul end
+    begin exec.utils::quadruple exec.double end";
+    let program = assembler.compile(source).unwrap();
+    let expected = super::compile("begin dup add dup add push.2 mul end").unwrap();
+    assert_eq!(format!("{:?}", expected), format!("{:?}", program));
+
+    // standard library is available alongside the custom modules
+    let source = "use.crypto::utils use.std::math begin exec.math::double exec.utils::double end";
+    let program = assembler.compile(source).unwrap();
+    let expected = super::compile("begin dup add dup add end").unwrap();
+    assert_eq!(format!("{:?}", expected), format!("{:?}", program));
+}
+
+#[test]
+fn module_errors() {
+    let assembler = super::Assembler::with_module_provider(TestModules);
+
+    // module does not exist
+    let error = assembler
+        .compile("use.std::foo begin noop end")
+        .unwrap_err();
+    assert_eq!("module 'std::foo' could not be found", error.message());
+
+    // module is not imported
+    let error = assembler
+        .compile("begin exec.utils::double end")
+        .unwrap_err();
+    assert_eq!("procedure 'utils::double' is not defined", error.message());
+
+    // procedure is not defined in the module
+    let error = assembler
+        .compile("use.crypto::utils begin exec.utils::triple end")
+        .unwrap_err();
+    assert_eq!("procedure 'utils::triple' is not defined", error.message());
+
+    // two modules with the same alias
+    let error = assembler
+        .compile("use.crypto::utils use.std::math use.crypto::utils begin noop end")
+        .unwrap_err();
+    assert_eq!(2, error.step());
+    assert!(error.message().contains("already imported"));
+
+    // errors in the module are reported at the import
+    let error = assembler
+        .compile("use.crypto::broken begin noop end")
+        .unwrap_err();
+    assert_eq!(
+        "module 'crypto::broken' is invalid: instruction foo is invalid at 2:5",
+        error.message()
+    );
+    assert_eq!(&super::SourceSpan::new(1, 1, 18), error.span());
+}
+
+struct TestModules;
+
+impl super::ModuleProvider for TestModules {
+    fn get_module(&self, path: &str) -> Option<&str> {
+        match path {
+            "crypto::utils" => {
+                Some("proc.double dup add end proc.quadruple exec.double exec.double end")
+            }
+            "crypto::broken" => Some("proc.double\n    foo\nend"),
+            _ => None,
+        }
+    }
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+#[test]
+fn program_serialization() {
+    use vm_core::{Deserializable, Serializable};
+
+    let source = "\
+    begin
+        push.3 read if.true pmpath.4 else gt.32 end
+        repeat.2 rc.16 end
+        while.true hash.2 read end
+    end";
+    let program = super::compile(source).unwrap();
+    let bytes = program.to_bytes();
+
+    let result = Program::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(program.hash(), result.hash());
+    assert_eq!(format!("{:?}", program), format!("{:?}", result));
+}
+
+// PROGRAM BUILDER
+// ================================================================================================
+
+#[test]
+fn builder_matches_compiled_program() {
+    let source = "\
+    begin
+        push.3 push.7 mem.store read.ab dup.3 pad.5 eq ne gt.8 u32add
+        if.true
+            lt.16 isodd.8
+        else
+            smpath.2 pmpath.3
+        end
+        block rc.32 end
+        repeat.3 hash.4 swap.2 end
+        call.42
+        while.true dup.2 push.0 read end
+        if.true push.1 end
+    end";
+    let expected = super::compile(source).unwrap();
+
+    let program = super::ProgramBuilder::new()
+        .push(3)
+        .push(7)
+        .mem_store()
+        .read_ab()
+        .dup(3)
+        .pad(5)
+        .eq()
+        .ne()
+        .gt(8)
+        .u32add()
+        .if_else(|t| t.lt(16).isodd(8), |f| f.smpath(2).pmpath(3))
+        .block(|b| b.rc(32))
+        .repeat(3, |b| b.hash(4).swap(2))
+        .call(BaseElement::new(42))
+        .while_true(|b| b.dup(2).push(0).read())
+        .if_true(|t| t.push(1))
+        .build()
+        .unwrap();
+
+    assert_eq!(expected.hash(), program.hash());
+    assert_eq!(format!("{:?}", expected), format!("{:?}", program));
+
+    // nested blocks at the start of a block are preceded by an empty span
+    let expected = super::compile("begin if.true while.true add end end end").unwrap();
+    let program = super::ProgramBuilder::new()
+        .if_true(|t| t.while_true(|b| b.add()))
+        .build()
+        .unwrap();
+    assert_eq!(format!("{:?}", expected), format!("{:?}", program));
+}
+
+#[test]
+fn builder_errors() {
+    let error = super::ProgramBuilder::new()
+        .push(1)
+        .dup(7)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        super::compile("begin push.1 dup.7 end")
+            .unwrap_err()
+            .message(),
+        error.message()
+    );
+    assert_eq!(2, error.step());
+
+    let error = super::ProgramBuilder::new()
+        .push(1)
+        .if_else(|t| t.add(), |f| f)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        "a program block must contain at least one instruction",
+        error.message()
+    );
+    assert_eq!(4, error.step());
+
+    let error = super::ProgramBuilder::new()
+        .repeat(1, |b| b.add())
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        "invalid repeat statement 'repeat.1': between 2 and 4294967295 iterations must be specified",
+        error.message()
+    );
+
+    let error = super::ProgramBuilder::new()
+        .repeat(u32::MAX as usize + 1, |b| b.add())
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        "invalid repeat statement 'repeat.4294967296': between 2 and 4294967295 iterations must be specified",
+        error.message()
+    );
+
+    let error = super::ProgramBuilder::new().build().unwrap_err();
+    assert_eq!(
+        "a program block must contain at least one instruction",
+        error.message()
+    );
+}
+
+// DISASSEMBLER
+// ================================================================================================
+
+#[test]
+fn disassemble_simple_program() {
+    let program = super::compile("begin push.3 push.5 gt.4 if.true hash.2 end end").unwrap();
+    let expected = "\
+begin
+    push.3
+    push.5
+    gt.4
+    if.true
+        hash.2
+    end
+end
+";
+    assert_eq!(expected, super::disassemble(&program));
+}
+
+#[test]
+fn disassemble_round_trip() {
+    let source = "\
+    use.std::math
+    begin
+        push.3 push.7 mem.store push.7 mem.load read.ab dup.3 pad.5 pick.3 drop.7 swap.4 roll.8 choose.2
+        sub div neg inv not and or eq ne assert.eq noop noop
+        u32add u32sub u32mul u32div u32and u32xor u32shl u32shr
+        if.true
+            lt.16 isodd.8
+        else
+            smpath.2 smpath.5 pmpath.3
+        end
+        block rc.32 end
+        repeat.3 hash.4 exec.math::pow4 end
+        call.0x2a
+        while.true dup.2 push.0 read end
+        if.true push.1 end
+    end";
+    let program = super::compile(source).unwrap();
+    let disassembled = super::disassemble(&program);
+    assert!(!disassembled.contains("unsupported"));
+
+    let result = super::compile(&disassembled).unwrap();
+    assert_eq!(program.hash(), result.hash());
+    assert_eq!(format!("{:?}", program), format!("{:?}", result));
+}
@@ -4,10 +4,14 @@ use core::ops::Range;
 // ================================================================================================
 
 pub use math::{fields::f128::BaseElement, FieldElement, StarkField};
+pub use winter_utils::{
+    ByteReader, ByteWriter, Deserializable, DeserializationError, Serializable, SliceReader,
+};
 pub mod hasher;
 pub mod op_sponge;
 pub mod opcodes;
 pub mod program;
+pub mod storage;
 pub mod utils;
 
 mod trace_state;
@@ -20,6 +24,7 @@ pub const MAX_CONTEXT_DEPTH: usize = 16;
 pub const MAX_LOOP_DEPTH: usize = 8;
 pub const MIN_TRACE_LENGTH: usize = 16;
 pub const BASE_CYCLE_LENGTH: usize = 16;
+pub const MAX_TRACE_WIDTH: usize = 255;
 
 pub const MIN_STACK_DEPTH: usize = 8;
 pub const MIN_CONTEXT_DEPTH: usize = 1;
@@ -31,9 +36,11 @@ pub const PUSH_OP_ALIGNMENT: usize = 8;
 
 // HASH OPERATION
 // ------------------------------------------------------------------------------------------------
+pub const HASH_OP_ALIGNMENT: usize = 16;
+
 const HASHER_STATE_RATE: usize = 4;
 const HASHER_STATE_CAPACITY: usize = 2;
-const HASHER_NUM_ROUNDS: usize = 10;
+pub const HASHER_NUM_ROUNDS: usize = 10;
 const HASHER_DIGEST_SIZE: usize = 2;
 
 // OPERATION SPONGE
@@ -45,9 +52,12 @@ pub const HACC_NUM_ROUNDS: usize = 14;
 // DECODER LAYOUT
 // ------------------------------------------------------------------------------------------------
 //
-//  ctr ╒═════ sponge ══════╕╒═══ cf_ops ══╕╒═══════ ld_ops ═══════╕╒═ hd_ops ╕╒═ ctx ══╕╒═ loop ═╕
-//   0    1    2    3    4    5    6    7    8    9    10   11   12   13   14   15   ..   ..   ..
-// ├────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┤
+//  ctr ╒═════ sponge ══════╕╒═══ cf_ops ══╕╒═══════ ld_ops ═══════╕╒═ hd_ops ╕╒═ ctx ══╕╒═ loop ═╕╒loop_ctr╕
+//   0    1    2    3    4    5    6    7    8    9    10   11   12   13   14   15   ..   ..   ..   ..   ..
+// ├────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┤
+//
+// every loop on the loop stack has a counter in the loop_ctr section; counters of while loops are
+// always 1, and counters of Repeat blocks count the iterations executed so far (see below)
 
 pub const NUM_CF_OP_BITS: usize = 3;
 pub const NUM_LD_OP_BITS: usize = 5;
@@ -63,6 +73,14 @@ pub const CF_OP_BITS_RANGE: Range<usize> = Range { start: 5, end: 8 };
 pub const LD_OP_BITS_RANGE: Range<usize> = Range { start: 8, end: 13 };
 pub const HD_OP_BITS_RANGE: Range<usize> = Range { start: 13, end: 15 };
 
+/// Base of loop counters: a while loop starts with the counter set to 1, and a Repeat block
+/// starts with the counter set to 0; every WRAP operation updates the counter of the innermost
+/// loop as ctr' = g * ctr + (1 - g), where g is this value. Thus, the counter of a while loop
+/// never changes, and after k WRAP operations of a Repeat block, its counter is 1 - g^k. Since g
+/// generates the multiplicative group of the field, counters of Repeat blocks with different
+/// numbers of iterations are different.
+pub const LOOP_COUNTER_BASE: BaseElement = BaseElement::GENERATOR;
+
 // STACK LAYOUT
 // ------------------------------------------------------------------------------------------------
 //
@@ -73,3 +91,66 @@ pub const HD_OP_BITS_RANGE: Range<usize> = Range { start: 13, end: 15 };
 pub const MAX_PUBLIC_INPUTS: usize = 8;
 pub const MAX_OUTPUTS: usize = MAX_PUBLIC_INPUTS;
 pub const MAX_STACK_DEPTH: usize = 32;
+
+// MEMORY LAYOUT
+// ------------------------------------------------------------------------------------------------
+//
+//  rt0  rt1
+//   0    1
+// ├────┴────┤
+//
+// the registers hold the root of the memory tree; the tree has the same shape as the storage
+// tree, and memory starts out empty; the root changes only when MROOT operation swaps it with
+// the top two items of the stack
+
+pub const MEMORY_TRACE_WIDTH: usize = 2;
+
+// STORAGE LAYOUT
+// ------------------------------------------------------------------------------------------------
+//
+//  rt0  rt1
+//   0    1
+// ├────┴────┤
+//
+// the registers hold the root of the storage tree; the root changes only when SROOT operation
+// swaps it with the top two items of the stack
+
+/// Depth of the sparse Merkle tree which holds the storage; keys of the storage are 32-bit
+/// integers, and thus, every key is mapped to a distinct leaf of the tree.
+pub const STORAGE_TREE_DEPTH: usize = 32;
+pub const STORAGE_TRACE_WIDTH: usize = 2;
+
+// U32 OPERATIONS LAYOUT
+// ------------------------------------------------------------------------------------------------
+//
+// ╒═ selectors ═╕╒═ a bits ═╕╒═ b bits ═╕╒═ c bits ═╕╒═ d bits ═╕╒═ e bits ═╕╒═ powers ══╕
+//   0   ..   7   8  ..  39   40  ..  71   72  .. 103  104 .. 135  136 .. 167  168  169  170
+// ├────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┴────┤
+//
+// a and b are the operands of the operation (a is the top of the stack), c is the result, d and e
+// hold auxiliary values (carries, high words, and remainders); the last power register holds
+// 2^a which is used by shift operations
+
+pub const NUM_U32_OPS: usize = 8;
+pub const U32_BITS: usize = 32;
+
+pub const U32_SELECTORS_RANGE: Range<usize> = Range { start: 0, end: 8 };
+pub const U32_A_BITS_RANGE: Range<usize> = Range { start: 8, end: 40 };
+pub const U32_B_BITS_RANGE: Range<usize> = Range { start: 40, end: 72 };
+pub const U32_C_BITS_RANGE: Range<usize> = Range {
+    start: 72,
+    end: 104,
+};
+pub const U32_D_BITS_RANGE: Range<usize> = Range {
+    start: 104,
+    end: 136,
+};
+pub const U32_E_BITS_RANGE: Range<usize> = Range {
+    start: 136,
+    end: 168,
+};
+pub const U32_POWERS_RANGE: Range<usize> = Range {
+    start: 168,
+    end: 171,
+};
+pub const U32_TRACE_WIDTH: usize = 171;
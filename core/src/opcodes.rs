@@ -1,7 +1,10 @@
 #![allow(clippy::unusual_byte_groupings)]
 
-use crate::{BaseElement, FieldElement};
-use core::fmt;
+use crate::{
+    BaseElement, ByteReader, ByteWriter, Deserializable, DeserializationError, FieldElement,
+    Serializable,
+};
+use core::{convert::TryFrom, fmt};
 
 // FLOW CONTROL OPERATIONS
 // ================================================================================================
@@ -57,21 +60,21 @@ pub enum UserOps {
     Choose2 = 0b0_11_00110,  // left shift: 4
     CSwap2 = 0b0_11_00111,   // left shift: 2
 
-    Add = 0b0_11_01000, // left shift: 1
-    Mul = 0b0_11_01001, // left shift: 1
-    And = 0b0_11_01010, // left shift: 1
-    Or = 0b0_11_01011,  // left shift: 1
-    Inv = 0b0_11_01100, // no shift
-    Neg = 0b0_11_01101, // no shift
-    Not = 0b0_11_01110, // no shift
-    //??? = 0b0_11_01111,
+    Add = 0b0_11_01000,   // left shift: 1
+    Mul = 0b0_11_01001,   // left shift: 1
+    And = 0b0_11_01010,   // left shift: 1
+    Or = 0b0_11_01011,    // left shift: 1
+    Inv = 0b0_11_01100,   // no shift
+    Neg = 0b0_11_01101,   // no shift
+    Not = 0b0_11_01110,   // no shift
+    MRoot = 0b0_11_01111, // no shift
     Read = 0b0_11_10000,  // right shift: 1
     Read2 = 0b0_11_10001, // right shift: 2
     Dup = 0b0_11_10010,   // right shift: 1
     Dup2 = 0b0_11_10011,  // right shift: 2
     Dup4 = 0b0_11_10100,  // right shift: 4
     Pad2 = 0b0_11_10101,  // right shift: 2
-    //??? = 0b0_11_10110,
+    U32 = 0b0_11_10110,   // left shift: 1
     //??? = 0b0_11_10111,
     Swap = 0b0_11_11000,   // no shift
     Swap2 = 0b0_11_11001,  // no shift
@@ -79,7 +82,7 @@ pub enum UserOps {
     Roll4 = 0b0_11_11011,  // no shift
     Roll8 = 0b0_11_11100,  // no shift
     BinAcc = 0b0_11_11101, // no shift
-    //??? = 0b0_11_11110,
+    SRoot = 0b0_11_11110,  // no shift
 
     // high-degree operations
     Push = 0b0_00_11111,  // right shift: 1
@@ -113,6 +116,57 @@ impl UserOps {
     }
 }
 
+impl TryFrom<u8> for UserOps {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0b0_11_00000 => Ok(UserOps::Assert),
+            0b0_11_00001 => Ok(UserOps::AssertEq),
+            0b0_11_00010 => Ok(UserOps::Eq),
+            0b0_11_00011 => Ok(UserOps::Drop),
+            0b0_11_00100 => Ok(UserOps::Drop4),
+            0b0_11_00101 => Ok(UserOps::Choose),
+            0b0_11_00110 => Ok(UserOps::Choose2),
+            0b0_11_00111 => Ok(UserOps::CSwap2),
+
+            0b0_11_01000 => Ok(UserOps::Add),
+            0b0_11_01001 => Ok(UserOps::Mul),
+            0b0_11_01010 => Ok(UserOps::And),
+            0b0_11_01011 => Ok(UserOps::Or),
+            0b0_11_01100 => Ok(UserOps::Inv),
+            0b0_11_01101 => Ok(UserOps::Neg),
+            0b0_11_01110 => Ok(UserOps::Not),
+            0b0_11_01111 => Ok(UserOps::MRoot),
+
+            0b0_11_10000 => Ok(UserOps::Read),
+            0b0_11_10001 => Ok(UserOps::Read2),
+            0b0_11_10010 => Ok(UserOps::Dup),
+            0b0_11_10011 => Ok(UserOps::Dup2),
+            0b0_11_10100 => Ok(UserOps::Dup4),
+            0b0_11_10101 => Ok(UserOps::Pad2),
+            0b0_11_10110 => Ok(UserOps::U32),
+
+            0b0_11_11000 => Ok(UserOps::Swap),
+            0b0_11_11001 => Ok(UserOps::Swap2),
+            0b0_11_11010 => Ok(UserOps::Swap4),
+            0b0_11_11011 => Ok(UserOps::Roll4),
+            0b0_11_11100 => Ok(UserOps::Roll8),
+            0b0_11_11101 => Ok(UserOps::BinAcc),
+            0b0_11_11110 => Ok(UserOps::SRoot),
+
+            0b0_00_11111 => Ok(UserOps::Push),
+            0b0_01_11111 => Ok(UserOps::Cmp),
+            0b0_10_11111 => Ok(UserOps::RescR),
+
+            0b0_00_00000 => Ok(UserOps::Begin),
+            0b0_11_11111 => Ok(UserOps::Noop),
+
+            _ => Err(format!("invalid operation code {:#09b}", value)),
+        }
+    }
+}
+
 impl fmt::Display for UserOps {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -158,31 +212,165 @@ impl fmt::Display for UserOps {
             UserOps::BinAcc => write!(f, "binacc"),
 
             UserOps::RescR => write!(f, "rescr"),
+
+            UserOps::U32 => write!(f, "u32"),
+
+            UserOps::MRoot => write!(f, "mroot"),
+
+            UserOps::SRoot => write!(f, "sroot"),
+        }
+    }
+}
+
+// U32 OPERATIONS
+// ================================================================================================
+
+/// Operations on 32-bit unsigned integers; all of them are executed by the U32 user operation,
+/// and the hint of the operation specifies which one is to be executed.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum U32Op {
+    Add = 1,
+    Sub = 2,
+    Mul = 3,
+    Div = 4,
+    And = 5,
+    Xor = 6,
+    Shl = 7,
+    Shr = 8,
+}
+
+impl U32Op {
+    /// Returns the index of the selector register which is set when this operation is executed.
+    pub fn selector_index(&self) -> usize {
+        (*self as usize) - 1
+    }
+}
+
+impl TryFrom<u8> for U32Op {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(U32Op::Add),
+            2 => Ok(U32Op::Sub),
+            3 => Ok(U32Op::Mul),
+            4 => Ok(U32Op::Div),
+            5 => Ok(U32Op::And),
+            6 => Ok(U32Op::Xor),
+            7 => Ok(U32Op::Shl),
+            8 => Ok(U32Op::Shr),
+            _ => Err(format!("invalid u32 operation {}", value)),
+        }
+    }
+}
+
+impl fmt::Display for U32Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            U32Op::Add => write!(f, "add"),
+            U32Op::Sub => write!(f, "sub"),
+            U32Op::Mul => write!(f, "mul"),
+            U32Op::Div => write!(f, "div"),
+            U32Op::And => write!(f, "and"),
+            U32Op::Xor => write!(f, "xor"),
+            U32Op::Shl => write!(f, "shl"),
+            U32Op::Shr => write!(f, "shr"),
         }
     }
 }
 
 // OPERATION HINTS
 // ================================================================================================
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum OpHint {
     EqStart,
     RcStart(u32),
     CmpStart(u32),
     PmpathStart(u32),
     PushValue(BaseElement),
+    U32Op(U32Op),
+    StorageRead,
+    StorageWrite,
+    MemoryRead,
+    MemoryWrite,
     None,
 }
 
 impl OpHint {
+    /// Returns the value which is injected into the program hash together with the operation;
+    /// this is the pushed value for PUSH operation, and the code of the executed u32 operation
+    /// for U32 operation.
     pub fn value(&self) -> BaseElement {
         match self {
             OpHint::PushValue(value) => *value,
+            OpHint::U32Op(op) => BaseElement::from(*op as u8),
             _ => BaseElement::ZERO,
         }
     }
 }
 
+impl Serializable for OpHint {
+    /// Serializes this hint into a single byte identifying the hint type followed by the value
+    /// of the hint (if any).
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        match self {
+            OpHint::EqStart => target.write_u8(0),
+            OpHint::RcStart(value) => {
+                target.write_u8(1);
+                target.write_u32(*value);
+            }
+            OpHint::CmpStart(value) => {
+                target.write_u8(2);
+                target.write_u32(*value);
+            }
+            OpHint::PmpathStart(value) => {
+                target.write_u8(3);
+                target.write_u32(*value);
+            }
+            OpHint::PushValue(value) => {
+                target.write_u8(4);
+                value.write_into(target);
+            }
+            OpHint::None => target.write_u8(5),
+            OpHint::U32Op(op) => {
+                target.write_u8(6);
+                target.write_u8(*op as u8);
+            }
+            OpHint::StorageRead => target.write_u8(7),
+            OpHint::StorageWrite => target.write_u8(8),
+            OpHint::MemoryRead => target.write_u8(9),
+            OpHint::MemoryWrite => target.write_u8(10),
+        }
+    }
+}
+
+impl Deserializable for OpHint {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        match source.read_u8()? {
+            0 => Ok(OpHint::EqStart),
+            1 => Ok(OpHint::RcStart(source.read_u32()?)),
+            2 => Ok(OpHint::CmpStart(source.read_u32()?)),
+            3 => Ok(OpHint::PmpathStart(source.read_u32()?)),
+            4 => Ok(OpHint::PushValue(BaseElement::read_from(source)?)),
+            5 => Ok(OpHint::None),
+            6 => {
+                let op = U32Op::try_from(source.read_u8()?)
+                    .map_err(DeserializationError::InvalidValue)?;
+                Ok(OpHint::U32Op(op))
+            }
+            7 => Ok(OpHint::StorageRead),
+            8 => Ok(OpHint::StorageWrite),
+            9 => Ok(OpHint::MemoryRead),
+            10 => Ok(OpHint::MemoryWrite),
+            value => Err(DeserializationError::InvalidValue(format!(
+                "invalid operation hint type {}",
+                value
+            ))),
+        }
+    }
+}
+
 impl fmt::Display for OpHint {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -191,6 +379,11 @@ impl fmt::Display for OpHint {
             OpHint::CmpStart(value) => write!(f, ".{}", value),
             OpHint::PmpathStart(value) => write!(f, ".{}", value),
             OpHint::PushValue(value) => write!(f, "({})", value),
+            OpHint::U32Op(op) => write!(f, "{}", op),
+            OpHint::StorageRead => write!(f, "::read"),
+            OpHint::StorageWrite => write!(f, "::write"),
+            OpHint::MemoryRead => write!(f, "::mread"),
+            OpHint::MemoryWrite => write!(f, "::mwrite"),
             OpHint::None => Ok(()),
         }
     }
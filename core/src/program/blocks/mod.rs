@@ -1,5 +1,9 @@
-use super::{hash_op, hash_seq, BaseElement, FieldElement, OpCode, OpHint, BASE_CYCLE_LENGTH};
-use core::fmt;
+use super::{
+    hash_counted_seq, hash_op, hash_seq, BaseElement, ByteReader, ByteWriter, Deserializable,
+    DeserializationError, FieldElement, OpCode, OpHint, Serializable, StarkField,
+    BASE_CYCLE_LENGTH,
+};
+use core::{convert::TryFrom, fmt};
 use winter_utils::collections::BTreeMap;
 
 //#[cfg(test)]
@@ -28,6 +32,10 @@ const LOOP_SKIP_BLOCK: [OpCode; 15] = [
     OpCode::Noop,
 ];
 
+const REPEAT_EXIT_BLOCK: [OpCode; 15] = [OpCode::Noop; 15];
+
+const REPEAT_BLOCK_SUFFIX: [u8; 16] = [OpCode::Noop as u8; 16];
+
 const LOOP_BLOCK_SUFFIX: [u8; 16] = [
     OpCode::Not as u8,
     OpCode::Assert as u8,
@@ -56,12 +64,15 @@ pub enum ProgramBlock {
     Group(Group),
     Switch(Switch),
     Loop(Loop),
+    Repeat(Repeat),
+    Call(Call),
 }
 
 #[derive(Clone)]
 pub struct Span {
     op_codes: Vec<OpCode>,
     op_hints: BTreeMap<usize, OpHint>,
+    op_locations: BTreeMap<usize, SourceLocation>,
 }
 
 #[derive(Clone)]
@@ -81,6 +92,26 @@ pub struct Loop {
     skip: Vec<ProgramBlock>,
 }
 
+#[derive(Clone)]
+pub struct Repeat {
+    body: Vec<ProgramBlock>,
+    num_iterations: usize,
+    exit: Span,
+}
+
+#[derive(Clone)]
+pub struct Call {
+    hash: BaseElement,
+}
+
+/// Location of an instruction in the source code from which a program was compiled; lines and
+/// columns are 1-based.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourceLocation {
+    line: usize,
+    column: usize,
+}
+
 // PROGRAM BLOCK IMPLEMENTATION
 // ================================================================================================
 
@@ -97,6 +128,8 @@ impl fmt::Debug for ProgramBlock {
             ProgramBlock::Group(block) => write!(f, "{:?}", block)?,
             ProgramBlock::Switch(block) => write!(f, "{:?}", block)?,
             ProgramBlock::Loop(block) => write!(f, "{:?}", block)?,
+            ProgramBlock::Repeat(block) => write!(f, "{:?}", block)?,
+            ProgramBlock::Call(block) => write!(f, "{:?}", block)?,
         }
         Ok(())
     }
@@ -106,54 +139,27 @@ impl fmt::Debug for ProgramBlock {
 // ================================================================================================
 impl Span {
     pub fn new(instructions: Vec<OpCode>, hints: BTreeMap<usize, OpHint>) -> Span {
-        let alignment = instructions.len() % BASE_CYCLE_LENGTH;
-        assert!(
-            alignment == BASE_CYCLE_LENGTH - 1,
-            "invalid number of instructions: expected one less than a multiple of {}, but was {}",
-            BASE_CYCLE_LENGTH,
-            instructions.len()
-        );
-
-        // make sure all instructions are valid
-        for (i, &op_code) in instructions.iter().enumerate() {
-            if op_code == OpCode::Push {
-                assert!(
-                    i % 8 == 0,
-                    "PUSH is not allowed on step {}, must be on step which is a multiple of 8",
-                    i
-                );
-                let hint = hints.get(&i);
-                assert!(
-                    hint.is_some(),
-                    "invalid PUSH operation on step {}: operation value is missing",
-                    i
-                );
-                match hint.unwrap() {
-                    OpHint::PushValue(_) => (),
-                    _ => panic!(
-                        "invalid PUSH operation on step {}: operation value is of wrong type",
-                        i
-                    ),
-                }
-            }
-        }
-
-        // make sure all hints are within bounds
-        for &step in hints.keys() {
-            assert!(
-                step < instructions.len(),
-                "hint out of bounds: step must be smaller than {} but is {}",
-                instructions.len(),
-                step
-            );
+        if let Err(message) = validate_span(&instructions, &hints) {
+            panic!("{}", message);
         }
 
         Span {
             op_codes: instructions,
             op_hints: hints,
+            op_locations: BTreeMap::new(),
         }
     }
 
+    /// Attaches source locations to this span; `locations` maps indexes of operations to the
+    /// locations of the instructions which start with these operations.
+    ///
+    /// Source locations are debug information: they do not affect the hash of the span and
+    /// are not serialized.
+    pub fn with_locations(mut self, locations: BTreeMap<usize, SourceLocation>) -> Span {
+        self.op_locations = locations;
+        self
+    }
+
     pub fn new_block(instructions: Vec<OpCode>) -> ProgramBlock {
         ProgramBlock::Span(Span::new(instructions, BTreeMap::new()))
     }
@@ -181,15 +187,34 @@ impl Span {
         }
     }
 
+    /// Returns the source location of the instruction which starts with the operation at the
+    /// specified index, or None if no instruction starts there or the location is unknown.
+    pub fn get_location(&self, op_index: usize) -> Option<SourceLocation> {
+        self.op_locations.get(&op_index).copied()
+    }
+
+    /// Returns the source location of the instruction to which the operation at the specified
+    /// index belongs; this is the location of the closest instruction which starts at or before
+    /// the operation. None is returned if the location is unknown.
+    pub fn get_instruction_location(&self, op_index: usize) -> Option<SourceLocation> {
+        self.op_locations
+            .range(..=op_index)
+            .next_back()
+            .map(|(_, &location)| location)
+    }
+
     pub fn hash(&self, mut state: [BaseElement; 4]) -> [BaseElement; 4] {
         for (i, &op_code) in self.op_codes.iter().enumerate() {
-            let op_value = if op_code == OpCode::Push {
-                match self.get_hint(i) {
+            let op_value = match op_code {
+                OpCode::Push => match self.get_hint(i) {
                     OpHint::PushValue(op_value) => op_value,
                     _ => panic!("value for PUSH operation is missing"),
-                }
-            } else {
-                BaseElement::ZERO
+                },
+                OpCode::U32 => match self.get_hint(i) {
+                    hint @ OpHint::U32Op(_) => hint.value(),
+                    _ => panic!("u32 operation hint for U32 operation is missing"),
+                },
+                _ => BaseElement::ZERO,
             };
             hash_op(&mut state, op_code as u8, op_value, i)
         }
@@ -209,8 +234,14 @@ impl Span {
             new_hints.insert(step + offset, hint);
         }
 
+        // merge source locations
+        let mut new_locations = span1.op_locations.clone();
+        for (step, &location) in &span2.op_locations {
+            new_locations.insert(step + offset, location);
+        }
+
         // build and return a new Span
-        Span::new(new_op_codes, new_hints)
+        Span::new(new_op_codes, new_hints).with_locations(new_locations)
     }
 }
 
@@ -372,26 +403,444 @@ impl fmt::Debug for Loop {
     }
 }
 
+// REPEAT IMPLEMENTATION
+// ================================================================================================
+impl Repeat {
+    /// Returns a block which executes the `body` the specified number of times.
+    ///
+    /// A Repeat block is executed as a loop which is always entered: the body is executed once
+    /// for every iteration, and the number of iterations is tracked by the loop counter of the
+    /// decoder rather than by the value at the top of the stack. Thus, neither the size of the
+    /// block nor the time needed to compute its hash depends on the number of iterations, but
+    /// the hash of the block commits to the number of iterations via the final value of the
+    /// counter.
+    ///
+    /// # Panics
+    /// Panics if the number of iterations is 0 or greater than [u32::MAX].
+    pub fn new(body: Vec<ProgramBlock>, num_iterations: usize) -> Repeat {
+        validate_block_list(&body, &[]);
+        if let Err(message) = check_num_iterations(num_iterations) {
+            panic!("{}", message);
+        }
+
+        let exit = Span::from_instructions(REPEAT_EXIT_BLOCK.to_vec());
+        Repeat {
+            body,
+            num_iterations,
+            exit,
+        }
+    }
+
+    pub fn new_block(body: Vec<ProgramBlock>, num_iterations: usize) -> ProgramBlock {
+        ProgramBlock::Repeat(Repeat::new(body, num_iterations))
+    }
+
+    /// Returns the body which is executed in every iteration.
+    pub fn body(&self) -> &[ProgramBlock] {
+        &self.body
+    }
+
+    /// Returns the number of times the body is executed.
+    pub fn num_iterations(&self) -> usize {
+        self.num_iterations
+    }
+
+    /// Returns the hash of a single iteration of the body; this value is placed on the loop
+    /// stack when the block is entered.
+    pub fn image(&self) -> BaseElement {
+        hash_seq(&self.body, &[], 0)
+    }
+
+    /// Returns the hash of the body executed for all iterations.
+    pub fn body_hash(&self) -> BaseElement {
+        hash_counted_seq(&self.body, self.num_iterations, &REPEAT_BLOCK_SUFFIX, 0)
+    }
+
+    /// Returns the Span block which is executed after the last iteration of the body.
+    pub fn exit(&self) -> &Span {
+        &self.exit
+    }
+
+    pub fn get_hash(&self) -> (BaseElement, BaseElement) {
+        let v0 = self.body_hash();
+        (v0, BaseElement::ZERO)
+    }
+}
+
+impl fmt::Debug for Repeat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "repeat.{} ", self.num_iterations)?;
+        for block in self.body.iter() {
+            write!(f, "{:?} ", block)?;
+        }
+        write!(f, "end")
+    }
+}
+
+// CALL IMPLEMENTATION
+// ================================================================================================
+impl Call {
+    /// Returns a block which executes the program block with the specified hash; the callee is
+    /// not a part of the program, and is looked up in a program library when the program is
+    /// executed.
+    ///
+    /// A Call block is hashed in the same way as a Group block with the callee's body, and thus,
+    /// a program which calls a block has the same hash as the program which inlines it.
+    pub fn new(hash: BaseElement) -> Call {
+        Call { hash }
+    }
+
+    pub fn new_block(hash: BaseElement) -> ProgramBlock {
+        ProgramBlock::Call(Call::new(hash))
+    }
+
+    /// Returns the hash of the block executed by this call.
+    pub fn callee_hash(&self) -> BaseElement {
+        self.hash
+    }
+
+    pub fn get_hash(&self) -> (BaseElement, BaseElement) {
+        (self.hash, BaseElement::ZERO)
+    }
+}
+
+impl fmt::Debug for Call {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "call.{:#x}", self.hash.as_int())
+    }
+}
+
+// SOURCE LOCATION IMPLEMENTATION
+// ================================================================================================
+impl SourceLocation {
+    pub fn new(line: usize, column: usize) -> SourceLocation {
+        SourceLocation { line, column }
+    }
+
+    /// Returns the line on which the instruction is located.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the column at which the instruction starts.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+const SPAN_BLOCK_TAG: u8 = 0;
+const GROUP_BLOCK_TAG: u8 = 1;
+const SWITCH_BLOCK_TAG: u8 = 2;
+const LOOP_BLOCK_TAG: u8 = 3;
+const CALL_BLOCK_TAG: u8 = 4;
+const REPEAT_BLOCK_TAG: u8 = 5;
+
+impl Serializable for ProgramBlock {
+    /// Serializes this block into a single byte identifying the block type followed by the
+    /// contents of the block.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        match self {
+            ProgramBlock::Span(block) => {
+                target.write_u8(SPAN_BLOCK_TAG);
+                block.write_into(target);
+            }
+            ProgramBlock::Group(block) => {
+                target.write_u8(GROUP_BLOCK_TAG);
+                block.write_into(target);
+            }
+            ProgramBlock::Switch(block) => {
+                target.write_u8(SWITCH_BLOCK_TAG);
+                block.write_into(target);
+            }
+            ProgramBlock::Loop(block) => {
+                target.write_u8(LOOP_BLOCK_TAG);
+                block.write_into(target);
+            }
+            ProgramBlock::Repeat(block) => {
+                target.write_u8(REPEAT_BLOCK_TAG);
+                block.write_into(target);
+            }
+            ProgramBlock::Call(block) => {
+                target.write_u8(CALL_BLOCK_TAG);
+                block.write_into(target);
+            }
+        }
+    }
+}
+
+impl Deserializable for ProgramBlock {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        match source.read_u8()? {
+            SPAN_BLOCK_TAG => Ok(ProgramBlock::Span(Span::read_from(source)?)),
+            GROUP_BLOCK_TAG => Ok(ProgramBlock::Group(Group::read_from(source)?)),
+            SWITCH_BLOCK_TAG => Ok(ProgramBlock::Switch(Switch::read_from(source)?)),
+            LOOP_BLOCK_TAG => Ok(ProgramBlock::Loop(Loop::read_from(source)?)),
+            CALL_BLOCK_TAG => Ok(ProgramBlock::Call(Call::read_from(source)?)),
+            REPEAT_BLOCK_TAG => Ok(ProgramBlock::Repeat(Repeat::read_from(source)?)),
+            value => Err(DeserializationError::InvalidValue(format!(
+                "invalid program block type {}",
+                value
+            ))),
+        }
+    }
+}
+
+impl Serializable for Span {
+    /// Serializes this block as the number of instructions followed by instruction op codes,
+    /// and then the number of hints followed by (step, hint) pairs.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u32(self.op_codes.len() as u32);
+        for &op_code in self.op_codes.iter() {
+            target.write_u8(op_code as u8);
+        }
+
+        target.write_u32(self.op_hints.len() as u32);
+        for (&step, hint) in self.op_hints.iter() {
+            target.write_u32(step as u32);
+            hint.write_into(target);
+        }
+    }
+}
+
+impl Deserializable for Span {
+    /// Reads a Span block from the `source` and makes sure the block is valid.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let num_op_codes = source.read_u32()? as usize;
+        let mut op_codes = Vec::new();
+        for _ in 0..num_op_codes {
+            let op_code =
+                OpCode::try_from(source.read_u8()?).map_err(DeserializationError::InvalidValue)?;
+            op_codes.push(op_code);
+        }
+
+        let num_hints = source.read_u32()? as usize;
+        let mut op_hints = BTreeMap::new();
+        for _ in 0..num_hints {
+            let step = source.read_u32()? as usize;
+            op_hints.insert(step, OpHint::read_from(source)?);
+        }
+
+        validate_span(&op_codes, &op_hints).map_err(DeserializationError::InvalidValue)?;
+        Ok(Span {
+            op_codes,
+            op_hints,
+            op_locations: BTreeMap::new(),
+        })
+    }
+}
+
+impl Serializable for Group {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        write_block_list(&self.body, target);
+    }
+}
+
+impl Deserializable for Group {
+    /// Reads a Group block from the `source` and makes sure the block is valid.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let body = read_block_list(source, &[])?;
+        Ok(Group { body })
+    }
+}
+
+impl Serializable for Switch {
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        write_block_list(&self.t_branch, target);
+        write_block_list(&self.f_branch, target);
+    }
+}
+
+impl Deserializable for Switch {
+    /// Reads a Switch block from the `source` and makes sure the block is valid.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let t_branch = read_block_list(source, &[OpCode::Assert])?;
+        let f_branch = read_block_list(source, &[OpCode::Not, OpCode::Assert])?;
+        Ok(Switch { t_branch, f_branch })
+    }
+}
+
+impl Serializable for Loop {
+    /// Serializes the body of this loop; the skip block is always the same, and thus, it is
+    /// not serialized.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        write_block_list(&self.body, target);
+    }
+}
+
+impl Deserializable for Loop {
+    /// Reads a Loop block from the `source` and makes sure the block is valid.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let body = read_block_list(source, &[OpCode::Assert])?;
+        Ok(Loop::new(body))
+    }
+}
+
+impl Serializable for Repeat {
+    /// Serializes this block as the number of iterations followed by the body of the block.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u32(self.num_iterations as u32);
+        write_block_list(&self.body, target);
+    }
+}
+
+impl Deserializable for Repeat {
+    /// Reads a Repeat block from the `source` and makes sure the block is valid.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let num_iterations = source.read_u32()? as usize;
+        check_num_iterations(num_iterations).map_err(DeserializationError::InvalidValue)?;
+        let body = read_block_list(source, &[])?;
+        Ok(Repeat::new(body, num_iterations))
+    }
+}
+
+impl Serializable for Call {
+    /// Serializes this block as the hash of the callee.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        self.hash.write_into(target);
+    }
+}
+
+impl Deserializable for Call {
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let hash = BaseElement::read_from(source)?;
+        Ok(Call { hash })
+    }
+}
+
+/// Serializes a list of blocks as the number of blocks followed by the blocks themselves.
+fn write_block_list<W: ByteWriter>(blocks: &[ProgramBlock], target: &mut W) {
+    target.write_u32(blocks.len() as u32);
+    for block in blocks.iter() {
+        block.write_into(target);
+    }
+}
+
+/// Reads a list of blocks from the `source` and makes sure the list is valid.
+fn read_block_list<R: ByteReader>(
+    source: &mut R,
+    starts_with: &[OpCode],
+) -> Result<Vec<ProgramBlock>, DeserializationError> {
+    let num_blocks = source.read_u32()? as usize;
+    let mut blocks = Vec::new();
+    for _ in 0..num_blocks {
+        blocks.push(ProgramBlock::read_from(source)?);
+    }
+
+    check_block_list(&blocks, starts_with).map_err(DeserializationError::InvalidValue)?;
+    Ok(blocks)
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
+
+/// Makes sure that the instructions and hints form a valid Span block.
+fn validate_span(instructions: &[OpCode], hints: &BTreeMap<usize, OpHint>) -> Result<(), String> {
+    let alignment = instructions.len() % BASE_CYCLE_LENGTH;
+    if alignment != BASE_CYCLE_LENGTH - 1 {
+        return Err(format!(
+            "invalid number of instructions: expected one less than a multiple of {}, but was {}",
+            BASE_CYCLE_LENGTH,
+            instructions.len()
+        ));
+    }
+
+    // make sure all instructions are valid
+    for (i, &op_code) in instructions.iter().enumerate() {
+        if op_code == OpCode::Push {
+            if i % 8 != 0 {
+                return Err(format!(
+                    "PUSH is not allowed on step {}, must be on step which is a multiple of 8",
+                    i
+                ));
+            }
+            match hints.get(&i) {
+                Some(OpHint::PushValue(_)) => (),
+                Some(_) => {
+                    return Err(format!(
+                        "invalid PUSH operation on step {}: operation value is of wrong type",
+                        i
+                    ))
+                }
+                None => {
+                    return Err(format!(
+                        "invalid PUSH operation on step {}: operation value is missing",
+                        i
+                    ))
+                }
+            }
+        } else if op_code == OpCode::U32 {
+            match hints.get(&i) {
+                Some(OpHint::U32Op(_)) => (),
+                _ => {
+                    return Err(format!(
+                        "invalid U32 operation on step {}: u32 operation is missing",
+                        i
+                    ))
+                }
+            }
+        }
+    }
+
+    // make sure all hints are within bounds
+    for &step in hints.keys() {
+        if step >= instructions.len() {
+            return Err(format!(
+                "hint out of bounds: step must be smaller than {} but is {}",
+                instructions.len(),
+                step
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Makes sure that the number of iterations of a Repeat block is valid.
+fn check_num_iterations(num_iterations: usize) -> Result<(), String> {
+    if num_iterations == 0 || num_iterations > u32::MAX as usize {
+        return Err(format!(
+            "invalid number of iterations: expected a value between 1 and {}, but was {}",
+            u32::MAX,
+            num_iterations
+        ));
+    }
+    Ok(())
+}
+
 fn validate_block_list(blocks: &[ProgramBlock], starts_with: &[OpCode]) {
-    assert!(
-        !blocks.is_empty(),
-        "a sequence of blocks must contain at least one block"
-    );
+    if let Err(message) = check_block_list(blocks, starts_with) {
+        panic!("{}", message);
+    }
+}
+
+/// Makes sure that the list of blocks is not empty, starts with a Span block which begins with
+/// the specified instructions, and does not contain consecutive Span blocks.
+fn check_block_list(blocks: &[ProgramBlock], starts_with: &[OpCode]) -> Result<(), String> {
+    if blocks.is_empty() {
+        return Err("a sequence of blocks must contain at least one block".to_string());
+    }
 
     // first block must be a span block
     match &blocks[0] {
         ProgramBlock::Span(block) => {
             // if the block must start with a specific sequence of instructions, make sure it does
-            if !starts_with.is_empty() {
-                assert!(
-                    block.starts_with(starts_with),
+            if !starts_with.is_empty() && !block.starts_with(starts_with) {
+                return Err(
                     "the first block does not start with a valid sequence of instructions"
+                        .to_string(),
                 );
             }
         }
-        _ => panic!("a sequence of blocks must start with a Span block"),
+        _ => return Err("a sequence of blocks must start with a Span block".to_string()),
     };
 
     // span block cannot be followed by another span block
@@ -399,12 +848,13 @@ fn validate_block_list(blocks: &[ProgramBlock], starts_with: &[OpCode]) {
     for block in blocks.iter().skip(1) {
         match block {
             ProgramBlock::Span(_) => {
-                assert!(
-                    !was_span,
-                    "a Span block cannot be followed by another Span block"
-                );
+                if was_span {
+                    return Err("a Span block cannot be followed by another Span block".to_string());
+                }
             }
             _ => was_span = false,
         }
     }
+
+    Ok(())
 }
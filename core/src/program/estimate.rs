@@ -0,0 +1,297 @@
+use super::{
+    blocks::{Group, ProgramBlock, Span},
+    BaseElement, OpCode, ProgramLibrary, BASE_CYCLE_LENGTH, HACC_NUM_ROUNDS,
+};
+use crate::{
+    TraceState, MAX_STACK_DEPTH, MEMORY_TRACE_WIDTH, MIN_TRACE_LENGTH, STORAGE_TRACE_WIDTH,
+    U32_TRACE_WIDTH,
+};
+use core::cmp;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of cycles needed to start a Group, Switch, or Call block (a single BEGIN operation),
+/// or to enter a loop (a single LOOP operation).
+const BLOCK_START_CYCLES: usize = 1;
+
+/// Number of cycles needed to exit a Repeat block: a BREAK operation followed by the exit block.
+const REPEAT_EXIT_CYCLES: usize = BASE_CYCLE_LENGTH;
+
+/// Number of cycles needed to close a block: a padding NOOP, a TEND or FEND operation, and NOOPs
+/// for merging the hash of the block into the program hash.
+const BLOCK_END_CYCLES: usize = HACC_NUM_ROUNDS + 2;
+
+// CYCLE ESTIMATE
+// ================================================================================================
+
+/// Number of VM cycles needed to execute a program, and the shape of the execution trace
+/// resulting from it, computed without executing the program.
+///
+/// The number of cycles is exact for programs which consist of straight-line code only (this
+/// includes Repeat blocks, as their number of iterations is fixed). For programs with
+/// conditional branches, the minimum and the maximum number of cycles are computed over all
+/// branches; for programs with loops, the maximum number of cycles is not bounded, and the
+/// minimum assumes that no loop is entered.
+///
+/// Cycle counts saturate at `usize::MAX`, and trace lengths saturate at the largest power of two
+/// which fits into `usize`; this can happen only for programs with deeply nested Repeat blocks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CycleEstimate {
+    min_cycles: usize,
+    max_cycles: Option<usize>,
+    ctx_depth: usize,
+    loop_depth: usize,
+    has_memory: bool,
+    has_storage: bool,
+    has_u32_table: bool,
+    has_unknown_calls: bool,
+}
+
+impl CycleEstimate {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns an estimate for the program with the specified root block; blocks called by the
+    /// program are looked up in the specified `library`.
+    pub(super) fn new(root: &Group, library: &ProgramLibrary) -> CycleEstimate {
+        let body = estimate_sequence(root.body(), library);
+        CycleEstimate {
+            min_cycles: body.min_cycles.saturating_add(BLOCK_END_CYCLES),
+            max_cycles: body
+                .max_cycles
+                .map(|cycles| cycles.saturating_add(BLOCK_END_CYCLES)),
+            ..body
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the minimum number of cycles needed to execute the program.
+    pub fn min_cycles(&self) -> usize {
+        self.min_cycles
+    }
+
+    /// Returns the maximum number of cycles needed to execute the program, or None if the
+    /// number of cycles is not bounded (e.g. the program contains loops).
+    pub fn max_cycles(&self) -> Option<usize> {
+        self.max_cycles
+    }
+
+    /// Returns true if the program is always executed in the same number of cycles.
+    pub fn is_exact(&self) -> bool {
+        self.max_cycles == Some(self.min_cycles)
+    }
+
+    /// Returns the minimum length of the execution trace of the program.
+    pub fn min_trace_length(&self) -> usize {
+        get_trace_length(self.min_cycles)
+    }
+
+    /// Returns the maximum length of the execution trace of the program, or None if the number
+    /// of cycles is not bounded.
+    pub fn max_trace_length(&self) -> Option<usize> {
+        self.max_cycles.map(get_trace_length)
+    }
+
+    /// Returns the maximum number of context stack registers needed to execute the program;
+    /// this is the depth of block nesting.
+    pub fn ctx_depth(&self) -> usize {
+        self.ctx_depth
+    }
+
+    /// Returns the maximum number of loop stack registers needed to execute the program; this
+    /// is the depth of loop nesting.
+    pub fn loop_depth(&self) -> usize {
+        self.loop_depth
+    }
+
+    /// Returns the maximum number of decoder registers in the execution trace of the program;
+    /// this does not include registers of the stack, memory, storage, and u32 operations table.
+    pub fn decoder_width(&self) -> usize {
+        TraceState::<BaseElement>::compute_decoder_width(self.ctx_depth, self.loop_depth)
+    }
+
+    /// Returns the maximum number of registers in the execution trace of the program, or None
+    /// if the program calls blocks which are not in the library.
+    ///
+    /// The depth of the stack depends on the inputs of the program, and thus, the width is
+    /// computed for the stack of [MAX_STACK_DEPTH] items. Memory, storage, and u32 operations
+    /// registers are counted if the program contains operations which access them, even if these
+    /// operations may not be executed.
+    pub fn max_trace_width(&self) -> Option<usize> {
+        if self.has_unknown_calls {
+            return None;
+        }
+
+        let mut width = self.decoder_width() + MAX_STACK_DEPTH;
+        if self.has_memory {
+            width += MEMORY_TRACE_WIDTH;
+        }
+        if self.has_storage {
+            width += STORAGE_TRACE_WIDTH;
+        }
+        if self.has_u32_table {
+            width += U32_TRACE_WIDTH;
+        }
+        Some(width)
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Updates the depths of this estimate to account for a block nested in the estimated
+    /// sequence; the nested block adds one item to the context stack and `num_loops` items to
+    /// the loop stack on top of the depths of its `body`.
+    fn add_nested(&mut self, body: &CycleEstimate, num_loops: usize) {
+        self.ctx_depth = cmp::max(self.ctx_depth, body.ctx_depth + 1);
+        self.loop_depth = cmp::max(self.loop_depth, body.loop_depth + num_loops);
+        self.add_sections(body);
+    }
+
+    /// Updates this estimate to include the sections of the execution trace which are needed
+    /// by `other` estimate.
+    fn add_sections(&mut self, other: &CycleEstimate) {
+        self.has_memory |= other.has_memory;
+        self.has_storage |= other.has_storage;
+        self.has_u32_table |= other.has_u32_table;
+        self.has_unknown_calls |= other.has_unknown_calls;
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Estimates the cycles needed to execute the specified sequence of blocks; the depths of the
+/// resulting estimate are not limited by the sizes of the context and loop stacks.
+///
+/// Every Span block which is not the first block of the sequence is pre-padded with a NOOP; every
+/// other block is started with a single operation and closed in [BLOCK_END_CYCLES] cycles.
+fn estimate_sequence(blocks: &[ProgramBlock], library: &ProgramLibrary) -> CycleEstimate {
+    let mut result = CycleEstimate {
+        min_cycles: 0,
+        max_cycles: Some(0),
+        ctx_depth: 0,
+        loop_depth: 0,
+        has_memory: false,
+        has_storage: false,
+        has_u32_table: false,
+        has_unknown_calls: false,
+    };
+
+    for (i, block) in blocks.iter().enumerate() {
+        let (min_cycles, max_cycles) = match block {
+            ProgramBlock::Span(block) => {
+                add_span_sections(&mut result, block);
+                let cycles = block.length() + (i > 0) as usize;
+                (cycles, Some(cycles))
+            }
+            ProgramBlock::Group(block) => {
+                let body = estimate_sequence(block.body(), library);
+                result.add_nested(&body, 0);
+                wrap_block(&body)
+            }
+            ProgramBlock::Switch(block) => {
+                let t_branch = estimate_sequence(block.true_branch(), library);
+                let f_branch = estimate_sequence(block.false_branch(), library);
+                result.add_nested(&t_branch, 0);
+                result.add_nested(&f_branch, 0);
+
+                let (t_min, t_max) = wrap_block(&t_branch);
+                let (f_min, f_max) = wrap_block(&f_branch);
+                let max_cycles = t_max.and_then(|t_max| f_max.map(|f_max| cmp::max(t_max, f_max)));
+                (cmp::min(t_min, f_min), max_cycles)
+            }
+            ProgramBlock::Loop(block) => {
+                // when the loop is not entered, its skip block is executed as a Group block;
+                // otherwise, the number of iterations is not known
+                let body = estimate_sequence(block.body(), library);
+                let skip = estimate_sequence(block.skip(), library);
+                result.add_nested(&body, 1);
+                result.add_sections(&skip);
+                (wrap_block(&skip).0, None)
+            }
+            ProgramBlock::Repeat(block) => {
+                // the body is executed as a loop which is always entered; every iteration but
+                // the last one is followed by a WRAP operation, and the last one is followed by
+                // a BREAK operation and the exit block
+                let body = estimate_sequence(block.body(), library);
+                result.add_nested(&body, 1);
+
+                let n = block.num_iterations();
+                let body = CycleEstimate {
+                    min_cycles: repeat_cycles(body.min_cycles, n)
+                        .saturating_add(REPEAT_EXIT_CYCLES),
+                    max_cycles: body
+                        .max_cycles
+                        .map(|cycles| repeat_cycles(cycles, n).saturating_add(REPEAT_EXIT_CYCLES)),
+                    ..body
+                };
+                wrap_block(&body)
+            }
+            ProgramBlock::Call(block) => match library.get(block.callee_hash()) {
+                Some(callee) => {
+                    let body = estimate_sequence(callee.body(), library);
+                    result.add_nested(&body, 0);
+                    wrap_block(&body)
+                }
+                // if the callee is not in the library, only the cost of the call itself is known
+                None => {
+                    result.ctx_depth = cmp::max(result.ctx_depth, 1);
+                    result.has_unknown_calls = true;
+                    (BLOCK_START_CYCLES + BLOCK_END_CYCLES, None)
+                }
+            },
+        };
+
+        result.min_cycles = result.min_cycles.saturating_add(min_cycles);
+        result.max_cycles = result
+            .max_cycles
+            .and_then(|total| max_cycles.map(|cycles| total.saturating_add(cycles)));
+    }
+    result
+}
+
+/// Updates the estimate to include the sections of the execution trace which are accessed by
+/// operations of the specified Span block.
+fn add_span_sections(estimate: &mut CycleEstimate, block: &Span) {
+    for i in 0..block.length() {
+        match block.get_op(i).0 {
+            OpCode::MRoot => estimate.has_memory = true,
+            OpCode::SRoot => estimate.has_storage = true,
+            OpCode::U32 => estimate.has_u32_table = true,
+            _ => (),
+        }
+    }
+}
+
+/// Returns the minimum and the maximum number of cycles needed to execute a block with the
+/// specified body, including the cycles needed to start and to close the block.
+fn wrap_block(body: &CycleEstimate) -> (usize, Option<usize>) {
+    let overhead = BLOCK_START_CYCLES + BLOCK_END_CYCLES;
+    (
+        body.min_cycles.saturating_add(overhead),
+        body.max_cycles
+            .map(|cycles| cycles.saturating_add(overhead)),
+    )
+}
+
+/// Returns the number of cycles needed to execute a body of the specified length the specified
+/// number of times; every iteration but the last one is followed by a WRAP operation.
+pub(super) fn repeat_cycles(body_cycles: usize, num_iterations: usize) -> usize {
+    body_cycles
+        .saturating_mul(num_iterations)
+        .saturating_add(num_iterations - 1)
+}
+
+/// Returns the length of the execution trace of a program executed in the specified number of
+/// cycles; the trace contains the initial state and a state for every cycle, and its length is
+/// padded to the next power of two.
+pub(super) fn get_trace_length(num_cycles: usize) -> usize {
+    let trace_length = num_cycles
+        .saturating_add(1)
+        .checked_next_power_of_two()
+        .unwrap_or(1 << (usize::BITS - 1));
+    cmp::max(trace_length, MIN_TRACE_LENGTH)
+}
@@ -1,6 +1,6 @@
 use super::{
     op_sponge, BaseElement, FieldElement, OpCode, ProgramBlock, BASE_CYCLE_LENGTH, HACC_NUM_ROUNDS,
-    OP_SPONGE_WIDTH as STATE_WIDTH,
+    LOOP_COUNTER_BASE, OP_SPONGE_WIDTH as STATE_WIDTH,
 };
 
 // CONSTANTS
@@ -13,6 +13,59 @@ pub const NOOP_VALUE: u8 = OpCode::Noop as u8;
 
 /// Returns a hash of a sequence of program blocks.
 pub fn hash_seq(blocks: &[ProgramBlock], suffix: &[u8], suffix_offset: usize) -> BaseElement {
+    let mut state = hash_blocks(blocks);
+    apply_suffix(&mut state, suffix, suffix_offset);
+    state[0]
+}
+
+/// Returns a hash of the body of a Repeat block which executes the specified `blocks` the
+/// specified number of times.
+///
+/// The body is hashed only once: when the block is exited, the counter of the loop minus 1 is
+/// added to the last element of the sponge state. The counter starts at 0 and is updated as
+/// ctr' = g * ctr + (1 - g) on every WRAP, and thus, after `num_iterations - 1` updates, the
+/// added value is -g^(num_iterations - 1); so, the hash is computed in time logarithmic in the
+/// number of iterations.
+pub fn hash_counted_seq(
+    blocks: &[ProgramBlock],
+    num_iterations: usize,
+    suffix: &[u8],
+    suffix_offset: usize,
+) -> BaseElement {
+    let mut state = hash_blocks(blocks);
+    state[3] -= LOOP_COUNTER_BASE.exp((num_iterations - 1) as u128);
+    apply_suffix(&mut state, suffix, suffix_offset);
+    state[0]
+}
+
+/// Merges an operation with the state of the sponge.
+pub fn hash_op(
+    state: &mut [BaseElement; STATE_WIDTH],
+    op_code: u8,
+    op_value: BaseElement,
+    step: usize,
+) {
+    op_sponge::apply_round(state, BaseElement::from(op_code), op_value, step);
+}
+
+/// Merges hash of a control block (v0, v1) into the hash of the parent block.
+pub fn hash_acc(
+    parent_hash: BaseElement,
+    v0: BaseElement,
+    v1: BaseElement,
+) -> [BaseElement; STATE_WIDTH] {
+    let mut state = [parent_hash, v0, v1, BaseElement::ZERO];
+    for i in HACC_ROUND_OFFSET..(HACC_ROUND_OFFSET + HACC_NUM_ROUNDS) {
+        hash_op(&mut state, NOOP_VALUE, BaseElement::ZERO, i);
+    }
+    state
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the state of the sponge after all blocks of the sequence have been merged into it.
+fn hash_blocks(blocks: &[ProgramBlock]) -> [BaseElement; STATE_WIDTH] {
     // initialize the state to all zeros
     let mut state = [BaseElement::ZERO; STATE_WIDTH];
 
@@ -44,6 +97,8 @@ pub fn hash_seq(blocks: &[ProgramBlock], suffix: &[u8], suffix_offset: usize) ->
                     ProgramBlock::Group(block) => block.get_hash(),
                     ProgramBlock::Switch(block) => block.get_hash(),
                     ProgramBlock::Loop(block) => block.get_hash(),
+                    ProgramBlock::Repeat(block) => block.get_hash(),
+                    ProgramBlock::Call(block) => block.get_hash(),
                     ProgramBlock::Span(_) => (BaseElement::ZERO, BaseElement::ZERO), // can't happen
                 };
 
@@ -53,35 +108,14 @@ pub fn hash_seq(blocks: &[ProgramBlock], suffix: &[u8], suffix_offset: usize) ->
         };
     }
 
-    // apply sequence suffix to ensure alignment on 16-cycle boundary;
-    // for non-loop sequences, suffix will be just a single NOOP;
-    // for loop bodies, suffix will be NOT ASSERT followed by 14 NOOPs;
-    for (i, &op_code) in suffix.iter().enumerate() {
-        hash_op(&mut state, op_code, BaseElement::ZERO, suffix_offset + i);
-    }
-
-    state[0]
-}
-
-/// Merges an operation with the state of the sponge.
-pub fn hash_op(
-    state: &mut [BaseElement; STATE_WIDTH],
-    op_code: u8,
-    op_value: BaseElement,
-    step: usize,
-) {
-    op_sponge::apply_round(state, BaseElement::from(op_code), op_value, step);
+    state
 }
 
-/// Merges hash of a control block (v0, v1) into the hash of the parent block.
-pub fn hash_acc(
-    parent_hash: BaseElement,
-    v0: BaseElement,
-    v1: BaseElement,
-) -> [BaseElement; STATE_WIDTH] {
-    let mut state = [parent_hash, v0, v1, BaseElement::ZERO];
-    for i in HACC_ROUND_OFFSET..(HACC_ROUND_OFFSET + HACC_NUM_ROUNDS) {
-        hash_op(&mut state, NOOP_VALUE, BaseElement::ZERO, i);
+/// Applies sequence suffix to ensure alignment on 16-cycle boundary; for non-loop sequences,
+/// suffix will be just a single NOOP; for loop bodies, suffix will be NOT ASSERT followed by
+/// 14 NOOPs, and for bodies of Repeat blocks, suffix will be 16 NOOPs.
+fn apply_suffix(state: &mut [BaseElement; STATE_WIDTH], suffix: &[u8], suffix_offset: usize) {
+    for (i, &op_code) in suffix.iter().enumerate() {
+        hash_op(state, op_code, BaseElement::ZERO, suffix_offset + i);
     }
-    state
 }
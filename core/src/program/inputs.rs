@@ -1,12 +1,23 @@
-use super::{BaseElement, MAX_PUBLIC_INPUTS};
+use super::{BaseElement, StarkField, MAX_PUBLIC_INPUTS};
+use crate::storage::Storage;
+use core::fmt;
+
+#[cfg(feature = "json")]
+use core::convert::TryFrom;
+
+#[cfg(feature = "json")]
+use serde::Deserialize;
 
 // PROGRAM INPUTS
 // ================================================================================================
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(Deserialize))]
+#[cfg_attr(feature = "json", serde(try_from = "InputFile"))]
 pub struct ProgramInputs {
     public: Vec<BaseElement>,
     secret: [Vec<BaseElement>; 2],
+    storage: Storage,
 }
 
 impl ProgramInputs {
@@ -27,7 +38,58 @@ impl ProgramInputs {
                 secret_a.iter().map(|&v| BaseElement::new(v)).collect(),
                 secret_b.iter().map(|&v| BaseElement::new(v)).collect(),
             ],
+            storage: Storage::new(),
+        }
+    }
+
+    /// Returns `ProgramInputs` initialized with the provided public and secret inputs.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// * The number of public inputs is greater than [MAX_PUBLIC_INPUTS].
+    /// * Secret input tape B contains more values than secret input tape A.
+    /// * Any of the values is not a valid field element.
+    pub fn try_new(
+        public: &[u128],
+        secret_a: &[u128],
+        secret_b: &[u128],
+    ) -> Result<ProgramInputs, InputError> {
+        if public.len() > MAX_PUBLIC_INPUTS {
+            return Err(InputError::TooManyPublicInputs(public.len()));
+        }
+        if secret_a.len() < secret_b.len() {
+            return Err(InputError::SecretTapesMismatch(
+                secret_a.len(),
+                secret_b.len(),
+            ));
         }
+
+        Ok(ProgramInputs {
+            public: to_elements(public)?,
+            secret: [to_elements(secret_a)?, to_elements(secret_b)?],
+            storage: Storage::new(),
+        })
+    }
+
+    /// Returns `ProgramInputs` parsed from the specified JSON string.
+    ///
+    /// The JSON object may contain the following fields, all of which are optional:
+    /// * `stack_init` - public inputs used to initialize the stack.
+    /// * `tape_a` - secret inputs for tape A.
+    /// * `tape_b` - secret inputs for tape B.
+    ///
+    /// Every field is an array of values, where each value is either an integer smaller than
+    /// 2^64, or a string containing a decimal or a hexadecimal (prefixed with `0x`) integer; for
+    /// example: `{ "stack_init": [1, "2"], "tape_a": ["0xff"] }`.
+    ///
+    /// # Errors
+    /// Returns an error if the string is not a valid JSON object of the above format, or if the
+    /// inputs are not valid (see [ProgramInputs::try_new()]).
+    #[cfg(feature = "json")]
+    pub fn from_json(source: &str) -> Result<ProgramInputs, InputError> {
+        let file: InputFile =
+            serde_json::from_str(source).map_err(|err| InputError::InvalidJson(err.to_string()))?;
+        ProgramInputs::try_from(file)
     }
 
     /// Returns `ProgramInputs` with public and secret input tapes set to empty vectors.
@@ -35,6 +97,7 @@ impl ProgramInputs {
         ProgramInputs {
             public: Vec::new(),
             secret: [Vec::new(), Vec::new()],
+            storage: Storage::new(),
         }
     }
 
@@ -44,9 +107,17 @@ impl ProgramInputs {
         ProgramInputs {
             public: public.iter().map(|&v| BaseElement::new(v)).collect(),
             secret: [vec![], vec![]],
+            storage: Storage::new(),
         }
     }
 
+    /// Returns these inputs with the initial state of the storage set to the provided snapshot;
+    /// by default, programs are executed against empty storage.
+    pub fn with_storage(mut self, storage: Storage) -> ProgramInputs {
+        self.storage = storage;
+        self
+    }
+
     pub fn public_inputs(&self) -> &[BaseElement] {
         &self.public
     }
@@ -54,4 +125,128 @@ impl ProgramInputs {
     pub fn secret_inputs(&self) -> &[Vec<BaseElement>; 2] {
         &self.secret
     }
+
+    /// Returns the state of the storage at the start of program execution.
+    pub fn storage(&self) -> &Storage {
+        &self.storage
+    }
+}
+
+// INPUT FILE
+// ================================================================================================
+
+/// Program inputs as they are specified in an input file; see [ProgramInputs::from_json()] for
+/// the description of the format.
+#[cfg(feature = "json")]
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct InputFile {
+    stack_init: Vec<InputValue>,
+    tape_a: Vec<InputValue>,
+    tape_b: Vec<InputValue>,
+}
+
+/// A single input value which can be specified either as an integer or as a string.
+#[cfg(feature = "json")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum InputValue {
+    Number(u64),
+    Text(String),
+}
+
+#[cfg(feature = "json")]
+impl InputValue {
+    /// Returns this value as an integer; strings are parsed as hexadecimal integers if they
+    /// start with `0x`, and as decimal integers otherwise.
+    fn parse(&self) -> Result<u128, InputError> {
+        match self {
+            InputValue::Number(value) => Ok(*value as u128),
+            InputValue::Text(text) => {
+                let result = match text.strip_prefix("0x") {
+                    Some(digits) => u128::from_str_radix(digits, 16),
+                    None => text.parse::<u128>(),
+                };
+                result.map_err(|_| InputError::InvalidValue(text.clone()))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl TryFrom<InputFile> for ProgramInputs {
+    type Error = InputError;
+
+    fn try_from(file: InputFile) -> Result<Self, Self::Error> {
+        let parse = |values: &[InputValue]| {
+            values
+                .iter()
+                .map(|value| value.parse())
+                .collect::<Result<Vec<_>, _>>()
+        };
+        ProgramInputs::try_new(
+            &parse(&file.stack_init)?,
+            &parse(&file.tape_a)?,
+            &parse(&file.tape_b)?,
+        )
+    }
+}
+
+// INPUT ERROR
+// ================================================================================================
+
+/// Describes the reason why a set of program inputs is invalid.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputError {
+    /// The number of public inputs is greater than [MAX_PUBLIC_INPUTS].
+    TooManyPublicInputs(usize),
+    /// Secret input tape B contains more values than secret input tape A; the values are the
+    /// lengths of tapes A and B.
+    SecretTapesMismatch(usize, usize),
+    /// The value is not a valid field element.
+    InvalidValue(String),
+    /// The source could not be parsed as JSON.
+    #[cfg(feature = "json")]
+    InvalidJson(String),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::TooManyPublicInputs(num_inputs) => write!(
+                f,
+                "expected no more than {} public inputs, but received {}",
+                MAX_PUBLIC_INPUTS, num_inputs
+            ),
+            InputError::SecretTapesMismatch(len_a, len_b) => write!(
+                f,
+                "tape A must contain at least as many values as tape B, but it contains {} values \
+                while tape B contains {} values",
+                len_a, len_b
+            ),
+            InputError::InvalidValue(value) => {
+                write!(f, "value {} is not a valid field element", value)
+            }
+            #[cfg(feature = "json")]
+            InputError::InvalidJson(message) => write!(f, "invalid JSON: {}", message),
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Converts the values into field elements; returns an error if any of the values is not a
+/// valid field element.
+fn to_elements(values: &[u128]) -> Result<Vec<BaseElement>, InputError> {
+    values
+        .iter()
+        .map(|&value| {
+            if value < BaseElement::MODULUS {
+                Ok(BaseElement::new(value))
+            } else {
+                Err(InputError::InvalidValue(value.to_string()))
+            }
+        })
+        .collect()
 }
@@ -0,0 +1,65 @@
+use super::{
+    blocks::{Group, ProgramBlock},
+    BaseElement, OpCode, StarkField,
+};
+use winter_utils::collections::BTreeMap;
+
+// PROGRAM LIBRARY
+// ================================================================================================
+
+/// A registry of program blocks which can be executed by programs via Call blocks.
+///
+/// Blocks are keyed by their hashes, and thus, a program can execute a block from the library
+/// only if it knows the exact contents of the block. This makes it possible to share a block
+/// between many programs without inlining it into each of them.
+#[derive(Clone, Default)]
+pub struct ProgramLibrary {
+    blocks: BTreeMap<u128, Group>,
+}
+
+impl ProgramLibrary {
+    /// Returns a new empty library.
+    pub fn new() -> ProgramLibrary {
+        ProgramLibrary::default()
+    }
+
+    /// Adds the specified block to the library, and returns the hash by which the block can be
+    /// called from other programs.
+    ///
+    /// # Panics
+    /// Panics if the block starts with BEGIN operation; BEGIN can be executed only at the first
+    /// step of a program, and thus, such a block cannot be called.
+    pub fn add(&mut self, block: Group) -> BaseElement {
+        if let ProgramBlock::Span(span) = &block.body()[0] {
+            assert!(
+                !span.starts_with(&[OpCode::Begin]),
+                "a block which starts with BEGIN operation cannot be called"
+            );
+        }
+
+        let hash = block.body_hash();
+        self.blocks.insert(hash.as_int(), block);
+        hash
+    }
+
+    /// Returns the block with the specified hash, or None if the library does not contain
+    /// such a block.
+    pub fn get(&self, hash: BaseElement) -> Option<&Group> {
+        self.blocks.get(&hash.as_int())
+    }
+
+    /// Returns true if the library contains a block with the specified hash.
+    pub fn contains(&self, hash: BaseElement) -> bool {
+        self.blocks.contains_key(&hash.as_int())
+    }
+
+    /// Returns the number of blocks in the library.
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Returns true if the library does not contain any blocks.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
     op_sponge,
     opcodes::{OpHint, UserOps as OpCode},
-    BaseElement, FieldElement, BASE_CYCLE_LENGTH, HACC_NUM_ROUNDS, MAX_PUBLIC_INPUTS,
-    OP_SPONGE_WIDTH, PROGRAM_DIGEST_SIZE,
+    BaseElement, ByteReader, ByteWriter, Deserializable, DeserializationError, FieldElement,
+    Serializable, StarkField, BASE_CYCLE_LENGTH, HACC_NUM_ROUNDS, LOOP_COUNTER_BASE,
+    MAX_PUBLIC_INPUTS, OP_SPONGE_WIDTH, PROGRAM_DIGEST_SIZE,
 };
 use core::fmt;
 
@@ -10,14 +11,27 @@ pub mod blocks;
 use blocks::{Group, ProgramBlock};
 
 mod inputs;
-pub use inputs::ProgramInputs;
+pub use inputs::{InputError, ProgramInputs};
+
+mod library;
+pub use library::ProgramLibrary;
+
+mod estimate;
+pub use estimate::CycleEstimate;
 
 mod hashing;
-use hashing::{hash_acc, hash_op, hash_seq};
+use hashing::{hash_acc, hash_counted_seq, hash_op, hash_seq};
 
 #[cfg(test)]
 mod tests;
 
+// CONSTANTS
+// ================================================================================================
+
+/// Version of the binary format used for serializing programs; this must be incremented every
+/// time the format changes.
+pub const PROGRAM_FORMAT_VERSION: u8 = 4;
+
 // PROGRAM
 // ================================================================================================
 #[derive(Clone)]
@@ -29,29 +43,28 @@ pub struct Program {
 impl Program {
     /// Constructs a new program from the specified root block.
     pub fn new(root: Group) -> Program {
-        // make sure the root block starts with BEGIN operation
-        match &root.body()[0] {
-            ProgramBlock::Span(block) => {
-                let (op_code, _) = block.get_op(0);
-                assert!(
-                    op_code == OpCode::Begin,
-                    "a program must start with BEGIN operation"
-                );
-            }
-            _ => panic!("a program must start with a Span block"),
+        if let Err(message) = validate_root(&root) {
+            panic!("{}", message);
         }
 
-        // compute program hash
-        let (v0, v1) = root.get_hash();
-        let hash = hash_acc(BaseElement::ZERO, v0, v1);
-        let mut hash_bytes = [0u8; 32];
-        hash_bytes.copy_from_slice(BaseElement::elements_as_bytes(&hash[..PROGRAM_DIGEST_SIZE]));
+        let hash = compute_hash(&root);
+        Program { root, hash }
+    }
 
-        Program {
-            root,
-            hash: hash_bytes,
-        }
+    /// Reads a program serialized via [Serializable::to_bytes()] from the `source` without
+    /// recomputing its hash; the hash recorded in the `source` is assumed to be correct.
+    ///
+    /// This is intended only for programs serialized by a trusted party, such as programs
+    /// embedded into Rust code by the `masm!` macro, for which computing the hash again would
+    /// only slow down loading of the program. Use [Program::read_from()] for all other programs.
+    ///
+    /// # Errors
+    /// Returns an error if the `source` does not contain a valid serialized program.
+    pub fn read_trusted<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let (root, hash) = read_program(source)?;
+        Ok(Program { root, hash })
     }
+
     /// Returns the root block of the program.
     pub fn root(&self) -> &Group {
         &self.root
@@ -61,6 +74,49 @@ impl Program {
     pub fn hash(&self) -> &[u8; 32] {
         &self.hash
     }
+
+    /// Returns an estimate of the number of cycles needed to execute this program, and of the
+    /// shape of the resulting execution trace; the program is not executed.
+    ///
+    /// Blocks called by the program are assumed to be unknown; see
+    /// [Program::estimate_cycles_with_library()] for programs which call blocks.
+    pub fn estimate_cycles(&self) -> CycleEstimate {
+        self.estimate_cycles_with_library(&ProgramLibrary::new())
+    }
+
+    /// Returns an estimate of the number of cycles needed to execute this program, and of the
+    /// shape of the resulting execution trace; blocks called by the program are looked up in
+    /// the specified `library`.
+    pub fn estimate_cycles_with_library(&self, library: &ProgramLibrary) -> CycleEstimate {
+        CycleEstimate::new(&self.root, library)
+    }
+}
+
+impl Serializable for Program {
+    /// Serializes this program as the format version, followed by the program hash, and then
+    /// the blocks of the program's root.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u8(PROGRAM_FORMAT_VERSION);
+        target.write_u8_slice(&self.hash);
+        self.root.write_into(target);
+    }
+}
+
+impl Deserializable for Program {
+    /// Reads a program from the `source`, makes sure all blocks of the program are valid, and
+    /// that the hash of the program matches the hash recorded in the `source`.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let (root, expected_hash) = read_program(source)?;
+
+        let hash = compute_hash(&root);
+        if hash != expected_hash {
+            return Err(DeserializationError::InvalidValue(
+                "program hash does not match the hash of the program's blocks".to_string(),
+            ));
+        }
+
+        Ok(Program { root, hash })
+    }
 }
 
 impl fmt::Debug for Program {
@@ -73,3 +129,48 @@ impl fmt::Debug for Program {
         Ok(())
     }
 }
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Reads the format version, the program hash, and the root block of a serialized program from
+/// the `source`, and makes sure all blocks of the program are valid; the hash is not checked.
+fn read_program<R: ByteReader>(source: &mut R) -> Result<(Group, [u8; 32]), DeserializationError> {
+    let version = source.read_u8()?;
+    if version != PROGRAM_FORMAT_VERSION {
+        return Err(DeserializationError::InvalidValue(format!(
+            "unsupported program format version: expected {}, but was {}",
+            PROGRAM_FORMAT_VERSION, version
+        )));
+    }
+
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&source.read_u8_vec(32)?);
+
+    let root = Group::read_from(source)?;
+    validate_root(&root).map_err(DeserializationError::InvalidValue)?;
+    Ok((root, hash))
+}
+
+/// Makes sure the root block of a program starts with BEGIN operation.
+fn validate_root(root: &Group) -> Result<(), String> {
+    match &root.body()[0] {
+        ProgramBlock::Span(block) => {
+            let (op_code, _) = block.get_op(0);
+            if op_code != OpCode::Begin {
+                return Err("a program must start with BEGIN operation".to_string());
+            }
+        }
+        _ => return Err("a program must start with a Span block".to_string()),
+    }
+    Ok(())
+}
+
+/// Computes hash of a program with the specified root block.
+fn compute_hash(root: &Group) -> [u8; 32] {
+    let (v0, v1) = root.get_hash();
+    let hash = hash_acc(BaseElement::ZERO, v0, v1);
+    let mut hash_bytes = [0u8; 32];
+    hash_bytes.copy_from_slice(BaseElement::elements_as_bytes(&hash[..PROGRAM_DIGEST_SIZE]));
+    hash_bytes
+}
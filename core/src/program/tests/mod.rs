@@ -1,7 +1,14 @@
 use super::{
-    blocks::{Group, Loop, ProgramBlock, Span, Switch},
-    BaseElement, FieldElement, OpCode, Program,
+    blocks::{Call, Group, Loop, ProgramBlock, Repeat, Span, Switch},
+    estimate::{get_trace_length, repeat_cycles},
+    BaseElement, Deserializable, DeserializationError, FieldElement, InputError, OpCode, OpHint,
+    Program, ProgramInputs, ProgramLibrary, Serializable, StarkField, MAX_PUBLIC_INPUTS,
+    PROGRAM_FORMAT_VERSION,
 };
+use crate::{
+    opcodes::U32Op, SliceReader, TraceState, MAX_STACK_DEPTH, MEMORY_TRACE_WIDTH, U32_TRACE_WIDTH,
+};
+use winter_utils::collections::BTreeMap;
 
 mod utils;
 use utils::{close_block, traverse};
@@ -267,8 +274,455 @@ fn simple_loop() {
     assert_eq!(111, step);
 }
 
+#[test]
+fn call_blocks() {
+    let block1 = build_first_block(OpCode::Noop, 15);
+    let callee = Group::new(vec![Span::new_block(vec![OpCode::Add; 15])]);
+
+    let mut library = ProgramLibrary::new();
+    let callee_hash = library.add(callee.clone());
+    assert_eq!(callee.body_hash(), callee_hash);
+    assert!(library.contains(callee_hash));
+    assert!(!library.contains(callee_hash + BaseElement::ONE));
+    assert_eq!(1, library.len());
+
+    // a program which calls a block has the same hash as the program which inlines the block
+    let program = Program::new(Group::new(vec![
+        block1.clone(),
+        Call::new_block(callee_hash),
+    ]));
+    let inlined = Program::new(Group::new(vec![
+        block1,
+        ProgramBlock::Group(library.get(callee_hash).unwrap().clone()),
+    ]));
+    assert_eq!(program.hash(), inlined.hash());
+}
+
+#[test]
+#[should_panic(expected = "a block which starts with BEGIN operation cannot be called")]
+fn call_blocks_begin() {
+    let mut library = ProgramLibrary::new();
+    library.add(Group::new(vec![build_first_block(OpCode::Noop, 15)]));
+}
+
+#[test]
+fn repeat_blocks() {
+    let block1 = build_first_block(OpCode::Noop, 15);
+    let add = Span::new_block(vec![OpCode::Add; 15]);
+    let mul = Span::new_block(vec![OpCode::Mul; 15]);
+
+    // the body is executed as a loop: LOOP, 3 iterations separated by WRAPs, BREAK, and the
+    // exit block
+    let body = vec![add.clone()];
+    let program = Program::new(Group::new(vec![
+        block1.clone(),
+        Repeat::new_block(body.clone(), 3),
+    ]));
+    let mut program_hash = [BaseElement::ZERO; 4];
+    let step = traverse(program.root().body(), &mut vec![], &mut program_hash, 0);
+    let step = close_block(
+        &mut program_hash,
+        BaseElement::ZERO,
+        BaseElement::ZERO,
+        true,
+        step,
+    );
+    assert_eq!(*program.hash(), hash_to_bytes(&program_hash));
+    assert_eq!(111, step);
+
+    // the hash of the block commits to the number of iterations
+    let program2 = Program::new(Group::new(vec![block1.clone(), Repeat::new_block(body, 2)]));
+    assert_ne!(program.hash(), program2.hash());
+
+    // a body ending with a control block is executed in the same way
+    let body = vec![add.clone(), Group::new_block(vec![mul])];
+    let program = Program::new(Group::new(vec![block1, Repeat::new_block(body.clone(), 2)]));
+    let mut program_hash = [BaseElement::ZERO; 4];
+    let step = traverse(program.root().body(), &mut vec![], &mut program_hash, 0);
+    let step = close_block(
+        &mut program_hash,
+        BaseElement::ZERO,
+        BaseElement::ZERO,
+        true,
+        step,
+    );
+    assert_eq!(*program.hash(), hash_to_bytes(&program_hash));
+    assert_eq!(159, step);
+
+    // the time needed to hash the block does not depend on the number of iterations
+    let block1 = Repeat::new(body.clone(), u32::MAX as usize);
+    let block2 = Repeat::new(body, u32::MAX as usize - 1);
+    assert_ne!(block1.get_hash(), block2.get_hash());
+}
+
+#[test]
+#[should_panic(expected = "invalid number of iterations")]
+fn repeat_blocks_zero_iterations() {
+    Repeat::new(vec![Span::new_block(vec![OpCode::Add; 15])], 0);
+}
+
+#[test]
+#[should_panic(expected = "invalid number of iterations")]
+fn repeat_blocks_too_many_iterations() {
+    Repeat::new(
+        vec![Span::new_block(vec![OpCode::Add; 15])],
+        u32::MAX as usize + 1,
+    );
+}
+
+// ESTIMATES
+// ================================================================================================
+
+#[test]
+fn cycle_estimates() {
+    // straight-line code is estimated exactly
+    let program = Program::new(Group::new(vec![
+        build_first_block(OpCode::Noop, 15),
+        Group::new_block(vec![Span::new_block(vec![OpCode::Add; 15])]),
+    ]));
+    let estimate = program.estimate_cycles();
+    assert!(estimate.is_exact());
+    assert_eq!(63, estimate.min_cycles());
+    assert_eq!(Some(64), estimate.max_trace_length());
+    assert_eq!(1, estimate.ctx_depth());
+    assert_eq!(0, estimate.loop_depth());
+    let decoder_width = TraceState::<BaseElement>::compute_decoder_width(1, 0);
+    assert_eq!(decoder_width, estimate.decoder_width());
+    assert_eq!(
+        Some(decoder_width + MAX_STACK_DEPTH),
+        estimate.max_trace_width()
+    );
+
+    // memory and u32 operations registers are counted if any branch accesses them
+    let mut t_branch = vec![OpCode::Noop; 15];
+    t_branch[0] = OpCode::Assert;
+    t_branch[1] = OpCode::MRoot;
+    let mut f_branch = vec![OpCode::Noop; 15];
+    f_branch[0] = OpCode::Not;
+    f_branch[1] = OpCode::Assert;
+    f_branch[2] = OpCode::U32;
+    let mut f_hints = BTreeMap::new();
+    f_hints.insert(2, OpHint::U32Op(U32Op::Add));
+    let program = Program::new(Group::new(vec![
+        build_first_block(OpCode::Noop, 15),
+        Switch::new_block(
+            vec![Span::new_block(t_branch)],
+            vec![ProgramBlock::Span(Span::new(f_branch, f_hints))],
+        ),
+    ]));
+    let estimate = program.estimate_cycles();
+    assert_eq!(
+        Some(decoder_width + MAX_STACK_DEPTH + MEMORY_TRACE_WIDTH + U32_TRACE_WIDTH),
+        estimate.max_trace_width()
+    );
+
+    // every iteration of a repeat block but the last one is followed by a WRAP operation
+    let program = Program::new(Group::new(vec![
+        build_first_block(OpCode::Noop, 15),
+        Repeat::new_block(vec![Span::new_block(vec![OpCode::Add; 15])], 3),
+    ]));
+    let estimate = program.estimate_cycles();
+    assert!(estimate.is_exact());
+    assert_eq!(111, estimate.min_cycles());
+    assert_eq!(1, estimate.ctx_depth());
+    assert_eq!(1, estimate.loop_depth());
+
+    // a loop which is not entered executes its skip block; the number of iterations is unknown
+    let program = Program::new(Group::new(vec![
+        build_first_block(OpCode::Noop, 15),
+        Loop::new_block(vec![Span::new_block(vec![OpCode::Assert; 15])]),
+    ]));
+    let estimate = program.estimate_cycles();
+    assert_eq!(63, estimate.min_cycles());
+    assert_eq!(None, estimate.max_cycles());
+    assert_eq!(64, estimate.min_trace_length());
+    assert_eq!(1, estimate.loop_depth());
+
+    // branches are bounded by the shortest and the longest branch; the callee is unknown unless
+    // a library is provided
+    let program = build_test_program();
+    let estimate = program.estimate_cycles();
+    assert_eq!(15 + 32 + 32 + 17 + 16, estimate.min_cycles());
+    assert_eq!(None, estimate.max_cycles());
+    assert_eq!(1, estimate.ctx_depth());
+    assert_eq!(None, estimate.max_trace_width());
+
+    let mut library = ProgramLibrary::new();
+    let callee = Group::new(vec![Span::new_block(vec![OpCode::Add; 15])]);
+    let callee_hash = library.add(callee);
+    let mut f_branch = vec![OpCode::Noop; 15];
+    f_branch[0] = OpCode::Not;
+    f_branch[1] = OpCode::Assert;
+    let program = Program::new(Group::new(vec![
+        build_first_block(OpCode::Noop, 15),
+        Switch::new_block(
+            vec![Span::new_block(vec![OpCode::Assert; 15])],
+            vec![Span::new_block(f_branch), Call::new_block(callee_hash)],
+        ),
+    ]));
+    let estimate = program.estimate_cycles_with_library(&library);
+    assert_eq!(63, estimate.min_cycles());
+    assert_eq!(Some(95), estimate.max_cycles());
+    assert_eq!(Some(128), estimate.max_trace_length());
+    assert_eq!(2, estimate.ctx_depth());
+    assert_eq!(
+        TraceState::<BaseElement>::compute_decoder_width(2, 0),
+        estimate.decoder_width()
+    );
+    assert_eq!(
+        Some(estimate.decoder_width() + MAX_STACK_DEPTH),
+        estimate.max_trace_width()
+    );
+}
+
+#[test]
+fn cycle_estimates_saturate() {
+    // cycle counts of deeply nested repeat blocks saturate instead of overflowing
+    let cycles = repeat_cycles(usize::MAX / 2, u32::MAX as usize);
+    assert_eq!(usize::MAX, cycles);
+    assert_eq!(usize::MAX, repeat_cycles(cycles, 2));
+    assert_eq!(1 << (usize::BITS - 1), get_trace_length(cycles));
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+#[test]
+fn serialization_round_trip() {
+    let program = build_test_program();
+    let bytes = program.to_bytes();
+    assert_eq!(PROGRAM_FORMAT_VERSION, bytes[0]);
+    assert_eq!(&program.hash()[..], &bytes[1..33]);
+
+    let result = Program::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(program.hash(), result.hash());
+    assert_eq!(format!("{:?}", program), format!("{:?}", result));
+
+    let program = Program::new(Group::new(vec![
+        build_first_block(OpCode::Noop, 15),
+        Repeat::new_block(vec![Span::new_block(vec![OpCode::Add; 15])], 1000),
+    ]));
+    let bytes = program.to_bytes();
+    let result = Program::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(program.hash(), result.hash());
+    assert_eq!(format!("{:?}", program), format!("{:?}", result));
+}
+
+#[test]
+fn trusted_deserialization() {
+    let program = build_test_program();
+    let bytes = program.to_bytes();
+    let result = Program::read_trusted(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(program.hash(), result.hash());
+    assert_eq!(format!("{:?}", program), format!("{:?}", result));
+
+    // the recorded hash is not checked against program blocks, but the blocks are still validated
+    let mut tampered = bytes.clone();
+    tampered[1] ^= 1;
+    let result = Program::read_trusted(&mut SliceReader::new(&tampered)).unwrap();
+    assert_eq!(&tampered[1..33], &result.hash()[..]);
+
+    let mut invalid = bytes;
+    invalid[0] = PROGRAM_FORMAT_VERSION + 1;
+    assert!(matches!(
+        Program::read_trusted(&mut SliceReader::new(&invalid)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+}
+
+#[test]
+fn deserialization_errors() {
+    let bytes = build_test_program().to_bytes();
+
+    // unsupported version
+    let mut invalid = bytes.clone();
+    invalid[0] = PROGRAM_FORMAT_VERSION + 1;
+    assert!(matches!(
+        Program::read_from(&mut SliceReader::new(&invalid)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    // program hash does not match program blocks
+    let mut invalid = bytes.clone();
+    invalid[1] ^= 1;
+    assert!(matches!(
+        Program::read_from(&mut SliceReader::new(&invalid)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    // invalid op code in the first span: version (1), hash (32), number of root blocks (4),
+    // block type (1), and number of op codes (4) precede the first op code
+    let mut invalid = bytes.clone();
+    invalid[43] = 0b0110_1111;
+    assert!(matches!(
+        Program::read_from(&mut SliceReader::new(&invalid)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    // PUSH on a step which is not a multiple of 8 violates Span invariants
+    let mut invalid = bytes.clone();
+    invalid[43] = OpCode::Push as u8;
+    assert!(matches!(
+        Program::read_from(&mut SliceReader::new(&invalid)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    // U32 operation without a u32 operation hint violates Span invariants
+    let mut invalid = bytes.clone();
+    invalid[43] = OpCode::U32 as u8;
+    assert!(matches!(
+        Program::read_from(&mut SliceReader::new(&invalid)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    // truncated program
+    assert!(Program::read_from(&mut SliceReader::new(&bytes[..bytes.len() - 1])).is_err());
+
+    // repeat block without iterations: the number of iterations follows the first Span block
+    // (which ends at byte 61 with the number of hints) and the block type
+    let program = Program::new(Group::new(vec![
+        build_first_block(OpCode::Noop, 15),
+        Repeat::new_block(vec![Span::new_block(vec![OpCode::Add; 15])], 2),
+    ]));
+    let mut invalid = program.to_bytes();
+    assert_eq!(2, invalid[62]);
+    invalid[62] = 0;
+    assert!(matches!(
+        Program::read_from(&mut SliceReader::new(&invalid)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+}
+
+// INPUTS
+// ================================================================================================
+
+#[test]
+fn program_inputs() {
+    let inputs = ProgramInputs::try_new(&[1, 2], &[3, 4], &[5]).unwrap();
+    assert_eq!(
+        &[BaseElement::new(1), BaseElement::new(2)],
+        inputs.public_inputs()
+    );
+    assert_eq!(vec![BaseElement::new(5)], inputs.secret_inputs()[1]);
+
+    assert_eq!(
+        Err(InputError::TooManyPublicInputs(MAX_PUBLIC_INPUTS + 1)),
+        ProgramInputs::try_new(&[0; MAX_PUBLIC_INPUTS + 1], &[], &[]).map(|_| ())
+    );
+    assert_eq!(
+        Err(InputError::SecretTapesMismatch(1, 2)),
+        ProgramInputs::try_new(&[], &[1], &[2, 3]).map(|_| ())
+    );
+    assert_eq!(
+        Err(InputError::InvalidValue(BaseElement::MODULUS.to_string())),
+        ProgramInputs::try_new(&[], &[BaseElement::MODULUS], &[]).map(|_| ())
+    );
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn program_inputs_from_json() {
+    let source = r#"{
+        "stack_init": [1, "2"],
+        "tape_a": ["0xff", "340282366920938463463374557953744961536"],
+        "tape_b": []
+    }"#;
+    let inputs = ProgramInputs::from_json(source).unwrap();
+    assert_eq!(
+        &[BaseElement::new(1), BaseElement::new(2)],
+        inputs.public_inputs()
+    );
+    assert_eq!(
+        vec![
+            BaseElement::new(255),
+            BaseElement::new(BaseElement::MODULUS - 1)
+        ],
+        inputs.secret_inputs()[0]
+    );
+
+    // all fields are optional
+    let inputs = ProgramInputs::from_json(r#"{ "tape_a": [7] }"#).unwrap();
+    assert!(inputs.public_inputs().is_empty());
+    assert_eq!(vec![BaseElement::new(7)], inputs.secret_inputs()[0]);
+
+    // invalid values
+    let modulus = BaseElement::MODULUS.to_string();
+    let source = format!(r#"{{ "stack_init": ["{}"] }}"#, modulus);
+    assert_eq!(
+        Err(InputError::InvalidValue(modulus)),
+        ProgramInputs::from_json(&source).map(|_| ())
+    );
+    assert_eq!(
+        Err(InputError::InvalidValue("0xfg".to_string())),
+        ProgramInputs::from_json(r#"{ "tape_a": ["0xfg"] }"#).map(|_| ())
+    );
+    assert_eq!(
+        Err(InputError::TooManyPublicInputs(9)),
+        ProgramInputs::from_json(r#"{ "stack_init": [1, 2, 3, 4, 5, 6, 7, 8, 9] }"#).map(|_| ())
+    );
+
+    // malformed JSON
+    assert!(matches!(
+        ProgramInputs::from_json(r#"{ "tape_c": [1] }"#),
+        Err(InputError::InvalidJson(_))
+    ));
+    assert!(matches!(
+        ProgramInputs::from_json(r#"{ "tape_a": [-1] }"#),
+        Err(InputError::InvalidJson(_))
+    ));
+    assert!(matches!(
+        ProgramInputs::from_json("[1, 2]"),
+        Err(InputError::InvalidJson(_))
+    ));
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
+
+/// Builds a program with all types of blocks, a PUSH operation, a U32 operation, and storage
+/// hints.
+fn build_test_program() -> Program {
+    let mut instructions = vec![OpCode::Noop; 15];
+    instructions[0] = OpCode::Begin;
+    instructions[8] = OpCode::Push;
+    instructions[9] = OpCode::U32;
+    instructions[10] = OpCode::Read;
+    instructions[11] = OpCode::SRoot;
+    let mut hints = BTreeMap::new();
+    hints.insert(8, OpHint::PushValue(BaseElement::new(42)));
+    hints.insert(9, OpHint::U32Op(U32Op::Xor));
+    hints.insert(10, OpHint::StorageRead);
+    hints.insert(11, OpHint::StorageWrite);
+    let block1 = ProgramBlock::Span(Span::new(instructions, hints));
+
+    let t_branch = vec![Span::new_block(vec![OpCode::Assert; 15])];
+    let f_branch = vec![Span::new_block(vec![
+        OpCode::Not,
+        OpCode::Assert,
+        OpCode::Add,
+        OpCode::Noop,
+        OpCode::Noop,
+        OpCode::Noop,
+        OpCode::Noop,
+        OpCode::Noop,
+        OpCode::Noop,
+        OpCode::Noop,
+        OpCode::Noop,
+        OpCode::Noop,
+        OpCode::Noop,
+        OpCode::Noop,
+        OpCode::Noop,
+    ])];
+    let block2 = Switch::new_block(t_branch, f_branch);
+
+    let loop_body = vec![Span::new_block(vec![OpCode::Assert; 15])];
+    let block3 = Loop::new_block(loop_body);
+
+    let block4 = Call::new_block(BaseElement::new(42));
+
+    Program::new(Group::new(vec![block1, block2, block3, block4]))
+}
+
 fn build_first_block(op_code: OpCode, length: usize) -> ProgramBlock {
     let mut instructions = vec![op_code; length];
     instructions[0] = OpCode::Begin;
@@ -1,6 +1,6 @@
 use super::{
-    super::{hashing::hash_op, HACC_NUM_ROUNDS},
-    BaseElement, FieldElement, Loop, OpCode, ProgramBlock, Span,
+    super::{hashing::hash_op, HACC_NUM_ROUNDS, LOOP_COUNTER_BASE},
+    BaseElement, FieldElement, Loop, OpCode, ProgramBlock, Repeat, Span,
 };
 
 // PUBLIC FUNCTIONS
@@ -20,60 +20,7 @@ pub fn traverse(
 
     // execute all other blocks in the sequence one after another
     for block in blocks.iter().skip(1) {
-        step = match block {
-            ProgramBlock::Span(block) => traverse_span(block, hash, false, step),
-            ProgramBlock::Group(block) => {
-                step += 1; // BEGIN
-                let mut state = [BaseElement::ZERO; 4];
-                step = traverse(block.body(), stack, &mut state, step);
-                step = close_block(&mut state, hash[0], BaseElement::ZERO, true, step);
-                hash.copy_from_slice(&state);
-                step
-            }
-            ProgramBlock::Switch(block) => {
-                step += 1; // BEGIN
-                let mut state = [BaseElement::ZERO; 4];
-                let condition = stack.pop().unwrap();
-                match condition {
-                    BaseElement::ZERO => {
-                        step = traverse(block.false_branch(), stack, &mut state, step);
-                        step =
-                            close_block(&mut state, hash[0], block.true_branch_hash(), false, step);
-                        hash.copy_from_slice(&state);
-                        step
-                    }
-                    BaseElement::ONE => {
-                        step = traverse(block.true_branch(), stack, &mut state, step);
-                        step =
-                            close_block(&mut state, hash[0], block.false_branch_hash(), true, step);
-                        hash.copy_from_slice(&state);
-                        step
-                    }
-                    _ => panic!(
-                        "cannot select a branch based on a non-binary condition {}",
-                        condition
-                    ),
-                }
-            }
-            ProgramBlock::Loop(block) => {
-                let condition = stack.pop().unwrap();
-                match condition {
-                    BaseElement::ZERO => {
-                        step += 1; // BEGIN
-                        let mut state = [BaseElement::ZERO; 4];
-                        step = traverse(block.skip(), stack, &mut state, step);
-                        step = close_block(&mut state, hash[0], block.body_hash(), false, step);
-                        hash.copy_from_slice(&state);
-                        step
-                    }
-                    BaseElement::ONE => traverse_loop(block, hash, stack, step),
-                    _ => panic!(
-                        "cannot enter loop based on a non-binary condition {}",
-                        condition
-                    ),
-                }
-            }
-        };
+        step = traverse_block(block, stack, hash, step);
     }
 
     return step;
@@ -82,6 +29,69 @@ pub fn traverse(
 // HELPER FUNCTIONS
 // ================================================================================================
 
+/// Executes a block which is not the first block in a sequence.
+fn traverse_block(
+    block: &ProgramBlock,
+    stack: &mut Vec<BaseElement>,
+    hash: &mut [BaseElement; 4],
+    mut step: usize,
+) -> usize {
+    match block {
+        ProgramBlock::Span(block) => traverse_span(block, hash, false, step),
+        ProgramBlock::Group(block) => {
+            step += 1; // BEGIN
+            let mut state = [BaseElement::ZERO; 4];
+            step = traverse(block.body(), stack, &mut state, step);
+            step = close_block(&mut state, hash[0], BaseElement::ZERO, true, step);
+            hash.copy_from_slice(&state);
+            step
+        }
+        ProgramBlock::Switch(block) => {
+            step += 1; // BEGIN
+            let mut state = [BaseElement::ZERO; 4];
+            let condition = stack.pop().unwrap();
+            match condition {
+                BaseElement::ZERO => {
+                    step = traverse(block.false_branch(), stack, &mut state, step);
+                    step = close_block(&mut state, hash[0], block.true_branch_hash(), false, step);
+                    hash.copy_from_slice(&state);
+                    step
+                }
+                BaseElement::ONE => {
+                    step = traverse(block.true_branch(), stack, &mut state, step);
+                    step = close_block(&mut state, hash[0], block.false_branch_hash(), true, step);
+                    hash.copy_from_slice(&state);
+                    step
+                }
+                _ => panic!(
+                    "cannot select a branch based on a non-binary condition {}",
+                    condition
+                ),
+            }
+        }
+        ProgramBlock::Loop(block) => {
+            let condition = stack.pop().unwrap();
+            match condition {
+                BaseElement::ZERO => {
+                    step += 1; // BEGIN
+                    let mut state = [BaseElement::ZERO; 4];
+                    step = traverse(block.skip(), stack, &mut state, step);
+                    step = close_block(&mut state, hash[0], block.body_hash(), false, step);
+                    hash.copy_from_slice(&state);
+                    step
+                }
+                BaseElement::ONE => traverse_loop(block, hash, stack, step),
+                _ => panic!(
+                    "cannot enter loop based on a non-binary condition {}",
+                    condition
+                ),
+            }
+        }
+        ProgramBlock::Repeat(block) => traverse_repeat(block, hash, stack, step),
+        ProgramBlock::Call(_) => panic!("call blocks cannot be traversed without a library"),
+    }
+}
+
 fn traverse_span(
     block: &Span,
     hash: &mut [BaseElement; 4],
@@ -180,3 +190,35 @@ fn traverse_loop(
     hash.copy_from_slice(&state);
     return step;
 }
+
+fn traverse_repeat(
+    block: &Repeat,
+    hash: &mut [BaseElement; 4],
+    stack: &mut Vec<BaseElement>,
+    mut step: usize,
+) -> usize {
+    step += 1; // LOOP
+    let mut state = [BaseElement::ZERO; 4];
+    let mut counter = BaseElement::ZERO;
+
+    for i in 0..block.num_iterations() {
+        if i > 0 {
+            state = [BaseElement::ZERO; 4];
+            counter = LOOP_COUNTER_BASE * counter + (BaseElement::ONE - LOOP_COUNTER_BASE);
+            step += 1; // WRAP
+        }
+        step = traverse(block.body(), stack, &mut state, step);
+        assert!(
+            state[0] == block.image(),
+            "loop image didn't match loop body hash"
+        );
+    }
+
+    step += 1; // BREAK
+    state[3] += counter - BaseElement::ONE;
+
+    step = traverse_span(block.exit(), &mut state, true, step);
+    step = close_block(&mut state, hash[0], BaseElement::ZERO, true, step);
+    hash.copy_from_slice(&state);
+    return step;
+}
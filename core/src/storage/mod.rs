@@ -0,0 +1,197 @@
+use crate::{
+    hasher, BaseElement, FieldElement, StarkField, HASHER_NUM_ROUNDS as NUM_ROUNDS,
+    STORAGE_TREE_DEPTH,
+};
+use core::convert::TryFrom;
+use winter_utils::collections::BTreeMap;
+
+#[cfg(test)]
+mod tests;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A node of the storage tree.
+pub type StorageNode = [BaseElement; hasher::DIGEST_SIZE];
+
+/// Persistent key-value storage of the VM.
+///
+/// The storage is a sparse Merkle tree of depth 32: every 32-bit key is mapped to a leaf of the
+/// tree, and the leaf holds the value stored under the key (or zero if nothing was stored). The
+/// root of the tree commits to the entire content of the storage, and thus, programs can read
+/// and update the storage against the root only, as long as they are provided with authentication
+/// paths of the accessed leaves.
+///
+/// Nodes of the tree are merged in the same way as the VM hashes two words on the stack, and
+/// thus, the tree can be authenticated by a program.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Storage {
+    /// Non-empty nodes of the tree by level; level 0 holds the leaves, and level 32 holds the
+    /// root. Nodes are keyed by their position within a level.
+    nodes: Vec<BTreeMap<u32, StorageNode>>,
+    /// Roots of empty subtrees of every height.
+    empty: Vec<StorageNode>,
+}
+
+// STORAGE IMPLEMENTATION
+// ================================================================================================
+impl Storage {
+    /// Returns empty storage; all keys of empty storage are mapped to zeros.
+    pub fn new() -> Storage {
+        let mut empty = Vec::with_capacity(STORAGE_TREE_DEPTH + 1);
+        empty.push([BaseElement::ZERO; hasher::DIGEST_SIZE]);
+        for i in 0..STORAGE_TREE_DEPTH {
+            empty.push(merge(&empty[i], &empty[i]));
+        }
+
+        Storage {
+            nodes: vec![BTreeMap::new(); STORAGE_TREE_DEPTH + 1],
+            empty,
+        }
+    }
+
+    /// Returns storage which maps the specified keys to the specified values; all other keys are
+    /// mapped to zeros.
+    pub fn from_values(values: &[(u32, u128)]) -> Storage {
+        let mut storage = Storage::new();
+        for &(key, value) in values.iter() {
+            storage.set(key, BaseElement::new(value));
+        }
+        storage
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the root of the storage tree serialized into 32 bytes.
+    pub fn root(&self) -> [u8; 32] {
+        root_to_bytes(&self.root_node())
+    }
+
+    /// Returns the root of the storage tree.
+    pub fn root_node(&self) -> StorageNode {
+        self.get_node(STORAGE_TREE_DEPTH, 0)
+    }
+
+    /// Returns the value stored under the specified key.
+    pub fn get(&self, key: u32) -> BaseElement {
+        self.get_node(0, key)[0]
+    }
+
+    /// Returns the authentication path of the leaf with the specified key; the path consists of
+    /// siblings of all nodes on the way from the leaf to the root, sibling of the leaf first.
+    pub fn get_path(&self, key: u32) -> Vec<StorageNode> {
+        (0..STORAGE_TREE_DEPTH)
+            .map(|level| self.get_node(level, get_index(key, level) ^ 1))
+            .collect()
+    }
+
+    /// Returns true if no non-zero values are stored in the storage.
+    pub fn is_empty(&self) -> bool {
+        self.nodes[0].is_empty()
+    }
+
+    // STATE MUTATORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Stores the `value` under the specified key, and returns the value previously stored
+    /// under this key.
+    pub fn set(&mut self, key: u32, value: BaseElement) -> BaseElement {
+        let old_value = self.get(key);
+        self.set_node(0, key, [value, BaseElement::ZERO]);
+
+        // update all nodes on the way from the leaf to the root
+        for level in 0..STORAGE_TREE_DEPTH {
+            let index = get_index(key, level);
+            let node = self.get_node(level, index);
+            let sibling = self.get_node(level, index ^ 1);
+            let parent = if index & 1 == 0 {
+                merge(&node, &sibling)
+            } else {
+                merge(&sibling, &node)
+            };
+            self.set_node(level + 1, index >> 1, parent);
+        }
+
+        old_value
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    fn get_node(&self, level: usize, index: u32) -> StorageNode {
+        match self.nodes[level].get(&index) {
+            Some(node) => *node,
+            None => self.empty[level],
+        }
+    }
+
+    /// Sets the node at the specified position; roots of empty subtrees are not kept in the
+    /// tree.
+    fn set_node(&mut self, level: usize, index: u32, node: StorageNode) {
+        if node == self.empty[level] {
+            self.nodes[level].remove(&index);
+        } else {
+            self.nodes[level].insert(index, node);
+        }
+    }
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Storage::new()
+    }
+}
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Merges two nodes of the storage tree into their parent node.
+///
+/// This mirrors hashing of two words on the stack of the VM: the stack is padded with two zeros,
+/// 10 rounds of Rescue permutation are applied to the top 6 items of the stack, and the
+/// resulting node is taken from the 5th and 6th items.
+pub fn merge(left: &StorageNode, right: &StorageNode) -> StorageNode {
+    let mut state = [
+        BaseElement::ZERO,
+        BaseElement::ZERO,
+        left[0],
+        left[1],
+        right[0],
+        right[1],
+    ];
+    for i in 0..NUM_ROUNDS {
+        hasher::apply_round(&mut state, i);
+    }
+    [state[4], state[5]]
+}
+
+/// Serializes a root of the storage tree into 32 bytes.
+pub fn root_to_bytes(root: &StorageNode) -> [u8; 32] {
+    let mut result = [0; 32];
+    result.copy_from_slice(BaseElement::elements_as_bytes(root));
+    result
+}
+
+/// Parses a root of the storage tree from 32 bytes; returns None if the bytes do not encode two
+/// valid field elements.
+pub fn root_from_bytes(bytes: &[u8; 32]) -> Option<StorageNode> {
+    let mut result = [BaseElement::ZERO; hasher::DIGEST_SIZE];
+    for (i, chunk) in bytes.chunks(16).enumerate() {
+        let value = u128::from_le_bytes(<[u8; 16]>::try_from(chunk).ok()?);
+        if value >= BaseElement::MODULUS {
+            return None;
+        }
+        result[i] = BaseElement::new(value);
+    }
+    Some(result)
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the position of the node at the specified level on the path from the leaf with the
+/// specified key to the root.
+fn get_index(key: u32, level: usize) -> u32 {
+    ((key as u64) >> level) as u32
+}
@@ -0,0 +1,69 @@
+use super::{merge, root_from_bytes, BaseElement, FieldElement, Storage, StorageNode};
+
+#[test]
+fn get_and_set() {
+    let mut storage = Storage::new();
+    assert!(storage.is_empty());
+    assert_eq!(BaseElement::ZERO, storage.get(0));
+    assert_eq!(BaseElement::ZERO, storage.get(u32::MAX));
+
+    assert_eq!(BaseElement::ZERO, storage.set(7, BaseElement::new(42)));
+    assert_eq!(BaseElement::new(42), storage.get(7));
+    assert_eq!(BaseElement::ZERO, storage.get(6));
+    assert!(!storage.is_empty());
+
+    assert_eq!(BaseElement::new(42), storage.set(7, BaseElement::new(43)));
+    assert_eq!(BaseElement::new(43), storage.get(7));
+}
+
+#[test]
+fn root() {
+    let empty_root = Storage::new().root();
+
+    // the root commits to the content of the storage, but not to the order of updates
+    let storage = Storage::from_values(&[(1, 10), (u32::MAX, 20), (0x8000_0000, 30)]);
+    assert_ne!(empty_root, storage.root());
+    let other = Storage::from_values(&[(0x8000_0000, 30), (1, 10), (u32::MAX, 20)]);
+    assert_eq!(storage.root(), other.root());
+
+    // setting all values back to zeros brings the storage back to the empty state
+    let mut storage = storage;
+    storage.set(1, BaseElement::ZERO);
+    storage.set(u32::MAX, BaseElement::ZERO);
+    storage.set(0x8000_0000, BaseElement::ZERO);
+    assert!(storage.is_empty());
+    assert_eq!(empty_root, storage.root());
+    assert_eq!(Storage::new(), storage);
+
+    // roots can be parsed back from bytes
+    let storage = Storage::from_values(&[(3, 5)]);
+    assert_eq!(Some(storage.root_node()), root_from_bytes(&storage.root()));
+    assert_eq!(None, root_from_bytes(&[255; 32]));
+}
+
+#[test]
+fn get_path() {
+    let storage = Storage::from_values(&[(0, 1), (1, 2), (5, 3), (0xffff_0000, 4)]);
+    for &key in [0, 1, 2, 5, 0xffff_0000, u32::MAX].iter() {
+        let path = storage.get_path(key);
+        assert_eq!(32, path.len());
+
+        let leaf = [storage.get(key), BaseElement::ZERO];
+        assert_eq!(storage.root_node(), compute_root(key, leaf, &path));
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Computes the root of the tree from the leaf with the specified key and its authentication
+/// path.
+fn compute_root(key: u32, leaf: StorageNode, path: &[StorageNode]) -> StorageNode {
+    path.iter().enumerate().fold(leaf, |node, (i, sibling)| {
+        if (key >> i) & 1 == 0 {
+            merge(&node, sibling)
+        } else {
+            merge(sibling, &node)
+        }
+    })
+}
@@ -1,7 +1,8 @@
 use crate::{
     BaseElement, FieldElement, StarkField, CF_OP_BITS_RANGE, HD_OP_BITS_RANGE, LD_OP_BITS_RANGE,
-    MIN_CONTEXT_DEPTH, MIN_LOOP_DEPTH, MIN_STACK_DEPTH, NUM_CF_OP_BITS, NUM_HD_OP_BITS,
-    NUM_LD_OP_BITS, OP_COUNTER_IDX, OP_SPONGE_RANGE, OP_SPONGE_WIDTH, PROGRAM_DIGEST_SIZE,
+    MEMORY_TRACE_WIDTH, MIN_CONTEXT_DEPTH, MIN_LOOP_DEPTH, MIN_STACK_DEPTH, NUM_CF_OP_BITS,
+    NUM_HD_OP_BITS, NUM_LD_OP_BITS, OP_COUNTER_IDX, OP_SPONGE_RANGE, OP_SPONGE_WIDTH,
+    PROGRAM_DIGEST_SIZE, STORAGE_TRACE_WIDTH, U32_TRACE_WIDTH,
 };
 use core::{cmp, fmt};
 
@@ -24,7 +25,11 @@ pub struct TraceState<E: FieldElement<BaseField = BaseElement>> {
     hd_op_bits: [E; NUM_HD_OP_BITS],
     ctx_stack: Vec<E>,
     loop_stack: Vec<E>,
+    loop_counters: Vec<E>,
     user_stack: Vec<E>,
+    memory: Vec<E>,
+    storage: Vec<E>,
+    u32_table: Vec<E>,
 
     ctx_depth: usize,
     loop_depth: usize,
@@ -46,13 +51,39 @@ impl<E: FieldElement<BaseField = BaseElement>> TraceState<E> {
             hd_op_bits: [E::ZERO; NUM_HD_OP_BITS],
             ctx_stack: vec![E::ZERO; cmp::max(ctx_depth, MIN_CONTEXT_DEPTH)],
             loop_stack: vec![E::ZERO; cmp::max(loop_depth, MIN_LOOP_DEPTH)],
+            loop_counters: vec![E::ZERO; cmp::max(loop_depth, MIN_LOOP_DEPTH)],
             user_stack: vec![E::ZERO; cmp::max(stack_depth, MIN_STACK_DEPTH)],
+            memory: Vec::new(),
+            storage: Vec::new(),
+            u32_table: Vec::new(),
             ctx_depth,
             loop_depth,
             stack_depth,
         }
     }
 
+    /// Returns this trace state extended with registers which hold the root of the memory.
+    pub fn with_memory(mut self) -> Self {
+        self.memory = vec![E::ZERO; MEMORY_TRACE_WIDTH];
+        self
+    }
+
+    /// Returns this trace state extended with registers which hold the root of the storage.
+    pub fn with_storage(mut self) -> Self {
+        self.storage = vec![E::ZERO; STORAGE_TRACE_WIDTH];
+        self
+    }
+
+    /// Returns this trace state extended with registers of the u32 operations table.
+    pub fn with_u32_table(mut self) -> Self {
+        self.u32_table = vec![E::ZERO; U32_TRACE_WIDTH];
+        self
+    }
+
+    /// Builds a trace state from the specified row of an execution trace; only the decoder
+    /// registers and the user stack are read from the row, and registers which follow the user
+    /// stack (if any) are ignored. To read the remaining registers, a state extended with the
+    /// corresponding sections should be updated with the row instead.
     pub fn from_slice(
         ctx_depth: usize,
         loop_depth: usize,
@@ -81,8 +112,13 @@ impl<E: FieldElement<BaseField = BaseElement>> TraceState<E> {
         let loop_stack_end = ctx_stack_end + loop_depth;
         loop_stack[..loop_depth].copy_from_slice(&state[ctx_stack_end..loop_stack_end]);
 
+        let mut loop_counters = vec![E::ZERO; cmp::max(loop_depth, MIN_LOOP_DEPTH)];
+        let loop_counters_end = loop_stack_end + loop_depth;
+        loop_counters[..loop_depth].copy_from_slice(&state[loop_stack_end..loop_counters_end]);
+
         let mut user_stack = vec![E::ZERO; cmp::max(stack_depth, MIN_STACK_DEPTH)];
-        user_stack[..stack_depth].copy_from_slice(&state[loop_stack_end..]);
+        let user_stack_end = loop_counters_end + stack_depth;
+        user_stack[..stack_depth].copy_from_slice(&state[loop_counters_end..user_stack_end]);
 
         TraceState {
             op_counter,
@@ -92,7 +128,11 @@ impl<E: FieldElement<BaseField = BaseElement>> TraceState<E> {
             hd_op_bits,
             ctx_stack,
             loop_stack,
+            loop_counters,
             user_stack,
+            memory: Vec::new(),
+            storage: Vec::new(),
+            u32_table: Vec::new(),
             ctx_depth,
             loop_depth,
             stack_depth,
@@ -116,14 +156,27 @@ impl<E: FieldElement<BaseField = BaseElement>> TraceState<E> {
     // STATIC FUNCTIONS
     // --------------------------------------------------------------------------------------------
     pub fn compute_decoder_width(ctx_depth: usize, loop_depth: usize) -> usize {
-        NUM_STATIC_DECODER_REGISTERS + ctx_depth + loop_depth
+        // every loop level needs a register for the loop image and a register for the counter
+        NUM_STATIC_DECODER_REGISTERS + ctx_depth + 2 * loop_depth
     }
 
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
     #[cfg(test)]
     pub fn width(&self) -> usize {
-        HD_OP_BITS_RANGE.end + self.ctx_depth + self.loop_depth + self.stack_depth
+        HD_OP_BITS_RANGE.end
+            + self.ctx_depth
+            + 2 * self.loop_depth
+            + self.stack_depth
+            + self.memory.len()
+            + self.storage.len()
+            + self.u32_table.len()
+    }
+
+    /// Returns the number of loop stack registers (and loop counter registers) present in the
+    /// execution trace.
+    pub fn loop_depth(&self) -> usize {
+        self.loop_depth
     }
 
     #[cfg(test)]
@@ -197,10 +250,41 @@ impl<E: FieldElement<BaseField = BaseElement>> TraceState<E> {
         &self.loop_stack
     }
 
+    pub fn loop_counters(&self) -> &[E] {
+        &self.loop_counters
+    }
+
     pub fn user_stack(&self) -> &[E] {
         &self.user_stack
     }
 
+    // MEMORY
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns registers which hold the root of the memory; the returned slice is empty if the
+    /// execution trace does not include these registers.
+    pub fn memory(&self) -> &[E] {
+        &self.memory
+    }
+
+    // STORAGE
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns registers which hold the root of the storage; the returned slice is empty if the
+    /// execution trace does not include these registers.
+    pub fn storage(&self) -> &[E] {
+        &self.storage
+    }
+
+    // U32 OPERATIONS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns registers of the u32 operations table; the returned slice is empty if the
+    /// execution trace does not include the table.
+    pub fn u32_table(&self) -> &[E] {
+        &self.u32_table
+    }
+
     // RAW STATE
     // --------------------------------------------------------------------------------------------
     #[cfg(test)]
@@ -213,7 +297,11 @@ impl<E: FieldElement<BaseField = BaseElement>> TraceState<E> {
         result.extend_from_slice(&self.hd_op_bits);
         result.extend_from_slice(&self.ctx_stack[..self.ctx_depth]);
         result.extend_from_slice(&self.loop_stack[..self.loop_depth]);
+        result.extend_from_slice(&self.loop_counters[..self.loop_depth]);
         result.extend_from_slice(&self.user_stack[..self.stack_depth]);
+        result.extend_from_slice(&self.memory);
+        result.extend_from_slice(&self.storage);
+        result.extend_from_slice(&self.u32_table);
         result
     }
 
@@ -244,10 +332,26 @@ impl<E: FieldElement<BaseField = BaseElement>> TraceState<E> {
             self.loop_stack[i] = row[j];
         }
 
-        let user_stack_end = loop_stack_end + self.stack_depth;
-        for (i, j) in (loop_stack_end..user_stack_end).enumerate() {
+        let loop_counters_end = loop_stack_end + self.loop_depth;
+        for (i, j) in (loop_stack_end..loop_counters_end).enumerate() {
+            self.loop_counters[i] = row[j];
+        }
+
+        let user_stack_end = loop_counters_end + self.stack_depth;
+        for (i, j) in (loop_counters_end..user_stack_end).enumerate() {
             self.user_stack[i] = row[j];
         }
+
+        let memory_end = user_stack_end + self.memory.len();
+        self.memory
+            .copy_from_slice(&row[user_stack_end..memory_end]);
+
+        let storage_end = memory_end + self.storage.len();
+        self.storage.copy_from_slice(&row[memory_end..storage_end]);
+
+        let u32_table_end = storage_end + self.u32_table.len();
+        self.u32_table
+            .copy_from_slice(&row[storage_end..u32_table_end]);
     }
 }
 
@@ -255,7 +359,7 @@ impl fmt::Debug for TraceState<BaseElement> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "[{:>4}] {:>32X?} {:?} {:?} {:?} {:>32X?} {:>32X?} {:?}",
+            "[{:>4}] {:>32X?} {:?} {:?} {:?} {:>32X?} {:>32X?} {:>32X?} {:?}",
             self.op_counter.as_int(),
             self.op_sponge
                 .iter()
@@ -281,6 +385,10 @@ impl fmt::Debug for TraceState<BaseElement> {
                 .iter()
                 .map(|v| v.as_int())
                 .collect::<Vec<_>>(),
+            self.loop_counters
+                .iter()
+                .map(|v| v.as_int())
+                .collect::<Vec<_>>(),
             self.user_stack
                 .iter()
                 .map(|v| v.as_int())
@@ -293,7 +401,7 @@ impl fmt::Display for TraceState<BaseElement> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "[{:>4}] {:>16X?} {:?} {:?} {:?} {:>16X?} {:>16X?} {:?}",
+            "[{:>4}] {:>16X?} {:?} {:?} {:?} {:>16X?} {:>16X?} {:?} {:?}",
             self.op_counter.as_int(),
             self.op_sponge
                 .iter()
@@ -310,6 +418,10 @@ impl fmt::Display for TraceState<BaseElement> {
                 .iter()
                 .map(|x| x.as_int() >> 64)
                 .collect::<Vec<u128>>(),
+            self.loop_counters
+                .iter()
+                .map(|x| x.as_int())
+                .collect::<Vec<u128>>(),
             &self.user_stack[..self.stack_depth]
         )
     }
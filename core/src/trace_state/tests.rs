@@ -1,5 +1,8 @@
 use super::TraceState;
-use crate::{utils::ToElements, BaseElement, FieldElement, StarkField};
+use crate::{
+    utils::ToElements, BaseElement, FieldElement, StarkField, MAX_CONTEXT_DEPTH, MAX_LOOP_DEPTH,
+    MAX_STACK_DEPTH, MAX_TRACE_WIDTH, MEMORY_TRACE_WIDTH, STORAGE_TRACE_WIDTH, U32_TRACE_WIDTH,
+};
 
 #[test]
 fn from_vec() {
@@ -58,7 +61,7 @@ fn from_vec() {
         9,
         &[
             101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23,
-            24, 25, 26,
+            24, 25, 26, 27,
         ],
     );
 
@@ -69,16 +72,17 @@ fn from_vec() {
     assert_eq!([13, 14].to_elements(), state.hd_op_bits());
     assert_eq!([15, 16].to_elements(), state.ctx_stack());
     assert_eq!([17].to_elements(), state.loop_stack());
+    assert_eq!([18].to_elements(), state.loop_counters());
     assert_eq!(
-        [18, 19, 20, 21, 22, 23, 24, 25, 26].to_elements(),
+        [19, 20, 21, 22, 23, 24, 25, 26, 27].to_elements(),
         state.user_stack()
     );
-    assert_eq!(27, state.width());
+    assert_eq!(28, state.width());
     assert_eq!(9, state.stack_depth());
     assert_eq!(
         [
             101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23,
-            24, 25, 26,
+            24, 25, 26, 27,
         ]
         .to_elements(),
         state.to_vec()
@@ -88,7 +92,7 @@ fn from_vec() {
 #[test]
 fn update() {
     let row_data = vec![
-        101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
+        101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21,
     ]
     .to_elements();
 
@@ -103,8 +107,9 @@ fn update() {
     assert_eq!([0, 0].to_elements(), state.hd_op_bits());
     assert_eq!([0, 0].to_elements(), state.ctx_stack());
     assert_eq!([0].to_elements(), state.loop_stack());
+    assert_eq!([0].to_elements(), state.loop_counters());
     assert_eq!([0, 0, 0, 0, 0, 0, 0, 0].to_elements(), state.user_stack());
-    assert_eq!(21, state.width());
+    assert_eq!(22, state.width());
     assert_eq!(3, state.stack_depth());
 
     // second row
@@ -117,14 +122,116 @@ fn update() {
     assert_eq!([13, 14].to_elements(), state.hd_op_bits());
     assert_eq!([15, 16].to_elements(), state.ctx_stack());
     assert_eq!([17].to_elements(), state.loop_stack());
+    assert_eq!([18].to_elements(), state.loop_counters());
     assert_eq!(
-        [18, 19, 20, 0, 0, 0, 0, 0].to_elements(),
+        [19, 20, 21, 0, 0, 0, 0, 0].to_elements(),
         state.user_stack()
     );
-    assert_eq!(21, state.width());
+    assert_eq!(22, state.width());
     assert_eq!(3, state.stack_depth());
 }
 
+#[test]
+fn memory_registers() {
+    let mut row_data = vec![
+        101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17,
+    ];
+    row_data.extend_from_slice(&[41, 42]);
+
+    // registers following the user stack are ignored when the state is built from a slice
+    let state = TraceState::from_u128_slice(1, 0, 2, &row_data);
+    assert_eq!([16, 17, 0, 0, 0, 0, 0, 0].to_elements(), state.user_stack());
+    assert!(state.memory().is_empty());
+    assert_eq!(18, state.width());
+
+    // memory registers are updated only when the state was created with memory
+    let mut state = TraceState::new(1, 0, 2);
+    state.update(&row_data.to_elements()[..18]);
+    assert!(state.memory().is_empty());
+
+    let mut state = TraceState::new(1, 0, 2).with_memory();
+    state.update(&row_data.to_elements());
+    assert_eq!([16, 17, 0, 0, 0, 0, 0, 0].to_elements(), state.user_stack());
+    assert_eq!([41, 42].to_elements(), state.memory());
+    assert_eq!(row_data.len(), state.width());
+    assert_eq!(row_data.to_elements(), state.to_vec());
+}
+
+#[test]
+fn storage_registers() {
+    use crate::U32_TRACE_WIDTH;
+
+    let u32_table = (300..(300 + U32_TRACE_WIDTH as u128)).collect::<Vec<_>>();
+    let mut row_data = vec![
+        101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 51, 52,
+    ];
+
+    // storage registers can follow the user stack directly
+    let mut state = TraceState::new(1, 0, 2).with_storage();
+    state.update(&row_data.to_elements());
+    assert!(state.memory().is_empty());
+    assert_eq!([51, 52].to_elements(), state.storage());
+    assert_eq!(row_data.len(), state.width());
+    assert_eq!(row_data.to_elements(), state.to_vec());
+
+    // or they can follow the memory registers and precede the u32 operations table
+    row_data.splice(18..18, [41, 42].iter().copied());
+    row_data.extend_from_slice(&u32_table);
+    let mut state = TraceState::new(1, 0, 2)
+        .with_memory()
+        .with_storage()
+        .with_u32_table();
+    state.update(&row_data.to_elements());
+    assert_eq!([16, 17, 0, 0, 0, 0, 0, 0].to_elements(), state.user_stack());
+    assert_eq!([41, 42].to_elements(), state.memory());
+    assert_eq!([51, 52].to_elements(), state.storage());
+    assert_eq!(u32_table.to_elements(), state.u32_table());
+    assert_eq!(row_data.to_elements(), state.to_vec());
+}
+
+#[test]
+fn u32_table_registers() {
+    use crate::U32_TRACE_WIDTH;
+
+    let u32_table = (300..(300 + U32_TRACE_WIDTH as u128)).collect::<Vec<_>>();
+    let mut row_data = vec![
+        101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17,
+    ];
+    row_data.extend_from_slice(&u32_table);
+
+    // the u32 operations table can follow the user stack directly
+    let mut state = TraceState::new(1, 0, 2).with_u32_table();
+    state.update(&row_data.to_elements());
+    assert!(state.memory().is_empty());
+    assert_eq!(u32_table.to_elements(), state.u32_table());
+    assert_eq!(row_data.to_elements(), state.to_vec());
+
+    // or it can follow the memory registers
+    row_data.splice(18..18, [41, 42].iter().copied());
+    let mut state = TraceState::new(1, 0, 2).with_memory().with_u32_table();
+    state.update(&row_data.to_elements());
+    assert_eq!([16, 17, 0, 0, 0, 0, 0, 0].to_elements(), state.user_stack());
+    assert_eq!([41, 42].to_elements(), state.memory());
+    assert_eq!(u32_table.to_elements(), state.u32_table());
+    assert_eq!(row_data.len(), state.width());
+    assert_eq!(row_data.to_elements(), state.to_vec());
+}
+
+#[test]
+fn max_trace_width() {
+    // an execution trace with all sections at their maximum widths must still fit into the
+    // number of registers supported by the prover
+    let decoder_width =
+        TraceState::<BaseElement>::compute_decoder_width(MAX_CONTEXT_DEPTH, MAX_LOOP_DEPTH);
+    let width = decoder_width
+        + MAX_STACK_DEPTH
+        + MEMORY_TRACE_WIDTH
+        + STORAGE_TRACE_WIDTH
+        + U32_TRACE_WIDTH;
+    assert_eq!(254, width);
+    assert!(width <= MAX_TRACE_WIDTH);
+}
+
 #[test]
 fn op_code() {
     let state = TraceState::from_u128_slice(
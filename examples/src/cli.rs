@@ -0,0 +1,243 @@
+use miden::{
+    assembly, BaseElement, Deserializable, ExecutionProof, Process, Program, ProgramInputs,
+    ProofOptions, Serializable, SliceReader, StarkField,
+};
+use std::{fs, path::Path, time::Instant};
+
+// COMMANDS
+// ================================================================================================
+
+/// Compiles Miden assembly source code from the specified file, and prints the hash of the
+/// resulting program together with the estimated number of cycles needed to execute it and the
+/// estimated width of the execution trace.
+pub fn compile(assembly_file: &Path) -> Result<(), String> {
+    let program = load_program(assembly_file)?;
+    println!("program hash: {}", hex::encode(program.hash()));
+
+    let estimate = program.estimate_cycles();
+    let (min_cycles, min_length) = (estimate.min_cycles(), estimate.min_trace_length());
+    match (estimate.max_cycles(), estimate.max_trace_length()) {
+        _ if estimate.is_exact() => println!(
+            "execution cycles: {} (trace length {})",
+            min_cycles, min_length
+        ),
+        (Some(max_cycles), Some(max_length)) => println!(
+            "execution cycles: {} to {} (trace length {} to {})",
+            min_cycles, max_cycles, min_length, max_length
+        ),
+        _ => println!(
+            "execution cycles: at least {} (trace length at least {})",
+            min_cycles, min_length
+        ),
+    }
+    if let Some(width) = estimate.max_trace_width() {
+        println!("trace width: at most {}", width);
+    }
+    Ok(())
+}
+
+/// Executes Miden assembly source code from the specified file without generating a proof, and
+/// prints the top `num_outputs` items of the stack.
+///
+/// If the `profile_file` is specified, the execution is profiled, and the profile is written
+/// into the file in the folded stack format which can be used to build a flamegraph.
+pub fn run(
+    assembly_file: &Path,
+    input_file: Option<&Path>,
+    num_outputs: usize,
+    profile_file: Option<&Path>,
+) -> Result<(), String> {
+    let source = read_source(assembly_file)?;
+    let program = assembly::compile(&source).map_err(|err| err.to_string())?;
+    let inputs = load_inputs(input_file)?;
+
+    let now = Instant::now();
+    let mut process = Process::new(&program, &inputs);
+    if profile_file.is_some() {
+        process = process.with_profiling();
+    }
+    process.run().map_err(|err| err.to_string())?;
+    println!(
+        "executed program in {} steps ({} ms)",
+        process.current_step(),
+        now.elapsed().as_millis()
+    );
+
+    let outputs = process.stack_state();
+    if num_outputs > outputs.len() {
+        return Err(format!(
+            "cannot return {} outputs; the stack contains only {} items",
+            num_outputs,
+            outputs.len()
+        ));
+    }
+    let outputs = to_integers(&outputs[..num_outputs]);
+    println!("outputs: {}", format_values(outputs.into_iter()));
+
+    if let (Some(profile_file), Some(profile)) = (profile_file, process.profile()) {
+        fs::write(profile_file, profile.to_folded(Some(&source)))
+            .map_err(|err| format!("failed to write {}: {}", profile_file.display(), err))?;
+        println!("profile written to {}", profile_file.display());
+    }
+    Ok(())
+}
+
+/// Executes Miden assembly source code from the specified file, and writes the proof of the
+/// execution into the `proof_file`; the proof is written as an execution proof bundle which
+/// also contains the hash of the program, its public inputs and outputs.
+pub fn prove(
+    assembly_file: &Path,
+    input_file: Option<&Path>,
+    num_outputs: usize,
+    proof_file: &Path,
+    options: &ProofOptions,
+) -> Result<(), String> {
+    let program = load_program(assembly_file)?;
+    let inputs = load_inputs(input_file)?;
+
+    let now = Instant::now();
+    let (outputs, storage_root, proof) =
+        miden::execute(&program, &inputs, num_outputs, options).map_err(|err| err.to_string())?;
+    println!(
+        "executed program and generated proof in {} ms",
+        now.elapsed().as_millis()
+    );
+
+    let public_inputs = to_integers(inputs.public_inputs());
+    let bundle = ExecutionProof::new(*program.hash(), &public_inputs, &outputs, proof)
+        .map_err(|err| err.to_string())?
+        .with_storage_roots(inputs.storage().root(), storage_root);
+    fs::write(proof_file, bundle.to_bytes())
+        .map_err(|err| format!("failed to write {}: {}", proof_file.display(), err))?;
+    println!("program hash: {}", hex::encode(program.hash()));
+    println!("outputs: {}", format_values(outputs.into_iter()));
+    println!("proof written to {}", proof_file.display());
+    Ok(())
+}
+
+/// Verifies the execution proof bundle from the specified file, and makes sure it proves
+/// execution of the program with the specified hash.
+///
+/// If the `input_file` or the `outputs` are provided, also makes sure the bundle contains the
+/// same public inputs or outputs.
+pub fn verify(
+    proof_file: &Path,
+    program_hash: &str,
+    input_file: Option<&Path>,
+    outputs: Option<&[u128]>,
+) -> Result<(), String> {
+    let proof_bytes = fs::read(proof_file)
+        .map_err(|err| format!("failed to read {}: {}", proof_file.display(), err))?;
+    let bundle = ExecutionProof::read_from(&mut SliceReader::new(&proof_bytes))
+        .map_err(|err| format!("failed to deserialize proof: {}", err))?;
+
+    if hex::encode(bundle.program_hash()) != program_hash.to_lowercase() {
+        return Err(format!(
+            "expected proof for program {}, but the proof is for program {}",
+            program_hash,
+            hex::encode(bundle.program_hash())
+        ));
+    }
+    if let Some(input_file) = input_file {
+        let public_inputs = to_integers(load_inputs(Some(input_file))?.public_inputs());
+        if public_inputs != bundle.public_inputs() {
+            return Err(format!(
+                "expected public inputs {}, but the proof is for public inputs {}",
+                format_values(public_inputs.into_iter()),
+                format_values(bundle.public_inputs().iter().copied())
+            ));
+        }
+    }
+    if let Some(outputs) = outputs {
+        if outputs != bundle.outputs() {
+            return Err(format!(
+                "expected outputs {}, but the proof is for outputs {}",
+                format_values(outputs.iter().copied()),
+                format_values(bundle.outputs().iter().copied())
+            ));
+        }
+    }
+
+    let outputs = bundle.outputs().to_vec();
+    let now = Instant::now();
+    miden::verify_bundle(bundle).map_err(|err| format!("failed to verify execution: {}", err))?;
+    println!("execution verified in {} ms", now.elapsed().as_millis());
+    println!("outputs: {}", format_values(outputs.into_iter()));
+    Ok(())
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Reads Miden assembly source code from the specified file and compiles it into a program.
+fn load_program(assembly_file: &Path) -> Result<Program, String> {
+    let source = read_source(assembly_file)?;
+    assembly::compile(&source).map_err(|err| err.to_string())
+}
+
+/// Reads Miden assembly source code from the specified file.
+fn read_source(assembly_file: &Path) -> Result<String, String> {
+    fs::read_to_string(assembly_file)
+        .map_err(|err| format!("failed to read {}: {}", assembly_file.display(), err))
+}
+
+/// Reads program inputs from the specified JSON or TOML file; if no file is specified, the
+/// program is assumed to have no inputs.
+///
+/// Both formats have the same structure; see [ProgramInputs::from_json()] for details.
+fn load_inputs(input_file: Option<&Path>) -> Result<ProgramInputs, String> {
+    let input_file = match input_file {
+        Some(input_file) => input_file,
+        None => return Ok(ProgramInputs::none()),
+    };
+
+    let source = fs::read_to_string(input_file)
+        .map_err(|err| format!("failed to read {}: {}", input_file.display(), err))?;
+    let extension = input_file
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default();
+    parse_inputs(&source, extension)
+        .map_err(|err| format!("failed to parse {}: {}", input_file.display(), err))
+}
+
+/// Parses program inputs from the specified source; the format of the source is determined by
+/// the `extension` (either "json" or "toml").
+fn parse_inputs(source: &str, extension: &str) -> Result<ProgramInputs, String> {
+    match extension {
+        "json" => ProgramInputs::from_json(source).map_err(|err| err.to_string()),
+        "toml" => toml::from_str(source).map_err(|err| err.to_string()),
+        _ => Err(format!(
+            "input file format {} is not supported; expected json or toml",
+            extension
+        )),
+    }
+}
+
+/// Converts the specified field elements into integers.
+fn to_integers(values: &[BaseElement]) -> Vec<u128> {
+    values.iter().map(|v| v.as_int()).collect()
+}
+
+/// Formats the specified values as a comma-separated list accepted by the `verify` command.
+fn format_values<I: Iterator<Item = u128>>(values: I) -> String {
+    values.map(|v| v.to_string()).collect::<Vec<_>>().join(",")
+}
+
+// TESTS
+// ================================================================================================
+
+#[test]
+fn parse_input_file() {
+    let json = r#"{ "stack_init": [1, "2"], "tape_a": ["0x3", 4] }"#;
+    let toml = "stack_init = [1, \"2\"]\ntape_a = [\"0x3\", 4]";
+    for (source, extension) in [(json, "json"), (toml, "toml")].iter() {
+        let inputs = parse_inputs(source, extension).unwrap();
+        assert_eq!(vec![1, 2], to_integers(inputs.public_inputs()));
+        assert_eq!(vec![3, 4], to_integers(&inputs.secret_inputs()[0]));
+    }
+
+    assert!(parse_inputs(r#"{ "tape_c": [1] }"#, "json").is_err());
+    assert!(parse_inputs("stack_init = [\"x\"]", "toml").is_err());
+    assert!(parse_inputs(json, "yaml").is_err());
+}
@@ -1,6 +1,6 @@
 use crate::Example;
 use log::debug;
-use miden::{assembly, BaseElement, FieldElement, ProgramInputs, StarkField};
+use miden::{masm, BaseElement, FieldElement, ProgramInputs, StarkField};
 
 // EXAMPLE BUILDER
 // ================================================================================================
@@ -15,7 +15,7 @@ pub fn get_example(start_value: usize) -> Example {
     // construct the program which executes an unbounded loop to compute a Collatz sequence
     // which starts with the provided value; the output of the program is the number of steps
     // needed to reach the end of the sequence
-    let program = assembly::compile(
+    let program = masm!(
         "
     begin
         pad read dup push.1 ne
@@ -30,8 +30,7 @@ pub fn get_example(start_value: usize) -> Example {
         end
         swap
     end",
-    )
-    .unwrap();
+    );
 
     debug!(
         "Generated a program to compute Collatz sequence; expected result: {}",
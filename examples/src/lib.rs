@@ -1,6 +1,9 @@
 use miden::{Program, ProgramInputs, ProofOptions};
+use std::path::PathBuf;
 use structopt::StructOpt;
 
+#[cfg(feature = "std")]
+pub mod cli;
 pub mod collatz;
 pub mod comparison;
 pub mod conditional;
@@ -9,6 +12,8 @@ pub mod fibonacci;
 pub mod merkle;
 #[cfg(feature = "std")]
 pub mod range;
+#[cfg(feature = "std")]
+pub mod repl;
 
 // EXAMPLE
 // ================================================================================================
@@ -86,6 +91,65 @@ pub enum ExampleType {
         #[structopt(short = "n", default_value = "100")]
         num_values: usize,
     },
+    /// Starts an interactive session for executing Miden assembly instructions
+    #[cfg(feature = "std")]
+    Repl,
+    /// Compiles a Miden assembly file and prints the hash of the resulting program
+    #[cfg(feature = "std")]
+    Compile {
+        /// Path to a file with Miden assembly source code
+        #[structopt(parse(from_os_str))]
+        assembly_file: PathBuf,
+    },
+    /// Executes a Miden assembly file without generating a proof, and prints the outputs
+    #[cfg(feature = "std")]
+    Run {
+        /// Path to a file with Miden assembly source code
+        #[structopt(parse(from_os_str))]
+        assembly_file: PathBuf,
+        /// Path to a JSON or TOML file with program inputs
+        #[structopt(short = "i", long = "input", parse(from_os_str))]
+        input_file: Option<PathBuf>,
+        /// Number of items from the top of the stack to output
+        #[structopt(short = "n", long = "num-outputs", default_value = "1")]
+        num_outputs: usize,
+        /// Path to a file into which the profile of the execution is written in the folded
+        /// stack format
+        #[structopt(short = "p", long = "profile", parse(from_os_str))]
+        profile_file: Option<PathBuf>,
+    },
+    /// Executes a Miden assembly file and writes the proof of its execution into a file
+    #[cfg(feature = "std")]
+    Prove {
+        /// Path to a file with Miden assembly source code
+        #[structopt(parse(from_os_str))]
+        assembly_file: PathBuf,
+        /// Path to a JSON or TOML file with program inputs
+        #[structopt(short = "i", long = "input", parse(from_os_str))]
+        input_file: Option<PathBuf>,
+        /// Number of items from the top of the stack to output
+        #[structopt(short = "n", long = "num-outputs", default_value = "1")]
+        num_outputs: usize,
+        /// Path to the file into which the proof is written
+        #[structopt(short = "o", long = "output", parse(from_os_str))]
+        proof_file: PathBuf,
+    },
+    /// Verifies a proof of execution of a program with the specified hash
+    #[cfg(feature = "std")]
+    Verify {
+        /// Path to a file with the proof of execution
+        #[structopt(parse(from_os_str))]
+        proof_file: PathBuf,
+        /// Hash of the executed program as a hex string
+        #[structopt(short = "p", long = "program-hash")]
+        program_hash: String,
+        /// Path to a JSON or TOML file with the expected public inputs of the program
+        #[structopt(short = "i", long = "input", parse(from_os_str))]
+        input_file: Option<PathBuf>,
+        /// Comma-separated list of the expected program outputs
+        #[structopt(short = "o", long = "outputs", use_delimiter = true)]
+        outputs: Option<Vec<u128>>,
+    },
 }
 
 // TESTS
@@ -111,7 +175,7 @@ pub fn test_example(example: Example, fail: bool) {
         256,
     );
 
-    let (mut outputs, proof) = miden::execute(&program, &inputs, num_outputs, &options).unwrap();
+    let (mut outputs, _, proof) = miden::execute(&program, &inputs, num_outputs, &options).unwrap();
 
     assert_eq!(
         expected_result, outputs,
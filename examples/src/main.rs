@@ -28,6 +28,61 @@ fn main() {
         ExampleType::Merkle { tree_depth } => examples::merkle::get_example(tree_depth),
         #[cfg(feature = "std")]
         ExampleType::Range { num_values } => examples::range::get_example(num_values),
+        #[cfg(feature = "std")]
+        ExampleType::Repl => {
+            examples::repl::start();
+            return;
+        }
+        #[cfg(feature = "std")]
+        ExampleType::Compile { assembly_file } => {
+            return exit_on_error(examples::cli::compile(&assembly_file));
+        }
+        #[cfg(feature = "std")]
+        ExampleType::Run {
+            assembly_file,
+            input_file,
+            num_outputs,
+            profile_file,
+        } => {
+            let result = examples::cli::run(
+                &assembly_file,
+                input_file.as_deref(),
+                num_outputs,
+                profile_file.as_deref(),
+            );
+            return exit_on_error(result);
+        }
+        #[cfg(feature = "std")]
+        ExampleType::Prove {
+            assembly_file,
+            input_file,
+            num_outputs,
+            proof_file,
+        } => {
+            let result = examples::cli::prove(
+                &assembly_file,
+                input_file.as_deref(),
+                num_outputs,
+                &proof_file,
+                &proof_options,
+            );
+            return exit_on_error(result);
+        }
+        #[cfg(feature = "std")]
+        ExampleType::Verify {
+            proof_file,
+            program_hash,
+            input_file,
+            outputs,
+        } => {
+            let result = examples::cli::verify(
+                &proof_file,
+                &program_hash,
+                input_file.as_deref(),
+                outputs.as_deref(),
+            );
+            return exit_on_error(result);
+        }
     };
 
     let Example {
@@ -43,7 +98,8 @@ fn main() {
     // execute the program and generate the proof of execution
     #[cfg(feature = "std")]
     let now = Instant::now();
-    let (outputs, proof) = miden::execute(&program, &inputs, num_outputs, &proof_options).unwrap();
+    let (outputs, _, proof) =
+        miden::execute(&program, &inputs, num_outputs, &proof_options).unwrap();
     debug!("--------------------------------");
     #[cfg(feature = "std")]
     debug!(
@@ -75,3 +131,11 @@ fn main() {
         Err(msg) => debug!("Failed to verify execution: {}", msg),
     }
 }
+
+/// Prints the error and terminates the process with a non-zero exit code if the command failed.
+fn exit_on_error(result: Result<(), String>) {
+    if let Err(message) = result {
+        eprintln!("error: {}", message);
+        std::process::exit(1);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::Example;
 use log::debug;
-use miden::{assembly, BaseElement, FieldElement, Program, ProgramInputs, StarkField};
+use miden::{
+    assembly::ProgramBuilder, BaseElement, FieldElement, Program, ProgramInputs, StarkField,
+};
 use rand_utils::prng_vector;
 use vm_core::hasher;
 
@@ -46,22 +48,18 @@ pub fn get_example(depth: usize) -> Example {
 /// the program first verifies the path using smpath operation, and then verifies
 /// the same path using pmpath operation.
 fn generate_merkle_program(n: usize, index: usize) -> Program {
-    let source = format!(
-        "
-    begin
-        read.ab
-        dup.2
-        smpath.{}
-        swap.2
-        push.{}
-        roll.4 swap swap.2
-        pmpath.{}
-    end
-    ",
-        n, index, n
-    );
-
-    assembly::compile(&source).unwrap()
+    ProgramBuilder::new()
+        .read_ab()
+        .dup(2)
+        .smpath(n)
+        .swap(2)
+        .push(index as u128)
+        .roll(4)
+        .swap(1)
+        .swap(2)
+        .pmpath(n)
+        .build()
+        .unwrap()
 }
 
 /// Converts Merkle authentication path for a node at the specified `index` into
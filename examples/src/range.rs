@@ -1,6 +1,6 @@
 use crate::Example;
 use log::debug;
-use miden::{assembly, Program, ProgramInputs};
+use miden::{assembly::ProgramBuilder, Program, ProgramInputs};
 use rand_utils::rand_vector;
 
 // EXAMPLE BUILDER
@@ -37,19 +37,15 @@ fn generate_values(n: usize) -> Vec<u128> {
 
 /// Generates a program to range-check a sequence of values.
 fn generate_range_check_program(n: usize) -> Program {
-    let mut program = String::with_capacity(n * 80);
-    program.push_str("begin ");
-
     // repeat the cycle of the following operations:
     // 1. read a value from secret tape A
     // 2. check if it fits into 63 bits (result is 1 if true, 0 otherwise)
     // 3. add the result into the running sum
+    let mut program = ProgramBuilder::new();
     for _ in 0..n {
-        program.push_str("read rc.63 add ");
+        program = program.read().rc(63).add();
     }
-    program.push_str("end");
-
-    assembly::compile(&program).unwrap()
+    program.build().unwrap()
 }
 
 /// Counts the number of values smaller than 63-bits in size.
@@ -0,0 +1,334 @@
+use miden::{assembly, BaseElement, Process, ProgramInputs, StarkField};
+use std::io::{self, BufRead, Write};
+
+// CONSTANTS
+// ================================================================================================
+
+const PROMPT: &str = ">> ";
+const CONTINUATION_PROMPT: &str = ".. ";
+
+const HELP: &str = "\
+Enter Miden assembly instructions to execute them; the state of the stack is printed after
+every instruction. Blocks (e.g. if.true ... end) can span multiple lines.
+
+Available commands:
+  :tape a|b [values]  set secret input tape A or B to the specified values (e.g. :tape a 1 2 3);
+                      if no values are provided, prints the values remaining on the tape
+  :undo               remove the last executed instruction
+  :reset              remove all executed instructions and clear input tapes
+  :help               print this message
+  :quit               exit the REPL";
+
+// REPL
+// ================================================================================================
+
+/// Starts an interactive session which reads Miden assembly instructions from the standard
+/// input and executes them one by one.
+pub fn start() {
+    let mut repl = Repl::new();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    println!("Miden assembly REPL; type :help for the list of available commands");
+    loop {
+        let prompt = if repl.is_pending() {
+            CONTINUATION_PROMPT
+        } else {
+            PROMPT
+        };
+        print!("{}", prompt);
+        io::stdout().flush().expect("failed to flush stdout");
+
+        let line = match lines.next() {
+            Some(line) => line.expect("failed to read from stdin"),
+            None => break,
+        };
+
+        match line.trim() {
+            ":quit" | ":exit" => break,
+            ":help" => println!("{}", HELP),
+            _ => match repl.eval(&line) {
+                Ok(Some(output)) => println!("{}", output),
+                Ok(None) => (),
+                Err(message) => println!("error: {}", message),
+            },
+        }
+    }
+}
+
+/// Keeps the state of an interactive Miden assembly session.
+///
+/// Instructions entered into the REPL are accumulated into a program which is re-executed from
+/// the start every time a new instruction is added; thus, the state of the VM after every
+/// instruction is exactly the same as it would be after executing the program in one go.
+pub struct Repl {
+    instructions: Vec<String>,
+    pending: Vec<String>,
+    tape_a: Vec<u128>,
+    tape_b: Vec<u128>,
+    stack: Vec<BaseElement>,
+}
+
+impl Repl {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a new REPL with no executed instructions and empty input tapes.
+    pub fn new() -> Self {
+        Repl {
+            instructions: Vec::new(),
+            pending: Vec::new(),
+            tape_a: Vec::new(),
+            tape_b: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the state of the stack after all executed instructions; the top of the stack is
+    /// the first value.
+    pub fn stack(&self) -> &[BaseElement] {
+        &self.stack
+    }
+
+    /// Returns true if the REPL is waiting for more lines to complete an unterminated block.
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    // EVALUATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Evaluates a single line of input, which can be either a REPL command or a sequence of
+    /// Miden assembly instructions; returns the text to be printed, if any.
+    ///
+    /// # Errors
+    /// Returns an error if the command is invalid, or if the instructions could not be
+    /// compiled or executed; in such cases, the state of the REPL remains unchanged.
+    pub fn eval(&mut self, line: &str) -> Result<Option<String>, String> {
+        let line = line.trim();
+        if line.starts_with(':') && !self.is_pending() {
+            return self.eval_command(line);
+        }
+        if line.is_empty() {
+            return Ok(None);
+        }
+
+        // accumulate lines until all blocks opened in them are closed
+        self.pending.push(line.to_string());
+        let source = self.pending.join("\n");
+        match get_block_depth(&source) {
+            Ok(depth) if depth > 0 => return Ok(None),
+            Ok(_) => self.pending.clear(),
+            Err(message) => {
+                self.pending.clear();
+                return Err(message);
+            }
+        }
+
+        self.instructions.push(source);
+        match self.execute() {
+            Ok(stack) => {
+                self.stack = stack;
+                Ok(Some(format_values(&self.stack)))
+            }
+            Err(message) => {
+                self.instructions.pop();
+                Err(message)
+            }
+        }
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Evaluates a REPL command (a line starting with ':').
+    fn eval_command(&mut self, line: &str) -> Result<Option<String>, String> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts[0] {
+            ":tape" => {
+                let is_tape_a = match parts.get(1) {
+                    Some(&"a") => true,
+                    Some(&"b") => false,
+                    Some(name) => {
+                        return Err(format!("tape {} does not exist; expected a or b", name))
+                    }
+                    None => return Err("tape name is missing; expected a or b".to_string()),
+                };
+                let values = parts[2..]
+                    .iter()
+                    .map(|&value| parse_value(value))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if values.is_empty() {
+                    // print the values remaining on the tape after all executed instructions
+                    let tape = self.execute_process(|process| {
+                        if is_tape_a {
+                            process.tape_a()
+                        } else {
+                            process.tape_b()
+                        }
+                    })?;
+                    Ok(Some(format_values(&tape)))
+                } else {
+                    self.set_tape(is_tape_a, values)?;
+                    Ok(None)
+                }
+            }
+            ":undo" => {
+                if self.instructions.pop().is_none() {
+                    return Err("there are no instructions to undo".to_string());
+                }
+                self.stack = self.execute()?;
+                Ok(Some(format_values(&self.stack)))
+            }
+            ":reset" => {
+                *self = Repl::new();
+                Ok(None)
+            }
+            command => Err(format!("command {} is not supported", command)),
+        }
+    }
+
+    /// Replaces the contents of input tape A (or tape B) with the specified values.
+    ///
+    /// Executed instructions are re-executed against the new values; if this fails, the
+    /// original contents of the tape are restored.
+    fn set_tape(&mut self, is_tape_a: bool, values: Vec<u128>) -> Result<(), String> {
+        let tape = if is_tape_a {
+            &mut self.tape_a
+        } else {
+            &mut self.tape_b
+        };
+        let original = std::mem::replace(tape, values);
+
+        match self.execute() {
+            Ok(stack) => {
+                self.stack = stack;
+                Ok(())
+            }
+            Err(message) => {
+                if is_tape_a {
+                    self.tape_a = original;
+                } else {
+                    self.tape_b = original;
+                }
+                Err(message)
+            }
+        }
+    }
+
+    /// Executes all accumulated instructions and returns the resulting state of the stack.
+    fn execute(&self) -> Result<Vec<BaseElement>, String> {
+        self.execute_process(|process| process.stack_state())
+    }
+
+    /// Executes all accumulated instructions, and reads the result from the final state of the
+    /// process using the specified `reader`.
+    fn execute_process<F, T>(&self, reader: F) -> Result<T, String>
+    where
+        F: Fn(&Process) -> T,
+    {
+        // an empty program is not valid, so a noop is executed when there are no instructions
+        let body = if self.instructions.is_empty() {
+            "noop".to_string()
+        } else {
+            self.instructions.join("\n")
+        };
+        let source = format!("begin\n{}\nend", body);
+        let program = assembly::compile(&source).map_err(|err| err.message().clone())?;
+
+        let inputs = ProgramInputs::new(&[], &self.tape_a, &self.tape_b);
+        let mut process = Process::new(&program, &inputs);
+        process.run().map_err(|err| err.to_string())?;
+        Ok(reader(&process))
+    }
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the number of blocks opened but not closed in the specified source code.
+fn get_block_depth(source: &str) -> Result<isize, String> {
+    let tokens = assembly::tokenize(source).map_err(|err| err.message().clone())?;
+    let mut depth = 0;
+    for token in tokens {
+        match token.parts()[0] {
+            "if" | "while" | "repeat" => depth += 1,
+            "end" => depth -= 1,
+            _ => (),
+        }
+        if depth < 0 {
+            return Err("end without matching block".to_string());
+        }
+    }
+    Ok(depth)
+}
+
+/// Parses a value for an input tape; the value must be a valid field element.
+fn parse_value(value: &str) -> Result<u128, String> {
+    match value.parse::<u128>() {
+        Ok(value) if value < BaseElement::MODULUS => Ok(value),
+        _ => Err(format!("{} is not a valid field element", value)),
+    }
+}
+
+/// Formats the specified values as a list of integers.
+fn format_values(values: &[BaseElement]) -> String {
+    let values: Vec<String> = values.iter().map(|v| v.as_int().to_string()).collect();
+    format!("[{}]", values.join(", "))
+}
+
+// TESTS
+// ================================================================================================
+
+#[test]
+fn eval_instructions() {
+    let mut repl = Repl::new();
+    assert_eq!(Ok(Some("[3]".to_string())), repl.eval("push.3"));
+    assert_eq!(Ok(Some("[5, 3]".to_string())), repl.eval("push.5"));
+    assert_eq!(Ok(Some("[8]".to_string())), repl.eval("add"));
+
+    // blocks can span multiple lines
+    assert_eq!(Ok(None), repl.eval("push.1 if.true"));
+    assert!(repl.is_pending());
+    assert_eq!(Ok(None), repl.eval("push.2 mul"));
+    assert_eq!(Ok(Some("[16]".to_string())), repl.eval("end"));
+
+    // failed instructions do not affect the state
+    assert!(repl.eval("push.2 assert").is_err());
+    assert!(repl.eval("foo").is_err());
+    assert_eq!(&[BaseElement::new(16)], repl.stack());
+
+    assert_eq!(Ok(Some("[8]".to_string())), repl.eval(":undo"));
+    assert_eq!(Ok(None), repl.eval(":reset"));
+    assert!(repl.stack().is_empty());
+}
+
+#[test]
+fn eval_tape_commands() {
+    let mut repl = Repl::new();
+    assert_eq!(Ok(None), repl.eval(":tape a 1 2 3"));
+    assert_eq!(Ok(Some("[1]".to_string())), repl.eval("read"));
+    assert_eq!(Ok(Some("[2, 1]".to_string())), repl.eval("read"));
+    assert_eq!(Ok(Some("[3]".to_string())), repl.eval(":tape a"));
+
+    // tape values cannot be removed if they were already read
+    assert!(repl.eval(":tape a 1").is_err());
+    assert_eq!(Ok(Some("[3]".to_string())), repl.eval(":tape a"));
+
+    // values from tape B are read together with values from tape A
+    assert_eq!(Ok(None), repl.eval(":tape b 7"));
+    assert_eq!(Ok(Some("[7, 3, 2, 1]".to_string())), repl.eval("read.ab"));
+
+    assert!(repl.eval(":tape c 1").is_err());
+    assert!(repl.eval(":tape a x").is_err());
+}
@@ -0,0 +1,98 @@
+use assembly::AssemblyError;
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, LitStr, Token,
+};
+use vm_core::Serializable;
+
+#[cfg(test)]
+mod tests;
+
+// MACROS
+// ================================================================================================
+
+/// Compiles Miden assembly source code provided as a string literal into a program while the
+/// Rust code is being compiled.
+///
+/// The macro expands into an expression which evaluates to the compiled `Program`; all errors
+/// in the source code are reported as compiler errors. Modules of the standard library can be
+/// imported by the source code. The expansion refers to items of the `miden` crate, and thus,
+/// the macro must be used via its re-export from this crate:
+/// ```ignore
+/// let program = miden::masm!("begin push.3 push.5 add end");
+/// ```
+///
+/// The literal may be followed by a trailing comma.
+///
+/// The program is embedded into the expansion in its serialized form together with its hash.
+/// Every evaluation of the expression deserializes the program, which takes time linear in the
+/// size of the program, but the hash of the program is not recomputed. Since serialized programs
+/// do not contain locations of instructions in the source code, programs compiled by this macro
+/// cannot be profiled by instruction.
+#[proc_macro]
+pub fn masm(input: TokenStream) -> TokenStream {
+    let MasmInput { source } = parse_macro_input!(input as MasmInput);
+    expand_masm(&source)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+// MACRO INPUT
+// ================================================================================================
+
+/// Input of the `masm!` macro: a string literal with the source code, optionally followed by a
+/// trailing comma.
+struct MasmInput {
+    source: LitStr,
+}
+
+impl Parse for MasmInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let source = input.parse()?;
+        let _: Option<Token![,]> = input.parse()?;
+        Ok(Self { source })
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Compiles the source code in the specified literal and returns the expansion of the `masm!`
+/// macro, or an error combining all errors encountered during compilation.
+fn expand_masm(literal: &LitStr) -> syn::Result<TokenStream2> {
+    let source = literal.value();
+    let program =
+        assembly::compile_all(&source).map_err(|errors| build_error(&errors, literal, &source))?;
+
+    // the program was compiled from the source just now, and thus, its hash does not need to be
+    // recomputed when the program is loaded
+    let bytes = program.to_bytes();
+    Ok(quote! {
+        ::miden::Program::read_trusted(&mut ::miden::SliceReader::new(&[#(#bytes),*]))
+            .expect("failed to deserialize a program compiled by masm!")
+    })
+}
+
+/// Converts assembly errors into a single compiler error; every assembly error is attached to
+/// the literal, and is rendered together with the location of the error within the source code
+/// and a snippet of the offending line.
+fn build_error(errors: &[AssemblyError], literal: &LitStr, source: &str) -> syn::Error {
+    let mut result: Option<syn::Error> = None;
+    for error in errors {
+        let rendered = error.render(source);
+        // the first line of the rendered error repeats the message, and is replaced by the
+        // message itself since the compiler prefixes it with "error: " as well
+        let snippet = rendered.split_once('\n').map_or("", |(_, snippet)| snippet);
+        let message = format!("{}\n{}", error.message(), snippet.trim_end());
+
+        let error = syn::Error::new(literal.span(), message);
+        match result.as_mut() {
+            Some(result) => result.combine(error),
+            None => result = Some(error),
+        }
+    }
+    result.expect("compilation failed without errors")
+}
@@ -0,0 +1,39 @@
+use super::{expand_masm, MasmInput};
+use syn::LitStr;
+
+#[test]
+fn expand_program() {
+    let literal: LitStr = syn::parse_str(r#""begin push.3 push.5 add end""#).unwrap();
+    let program = assembly::compile(&literal.value()).unwrap();
+    let bytes = vm_core::Serializable::to_bytes(&program);
+
+    let expansion = expand_masm(&literal).unwrap().to_string();
+    assert!(expansion.contains(":: miden :: Program :: read_trusted"));
+    assert!(expansion.contains(":: miden :: SliceReader :: new"));
+    assert!(expansion.contains(&format!("{}u8", bytes[bytes.len() - 1])));
+}
+
+#[test]
+fn parse_input() {
+    let input: MasmInput = syn::parse_str(r#""begin push.3 end""#).unwrap();
+    assert_eq!("begin push.3 end", input.source.value());
+
+    let input: MasmInput = syn::parse_str(r#""begin push.3 end","#).unwrap();
+    assert_eq!("begin push.3 end", input.source.value());
+
+    assert!(syn::parse_str::<MasmInput>(r#""begin push.3 end",,"#).is_err());
+    assert!(syn::parse_str::<MasmInput>(r#""begin push.3 end", "push.5""#).is_err());
+}
+
+#[test]
+fn report_errors() {
+    let literal: LitStr = syn::parse_str(r#""begin push.3 pushh.5 add dup.7 end""#).unwrap();
+    let error = expand_masm(&literal).unwrap_err();
+
+    // all errors are reported, each together with a snippet of the source code
+    let messages: Vec<String> = error.into_iter().map(|e| e.to_string()).collect();
+    assert_eq!(2, messages.len());
+    assert!(messages[0].starts_with("instruction pushh.5 is invalid\n  --> 1:14\n"));
+    assert!(messages[0].ends_with("  |              ^^^^^^^"));
+    assert!(messages[1].contains("dup.7"));
+}
@@ -1,47 +1,96 @@
 use air::{ProcessorAir, PublicInputs, TraceMetadata, TraceState, MAX_OUTPUTS, MIN_TRACE_LENGTH};
-use core::{convert::TryInto, ops::Deref};
+use core::{convert::TryInto, fmt, ops::Deref};
 #[cfg(feature = "std")]
 use log::debug;
-use prover::{ExecutionTrace, ProverError, Serializable};
+use prover::{ExecutionTrace, ProverError};
 #[cfg(feature = "std")]
 use std::time::Instant;
 
 #[cfg(test)]
 mod tests;
 
+// expansions of the masm! macro refer to items of this crate as ::miden, which is not in scope
+// within the crate itself unless it is aliased
+#[cfg(test)]
+extern crate self as miden;
+
 // EXPORTS
 // ================================================================================================
 
-pub use air::{FieldExtension, HashFunction, ProofOptions};
+pub use air::{
+    check_constraints, ConstraintError, Deserializable, DeserializationError, FieldExtension,
+    HashFunction, ProofOptions, PublicInputsError, Serializable, SliceReader,
+};
 pub use assembly;
-pub use processor::{BaseElement, FieldElement, Program, ProgramInputs, StarkField};
+pub use macros::masm;
+pub use processor::{
+    BaseElement, Breakpoint, CycleEstimate, Debugger, ExecutionErrorKind, FieldElement, Frame,
+    InputError, Operation, Overhead, Process, Profile, Program, ProgramInputs, ProgramLibrary,
+    SourceLocation, StarkField, Storage,
+};
 pub use prover::StarkProof;
-pub use verifier::{verify, VerifierError};
+pub use verifier::{
+    verify, verify_bundle, verify_with_storage, ExecutionProof, ExecutionProofError, VerifierError,
+    PROOF_FORMAT_VERSION,
+};
 
 // EXECUTOR
 // ================================================================================================
 
 /// Executes the specified `program` and returns the result together with a STARK-based proof of execution.
 ///
-/// * `inputs` specifies the initial stack state and provides secret input tapes;
+/// * `inputs` specifies the initial stack state, the initial state of the storage, and provides
+///   secret input tapes;
 /// * `num_outputs` specifies the number of elements from the top of the stack to be returned;
+///
+/// The result consists of the outputs and of the root of the storage after the program was
+/// executed; the initial root is the root of the storage snapshot provided in `inputs`.
+///
+/// # Errors
+/// Returns an error if more than `MAX_OUTPUTS` outputs were requested, if the program could not
+/// be executed against the specified inputs, or if the STARK proof of the execution could not be
+/// generated.
 pub fn execute(
     program: &Program,
     inputs: &ProgramInputs,
     num_outputs: usize,
     options: &ProofOptions,
-) -> Result<(Vec<u128>, StarkProof), ProverError> {
-    assert!(
-        num_outputs <= MAX_OUTPUTS,
-        "cannot produce more than {} outputs, but requested {}",
-        MAX_OUTPUTS,
-        num_outputs
-    );
+) -> Result<(Vec<u128>, [u8; 32], StarkProof), ExecutionError> {
+    execute_with_library(
+        program,
+        inputs,
+        &ProgramLibrary::new(),
+        num_outputs,
+        options,
+    )
+}
+
+/// Executes the specified `program` and returns the result together with a STARK-based proof of
+/// execution; blocks invoked by `call` instructions of the program are looked up in the `library`.
+///
+/// Since a call results in the same program hash as the called block inlined into the program,
+/// the proof can be verified against the program hash without access to the library.
+///
+/// # Errors
+/// Returns an error if more than `MAX_OUTPUTS` outputs were requested, if the program could not
+/// be executed against the specified inputs (e.g., if it calls a block which is not in the
+/// `library`), if the execution trace does not match the program, or if the STARK proof of the
+/// execution could not be generated.
+pub fn execute_with_library(
+    program: &Program,
+    inputs: &ProgramInputs,
+    library: &ProgramLibrary,
+    num_outputs: usize,
+    options: &ProofOptions,
+) -> Result<(Vec<u128>, [u8; 32], StarkProof), ExecutionError> {
+    if num_outputs > MAX_OUTPUTS {
+        return Err(ExecutionError::TooManyOutputs(num_outputs));
+    }
 
     // execute the program to create an execution trace
     #[cfg(feature = "std")]
     let now = Instant::now();
-    let trace = processor::execute(program, inputs);
+    let trace = processor::execute_with_library(program, inputs, library)?;
     #[cfg(feature = "std")]
     debug!(
         "Generated execution trace of {} registers and {} steps in {} ms",
@@ -57,23 +106,29 @@ pub fn execute(
         .map(|&v| v.as_int())
         .collect::<Vec<_>>();
 
+    // the root of the storage changes only if the program accessed the storage, and then, the
+    // updated root is kept in the storage root registers
+    let init_storage_root = inputs.storage().root();
+    let storage_root: [u8; 32] = if last_state.storage().is_empty() {
+        init_storage_root
+    } else {
+        last_state.storage().to_bytes().try_into().unwrap()
+    };
+
     // make sure number of executed operations was sufficient
-    assert!(
-        last_state.op_counter().as_int() as usize >= MIN_TRACE_LENGTH,
-        "a program must consist of at least {} operation, but only {} were executed",
-        MIN_TRACE_LENGTH,
-        last_state.op_counter()
-    );
+    let op_count = last_state.op_counter().as_int() as usize;
+    if op_count < MIN_TRACE_LENGTH {
+        return Err(ExecutionError::TooFewOperations(op_count));
+    }
 
     // make sure program hash generated by the VM matches the hash of the program
     let program_hash: [u8; 32] = last_state.program_hash().to_bytes().try_into().unwrap();
-    #[cfg(feature = "std")]
-    assert!(
-        *program.hash() == program_hash,
-        "expected program hash {} does not match trace hash {}",
-        hex::encode(program.hash()),
-        hex::encode(program_hash)
-    );
+    if *program.hash() != program_hash {
+        return Err(ExecutionError::ProgramHashMismatch(
+            *program.hash(),
+            program_hash,
+        ));
+    }
 
     // generate STARK proof
     let inputs = inputs
@@ -81,15 +136,103 @@ pub fn execute(
         .iter()
         .map(|&v| v.as_int())
         .collect::<Vec<_>>();
-    let pub_inputs = PublicInputs::new(program_hash, &inputs, &outputs);
+    let pub_inputs = PublicInputs::new(program_hash, &inputs, &outputs)?
+        .with_storage_roots(init_storage_root, storage_root)?;
     let proof = prover::prove::<ProcessorAir>(trace, pub_inputs, options.deref().clone())?;
 
-    Ok((outputs, proof))
+    Ok((outputs, storage_root, proof))
+}
+
+// EXECUTION ERROR
+// ================================================================================================
+
+/// Describes a failure which occurred while executing a program and proving its execution.
+pub enum ExecutionError {
+    /// More outputs were requested than a program can produce.
+    TooManyOutputs(usize),
+    /// The program could not be executed against the provided inputs.
+    ProcessorError(processor::ExecutionError),
+    /// The program executed fewer operations than the minimum length of an execution trace.
+    TooFewOperations(usize),
+    /// The hash of the program computed by the VM (second value) does not match the hash of the
+    /// program which was executed (first value).
+    ProgramHashMismatch([u8; 32], [u8; 32]),
+    /// Public inputs of the proof could not be built from the inputs and outputs of the program.
+    InvalidPublicInputs(PublicInputsError),
+    /// A proof of program execution could not be generated.
+    ProverError(ProverError),
+}
+
+impl From<processor::ExecutionError> for ExecutionError {
+    fn from(error: processor::ExecutionError) -> Self {
+        ExecutionError::ProcessorError(error)
+    }
+}
+
+impl From<PublicInputsError> for ExecutionError {
+    fn from(error: PublicInputsError) -> Self {
+        ExecutionError::InvalidPublicInputs(error)
+    }
+}
+
+impl From<ProverError> for ExecutionError {
+    fn from(error: ProverError) -> Self {
+        ExecutionError::ProverError(error)
+    }
+}
+
+impl fmt::Debug for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionError::TooManyOutputs(num_outputs) => write!(
+                f,
+                "cannot produce more than {} outputs, but requested {}",
+                MAX_OUTPUTS, num_outputs
+            ),
+            ExecutionError::ProcessorError(error) => write!(f, "{:?}", error),
+            ExecutionError::TooFewOperations(_)
+            | ExecutionError::ProgramHashMismatch(..)
+            | ExecutionError::InvalidPublicInputs(_) => write!(f, "{}", self),
+            ExecutionError::ProverError(error) => write!(f, "prover error: {:?}", error),
+        }
+    }
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionError::TooManyOutputs(num_outputs) => write!(
+                f,
+                "cannot produce more than {} outputs, but requested {}",
+                MAX_OUTPUTS, num_outputs
+            ),
+            ExecutionError::ProcessorError(error) => write!(f, "{}", error),
+            ExecutionError::TooFewOperations(op_count) => write!(
+                f,
+                "a program must consist of at least {} operations, but only {} were executed",
+                MIN_TRACE_LENGTH, op_count
+            ),
+            ExecutionError::ProgramHashMismatch(expected, actual) => write!(
+                f,
+                "expected program hash {} does not match trace hash {}",
+                to_hex(expected),
+                to_hex(actual)
+            ),
+            ExecutionError::InvalidPublicInputs(error) => {
+                write!(f, "invalid public inputs: {}", error)
+            }
+            ExecutionError::ProverError(error) => write!(f, "prover error: {}", error),
+        }
+    }
 }
 
 // HELPER FUNCTIONS
 // ================================================================================================
 
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
 fn get_last_state(trace: &ExecutionTrace<BaseElement>) -> TraceState<BaseElement> {
     let last_step = trace.length() - 1;
     let meta = TraceMetadata::from_trace_info(&trace.get_info());
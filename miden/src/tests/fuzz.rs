@@ -0,0 +1,615 @@
+//! Differential fuzzing of the processor against the AIR.
+//!
+//! Random programs are built directly as trees of program blocks, executed by the processor, and
+//! the resulting execution traces are checked against all constraints of the processor AIR; a
+//! trace which does not satisfy the constraints means that the processor and the AIR disagree on
+//! semantics of some operation. Generation is deterministic: by default, the same programs are
+//! checked on every run, but the number of programs and the seed from which they are generated
+//! can be changed via `MIDEN_FUZZ_ITERATIONS` and `MIDEN_FUZZ_SEED` environment variables.
+
+use super::get_trace_state;
+use crate::{
+    assembly, check_constraints, BaseElement, FieldElement, Program, ProgramInputs, PublicInputs,
+    StarkField, MAX_OUTPUTS,
+};
+use std::collections::BTreeMap;
+use vm_core::{
+    opcodes::{OpHint, U32Op, UserOps as OpCode},
+    program::blocks::{Group, Loop, ProgramBlock, Repeat, Span, Switch},
+    BASE_CYCLE_LENGTH, HASHER_NUM_ROUNDS as NUM_HASH_ROUNDS, HASH_OP_ALIGNMENT, MAX_PUBLIC_INPUTS,
+    PUSH_OP_ALIGNMENT,
+};
+
+// CONSTANTS
+// ================================================================================================
+
+const DEFAULT_ITERATIONS: u64 = 16;
+const DEFAULT_SEED: u64 = 0x6d69_6465_6e76_6d21;
+
+/// Maximum depth of nesting of Group, Switch, Loop, and Repeat blocks.
+const MAX_NESTING: usize = 3;
+
+/// Maximum number of non-Span blocks in a single sequence of blocks.
+const MAX_BLOCKS: u64 = 2;
+
+/// Maximum number of gadgets in a single Span block.
+const MAX_GADGETS: u64 = 6;
+
+/// Maximum number of iterations of a single loop or Repeat block.
+const MAX_ITERATIONS: u64 = 3;
+
+/// Gadgets which remove items from the stack are generated only if the stack is at least this
+/// deep, and gadgets which add items to the stack only if the stack is at most this deep. The
+/// stack starts with [MAX_PUBLIC_INPUTS] items and a gadget removes at most 4 items; so, the
+/// stack never gets shallower than 8 items. A gadget adds at most 5 items, and memory accesses
+/// need up to 15 more items on top of that; so, the stack never gets deeper than the 32 items
+/// it can hold.
+const TARGET_STACK_DEPTH: usize = 12;
+
+/// Random memory accesses are made to addresses smaller than [MAX_RANDOM_ADDR]; loop counters
+/// are kept in memory starting at [LOOP_COUNTER_ADDR].
+const MAX_RANDOM_ADDR: u64 = 16;
+const LOOP_COUNTER_ADDR: u128 = 1024;
+
+const U32_OPS: [U32Op; 8] = [
+    U32Op::Add,
+    U32Op::Sub,
+    U32Op::Mul,
+    U32Op::Div,
+    U32Op::And,
+    U32Op::Xor,
+    U32Op::Shl,
+    U32Op::Shr,
+];
+
+const NO_SHIFT_OPS: [OpCode; 7] = [
+    OpCode::Swap,
+    OpCode::Swap2,
+    OpCode::Swap4,
+    OpCode::Roll4,
+    OpCode::Roll8,
+    OpCode::Neg,
+    OpCode::Noop,
+];
+
+// FUZZ TARGET
+// ================================================================================================
+
+#[test]
+fn fuzz_processor_against_air() {
+    let iterations = read_env("MIDEN_FUZZ_ITERATIONS", DEFAULT_ITERATIONS);
+    let seed = read_env("MIDEN_FUZZ_SEED", DEFAULT_SEED);
+
+    for i in 0..iterations {
+        let program_seed = seed.wrapping_add(i);
+        let mut generator = ProgramGenerator::new(program_seed);
+        let (program, inputs) = generator.generate();
+
+        // the generator keeps track of the stack depth and of the number of values read from
+        // the secret input tapes; so, every generated program must execute successfully
+        let trace = match processor::execute(&program, &inputs) {
+            Ok(trace) => trace,
+            Err(err) => panic!(
+                "a program generated from seed {} failed to execute: {}\n{}",
+                program_seed,
+                err,
+                assembly::disassemble(&program)
+            ),
+        };
+
+        let last_state = get_trace_state(&trace, trace.length() - 1);
+        let outputs: Vec<u128> = last_state.user_stack()[..MAX_OUTPUTS]
+            .iter()
+            .map(|v| v.as_int())
+            .collect();
+        let pub_inputs =
+            PublicInputs::new(*program.hash(), generator.public_inputs(), &outputs).unwrap();
+
+        if let Err(err) = check_constraints(&trace, pub_inputs) {
+            panic!(
+                "execution trace of a program generated from seed {} does not satisfy AIR \
+                constraints: {}\n{}",
+                program_seed,
+                err,
+                assembly::disassemble(&program)
+            );
+        }
+    }
+}
+
+// PROGRAM GENERATOR
+// ================================================================================================
+
+/// Describes how a generated sequence of blocks ends.
+#[derive(Copy, Clone)]
+enum SequenceEnd {
+    /// The sequence can leave the stack at any depth.
+    Any,
+    /// The sequence restores the stack depth to what it was at the beginning of the sequence;
+    /// this is needed for bodies of Repeat blocks and for branches of Switch blocks.
+    RestoreDepth,
+    /// The sequence is a body of a loop with the specified counter: it restores the stack depth
+    /// and leaves the condition for the next iteration on the top of the stack.
+    LoopCondition(u128),
+}
+
+/// Generates random programs which can be executed against the inputs generated together with
+/// them; the programs consist of randomly nested Group, Switch, Loop, and Repeat blocks with Span
+/// blocks
+/// made of randomly selected gadgets.
+struct ProgramGenerator {
+    rng: u64,
+    public_inputs: Vec<u128>,
+    stack_depth: usize,
+    num_loops: u128,
+    tape_a_reads: usize,
+    tape_b_reads: usize,
+}
+
+impl ProgramGenerator {
+    fn new(seed: u64) -> Self {
+        ProgramGenerator {
+            // xorshift state must not be zero
+            rng: seed | 1,
+            public_inputs: Vec::new(),
+            stack_depth: 0,
+            num_loops: 0,
+            tape_a_reads: 0,
+            tape_b_reads: 0,
+        }
+    }
+
+    fn public_inputs(&self) -> &[u128] {
+        &self.public_inputs
+    }
+
+    /// Generates a new program together with the inputs for it.
+    fn generate(&mut self) -> (Program, ProgramInputs) {
+        self.public_inputs = (0..MAX_PUBLIC_INPUTS)
+            .map(|_| self.next_value().as_int())
+            .collect();
+        self.stack_depth = MAX_PUBLIC_INPUTS;
+        self.tape_a_reads = 0;
+        self.tape_b_reads = 0;
+
+        // the tapes hold exactly as many values as the program reads from them
+        let root = Group::new(self.generate_blocks(&[OpCode::Begin], 0, SequenceEnd::Any));
+        let tape_a: Vec<u128> = (0..self.tape_a_reads)
+            .map(|_| self.next_value().as_int())
+            .collect();
+        let tape_b: Vec<u128> = (0..self.tape_b_reads)
+            .map(|_| self.next_value().as_int())
+            .collect();
+        let inputs = ProgramInputs::new(&self.public_inputs, &tape_a, &tape_b);
+        (Program::new(root), inputs)
+    }
+
+    // BLOCKS
+    // --------------------------------------------------------------------------------------------
+
+    /// Generates a sequence of blocks which starts with the specified `prefix` operations, and
+    /// ends as specified by `end`.
+    fn generate_blocks(
+        &mut self,
+        prefix: &[OpCode],
+        nesting: usize,
+        end: SequenceEnd,
+    ) -> Vec<ProgramBlock> {
+        let initial_depth = self.stack_depth;
+        let mut blocks = Vec::new();
+        let mut span = SpanBuilder::new(prefix);
+        self.append_gadgets(&mut span);
+
+        let num_blocks = if nesting < MAX_NESTING {
+            self.below(MAX_BLOCKS + 1)
+        } else {
+            0
+        };
+        for _ in 0..num_blocks {
+            match self.below(4) {
+                0 => {
+                    blocks.push(span.build());
+                    let body = self.generate_blocks(&[], nesting + 1, SequenceEnd::Any);
+                    blocks.push(Group::new_block(body));
+                }
+                1 => {
+                    // both branches start by removing the condition from the stack, and restore
+                    // the stack depth; so, the block does not change the stack depth
+                    let condition = self.next_bit();
+                    span.push(condition);
+                    blocks.push(span.build());
+
+                    // only the tape reads of the branch which is taken are counted
+                    let end = SequenceEnd::RestoreDepth;
+                    let reads = self.tape_reads();
+                    let t_branch = self.generate_blocks(&[OpCode::Assert], nesting + 1, end);
+                    let t_reads = self.tape_reads();
+                    self.set_tape_reads(reads);
+                    let f_branch =
+                        self.generate_blocks(&[OpCode::Not, OpCode::Assert], nesting + 1, end);
+                    if condition == BaseElement::ONE {
+                        self.set_tape_reads(t_reads);
+                    }
+                    blocks.push(Switch::new_block(t_branch, f_branch));
+                }
+                2 => {
+                    // every iteration restores the stack depth, and thus, the block does not
+                    // change the stack depth
+                    blocks.push(span.build());
+                    let num_iterations = 1 + self.below(MAX_ITERATIONS) as usize;
+                    let reads = self.tape_reads();
+                    let body = self.generate_blocks(&[], nesting + 1, SequenceEnd::RestoreDepth);
+                    self.repeat_tape_reads(reads, num_iterations);
+                    blocks.push(Repeat::new_block(body, num_iterations));
+                }
+                _ => {
+                    // initialize the loop counter, and enter the loop only if it is not zero
+                    let counter = LOOP_COUNTER_ADDR + self.num_loops;
+                    self.num_loops += 1;
+                    let num_iterations = self.below(MAX_ITERATIONS + 1);
+                    span.push(BaseElement::from(num_iterations));
+                    span.push(BaseElement::from(counter));
+                    span.append_instructions("mem.store");
+                    span.push(BaseElement::from((num_iterations > 0) as u8));
+                    blocks.push(span.build());
+
+                    // the condition is removed from the stack at the beginning of every iteration,
+                    // and when the loop is exited; so, the loop does not change the stack depth
+                    let loop_depth = self.stack_depth;
+                    let reads = self.tape_reads();
+                    let end = SequenceEnd::LoopCondition(counter);
+                    let body = self.generate_blocks(&[OpCode::Assert], nesting + 1, end);
+                    self.stack_depth = loop_depth;
+                    self.repeat_tape_reads(reads, num_iterations as usize);
+                    blocks.push(Loop::new_block(body));
+                }
+            }
+
+            span = SpanBuilder::new(&[]);
+            self.append_gadgets(&mut span);
+        }
+
+        if let SequenceEnd::RestoreDepth | SequenceEnd::LoopCondition(_) = end {
+            while self.stack_depth < initial_depth {
+                span.op(OpCode::Pad2);
+                self.stack_depth += 2;
+            }
+            while self.stack_depth >= initial_depth + 4 {
+                span.op(OpCode::Drop4);
+                self.stack_depth -= 4;
+            }
+            while self.stack_depth > initial_depth {
+                span.op(OpCode::Drop);
+                self.stack_depth -= 1;
+            }
+        }
+        if let SequenceEnd::LoopCondition(counter) = end {
+            self.append_loop_condition(&mut span, counter);
+        }
+        blocks.push(span.build());
+        blocks
+    }
+
+    /// Decrements the loop counter kept in memory at the specified address, and pushes 1 onto
+    /// the stack if the counter is not zero yet, or 0 otherwise.
+    fn append_loop_condition(&mut self, span: &mut SpanBuilder, counter: u128) {
+        span.push(BaseElement::from(counter));
+        span.append_instructions("mem.load");
+        span.push(-BaseElement::ONE);
+        span.op(OpCode::Add);
+        span.op(OpCode::Dup);
+        span.push(BaseElement::from(counter));
+        span.append_instructions("mem.store");
+        span.push(BaseElement::ZERO);
+        span.op_with_hint(OpCode::Read, OpHint::EqStart);
+        span.op(OpCode::Eq);
+        span.op(OpCode::Not);
+        self.stack_depth += 1;
+    }
+
+    // TAPE READS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the number of values read from tapes A and B by the operations generated so far.
+    fn tape_reads(&self) -> (usize, usize) {
+        (self.tape_a_reads, self.tape_b_reads)
+    }
+
+    fn set_tape_reads(&mut self, (tape_a_reads, tape_b_reads): (usize, usize)) {
+        self.tape_a_reads = tape_a_reads;
+        self.tape_b_reads = tape_b_reads;
+    }
+
+    /// Updates the number of tape reads for a block body which is executed `num_iterations`
+    /// times; `reads` is the number of tape reads before the body was generated.
+    fn repeat_tape_reads(&mut self, reads: (usize, usize), num_iterations: usize) {
+        self.tape_a_reads = reads.0 + (self.tape_a_reads - reads.0) * num_iterations;
+        self.tape_b_reads = reads.1 + (self.tape_b_reads - reads.1) * num_iterations;
+    }
+
+    // GADGETS
+    // --------------------------------------------------------------------------------------------
+
+    /// Appends a random number of randomly selected gadgets to the span.
+    fn append_gadgets(&mut self, span: &mut SpanBuilder) {
+        for _ in 0..=self.below(MAX_GADGETS) {
+            self.append_gadget(span);
+        }
+    }
+
+    /// Appends a short sequence of operations to the span; gadgets are made of operations which
+    /// are valid for any values on the stack, or of operations preceded by pushing values for
+    /// which they are valid.
+    fn append_gadget(&mut self, span: &mut SpanBuilder) {
+        let gadget = if self.stack_depth < TARGET_STACK_DEPTH {
+            5 + self.below(15)
+        } else if self.stack_depth > TARGET_STACK_DEPTH {
+            self.below(5)
+        } else {
+            self.below(20)
+        };
+
+        let delta: isize = match gadget {
+            // operations which remove items from the stack
+            0 => {
+                let op = [OpCode::Add, OpCode::Mul, OpCode::Drop][self.below(3) as usize];
+                span.op(op);
+                -1
+            }
+            1 => {
+                span.op(OpCode::Drop4);
+                -4
+            }
+            2 => {
+                span.op(OpCode::Dup);
+                span.op(OpCode::AssertEq);
+                -1
+            }
+            3 => {
+                // compare the top two stack items
+                span.op_with_hint(OpCode::Read, OpHint::EqStart);
+                span.op(OpCode::Eq);
+                -1
+            }
+            4 => {
+                span.op(NO_SHIFT_OPS[self.below(NO_SHIFT_OPS.len() as u64) as usize]);
+                0
+            }
+            // operations which add items to the stack
+            5 => {
+                let op = [OpCode::Dup, OpCode::Dup2, OpCode::Dup4, OpCode::Pad2];
+                let op = op[self.below(4) as usize];
+                span.op(op);
+                match op {
+                    OpCode::Dup => 1,
+                    OpCode::Dup4 => 4,
+                    _ => 2,
+                }
+            }
+            6 => {
+                let value = self.next_value();
+                span.push(value);
+                1
+            }
+            7 => {
+                if self.next_bit() == BaseElement::ONE {
+                    span.op(OpCode::Read);
+                    self.tape_a_reads += 1;
+                    1
+                } else {
+                    span.op(OpCode::Read2);
+                    self.tape_a_reads += 1;
+                    self.tape_b_reads += 1;
+                    2
+                }
+            }
+            8 => {
+                let value = self.next_bit();
+                span.push(value);
+                span.op(OpCode::Not);
+                1
+            }
+            9 => {
+                let (a, b) = (self.next_bit(), self.next_bit());
+                span.push(a);
+                span.push(b);
+                span.op([OpCode::And, OpCode::Or][self.below(2) as usize]);
+                1
+            }
+            10 => {
+                let value = self.next_value();
+                if value == BaseElement::ZERO {
+                    span.push(BaseElement::ONE);
+                } else {
+                    span.push(value);
+                }
+                span.op(OpCode::Inv);
+                1
+            }
+            11 => {
+                span.push(BaseElement::ONE);
+                span.op(OpCode::Assert);
+                0
+            }
+            12 => {
+                let condition = self.next_bit();
+                span.push(condition);
+                match self.below(4) {
+                    0 => {
+                        let (a, b) = (self.next_value(), self.next_value());
+                        span.push(b);
+                        span.push(a);
+                        span.op(OpCode::Choose);
+                        1
+                    }
+                    1 => {
+                        span.op(OpCode::Dup4);
+                        span.op(OpCode::Choose2);
+                        1
+                    }
+                    _ => {
+                        span.op(OpCode::Dup4);
+                        span.op(OpCode::CSwap2);
+                        3
+                    }
+                }
+            }
+            13 => {
+                let addr = BaseElement::from(self.below(MAX_RANDOM_ADDR));
+                if self.next_bit() == BaseElement::ONE {
+                    let value = self.next_value();
+                    span.push(value);
+                    span.push(addr);
+                    span.append_instructions("mem.store");
+                    0
+                } else {
+                    span.push(addr);
+                    span.append_instructions("mem.load");
+                    1
+                }
+            }
+            14 => {
+                // shift amounts and divisors are kept small and non-zero
+                let op = U32_OPS[self.below(U32_OPS.len() as u64) as usize];
+                let a = 1 + self.below(31);
+                let b = self.next_u64() as u32;
+                span.push(BaseElement::from(b));
+                span.push(BaseElement::from(a));
+                span.op_with_hint(OpCode::U32, OpHint::U32Op(op));
+                1
+            }
+            15 => {
+                span.pad_to(HASH_OP_ALIGNMENT, 0);
+                for _ in 0..NUM_HASH_ROUNDS {
+                    span.op(OpCode::RescR);
+                }
+                0
+            }
+            16 | 17 => {
+                // CMP operations are generated by compiling a comparison instruction
+                let num_bits = 4 + self.below(29);
+                let a = self.below(1 << num_bits);
+                let b = self.below(1 << num_bits);
+                let op = ["gt", "lt"][self.below(2) as usize];
+                span.append_instructions(&format!("push.{} push.{} {}.{}", a, b, op, num_bits));
+                1
+            }
+            _ => {
+                // BINACC operations are generated by compiling a range check instruction
+                let num_bits = 4 + self.below(29);
+                let a = self.below(1 << num_bits);
+                let op = ["rc", "isodd"][self.below(2) as usize];
+                span.append_instructions(&format!("push.{} {}.{}", a, op, num_bits));
+                1
+            }
+        };
+        self.stack_depth = (self.stack_depth as isize + delta) as usize;
+    }
+
+    // RANDOM VALUES
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the next value of a xorshift64* generator.
+    fn next_u64(&mut self) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns a random integer smaller than `bound`.
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// Returns a random binary value.
+    fn next_bit(&mut self) -> BaseElement {
+        BaseElement::from(self.below(2))
+    }
+
+    /// Returns a random field element; small values are returned more often than others.
+    fn next_value(&mut self) -> BaseElement {
+        match self.below(4) {
+            0 => BaseElement::ZERO,
+            1 => BaseElement::ONE,
+            2 => BaseElement::from(self.below(256)),
+            _ => {
+                let value = ((self.next_u64() as u128) << 64) | self.next_u64() as u128;
+                BaseElement::new(value % BaseElement::MODULUS)
+            }
+        }
+    }
+}
+
+// SPAN BUILDER
+// ================================================================================================
+
+/// Builds a Span block, padding it with NOOPs to satisfy alignment requirements of operations.
+struct SpanBuilder {
+    op_codes: Vec<OpCode>,
+    op_hints: BTreeMap<usize, OpHint>,
+}
+
+impl SpanBuilder {
+    fn new(prefix: &[OpCode]) -> Self {
+        SpanBuilder {
+            op_codes: prefix.to_vec(),
+            op_hints: BTreeMap::new(),
+        }
+    }
+
+    fn op(&mut self, op_code: OpCode) {
+        self.op_codes.push(op_code);
+    }
+
+    fn op_with_hint(&mut self, op_code: OpCode, hint: OpHint) {
+        self.op_hints.insert(self.op_codes.len(), hint);
+        self.op_codes.push(op_code);
+    }
+
+    fn push(&mut self, value: BaseElement) {
+        self.pad_to(PUSH_OP_ALIGNMENT, 0);
+        self.op_with_hint(OpCode::Push, OpHint::PushValue(value));
+    }
+
+    /// Appends NOOPs until the number of operations is equal to `offset` modulo `alignment`.
+    fn pad_to(&mut self, alignment: usize, offset: usize) {
+        while self.op_codes.len() % alignment != offset {
+            self.op_codes.push(OpCode::Noop);
+        }
+    }
+
+    /// Compiles the specified assembly instructions, and appends the resulting operations to
+    /// the span; the operations are aligned in the same way as in the compiled program.
+    fn append_instructions(&mut self, source: &str) {
+        let program = assembly::compile(&format!("begin {} end", source)).unwrap();
+        let span = match &program.root().body()[0] {
+            ProgramBlock::Span(span) => span,
+            _ => unreachable!("straight-line code must compile into a single Span block"),
+        };
+
+        // the first operation of the compiled program is BEGIN
+        self.pad_to(BASE_CYCLE_LENGTH, 1);
+        for i in 1..span.length() {
+            match span.get_op(i) {
+                (op_code, OpHint::None) => self.op(op_code),
+                (op_code, hint) => self.op_with_hint(op_code, hint),
+            }
+        }
+    }
+
+    fn build(mut self) -> ProgramBlock {
+        self.pad_to(BASE_CYCLE_LENGTH, BASE_CYCLE_LENGTH - 1);
+        ProgramBlock::Span(Span::new(self.op_codes, self.op_hints))
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn read_env(name: &str, default: u64) -> u64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
@@ -0,0 +1,740 @@
+use crate::{
+    assembly, check_constraints, BaseElement, ConstraintError, Deserializable,
+    DeserializationError, ExecutionProof, ExecutionProofError, ExecutionTrace, FieldElement,
+    FieldExtension, HashFunction, ProgramInputs, ProgramLibrary, ProofOptions, PublicInputs,
+    PublicInputsError, Serializable, SliceReader, StarkField, Storage, TraceMetadata, TraceState,
+    PROOF_FORMAT_VERSION,
+};
+use air::{ToElements, MAX_PUBLIC_INPUTS};
+use processor::{ExecutionErrorKind, FlowOps, Operation, UserOps as OpCode};
+
+mod fuzz;
+
+#[test]
+fn execute_span() {
+    let program = assembly::compile("begin add push.5 mul push.7 end").unwrap();
+    let inputs = ProgramInputs::from_public(&[1, 2]);
+
+    let trace = processor::execute(&program, &inputs).unwrap();
+    let trace_length = trace.length();
+    let trace_width = trace.width();
+
+    assert_eq!(64, trace_length);
+    assert_eq!(17, trace_width);
+    let state = get_trace_state(&trace, trace_length - 1);
+
+    assert_eq!(BaseElement::new(46), state.op_counter());
+    assert_eq!(program.hash().to_vec(), state.program_hash().to_bytes());
+    assert_eq!([1, 1, 1].to_elements(), state.cf_op_bits());
+    assert_eq!([1, 1, 1, 1, 1].to_elements(), state.ld_op_bits());
+    assert_eq!([1, 1].to_elements(), state.hd_op_bits());
+    assert_eq!([0].to_elements(), state.ctx_stack());
+    assert_eq!([7, 15, 0, 0, 0, 0, 0, 0].to_elements(), state.user_stack());
+}
+
+#[test]
+fn execute_block() {
+    let program = assembly::compile("begin add block push.5 mul push.7 end end").unwrap();
+    let inputs = ProgramInputs::from_public(&[1, 2]);
+
+    let trace = processor::execute(&program, &inputs).unwrap();
+    let trace_length = trace.length();
+    let trace_width = trace.width();
+
+    assert_eq!(64, trace_length);
+    assert_eq!(18, trace_width);
+    let state = get_trace_state(&trace, trace_length - 1);
+
+    assert_eq!(BaseElement::new(60), state.op_counter());
+    assert_eq!(program.hash().to_vec(), state.program_hash().to_bytes());
+    assert_eq!([1, 1, 1].to_elements(), state.cf_op_bits());
+    assert_eq!([1, 1, 1, 1, 1].to_elements(), state.ld_op_bits());
+    assert_eq!([1, 1].to_elements(), state.hd_op_bits());
+    assert_eq!([0].to_elements(), state.ctx_stack());
+    assert_eq!([0].to_elements(), state.loop_stack());
+    assert_eq!([7, 15, 0, 0, 0, 0, 0, 0].to_elements(), state.user_stack());
+}
+
+#[test]
+fn execute_if_else() {
+    let program =
+        assembly::compile("begin read if.true add push.3 else push.7 add push.8 end mul end")
+            .unwrap();
+
+    // execute true branch
+    let inputs = ProgramInputs::new(&[5, 3], &[1], &[]);
+    let trace = processor::execute(&program, &inputs).unwrap();
+    let trace_length = trace.length();
+    let trace_width = trace.width();
+
+    assert_eq!(128, trace_length);
+    assert_eq!(19, trace_width);
+    let state = get_trace_state(&trace, trace_length - 1);
+
+    assert_eq!(BaseElement::new(76), state.op_counter());
+    assert_eq!(program.hash().to_vec(), state.program_hash().to_bytes());
+    assert_eq!([1, 1, 1].to_elements(), state.cf_op_bits());
+    assert_eq!([1, 1, 1, 1, 1].to_elements(), state.ld_op_bits());
+    assert_eq!([1, 1].to_elements(), state.hd_op_bits());
+    assert_eq!([0].to_elements(), state.ctx_stack());
+    assert_eq!([0].to_elements(), state.loop_stack());
+    assert_eq!([24, 0, 0, 0, 0, 0, 0, 0].to_elements(), state.user_stack());
+
+    // execute false branch
+    let inputs = ProgramInputs::new(&[5, 3], &[0], &[]);
+    let trace = processor::execute(&program, &inputs).unwrap();
+    let trace_length = trace.length();
+    let trace_width = trace.width();
+
+    assert_eq!(128, trace_length);
+    assert_eq!(19, trace_width);
+    let state = get_trace_state(&trace, trace_length - 1);
+
+    assert_eq!(BaseElement::new(92), state.op_counter());
+    assert_eq!(program.hash().to_vec(), state.program_hash().to_bytes());
+    assert_eq!([1, 1, 1].to_elements(), state.cf_op_bits());
+    assert_eq!([1, 1, 1, 1, 1].to_elements(), state.ld_op_bits());
+    assert_eq!([1, 1].to_elements(), state.hd_op_bits());
+    assert_eq!([0].to_elements(), state.ctx_stack());
+    assert_eq!([0].to_elements(), state.loop_stack());
+    assert_eq!([96, 3, 0, 0, 0, 0, 0, 0].to_elements(), state.user_stack());
+}
+
+#[test]
+fn execute_loop() {
+    let program = assembly::compile("begin mul read while.true dup mul read end end").unwrap();
+
+    // don't enter the loop
+    let inputs = ProgramInputs::new(&[5, 3], &[0], &[]);
+    let trace = processor::execute(&program, &inputs).unwrap();
+
+    assert_eq!(64, trace.length());
+    assert_eq!(18, trace.width());
+    let state = get_trace_state(&trace, trace.length() - 1);
+
+    assert_eq!(BaseElement::new(60), state.op_counter());
+    assert_eq!(program.hash().to_vec(), state.program_hash().to_bytes());
+    assert_eq!([1, 1, 1].to_elements(), state.cf_op_bits());
+    assert_eq!([1, 1, 1, 1, 1].to_elements(), state.ld_op_bits());
+    assert_eq!([1, 1].to_elements(), state.hd_op_bits());
+    assert_eq!([0].to_elements(), state.ctx_stack());
+    assert_eq!([0].to_elements(), state.loop_stack());
+    assert_eq!([15, 0, 0, 0, 0, 0, 0, 0].to_elements(), state.user_stack());
+
+    // execute one iteration
+    let inputs = ProgramInputs::new(&[5, 3], &[1, 0], &[]);
+    let trace = processor::execute(&program, &inputs).unwrap();
+
+    assert_eq!(128, trace.length());
+    assert_eq!(20, trace.width());
+    let state = get_trace_state(&trace, trace.length() - 1);
+
+    assert_eq!(BaseElement::new(75), state.op_counter());
+    assert_eq!(program.hash().to_vec(), state.program_hash().to_bytes());
+    assert_eq!([1, 1, 1].to_elements(), state.cf_op_bits());
+    assert_eq!([1, 1, 1, 1, 1].to_elements(), state.ld_op_bits());
+    assert_eq!([1, 1].to_elements(), state.hd_op_bits());
+    assert_eq!([0].to_elements(), state.ctx_stack());
+    assert_eq!([0].to_elements(), state.loop_stack());
+    assert_eq!([225, 0, 0, 0, 0, 0, 0, 0].to_elements(), state.user_stack());
+
+    // execute five iteration
+    let inputs = ProgramInputs::new(&[5, 3], &[1, 1, 1, 1, 1, 0], &[]);
+    let trace = processor::execute(&program, &inputs).unwrap();
+
+    assert_eq!(256, trace.length());
+    assert_eq!(20, trace.width());
+    let state = get_trace_state(&trace, trace.length() - 1);
+
+    assert_eq!(BaseElement::new(135), state.op_counter());
+    assert_eq!(program.hash().to_vec(), state.program_hash().to_bytes());
+    assert_eq!([1, 1, 1].to_elements(), state.cf_op_bits());
+    assert_eq!([1, 1, 1, 1, 1].to_elements(), state.ld_op_bits());
+    assert_eq!([1, 1].to_elements(), state.hd_op_bits());
+    assert_eq!([0].to_elements(), state.ctx_stack());
+    assert_eq!([0].to_elements(), state.loop_stack());
+    assert_eq!(
+        [43143988327398919500410556793212890625, 0, 0, 0, 0, 0, 0, 0].to_elements(),
+        state.user_stack()
+    );
+}
+
+#[test]
+fn execute_memory() {
+    let program =
+        assembly::compile("begin push.3 mem.store push.3 mem.load push.4 mem.load end").unwrap();
+    let inputs = ProgramInputs::from_public(&[5]);
+
+    let trace = processor::execute(&program, &inputs).unwrap();
+    let trace_length = trace.length();
+
+    let meta = TraceMetadata::from_trace_info(&trace.get_info());
+    assert!(meta.has_memory);
+    assert!(!meta.has_storage);
+
+    // the first read returns the written value, and the second read returns zero; the root
+    // registers start with the root of empty memory and end up holding the root of the memory
+    // with the written value
+    let state = get_trace_state(&trace, 0);
+    assert_eq!(&Storage::new().root_node(), state.memory());
+    let state = get_trace_state(&trace, trace_length - 1);
+    assert_eq!([0, 5].to_elements(), &state.user_stack()[..2]);
+    let expected = Storage::from_values(&[(3, 5)]).root_node();
+    assert_eq!(&expected, state.memory());
+
+    // addresses which do not fit into 32 bits are rejected
+    let program = assembly::compile("begin push.4294967296 mem.load end").unwrap();
+    let err = processor::execute(&program, &inputs).err().unwrap();
+    assert_eq!(
+        &ExecutionErrorKind::InvalidMemoryAddress(BaseElement::new(4294967296)),
+        err.kind()
+    );
+
+    // programs without memory operations do not have a memory trace
+    let program = assembly::compile("begin push.3 add end").unwrap();
+    let trace = processor::execute(&program, &inputs).unwrap();
+    let meta = TraceMetadata::from_trace_info(&trace.get_info());
+    assert!(!meta.has_memory);
+}
+
+#[test]
+fn execute_u32() {
+    let program = assembly::compile(
+        "begin push.4294967295 push.2 u32add push.10 u32mul push.3 u32div push.1 u32shl end",
+    )
+    .unwrap();
+
+    let trace = processor::execute(&program, &ProgramInputs::none()).unwrap();
+    let trace_length = trace.length();
+
+    let meta = TraceMetadata::from_trace_info(&trace.get_info());
+    assert!(meta.has_u32_table);
+    assert!(!meta.has_memory);
+    assert_eq!(2, meta.stack_depth);
+
+    let state = get_trace_state(&trace, trace_length - 1);
+    assert!(!state.u32_table().is_empty());
+    assert_eq!([6, 0, 0, 0, 0, 0, 0, 0].to_elements(), state.user_stack());
+
+    // values which do not fit into 32 bits are rejected
+    let program = assembly::compile("begin push.4294967296 push.1 u32add end").unwrap();
+    let err = processor::execute(&program, &ProgramInputs::none())
+        .err()
+        .unwrap();
+    assert_eq!(
+        &ExecutionErrorKind::NotU32Value(BaseElement::new(4294967296)),
+        err.kind()
+    );
+    assert_eq!(Operation::User(OpCode::U32), err.operation());
+
+    // a program which includes the u32 table and memory registers into the trace next to a
+    // full stack fails with an error instead of producing a trace which is too wide to prove
+    let mut source = String::from("begin");
+    for i in 1..=30 {
+        source.push_str(&format!(" push.{}", i));
+    }
+    source.push_str(" push.7 push.9 u32add push.5 mem.store end");
+    let program = assembly::compile(&source).unwrap();
+    let err = processor::execute(&program, &ProgramInputs::none())
+        .err()
+        .unwrap();
+    assert_eq!(&ExecutionErrorKind::StackOverflow, err.kind());
+}
+
+#[test]
+fn execute_storage() {
+    let source = "begin push.3 storage.read push.9 push.3 storage.write push.3 storage.read end";
+    let program = assembly::compile(source).unwrap();
+    let storage = Storage::from_values(&[(3, 7)]);
+    let inputs = ProgramInputs::none().with_storage(storage.clone());
+
+    let trace = processor::execute(&program, &inputs).unwrap();
+    let trace_length = trace.length();
+
+    let meta = TraceMetadata::from_trace_info(&trace.get_info());
+    assert!(meta.has_storage);
+
+    // the second read returns the written value; the root registers end up holding the root of
+    // the updated storage
+    let state = get_trace_state(&trace, trace_length - 1);
+    assert_eq!([9, 7].to_elements(), &state.user_stack()[..2]);
+    let expected = Storage::from_values(&[(3, 9)]).root_node();
+    assert_eq!(&expected, state.storage());
+
+    // keys which do not fit into 32 bits are rejected
+    let program = assembly::compile("begin push.4294967296 storage.read end").unwrap();
+    let err = processor::execute(&program, &inputs).err().unwrap();
+    assert_eq!(
+        &ExecutionErrorKind::InvalidStorageKey(BaseElement::new(4294967296)),
+        err.kind()
+    );
+}
+
+#[test]
+fn execute_deep_stack() {
+    // push 32 values onto the stack and then add them all together
+    let mut source = String::from("begin");
+    for i in 1..=32 {
+        source.push_str(&format!(" push.{}", i));
+    }
+    source.push_str(&" add".repeat(31));
+    source.push_str(" end");
+    let program = assembly::compile(&source).unwrap();
+
+    let trace = processor::execute(&program, &ProgramInputs::none()).unwrap();
+    let trace_length = trace.length();
+
+    let meta = TraceMetadata::from_trace_info(&trace.get_info());
+    assert!(!meta.has_memory);
+    assert_eq!(32, meta.stack_depth);
+
+    let state = get_trace_state(&trace, trace_length - 1);
+    assert_eq!(528, state.user_stack()[0].as_int());
+    assert!(state.user_stack()[1..]
+        .iter()
+        .all(|&v| v == BaseElement::ZERO));
+
+    // the stack cannot grow deeper than 32 items
+    let program = assembly::compile(&source.replace(" add", " push.33 add")).unwrap();
+    let err = processor::execute(&program, &ProgramInputs::none())
+        .err()
+        .unwrap();
+    assert_eq!(&ExecutionErrorKind::StackOverflow, err.kind());
+    assert_eq!(Operation::User(OpCode::Push), err.operation());
+}
+
+#[test]
+fn execute_call() {
+    let mut library = ProgramLibrary::new();
+    let callee = assembly::compile_block("block push.2 mul end").unwrap();
+    let callee_hash = library.add(callee);
+
+    let source = format!("begin push.3 call.{} push.1 add end", callee_hash.as_int());
+    let program = assembly::compile(&source).unwrap();
+    let inputs = ProgramInputs::none();
+
+    let trace = processor::execute_with_library(&program, &inputs, &library).unwrap();
+    let trace_length = trace.length();
+
+    // the hash computed by the VM is the same as the hash of the program with the callee inlined
+    let inlined = assembly::compile("begin push.3 block push.2 mul end push.1 add end").unwrap();
+    assert_eq!(inlined.hash(), program.hash());
+
+    let state = get_trace_state(&trace, trace_length - 1);
+    assert_eq!(program.hash().to_vec(), state.program_hash().to_bytes());
+    assert_eq!([0].to_elements(), state.ctx_stack());
+    assert_eq!([7, 0, 0, 0, 0, 0, 0, 0].to_elements(), state.user_stack());
+
+    // calling a block which is not in the library fails
+    let err = processor::execute(&program, &inputs).err().unwrap();
+    assert_eq!(
+        &ExecutionErrorKind::UndefinedCallee(callee_hash),
+        err.kind()
+    );
+    assert_eq!(Operation::Flow(FlowOps::Begin), err.operation());
+}
+
+#[test]
+fn execute_masm_program() {
+    let program = crate::masm!("begin push.3 push.5 add push.7 mul end");
+    let expected = assembly::compile("begin push.3 push.5 add push.7 mul end").unwrap();
+    assert_eq!(expected.hash(), program.hash());
+
+    let options = ProofOptions::new(
+        32,
+        8,
+        0,
+        HashFunction::Blake3_256,
+        FieldExtension::None,
+        8,
+        256,
+    );
+    let (outputs, _, proof) =
+        crate::execute(&program, &ProgramInputs::none(), 1, &options).unwrap();
+    assert_eq!(vec![56], outputs);
+    assert!(crate::verify(*program.hash(), &[], &outputs, proof).is_ok());
+}
+
+#[test]
+fn execute_within_cycle_estimates() {
+    let programs = [
+        // straight-line code
+        ("begin push.3 push.5 add end", ProgramInputs::none()),
+        // conditional branches of different lengths
+        (
+            "begin read if.true push.3 push.4 add else push.3 end end",
+            ProgramInputs::new(&[], &[1], &[]),
+        ),
+        (
+            "begin read if.true push.3 push.4 add else push.3 end end",
+            ProgramInputs::new(&[], &[0], &[]),
+        ),
+        // a loop which is executed 3 times
+        (
+            "begin push.1 push.1 while.true push.2 mul read end end",
+            ProgramInputs::new(&[], &[1, 1, 0], &[]),
+        ),
+        // a loop which is never entered
+        (
+            "begin push.1 push.0 while.true push.2 mul read end end",
+            ProgramInputs::none(),
+        ),
+        // a repeat block
+        (
+            "begin push.1 repeat.3 push.2 mul end end",
+            ProgramInputs::none(),
+        ),
+        // u32 operations
+        (
+            "begin push.5 push.3 u32add push.2 u32mul end",
+            ProgramInputs::none(),
+        ),
+        // memory accesses
+        (
+            "begin push.7 push.3 mem.store push.3 mem.load end",
+            ProgramInputs::none(),
+        ),
+    ];
+
+    for (source, inputs) in programs.iter() {
+        let program = assembly::compile(source).unwrap();
+        let estimate = program.estimate_cycles();
+
+        let (trace, profile) =
+            processor::execute_with_profile(&program, inputs, &ProgramLibrary::new()).unwrap();
+        let cycles = profile.num_cycles();
+
+        assert!(
+            estimate.min_cycles() <= cycles,
+            "'{}' was executed in {} cycles, but at least {} were estimated",
+            source,
+            cycles,
+            estimate.min_cycles()
+        );
+        if let Some(max_cycles) = estimate.max_cycles() {
+            assert!(
+                cycles <= max_cycles,
+                "'{}' was executed in {} cycles, but at most {} were estimated",
+                source,
+                cycles,
+                max_cycles
+            );
+        }
+        assert!(estimate.min_trace_length() <= trace.length());
+        assert!(trace.width() <= estimate.max_trace_width().unwrap());
+    }
+}
+
+#[test]
+fn execute_fail() {
+    // read from an empty tape
+    let program = assembly::compile("begin add read end").unwrap();
+    let inputs = ProgramInputs::from_public(&[1, 2]);
+    let err = processor::execute(&program, &inputs).err().unwrap();
+
+    assert_eq!(&ExecutionErrorKind::EmptyTapeA, err.kind());
+    assert_eq!(Operation::User(OpCode::Read), err.operation());
+    assert_eq!(3, err.step());
+    assert_eq!(&[BaseElement::new(3)], err.stack());
+
+    // enter a loop on a non-binary condition
+    let program = assembly::compile("begin read while.true dup mul read end end").unwrap();
+    let inputs = ProgramInputs::new(&[5], &[2], &[]);
+    let err = processor::execute(&program, &inputs).err().unwrap();
+
+    assert_eq!(
+        &ExecutionErrorKind::NonBinaryCondition(BaseElement::new(2)),
+        err.kind()
+    );
+    assert_eq!(Operation::Flow(FlowOps::Loop), err.operation());
+    assert_eq!(&[BaseElement::new(2), BaseElement::new(5)], err.stack());
+
+    // nest conditional blocks 15 levels deep, and then one level deeper
+    let nest_blocks = |depth: usize| {
+        let source = format!(
+            "begin{} push.2{} end",
+            " push.1 if.true".repeat(depth),
+            " end".repeat(depth)
+        );
+        assembly::compile(&source).unwrap()
+    };
+    let program = nest_blocks(15);
+    assert!(processor::execute(&program, &ProgramInputs::none()).is_ok());
+
+    let program = nest_blocks(16);
+    let err = processor::execute(&program, &ProgramInputs::none())
+        .err()
+        .unwrap();
+    assert_eq!(&ExecutionErrorKind::ContextStackOverflow, err.kind());
+    assert_eq!(Operation::Flow(FlowOps::Begin), err.operation());
+
+    // nest loops 8 levels deep, and then one level deeper
+    let nest_loops = |depth: usize| {
+        let source = format!(
+            "begin{}{} end",
+            " push.1 while.true".repeat(depth),
+            " push.0 end".repeat(depth)
+        );
+        assembly::compile(&source).unwrap()
+    };
+    let program = nest_loops(8);
+    assert!(processor::execute(&program, &ProgramInputs::none()).is_ok());
+
+    let program = nest_loops(9);
+    let err = processor::execute(&program, &ProgramInputs::none())
+        .err()
+        .unwrap();
+    assert_eq!(&ExecutionErrorKind::LoopStackOverflow, err.kind());
+    assert_eq!(Operation::Flow(FlowOps::Loop), err.operation());
+
+    // request more outputs than a program can produce
+    let program = assembly::compile("begin push.3 push.5 add end").unwrap();
+    let options = ProofOptions::default();
+    let result = crate::execute(&program, &ProgramInputs::none(), 9, &options);
+    assert!(matches!(
+        result,
+        Err(crate::ExecutionError::TooManyOutputs(9))
+    ));
+}
+
+#[test]
+fn execution_proof_bundle() {
+    let program = assembly::compile("begin push.3 push.5 add end").unwrap();
+    let inputs = ProgramInputs::from_public(&[1]);
+    let options = ProofOptions::new(
+        32,
+        8,
+        0,
+        HashFunction::Blake3_256,
+        FieldExtension::None,
+        8,
+        256,
+    );
+    let (outputs, storage_root, proof) = crate::execute(&program, &inputs, 2, &options).unwrap();
+    let bundle = ExecutionProof::new(*program.hash(), &[1], &outputs, proof.clone())
+        .unwrap()
+        .with_storage_roots(storage_root, storage_root);
+    assert_eq!(&options, bundle.options());
+
+    // the bundle can be verified after a serialization round trip
+    let bytes = bundle.to_bytes();
+    assert_eq!(PROOF_FORMAT_VERSION, bytes[0]);
+    let bundle = ExecutionProof::read_from(&mut SliceReader::new(&bytes)).unwrap();
+    assert_eq!(program.hash(), bundle.program_hash());
+    assert_eq!(&[1], bundle.public_inputs());
+    assert_eq!(&[8, 1], bundle.outputs());
+    assert!(crate::verify_bundle(bundle).is_ok());
+
+    // the first output is serialized right after the version, the program hash, and the inputs
+    let mut tampered = bytes.clone();
+    tampered[51] += 1;
+    let bundle = ExecutionProof::read_from(&mut SliceReader::new(&tampered)).unwrap();
+    assert_eq!(&[9, 1], bundle.outputs());
+    assert!(crate::verify_bundle(bundle).is_err());
+
+    // digests must consist of valid field elements
+    let mut non_canonical = bytes.clone();
+    non_canonical[1..17].copy_from_slice(&[255; 16]);
+    assert!(matches!(
+        ExecutionProof::read_from(&mut SliceReader::new(&non_canonical)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    let mut unsupported = bytes;
+    unsupported[0] = PROOF_FORMAT_VERSION + 1;
+    assert!(matches!(
+        ExecutionProof::read_from(&mut SliceReader::new(&unsupported)),
+        Err(DeserializationError::InvalidValue(_))
+    ));
+
+    // the number of inputs and outputs is checked when the bundle is created
+    let too_many = [0; MAX_PUBLIC_INPUTS + 1];
+    assert_eq!(
+        Err(ExecutionProofError::TooManyPublicInputs(too_many.len())),
+        ExecutionProof::new(*program.hash(), &too_many, &outputs, proof.clone()).map(|_| ())
+    );
+    assert_eq!(
+        Err(ExecutionProofError::TooManyOutputs(too_many.len())),
+        ExecutionProof::new(*program.hash(), &[1], &too_many, proof).map(|_| ())
+    );
+}
+
+#[test]
+fn execution_proof_memory() {
+    // double the value kept in memory at address 3 once per loop iteration
+    let source = "begin push.3 mem.store read while.true \
+        push.3 mem.load dup add push.3 mem.store read end push.3 mem.load end";
+    let program = assembly::compile(source).unwrap();
+    let inputs = ProgramInputs::new(&[7], &[1, 1, 0], &[]);
+    let options = ProofOptions::new(
+        32,
+        8,
+        0,
+        HashFunction::Blake3_256,
+        FieldExtension::None,
+        8,
+        256,
+    );
+    let (outputs, _, proof) = crate::execute(&program, &inputs, 1, &options).unwrap();
+    assert_eq!(vec![28], outputs);
+    assert!(crate::verify(*program.hash(), &[7], &outputs, proof).is_ok());
+}
+
+#[test]
+fn check_trace_constraints() {
+    let program = assembly::compile("begin push.3 push.5 add end").unwrap();
+    let inputs = ProgramInputs::from_public(&[1]);
+    let trace = processor::execute(&program, &inputs).unwrap();
+    let pub_inputs = PublicInputs::new(*program.hash(), &[1], &[8, 1]).unwrap();
+    assert_eq!(Ok(()), check_constraints(&trace, pub_inputs));
+
+    // claimed outputs are checked against the last step of the trace
+    let meta = TraceMetadata::from_trace_info(&trace.get_info());
+    let user_stack_start = trace.width() - meta.stack_depth;
+    let pub_inputs = PublicInputs::new(*program.hash(), &[1], &[9, 1]).unwrap();
+    assert_eq!(
+        Err(ConstraintError::Assertion {
+            register: user_stack_start,
+            step: trace.length() - 1,
+            expected: 9,
+            actual: 8,
+        }),
+        check_constraints(&trace, pub_inputs)
+    );
+
+    // the result of ADD is written into the top of the stack at step 18
+    let mut registers: Vec<Vec<BaseElement>> = (0..trace.width())
+        .map(|i| (0..trace.length()).map(|step| trace.get(i, step)).collect())
+        .collect();
+    registers[user_stack_start][18] += BaseElement::ONE;
+    let mut tampered = ExecutionTrace::init(registers);
+    tampered.set_meta(trace.get_info().meta().to_vec());
+
+    let pub_inputs = PublicInputs::new(*program.hash(), &[1], &[8, 1]).unwrap();
+    match check_constraints(&tampered, pub_inputs) {
+        Err(ConstraintError::TransitionConstraint {
+            constraint,
+            step,
+            operation,
+            ..
+        }) => {
+            assert_eq!("stack[0] is updated by the executed operation", constraint);
+            assert_eq!(17, step);
+            assert_eq!("add", operation);
+        }
+        result => panic!(
+            "expected a transition constraint error, but was {:?}",
+            result
+        ),
+    }
+}
+
+#[test]
+fn check_memory_constraints() {
+    let program = assembly::compile("begin push.7 push.3 mem.store push.3 mem.load end").unwrap();
+    let inputs = ProgramInputs::none();
+    let trace = processor::execute(&program, &inputs).unwrap();
+    let last_state = get_trace_state(&trace, trace.length() - 1);
+    let outputs: Vec<u128> = last_state.user_stack()[..crate::MAX_OUTPUTS]
+        .iter()
+        .map(|v| v.as_int())
+        .collect();
+    assert_eq!(7, outputs[0]);
+    let pub_inputs = PublicInputs::new(*program.hash(), &[], &outputs).unwrap();
+    assert_eq!(Ok(()), check_constraints(&trace, pub_inputs));
+
+    // the memory root registers follow the user stack, and can change only on MROOT
+    let meta = TraceMetadata::from_trace_info(&trace.get_info());
+    let memory_start =
+        TraceState::<BaseElement>::compute_decoder_width(meta.ctx_depth, meta.loop_depth)
+            + meta.stack_depth;
+    let mut registers: Vec<Vec<BaseElement>> = (0..trace.width())
+        .map(|i| (0..trace.length()).map(|step| trace.get(i, step)).collect())
+        .collect();
+    registers[memory_start][2] += BaseElement::ONE;
+    let mut tampered = ExecutionTrace::init(registers);
+    tampered.set_meta(trace.get_info().meta().to_vec());
+
+    let pub_inputs = PublicInputs::new(*program.hash(), &[], &outputs).unwrap();
+    match check_constraints(&tampered, pub_inputs) {
+        Err(ConstraintError::TransitionConstraint {
+            constraint, step, ..
+        }) => {
+            assert_eq!("memory root[0] is replaced only by MROOT", constraint);
+            assert_eq!(1, step);
+        }
+        result => panic!(
+            "expected a transition constraint error, but was {:?}",
+            result
+        ),
+    }
+}
+
+#[test]
+fn public_inputs_must_be_field_elements() {
+    let program = assembly::compile("begin push.3 push.5 add end").unwrap();
+    let invalid = BaseElement::MODULUS;
+    assert!(PublicInputs::new(*program.hash(), &[1], &[8, 1]).is_ok());
+    assert_eq!(
+        Err(PublicInputsError::InvalidInput(invalid)),
+        PublicInputs::new(*program.hash(), &[invalid], &[8, 1]).map(|_| ())
+    );
+    assert_eq!(
+        Err(PublicInputsError::InvalidOutput(invalid)),
+        PublicInputs::new(*program.hash(), &[1], &[invalid]).map(|_| ())
+    );
+    assert_eq!(
+        Err(PublicInputsError::InvalidProgramHash),
+        PublicInputs::new([255; 32], &[1], &[8, 1]).map(|_| ())
+    );
+
+    let empty_root = Storage::new().root();
+    assert_eq!(
+        Err(PublicInputsError::InvalidStorageRoot),
+        PublicInputs::new(*program.hash(), &[1], &[8, 1])
+            .and_then(|inputs| inputs.with_storage_roots(empty_root, [255; 32]))
+            .map(|_| ())
+    );
+
+    // the verifier rejects such values instead of panicking
+    let options = ProofOptions::new(
+        32,
+        8,
+        0,
+        HashFunction::Blake3_256,
+        FieldExtension::None,
+        8,
+        256,
+    );
+    let inputs = ProgramInputs::from_public(&[1]);
+    let (outputs, _, proof) = crate::execute(&program, &inputs, 2, &options).unwrap();
+    assert!(crate::verify(*program.hash(), &[invalid], &outputs, proof.clone()).is_err());
+    assert!(crate::verify_with_storage(
+        *program.hash(),
+        &[1],
+        &outputs,
+        [255; 32],
+        empty_root,
+        proof
+    )
+    .is_err());
+}
+
+fn get_trace_state(trace: &ExecutionTrace<BaseElement>, step: usize) -> TraceState<BaseElement> {
+    let meta = TraceMetadata::from_trace_info(&trace.get_info());
+    let mut state = TraceState::new(meta.ctx_depth, meta.loop_depth, meta.stack_depth);
+    if meta.has_memory {
+        state = state.with_memory();
+    }
+    if meta.has_storage {
+        state = state.with_storage();
+    }
+    if meta.has_u32_table {
+        state = state.with_u32_table();
+    }
+
+    let mut row = vec![BaseElement::ZERO; trace.width()];
+    trace.read_row_into(step, &mut row);
+    state.update(&row);
+    state
+}
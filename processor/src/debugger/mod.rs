@@ -0,0 +1,179 @@
+use crate::{
+    errors::{ExecutionError, Operation},
+    FlowOps, Process, Program, ProgramInputs, ProgramLibrary,
+};
+
+#[cfg(test)]
+mod tests;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A condition which stops execution of a program run by a [Debugger]; the condition is checked
+/// right before every cycle.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Breakpoint {
+    /// Stops before the specified operation is executed.
+    Operation(Operation),
+    /// Stops before an instruction located on the specified line of the source code is
+    /// executed; lines are 1-based.
+    Line(usize),
+    /// Stops after the specified number of cycles has been executed.
+    Step(usize),
+}
+
+/// Executes a program step by step, and stops execution at breakpoints.
+///
+/// The state of the VM can be inspected via the underlying [Process] whenever execution is
+/// stopped, including the cases when execution failed.
+pub struct Debugger<'a> {
+    process: Process<'a>,
+    breakpoints: Vec<Breakpoint>,
+}
+
+// DEBUGGER IMPLEMENTATION
+// ================================================================================================
+impl<'a> Debugger<'a> {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a new debugger for executing the `program` against the specified inputs.
+    pub fn new(program: &'a Program, inputs: &'a ProgramInputs) -> Debugger<'a> {
+        Debugger {
+            process: Process::new(program, inputs),
+            breakpoints: Vec::new(),
+        }
+    }
+
+    /// Returns a new debugger for executing the `program` against the specified inputs; blocks
+    /// called by the program are looked up in the specified `library`.
+    pub fn with_library(
+        program: &'a Program,
+        inputs: &'a ProgramInputs,
+        library: &'a ProgramLibrary,
+    ) -> Debugger<'a> {
+        Debugger {
+            process: Process::with_library(program, inputs, library),
+            breakpoints: Vec::new(),
+        }
+    }
+
+    // BREAKPOINTS
+    // --------------------------------------------------------------------------------------------
+
+    /// Adds the specified breakpoint to this debugger.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    /// Removes the specified breakpoint from this debugger; returns false if the debugger did
+    /// not have such a breakpoint.
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) -> bool {
+        let num_breakpoints = self.breakpoints.len();
+        self.breakpoints.retain(|&b| b != breakpoint);
+        self.breakpoints.len() != num_breakpoints
+    }
+
+    /// Returns breakpoints currently set in this debugger.
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    // EXECUTION
+    // --------------------------------------------------------------------------------------------
+
+    /// Executes a single VM cycle.
+    ///
+    /// # Errors
+    /// Returns an error if the cycle could not be executed.
+    pub fn step(&mut self) -> Result<(), ExecutionError> {
+        self.process.step()
+    }
+
+    /// Executes the next operation; if the operation starts a block (or the next iteration of a
+    /// loop), the entire block (or iteration) is executed unless a breakpoint is reached first.
+    ///
+    /// Returns the breakpoint at which execution stopped, or None if the operation was executed
+    /// in full.
+    ///
+    /// # Errors
+    /// Returns an error if any of the executed cycles failed.
+    pub fn step_over(&mut self) -> Result<Option<Breakpoint>, ExecutionError> {
+        let starts_block = matches!(
+            self.process.next_operation(),
+            Some(Operation::Flow(FlowOps::Begin))
+                | Some(Operation::Flow(FlowOps::Loop))
+                | Some(Operation::Flow(FlowOps::Wrap))
+        );
+
+        // all tasks added by the block are completed by the time the block is closed
+        let num_tasks = self.process.num_tasks();
+        self.process.step()?;
+        if !starts_block {
+            return Ok(None);
+        }
+        self.run_while(|process| process.num_tasks() > num_tasks)
+    }
+
+    /// Resumes execution of the program until a breakpoint is reached or the program is
+    /// executed to completion.
+    ///
+    /// Returns the breakpoint at which execution stopped, or None if the program was executed
+    /// to completion.
+    ///
+    /// # Errors
+    /// Returns an error if the program could not be executed.
+    pub fn resume(&mut self) -> Result<Option<Breakpoint>, ExecutionError> {
+        // always execute at least one cycle so that execution can be resumed from a breakpoint
+        self.process.step()?;
+        self.run_while(|_| true)
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the process executing the program.
+    pub fn process(&self) -> &Process<'a> {
+        &self.process
+    }
+
+    /// Converts this debugger into the process executing the program.
+    pub fn into_process(self) -> Process<'a> {
+        self.process
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Executes cycles while the `condition` holds for the process, and the program has not been
+    /// executed to completion; stops at the first reached breakpoint.
+    fn run_while<F>(&mut self, condition: F) -> Result<Option<Breakpoint>, ExecutionError>
+    where
+        F: Fn(&Process) -> bool,
+    {
+        while !self.process.is_finished() && condition(&self.process) {
+            if let Some(breakpoint) = self.reached_breakpoint() {
+                return Ok(Some(breakpoint));
+            }
+            self.process.step()?;
+        }
+        Ok(None)
+    }
+
+    /// Returns the first breakpoint which stops execution before the next cycle, if any.
+    fn reached_breakpoint(&self) -> Option<Breakpoint> {
+        self.breakpoints
+            .iter()
+            .copied()
+            .find(|breakpoint| match breakpoint {
+                Breakpoint::Operation(op) => self.process.next_operation() == Some(*op),
+                Breakpoint::Line(line) => match self.process.next_location() {
+                    Some(location) => location.line() == *line,
+                    None => false,
+                },
+                Breakpoint::Step(step) => self.process.current_step() == *step,
+            })
+    }
+}
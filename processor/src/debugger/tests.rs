@@ -0,0 +1,131 @@
+use super::{Breakpoint, Debugger, FlowOps, Operation, Program, ProgramInputs};
+use crate::{BaseElement, ExecutionErrorKind, FieldElement, OpCode, SourceLocation};
+use std::collections::BTreeMap;
+use vm_core::program::blocks::{Group, Loop, ProgramBlock, Span};
+
+#[test]
+fn breakpoints() {
+    let program = build_program();
+    let inputs = ProgramInputs::new(&[1], &[1, 0], &[]);
+    let mut debugger = Debugger::new(&program, &inputs);
+
+    // execution stops before READ operation in every iteration of the loop
+    let read = Breakpoint::Operation(Operation::User(OpCode::Read));
+    debugger.add_breakpoint(read);
+    assert_eq!(Some(read), debugger.resume().unwrap());
+    assert_eq!(
+        vec![BaseElement::ONE, BaseElement::ZERO],
+        debugger.process().tape_a()
+    );
+    assert_eq!(Some(read), debugger.resume().unwrap());
+    assert_eq!(vec![BaseElement::ZERO], debugger.process().tape_a());
+
+    // execution stops at the specified step and line
+    assert!(debugger.remove_breakpoint(read));
+    assert!(!debugger.remove_breakpoint(read));
+    debugger.add_breakpoint(Breakpoint::Step(40));
+    debugger.add_breakpoint(Breakpoint::Line(4));
+    assert_eq!(Some(Breakpoint::Step(40)), debugger.resume().unwrap());
+    assert_eq!(40, debugger.process().current_step());
+    assert_eq!(Some(Breakpoint::Line(4)), debugger.resume().unwrap());
+    assert_eq!(
+        Some(SourceLocation::new(4, 5)),
+        debugger.process().next_location()
+    );
+
+    // the program is executed to completion if no more breakpoints are reached
+    assert_eq!(None, debugger.resume().unwrap());
+    assert!(debugger.process().is_finished());
+    assert_eq!(None, debugger.resume().unwrap());
+}
+
+#[test]
+fn step_over() {
+    let program = build_program();
+    let inputs = ProgramInputs::new(&[1], &[1, 1, 0], &[]);
+    let mut debugger = Debugger::new(&program, &inputs);
+
+    // operations which do not start blocks are executed one at a time
+    assert_eq!(None, debugger.step_over().unwrap());
+    assert_eq!(1, debugger.process().current_step());
+
+    // entering the loop executes the first iteration; the loop is exited only after all of its
+    // iterations have been executed
+    debugger.add_breakpoint(Breakpoint::Operation(Operation::Flow(FlowOps::Loop)));
+    debugger.resume().unwrap();
+    debugger.add_breakpoint(Breakpoint::Operation(Operation::Flow(FlowOps::Wrap)));
+    assert_eq!(
+        Some(Breakpoint::Operation(Operation::Flow(FlowOps::Wrap))),
+        debugger.step_over().unwrap()
+    );
+    assert_eq!(
+        vec![BaseElement::new(1), BaseElement::ZERO],
+        debugger.process().tape_a()
+    );
+
+    // stepping over the next iteration stops right before the loop is either wrapped or broken
+    debugger.remove_breakpoint(Breakpoint::Operation(Operation::Flow(FlowOps::Wrap)));
+    assert_eq!(None, debugger.step_over().unwrap());
+    assert_eq!(
+        Some(Operation::Flow(FlowOps::Wrap)),
+        debugger.process().next_operation()
+    );
+    assert_eq!(1, debugger.process().loop_stack().len());
+    assert_eq!(None, debugger.step_over().unwrap());
+    assert_eq!(
+        Some(Operation::Flow(FlowOps::Break)),
+        debugger.process().next_operation()
+    );
+}
+
+#[test]
+fn failed_execution() {
+    let program = build_program();
+    let inputs = ProgramInputs::new(&[1], &[1], &[]);
+    let mut debugger = Debugger::new(&program, &inputs);
+
+    // tape A is exhausted in the second iteration of the loop
+    let err = debugger.resume().unwrap_err();
+    assert_eq!(&ExecutionErrorKind::EmptyTapeA, err.kind());
+    assert_eq!(
+        Some(Operation::User(OpCode::Read)),
+        debugger.process().next_operation()
+    );
+    assert_eq!(
+        Some(SourceLocation::new(3, 5)),
+        debugger.process().next_location()
+    );
+    assert_eq!(Err(err), debugger.step());
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Builds a program which reads values from tape A in a loop until a 0 is read, and then pads
+/// the stack with two zeros.
+fn build_program() -> Program {
+    let mut instructions = vec![OpCode::Noop; 15];
+    instructions[0] = OpCode::Begin;
+    let first = Span::new(instructions, BTreeMap::new());
+
+    let mut instructions = vec![OpCode::Noop; 15];
+    instructions[0] = OpCode::Assert;
+    instructions[1] = OpCode::Read;
+    let mut locations = BTreeMap::new();
+    locations.insert(0, SourceLocation::new(2, 5));
+    locations.insert(1, SourceLocation::new(3, 5));
+    let body = Span::new(instructions, BTreeMap::new()).with_locations(locations);
+
+    let mut instructions = vec![OpCode::Noop; 15];
+    instructions[0] = OpCode::Pad2;
+    let mut locations = BTreeMap::new();
+    locations.insert(0, SourceLocation::new(4, 5));
+    let last = Span::new(instructions, BTreeMap::new()).with_locations(locations);
+
+    let root = Group::new(vec![
+        ProgramBlock::Span(first),
+        Loop::new_block(vec![ProgramBlock::Span(body)]),
+        ProgramBlock::Span(last),
+    ]);
+    Program::new(root)
+}
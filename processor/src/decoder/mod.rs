@@ -1,9 +1,11 @@
 use crate::{
+    errors::ExecutionErrorKind,
     op_sponge,
     opcodes::{FlowOps, UserOps},
-    BaseElement, FieldElement, StarkField, BASE_CYCLE_LENGTH, MAX_CONTEXT_DEPTH, MAX_LOOP_DEPTH,
-    NUM_CF_OP_BITS, NUM_HD_OP_BITS, NUM_LD_OP_BITS, PUSH_OP_ALIGNMENT,
+    BaseElement, FieldElement, StarkField, BASE_CYCLE_LENGTH, LOOP_COUNTER_BASE, MAX_CONTEXT_DEPTH,
+    MAX_LOOP_DEPTH, NUM_CF_OP_BITS, NUM_HD_OP_BITS, NUM_LD_OP_BITS, PUSH_OP_ALIGNMENT,
 };
+use core::cmp;
 
 // TYPES AND INTERFACES
 // ================================================================================================
@@ -22,6 +24,7 @@ pub struct Decoder {
     ctx_depth: usize,
 
     loop_stack: Vec<Vec<BaseElement>>,
+    loop_counters: Vec<Vec<BaseElement>>,
     loop_depth: usize,
 }
 
@@ -65,6 +68,7 @@ impl Decoder {
         let ctx_depth = ctx_stack.len();
 
         let loop_stack = Vec::new();
+        let loop_counters = Vec::new();
         let loop_depth = loop_stack.len();
 
         // create and return decoder
@@ -79,6 +83,7 @@ impl Decoder {
             ctx_stack,
             ctx_depth,
             loop_stack,
+            loop_counters,
             loop_depth,
         }
     }
@@ -110,6 +115,24 @@ impl Decoder {
         self.loop_stack.len()
     }
 
+    /// Returns the items currently on the context stack; the top of the stack is the first item,
+    /// and the context of the program block (which is always 0) is the last item.
+    pub fn ctx_stack_state(&self) -> Vec<BaseElement> {
+        self.ctx_stack[..self.ctx_depth]
+            .iter()
+            .map(|register| register[self.step])
+            .collect()
+    }
+
+    /// Returns the images of the loops currently being executed; the image of the innermost
+    /// loop is the first item.
+    pub fn loop_stack_state(&self) -> Vec<BaseElement> {
+        self.loop_stack[..self.loop_depth]
+            .iter()
+            .map(|register| register[self.step])
+            .collect()
+    }
+
     /// Returns the state of the stack at the specified `step`.
     #[allow(unused, clippy::vec_init_then_push)]
     pub fn get_state(&self, step: usize) -> Vec<BaseElement> {
@@ -134,6 +157,9 @@ impl Decoder {
         for register in self.loop_stack.iter() {
             state.push(register[step]);
         }
+        for register in self.loop_counters.iter() {
+            state.push(register[step]);
+        }
 
         state
     }
@@ -169,6 +195,7 @@ impl Decoder {
         registers.append(&mut self.ctx_stack);
 
         registers.append(&mut self.loop_stack);
+        registers.append(&mut self.loop_counters);
 
         registers
     }
@@ -177,18 +204,25 @@ impl Decoder {
     // --------------------------------------------------------------------------------------------
 
     /// Initiates a new program block (Group or Switch).
-    pub fn start_block(&mut self) {
+    ///
+    /// Returns an error if the context stack is already at its maximum depth; in such a case,
+    /// the state of the decoder is not changed.
+    pub fn start_block(&mut self) -> Result<(), ExecutionErrorKind> {
         assert!(
             self.step % BASE_CYCLE_LENGTH == BASE_CYCLE_LENGTH - 1,
             "cannot start context block at step {}: operation alignment is not valid",
             self.step
         );
+        if self.ctx_depth == MAX_CONTEXT_DEPTH {
+            return Err(ExecutionErrorKind::ContextStackOverflow);
+        }
 
         self.advance_step(false);
         self.save_context();
         self.copy_loop_stack();
         self.set_op_bits(FlowOps::Begin, UserOps::Noop);
         self.set_sponge([BaseElement::ZERO; 4]);
+        Ok(())
     }
 
     /// Terminates a program block (Group, Switch, or Loop).
@@ -215,19 +249,43 @@ impl Decoder {
         }
     }
 
-    /// Initiates a new Loop block
-    pub fn start_loop(&mut self, loop_image: BaseElement) {
+    /// Initiates a new loop; this can be either a Loop block or a Repeat block (in which case
+    /// `is_counted` is true).
+    ///
+    /// The counter of a Loop block is set to 1 and never changes; the counter of a Repeat block
+    /// starts at 0 and is updated on every iteration, and its final value is merged into the
+    /// hash of the block when the loop is exited.
+    ///
+    /// Returns an error if either the context stack or the loop stack is already at its maximum
+    /// depth; in such a case, the state of the decoder is not changed.
+    pub fn start_loop(
+        &mut self,
+        loop_image: BaseElement,
+        is_counted: bool,
+    ) -> Result<(), ExecutionErrorKind> {
         assert!(
             self.step % BASE_CYCLE_LENGTH == BASE_CYCLE_LENGTH - 1,
             "cannot start a loop at step {}: operation alignment is not valid",
             self.step
         );
+        if self.ctx_depth == MAX_CONTEXT_DEPTH {
+            return Err(ExecutionErrorKind::ContextStackOverflow);
+        }
+        if self.loop_depth == MAX_LOOP_DEPTH {
+            return Err(ExecutionErrorKind::LoopStackOverflow);
+        }
 
         self.advance_step(false);
         self.save_context();
-        self.save_loop_image(loop_image);
+        let counter = if is_counted {
+            BaseElement::ZERO
+        } else {
+            BaseElement::ONE
+        };
+        self.save_loop_image(loop_image, counter);
         self.set_op_bits(FlowOps::Loop, UserOps::Noop);
         self.set_sponge([BaseElement::ZERO; 4]);
+        Ok(())
     }
 
     /// Prepares the decoder for the next iteration of a loop.
@@ -246,6 +304,7 @@ impl Decoder {
             "cannot wrap a loop at step {}: hash of the last iteration doesn't match loop image",
             self.step
         );
+        self.update_loop_counter();
         self.set_op_bits(FlowOps::Wrap, UserOps::Noop);
         self.set_sponge([BaseElement::ZERO; 4]);
     }
@@ -260,20 +319,25 @@ impl Decoder {
 
         self.advance_step(false);
         self.copy_context_stack();
-        let top_loop_image = self.pop_loop_image();
+        let (top_loop_image, counter) = self.pop_loop_image();
         assert!(
             self.op_sponge[0] == top_loop_image,
             "cannot break a loop at step {}: hash of the last iteration doesn't match loop image",
             self.step
         );
         self.set_op_bits(FlowOps::Break, UserOps::Noop);
-        self.set_sponge(self.op_sponge);
+
+        // merge the final value of the loop counter into the sponge state; for Loop blocks, the
+        // counter is always 1, and thus, the state doesn't change
+        let mut state = self.op_sponge;
+        state[3] += counter - BaseElement::ONE;
+        self.set_sponge(state);
     }
 
     /// Updates the decoder with the value of the specified operation.
     pub fn decode_op(&mut self, op_code: UserOps, op_value: BaseElement) {
-        // op_value can be provided only for a PUSH operation and only
-        // at steps which are multiples of 8
+        // op_value can be provided only for a PUSH operation at steps which are multiples of 8,
+        // and for a U32 operation
         if op_value != BaseElement::ZERO {
             match op_code {
                 UserOps::Push => assert!(
@@ -281,6 +345,7 @@ impl Decoder {
                     "invalid PUSH operation alignment at step {}",
                     self.step
                 ),
+                UserOps::U32 => (),
                 _ => panic!(
                     "invalid {:?} operation at step {}: op_value is non-zero",
                     op_code, self.step
@@ -323,11 +388,42 @@ impl Decoder {
         for register in self.loop_stack.iter_mut() {
             fill_register(register, self.step + 1, register[self.step]);
         }
+        for register in self.loop_counters.iter_mut() {
+            fill_register(register, self.step + 1, register[self.step]);
+        }
 
         // update the step pointer to point to the last step
         self.step = self.trace_length() - 1;
     }
 
+    /// Extends register traces to hold at least `trace_length` steps.
+    pub fn extend_trace(&mut self, trace_length: usize) {
+        let new_length = cmp::max(trace_length, self.trace_length());
+
+        self.op_counter.resize(new_length, BaseElement::ZERO);
+        for register in self.op_sponge_trace.iter_mut() {
+            register.resize(new_length, BaseElement::ZERO);
+        }
+        for register in self.cf_op_bits.iter_mut() {
+            register.resize(new_length, BaseElement::ZERO);
+        }
+        for register in self.ld_op_bits.iter_mut() {
+            register.resize(new_length, BaseElement::ZERO);
+        }
+        for register in self.hd_op_bits.iter_mut() {
+            register.resize(new_length, BaseElement::ZERO);
+        }
+        for register in self.ctx_stack.iter_mut() {
+            register.resize(new_length, BaseElement::ZERO);
+        }
+        for register in self.loop_stack.iter_mut() {
+            register.resize(new_length, BaseElement::ZERO);
+        }
+        for register in self.loop_counters.iter_mut() {
+            register.resize(new_length, BaseElement::ZERO);
+        }
+    }
+
     // HELPER METHODS
     // --------------------------------------------------------------------------------------------
 
@@ -338,27 +434,7 @@ impl Decoder {
 
         // make sure there is enough memory allocated for register traces
         if self.step >= self.trace_length() {
-            let new_length = self.trace_length() * 2;
-
-            self.op_counter.resize(new_length, BaseElement::ZERO);
-            for register in self.op_sponge_trace.iter_mut() {
-                register.resize(new_length, BaseElement::ZERO);
-            }
-            for register in self.cf_op_bits.iter_mut() {
-                register.resize(new_length, BaseElement::ZERO);
-            }
-            for register in self.ld_op_bits.iter_mut() {
-                register.resize(new_length, BaseElement::ZERO);
-            }
-            for register in self.hd_op_bits.iter_mut() {
-                register.resize(new_length, BaseElement::ZERO);
-            }
-            for register in self.ctx_stack.iter_mut() {
-                register.resize(new_length, BaseElement::ZERO);
-            }
-            for register in self.loop_stack.iter_mut() {
-                register.resize(new_length, BaseElement::ZERO);
-            }
+            self.extend_trace(self.trace_length() * 2);
         }
 
         // for user ops, increment counter by 1; otherwise, copy counter from thee previous step
@@ -395,13 +471,7 @@ impl Decoder {
 
     /// Pushes hash of the current program block onto the context stack.
     fn save_context(&mut self) {
-        // increment context depth and make sure it doesn't overflow the stack
         self.ctx_depth += 1;
-        assert!(
-            self.ctx_depth <= MAX_CONTEXT_DEPTH,
-            "context stack overflow at step {}",
-            self.step
-        );
 
         // if the depth exceeds current number of registers allocated for the context stack,
         // add a new register trace to the stack
@@ -417,7 +487,7 @@ impl Decoder {
 
         // set the top of the stack to the hash of the current program block
         // which is located in the first register of the sponge
-        self.ctx_stack[0][self.step] = self.op_sponge[0]
+        self.ctx_stack[0][self.step] = self.op_sponge[0];
     }
 
     /// Removes the top value from the context stack and returns it.
@@ -450,30 +520,29 @@ impl Decoder {
     // LOOP STACK HELPERS
     // --------------------------------------------------------------------------------------------
 
-    /// Pushes `loop_image` onto the loop stack.
-    fn save_loop_image(&mut self, loop_image: BaseElement) {
-        // increment loop depth and make sure it doesn't overflow the stack
+    /// Pushes `loop_image` onto the loop stack, and the initial value of the loop `counter`
+    /// onto the stack of loop counters.
+    fn save_loop_image(&mut self, loop_image: BaseElement, counter: BaseElement) {
         self.loop_depth += 1;
-        assert!(
-            self.loop_depth <= MAX_LOOP_DEPTH,
-            "loop stack overflow at step {}",
-            self.step
-        );
 
         // if the depth exceeds current number of registers allocated for the loop stack,
-        // add a new register trace to the stack
+        // add a new register trace to the stack and to the loop counters
         if self.loop_depth > self.loop_stack.len() {
             self.loop_stack
                 .push(vec![BaseElement::ZERO; self.trace_length()]);
+            self.loop_counters
+                .push(vec![BaseElement::ZERO; self.trace_length()]);
         }
 
         // shift all stack values by one to the right
         for i in 1..self.loop_stack.len() {
             self.loop_stack[i][self.step] = self.loop_stack[i - 1][self.step - 1];
+            self.loop_counters[i][self.step] = self.loop_counters[i - 1][self.step - 1];
         }
 
         // set the top of the stack to loop_image
         self.loop_stack[0][self.step] = loop_image;
+        self.loop_counters[0][self.step] = counter;
     }
 
     /// Copies contents of the loop stack from the previous to the current step and returns
@@ -487,16 +556,15 @@ impl Decoder {
         );
 
         // copy all values of the stack from the last step to the current step
-        for i in 0..self.loop_stack.len() {
-            self.loop_stack[i][self.step] = self.loop_stack[i][self.step - 1];
-        }
+        self.copy_loop_stack();
 
         // return top value of the stack
         self.loop_stack[0][self.step]
     }
 
-    // Removes the top value from the loop stack and returns it.
-    fn pop_loop_image(&mut self) -> BaseElement {
+    /// Removes the top value from the loop stack and the top value from the stack of loop
+    /// counters, and returns them.
+    fn pop_loop_image(&mut self) -> (BaseElement, BaseElement) {
         // make sure the stack is not empty
         assert!(
             self.loop_depth > 0,
@@ -507,21 +575,35 @@ impl Decoder {
         // shift all stack values by one item to the left
         for i in 1..self.loop_stack.len() {
             self.loop_stack[i - 1][self.step] = self.loop_stack[i][self.step - 1];
+            self.loop_counters[i - 1][self.step] = self.loop_counters[i][self.step - 1];
         }
 
-        // update the stack depth and return the value that was at the top of the stack
-        // before it was shifted to the left
+        // update the stack depth and return the values that were at the top of the stacks
+        // before they were shifted to the left
         self.loop_depth -= 1;
-        self.loop_stack[0][self.step - 1]
+        (
+            self.loop_stack[0][self.step - 1],
+            self.loop_counters[0][self.step - 1],
+        )
     }
 
-    /// Copies contents of the loop stack from the previous to the current step.
+    /// Copies contents of the loop stack and of the loop counters from the previous to the
+    /// current step.
     fn copy_loop_stack(&mut self) {
         for i in 0..self.loop_stack.len() {
             self.loop_stack[i][self.step] = self.loop_stack[i][self.step - 1];
+            self.loop_counters[i][self.step] = self.loop_counters[i][self.step - 1];
         }
     }
 
+    /// Updates the counter of the innermost loop as ctr' = g * ctr + (1 - g); the counter of a
+    /// Loop block is 1, and thus, it is not changed by the update.
+    fn update_loop_counter(&mut self) {
+        let counter = self.loop_counters[0][self.step - 1];
+        self.loop_counters[0][self.step] =
+            LOOP_COUNTER_BASE * counter + (BaseElement::ONE - LOOP_COUNTER_BASE);
+    }
+
     // HASH ACCUMULATOR HELPERS
     // --------------------------------------------------------------------------------------------
 
@@ -0,0 +1,211 @@
+use super::{
+    opcodes::{FlowOps, OpHint, UserOps},
+    BaseElement, StarkField,
+};
+use core::fmt;
+
+// EXECUTION ERROR
+// ================================================================================================
+
+/// Describes a failure which occurred while executing a program.
+///
+/// In addition to the kind of the failure, the error records the step at which the failure
+/// occurred, the operation which was being executed, and the state of the stack right before
+/// the failing operation was executed (the top of the stack is the first element).
+#[derive(Clone, PartialEq)]
+pub struct ExecutionError {
+    step: usize,
+    operation: Operation,
+    kind: ExecutionErrorKind,
+    stack: Vec<BaseElement>,
+}
+
+impl ExecutionError {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+
+    pub fn new(
+        step: usize,
+        operation: Operation,
+        kind: ExecutionErrorKind,
+        stack: Vec<BaseElement>,
+    ) -> Self {
+        ExecutionError {
+            step,
+            operation,
+            kind,
+            stack,
+        }
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the step at which the failure occurred.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Returns the operation which failed.
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Returns the kind of the failure.
+    pub fn kind(&self) -> &ExecutionErrorKind {
+        &self.kind
+    }
+
+    /// Returns the state of the stack right before the failing operation was executed.
+    pub fn stack(&self) -> &[BaseElement] {
+        &self.stack
+    }
+}
+
+impl fmt::Debug for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "execution error at step {} ({}): {}; stack: {:?}",
+            self.step, self.operation, self.kind, self.stack
+        )
+    }
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "execution error at step {} ({}): {}",
+            self.step, self.operation, self.kind
+        )
+    }
+}
+
+// ERROR KIND
+// ================================================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExecutionErrorKind {
+    /// The operation required more items than were present on the stack.
+    StackUnderflow,
+    /// The operation would grow the stack beyond the maximum allowed depth.
+    StackOverflow,
+    /// The operation attempted to read from secret input tape A, but the tape was exhausted.
+    EmptyTapeA,
+    /// The operation attempted to read from secret input tape B, but the tape was exhausted.
+    EmptyTapeB,
+    /// ASSERT or ASSERTEQ operation failed.
+    FailedAssertion,
+    /// The operation expected a binary value but received the specified value instead.
+    NonBinaryValue(BaseElement),
+    /// A conditional block or a loop was entered or exited based on a non-binary condition.
+    NonBinaryCondition(BaseElement),
+    /// The operation expected a power of two but received the specified value instead.
+    NotPowerOfTwo(BaseElement),
+    /// INV operation was applied to a zero value, or U32 division was executed with a zero
+    /// divisor.
+    DivisionByZero,
+    /// EQ operation was executed with an auxiliary value which is not the inverse of the
+    /// difference between the compared values.
+    InvalidAuxValue,
+    /// The operation was supplied with an execution hint which is not valid for it.
+    InvalidHint(OpHint),
+    /// Control blocks were nested deeper than the maximum allowed context depth.
+    ContextStackOverflow,
+    /// Loops were nested deeper than the maximum allowed loop depth.
+    LoopStackOverflow,
+    /// Memory was accessed with an address which is outside of the addressable memory range.
+    InvalidMemoryAddress(BaseElement),
+    /// U32 operation expected a 32-bit unsigned integer but received the specified value
+    /// instead.
+    NotU32Value(BaseElement),
+    /// U32 shift operation was executed with a shift amount which is not smaller than 32.
+    InvalidShiftAmount(BaseElement),
+    /// STORAGE.READ or STORAGE.WRITE instruction was executed with a key which is not a 32-bit
+    /// unsigned integer.
+    InvalidStorageKey(BaseElement),
+    /// A Call block referenced a block with the specified hash, but the program library did not
+    /// contain such a block.
+    UndefinedCallee(BaseElement),
+    /// A sequence of program blocks did not start with a Span block.
+    InvalidBlockSequence,
+}
+
+impl fmt::Display for ExecutionErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionErrorKind::StackUnderflow => write!(f, "stack underflow"),
+            ExecutionErrorKind::StackOverflow => write!(f, "stack overflow"),
+            ExecutionErrorKind::EmptyTapeA => write!(f, "attempt to read from empty tape A"),
+            ExecutionErrorKind::EmptyTapeB => write!(f, "attempt to read from empty tape B"),
+            ExecutionErrorKind::FailedAssertion => write!(f, "assertion failed"),
+            ExecutionErrorKind::NonBinaryValue(value) => {
+                write!(f, "expected a binary value, but received {}", value)
+            }
+            ExecutionErrorKind::NonBinaryCondition(value) => {
+                write!(f, "cannot branch on a non-binary condition {}", value)
+            }
+            ExecutionErrorKind::NotPowerOfTwo(value) => {
+                write!(f, "expected a power of 2, but received {}", value)
+            }
+            ExecutionErrorKind::DivisionByZero => write!(f, "division by zero"),
+            ExecutionErrorKind::InvalidAuxValue => write!(f, "invalid auxiliary value"),
+            ExecutionErrorKind::InvalidHint(hint) => {
+                write!(
+                    f,
+                    "execution hint {:?} is not valid for the operation",
+                    hint
+                )
+            }
+            ExecutionErrorKind::ContextStackOverflow => write!(f, "context stack overflow"),
+            ExecutionErrorKind::LoopStackOverflow => write!(f, "loop stack overflow"),
+            ExecutionErrorKind::InvalidMemoryAddress(value) => {
+                write!(f, "memory address {} is out of bounds", value)
+            }
+            ExecutionErrorKind::NotU32Value(value) => {
+                write!(
+                    f,
+                    "expected a 32-bit unsigned integer, but received {}",
+                    value
+                )
+            }
+            ExecutionErrorKind::InvalidShiftAmount(value) => {
+                write!(f, "cannot shift a 32-bit integer by {} bits", value)
+            }
+            ExecutionErrorKind::InvalidStorageKey(value) => {
+                write!(f, "storage key {} is not a 32-bit unsigned integer", value)
+            }
+            ExecutionErrorKind::UndefinedCallee(hash) => {
+                write!(
+                    f,
+                    "block with hash {:#x} is not in the program library",
+                    hash.as_int()
+                )
+            }
+            ExecutionErrorKind::InvalidBlockSequence => {
+                write!(f, "a sequence of blocks must start with a Span block")
+            }
+        }
+    }
+}
+
+// OPERATION
+// ================================================================================================
+
+/// An operation executed by the VM; this can be either a flow control operation handled by the
+/// decoder, or a user operation executed against the stack.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Operation {
+    Flow(FlowOps),
+    User(UserOps),
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Flow(op) => write!(f, "{}", op),
+            Operation::User(op) => write!(f, "{}", op),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{errors::ExecutionErrorKind, BaseElement, Storage, StorageNode, StorageState};
+
+#[cfg(test)]
+mod tests;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Random-access memory of the VM.
+///
+/// Memory is a sparse Merkle tree which maps 32-bit addresses to values, and which is empty at
+/// the start of every execution. The tree has the same shape as the storage tree, and memory is
+/// accessed in the same way as the storage: values and their authentication paths are supplied
+/// to `mem.load` and `mem.store` instructions via execution hints, while the VM keeps track only
+/// of the root of the tree. The root changes only when MROOT operation is executed.
+pub struct Memory {
+    state: StorageState,
+}
+
+// MEMORY IMPLEMENTATION
+// ================================================================================================
+impl Memory {
+    /// Returns a new empty memory.
+    pub fn new() -> Memory {
+        Memory {
+            state: StorageState::new(Storage::new()),
+        }
+    }
+
+    /// Returns true if MROOT operation has never been executed, and thus, the root registers
+    /// do not need to be included into the execution trace.
+    pub fn is_empty(&self) -> bool {
+        self.state.is_empty()
+    }
+
+    /// Returns the value located at the specified address together with the authentication
+    /// path of the leaf holding the value.
+    ///
+    /// # Errors
+    /// Returns an error if the address is not a 32-bit unsigned integer.
+    pub fn read(
+        &self,
+        addr: BaseElement,
+    ) -> Result<(BaseElement, Vec<StorageNode>), ExecutionErrorKind> {
+        self.state
+            .read(addr)
+            .map_err(|_| ExecutionErrorKind::InvalidMemoryAddress(addr))
+    }
+
+    /// Writes the `value` into the specified address, and returns the value previously located
+    /// at this address together with the authentication path of the leaf holding the value.
+    ///
+    /// # Errors
+    /// Returns an error if the address is not a 32-bit unsigned integer.
+    pub fn write(
+        &mut self,
+        addr: BaseElement,
+        value: BaseElement,
+    ) -> Result<(BaseElement, Vec<StorageNode>), ExecutionErrorKind> {
+        self.state
+            .write(addr, value)
+            .map_err(|_| ExecutionErrorKind::InvalidMemoryAddress(addr))
+    }
+
+    /// Sets the root of the memory as seen by the VM to `root` starting with the specified step,
+    /// and returns the previous root.
+    pub fn swap_root(&mut self, root: StorageNode, step: usize) -> StorageNode {
+        self.state.swap_root(root, step)
+    }
+
+    /// Builds register traces of the memory root for an execution trace of `trace_length`
+    /// steps; at every step, the registers hold the root as seen by the VM.
+    pub fn build_register_traces(&self, trace_length: usize) -> Vec<Vec<BaseElement>> {
+        self.state.build_register_traces(trace_length)
+    }
+}
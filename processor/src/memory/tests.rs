@@ -0,0 +1,67 @@
+use super::{BaseElement, ExecutionErrorKind, Memory, Storage};
+use vm_core::FieldElement;
+
+const TRACE_LENGTH: usize = 8;
+
+#[test]
+fn read_write() {
+    let mut memory = Memory::new();
+    assert!(memory.is_empty());
+
+    // values at addresses which were never written to are zeros
+    let empty = Storage::new();
+    let (value, path) = memory.read(BaseElement::new(5)).unwrap();
+    assert_eq!(BaseElement::ZERO, value);
+    assert_eq!(empty.get_path(5), path);
+
+    let (old_value, path) = memory
+        .write(BaseElement::new(5), BaseElement::new(7))
+        .unwrap();
+    assert_eq!(BaseElement::ZERO, old_value);
+    assert_eq!(empty.get_path(5), path);
+    assert_eq!(
+        BaseElement::new(7),
+        memory.read(BaseElement::new(5)).unwrap().0
+    );
+
+    // paths of other leaves authenticate the updated value
+    let tree = Storage::from_values(&[(5, 7)]);
+    assert_eq!(
+        tree.get_path(3),
+        memory.read(BaseElement::new(3)).unwrap().1
+    );
+
+    // writes do not change the root as seen by the VM
+    assert!(memory.is_empty());
+
+    // addresses must be 32-bit integers
+    let addr = BaseElement::new(u32::MAX as u128 + 1);
+    assert_eq!(
+        Err(ExecutionErrorKind::InvalidMemoryAddress(addr)),
+        memory.read(addr)
+    );
+    assert_eq!(
+        Err(ExecutionErrorKind::InvalidMemoryAddress(addr)),
+        memory.write(addr, BaseElement::ONE)
+    );
+}
+
+#[test]
+fn build_register_traces() {
+    let init_root = Storage::new().root_node();
+    let mut memory = Memory::new();
+
+    let root = [BaseElement::new(1), BaseElement::new(2)];
+    assert_eq!(init_root, memory.swap_root(root, 3));
+    assert!(!memory.is_empty());
+
+    // memory root starts out as the root of an empty tree
+    let registers = memory.build_register_traces(TRACE_LENGTH);
+    let expected = [
+        init_root, init_root, init_root, root, root, root, root, root,
+    ];
+    for (i, register) in registers.iter().enumerate() {
+        let expected = expected.iter().map(|root| root[i]).collect::<Vec<_>>();
+        assert_eq!(&expected, register);
+    }
+}
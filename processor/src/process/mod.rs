@@ -0,0 +1,612 @@
+use crate::{
+    errors::{ExecutionError, ExecutionErrorKind, Operation},
+    BaseElement, Decoder, FieldElement, FlowOps, Frame, OpCode, OpHint, Overhead, Profile, Program,
+    ProgramInputs, ProgramLibrary, Stack, HACC_NUM_ROUNDS, MIN_TRACE_LENGTH,
+};
+use vm_core::program::blocks::{Loop, ProgramBlock, Repeat, SourceLocation, Span};
+use winterfell::ExecutionTrace;
+
+#[cfg(test)]
+mod tests;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Execution of a program which can be advanced one VM cycle at a time.
+///
+/// Between cycles, the state of the VM can be inspected: the state of the stack, the values
+/// remaining on the secret input tapes, and the contents of the context and loop stacks of the
+/// decoder. Once the program has been executed to completion, the process can be converted into
+/// an execution trace.
+pub struct Process<'a> {
+    library: Option<&'a ProgramLibrary>,
+    decoder: Decoder,
+    stack: Stack,
+    tasks: Vec<Task<'a>>,
+    error: Option<ExecutionError>,
+    profile: Option<Profile>,
+}
+
+/// A unit of work remaining to be done by the process; every task executes at least one VM
+/// cycle, and tasks which have nothing left to do are removed from the task list right away.
+#[derive(Copy, Clone)]
+enum Task<'a> {
+    /// Executes blocks of a sequence starting with the block at the specified index.
+    Blocks(&'a [ProgramBlock], usize),
+    /// Executes operations of a Span block starting with the operation at the specified index.
+    Span(&'a Span, usize),
+    /// Executes a NOOP used for block alignment or for merging a block hash into the program
+    /// hash.
+    Noop,
+    /// Starts a Group, Switch, or Call block.
+    StartBlock,
+    /// Ends a block; the parameters are the hash of the sibling block and a flag specifying
+    /// whether the true branch of the block was executed.
+    EndBlock(BaseElement, bool),
+    /// Decides whether to execute another iteration of the loop or to exit it based on the
+    /// value at the top of the stack.
+    IterateLoop(&'a Loop),
+    /// Decides whether to execute another iteration of the Repeat block or to exit it based on
+    /// the number of iterations which have been executed so far.
+    IterateRepeat(&'a Repeat, usize),
+}
+
+// PROCESS IMPLEMENTATION
+// ================================================================================================
+impl<'a> Process<'a> {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a new process for executing the `program` against the specified inputs; the
+    /// process is positioned right before the first cycle of the program.
+    pub fn new(program: &'a Program, inputs: &'a ProgramInputs) -> Process<'a> {
+        Process::build(program, inputs, None)
+    }
+
+    /// Returns a new process for executing the `program` against the specified inputs; blocks
+    /// called by the program are looked up in the specified `library`.
+    pub fn with_library(
+        program: &'a Program,
+        inputs: &'a ProgramInputs,
+        library: &'a ProgramLibrary,
+    ) -> Process<'a> {
+        Process::build(program, inputs, Some(library))
+    }
+
+    /// Returns this process with profiling enabled; every cycle executed by the process is
+    /// recorded in a [Profile] which can be retrieved via [Process::profile()].
+    pub fn with_profiling(mut self) -> Process<'a> {
+        self.profile = Some(Profile::new());
+        self
+    }
+
+    fn build(
+        program: &'a Program,
+        inputs: &'a ProgramInputs,
+        library: Option<&'a ProgramLibrary>,
+    ) -> Process<'a> {
+        let mut process = Process {
+            library,
+            decoder: Decoder::new(MIN_TRACE_LENGTH),
+            stack: Stack::new(inputs, MIN_TRACE_LENGTH),
+            tasks: Vec::new(),
+            error: None,
+            profile: None,
+        };
+
+        // execute the body of the program, and then close the program block
+        process.close_block(BaseElement::ZERO, true);
+        process.tasks.push(Task::Blocks(program.root().body(), 0));
+        process
+    }
+
+    // EXECUTION
+    // --------------------------------------------------------------------------------------------
+
+    /// Executes a single VM cycle; does nothing if the program has been executed to completion.
+    ///
+    /// # Errors
+    /// Returns an error if the cycle could not be executed. Once an error is returned, the
+    /// process cannot advance any further, and all subsequent calls return the same error.
+    pub fn step(&mut self) -> Result<(), ExecutionError> {
+        if let Some(error) = &self.error {
+            return Err(error.clone());
+        }
+
+        let result = self.execute_cycle();
+        self.remove_finished_tasks();
+        if let Err(error) = &result {
+            self.error = Some(error.clone());
+        }
+        result
+    }
+
+    /// Executes the program to completion.
+    ///
+    /// # Errors
+    /// Returns an error if the program could not be executed to completion.
+    pub fn run(&mut self) -> Result<(), ExecutionError> {
+        while !self.is_finished() {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    /// Converts this process into an execution trace.
+    ///
+    /// # Panics
+    /// Panics if the program has not been executed to completion.
+    pub fn into_trace(self) -> ExecutionTrace<BaseElement> {
+        assert!(
+            self.is_finished(),
+            "program must be executed to completion before the trace can be built"
+        );
+        let Process {
+            mut decoder,
+            mut stack,
+            ..
+        } = self;
+
+        // fill in remaining steps to make sure the length of the trace is a power of 2
+        decoder.finalize_trace();
+        stack.finalize_trace();
+
+        // build memory root registers; these are included into the execution trace only if the
+        // program accessed memory
+        let mut memory_traces = stack.build_memory_traces();
+
+        // build storage root registers; these are included into the execution trace only if the
+        // program accessed the storage
+        let mut storage_traces = stack.build_storage_traces();
+
+        // build u32 operations table; it is included into the execution trace only if the
+        // program executed u32 operations
+        let mut u32_traces = stack.build_u32_traces();
+
+        // build execution trace metadata as a vector of bytes
+        let op_counter = decoder.max_op_counter_value();
+        let context_depth = decoder.max_ctx_stack_depth();
+        let loop_depth = decoder.max_loop_stack_depth();
+        let mut meta = op_counter.to_le_bytes().to_vec();
+        meta.push(context_depth as u8);
+        meta.push(loop_depth as u8);
+        meta.push(!memory_traces.is_empty() as u8);
+        meta.push(!u32_traces.is_empty() as u8);
+        meta.push(!storage_traces.is_empty() as u8);
+
+        // merge decoder, stack, memory, storage, and u32 operations register traces into a
+        // single vector
+        let mut register_traces = decoder.into_register_traces();
+        register_traces.append(&mut stack.into_register_traces());
+        register_traces.append(&mut memory_traces);
+        register_traces.append(&mut storage_traces);
+        register_traces.append(&mut u32_traces);
+
+        let mut trace = ExecutionTrace::init(register_traces);
+        trace.set_meta(meta);
+        trace
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns true if the program has been executed to completion.
+    pub fn is_finished(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Returns the error which stopped the process, if any.
+    pub fn error(&self) -> Option<&ExecutionError> {
+        self.error.as_ref()
+    }
+
+    /// Returns the number of cycles executed so far.
+    pub fn current_step(&self) -> usize {
+        self.stack.current_step()
+    }
+
+    /// Returns the operation which will be executed during the next cycle, or None if the
+    /// program has been executed to completion.
+    pub fn next_operation(&self) -> Option<Operation> {
+        let op = match self.tasks.last()? {
+            Task::Blocks(blocks, i) => match &blocks[*i] {
+                ProgramBlock::Span(block) if *i == 0 => Operation::User(block.get_op(0).0),
+                ProgramBlock::Span(_) => Operation::User(OpCode::Noop),
+                ProgramBlock::Loop(_) if self.stack.get_stack_top() != BaseElement::ZERO => {
+                    Operation::Flow(FlowOps::Loop)
+                }
+                ProgramBlock::Repeat(_) => Operation::Flow(FlowOps::Loop),
+                _ => Operation::Flow(FlowOps::Begin),
+            },
+            Task::Span(block, i) => Operation::User(block.get_op(*i).0),
+            Task::Noop => Operation::User(OpCode::Noop),
+            Task::StartBlock => Operation::Flow(FlowOps::Begin),
+            Task::EndBlock(_, true) => Operation::Flow(FlowOps::Tend),
+            Task::EndBlock(_, false) => Operation::Flow(FlowOps::Fend),
+            Task::IterateLoop(_) => match self.stack.get_stack_top() {
+                BaseElement::ZERO => Operation::Flow(FlowOps::Break),
+                _ => Operation::Flow(FlowOps::Wrap),
+            },
+            Task::IterateRepeat(block, i) if *i < block.num_iterations() => {
+                Operation::Flow(FlowOps::Wrap)
+            }
+            Task::IterateRepeat(..) => Operation::Flow(FlowOps::Break),
+        };
+        Some(op)
+    }
+
+    /// Returns the source location of the instruction which starts with the operation executed
+    /// during the next cycle; None is returned if the next operation does not start an
+    /// instruction, or if its location is unknown.
+    pub fn next_location(&self) -> Option<SourceLocation> {
+        match self.tasks.last()? {
+            Task::Blocks(blocks, 0) => match &blocks[0] {
+                ProgramBlock::Span(block) => block.get_location(0),
+                _ => None,
+            },
+            Task::Span(block, i) => block.get_location(*i),
+            _ => None,
+        }
+    }
+
+    /// Returns the values currently on the stack; the top of the stack is the first value.
+    pub fn stack_state(&self) -> Vec<BaseElement> {
+        self.stack.snapshot()
+    }
+
+    /// Returns the values remaining on secret input tape A in the order in which they will be
+    /// read.
+    pub fn tape_a(&self) -> Vec<BaseElement> {
+        self.stack.tape_a()
+    }
+
+    /// Returns the values remaining on secret input tape B in the order in which they will be
+    /// read.
+    pub fn tape_b(&self) -> Vec<BaseElement> {
+        self.stack.tape_b()
+    }
+
+    /// Returns hashes of the blocks enclosing the currently executing block; the hash of the
+    /// innermost block is the first item, and the context of the program block (which is always
+    /// 0) is the last item.
+    pub fn ctx_stack(&self) -> Vec<BaseElement> {
+        self.decoder.ctx_stack_state()
+    }
+
+    /// Returns images of the loops currently being executed; the image of the innermost loop is
+    /// the first item.
+    pub fn loop_stack(&self) -> Vec<BaseElement> {
+        self.decoder.loop_stack_state()
+    }
+
+    /// Returns the profile of the cycles executed so far, or None if profiling is not enabled.
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// Removes the profile from this process and returns it; this can be used to retain the
+    /// profile once the process is converted into an execution trace.
+    pub fn take_profile(&mut self) -> Option<Profile> {
+        self.profile.take()
+    }
+
+    /// Returns the number of tasks remaining to be done by the process; all tasks added while
+    /// a block is being executed are completed by the time the block is closed.
+    pub(crate) fn num_tasks(&self) -> usize {
+        self.tasks.len()
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Executes a single VM cycle by advancing the task at the top of the task list; tasks
+    /// which start new blocks are expanded into the tasks of these blocks first.
+    ///
+    /// If the cycle fails, the task which failed is put back at the top of the task list so
+    /// that the failed operation remains the next operation of the process.
+    fn execute_cycle(&mut self) -> Result<(), ExecutionError> {
+        loop {
+            let task = match self.tasks.pop() {
+                Some(task) => task,
+                None => return Ok(()),
+            };
+
+            match task {
+                Task::Blocks(blocks, i) => {
+                    self.tasks.push(Task::Blocks(blocks, i + 1));
+                    match self.start_block(&blocks[i], i == 0) {
+                        Ok(true) => return Ok(()),
+                        Ok(false) => (),
+                        Err(error) => {
+                            self.tasks.pop();
+                            self.tasks.push(task);
+                            return Err(error);
+                        }
+                    }
+                }
+                Task::Span(block, i) => {
+                    let (op_code, op_hint) = block.get_op(i);
+                    self.decoder.decode_op(op_code, op_hint.value());
+                    if let Err(error) = self.stack.execute(op_code, op_hint) {
+                        self.tasks.push(task);
+                        return Err(error);
+                    }
+                    self.record_span_cycle(block, i, op_code);
+                    self.tasks.push(Task::Span(block, i + 1));
+                    return Ok(());
+                }
+                Task::Noop => {
+                    self.decoder.decode_op(OpCode::Noop, BaseElement::ZERO);
+                    self.stack.execute(OpCode::Noop, OpHint::None)?;
+
+                    // NOOPs which precede a Span block or the end of a block are used for
+                    // alignment; all other NOOPs merge block hashes into the program hash
+                    match self.tasks.last() {
+                        Some(Task::Span(..)) => {
+                            self.record_cycle(&[Frame::Span, Frame::Overhead(Overhead::Padding)])
+                        }
+                        Some(Task::EndBlock(..)) => {
+                            self.record_cycle(&[Frame::Overhead(Overhead::Padding)])
+                        }
+                        _ => self.record_cycle(&[Frame::Overhead(Overhead::Hash)]),
+                    }
+                    return Ok(());
+                }
+                Task::StartBlock => {
+                    if let Err(kind) = self.decoder.start_block() {
+                        self.tasks.push(task);
+                        return Err(self.flow_error(FlowOps::Begin, kind));
+                    }
+                    self.stack.execute(OpCode::Noop, OpHint::None)?;
+                    self.record_cycle(&[Frame::Overhead(Overhead::Begin)]);
+                    return Ok(());
+                }
+                Task::EndBlock(sibling_hash, is_true_branch) => {
+                    self.decoder.end_block(sibling_hash, is_true_branch);
+                    self.stack.execute(OpCode::Noop, OpHint::None)?;
+                    self.record_cycle(&[Frame::Overhead(Overhead::End)]);
+                    return Ok(());
+                }
+                Task::IterateLoop(block) => {
+                    let condition = self.stack.get_stack_top();
+                    let overhead = match condition {
+                        BaseElement::ZERO => {
+                            // execute the contents of the skip block to make sure the loop was
+                            // exited correctly, and then close the block
+                            let skip = match first_span(block.skip()) {
+                                Ok(skip) => skip,
+                                Err(kind) => {
+                                    self.tasks.push(task);
+                                    return Err(self.flow_error(FlowOps::Break, kind));
+                                }
+                            };
+                            self.close_block(block.skip_hash(), true);
+                            self.tasks.push(Task::Span(skip, 0));
+                            self.decoder.break_loop();
+                            Overhead::Break
+                        }
+                        BaseElement::ONE => {
+                            self.tasks.push(Task::IterateLoop(block));
+                            self.tasks.push(Task::Blocks(block.body(), 0));
+                            self.decoder.wrap_loop();
+                            Overhead::Wrap
+                        }
+                        _ => {
+                            self.tasks.push(task);
+                            let kind = ExecutionErrorKind::NonBinaryCondition(condition);
+                            return Err(self.flow_error(FlowOps::Wrap, kind));
+                        }
+                    };
+                    self.stack.execute(OpCode::Noop, OpHint::None)?;
+                    self.record_cycle(&[Frame::Overhead(overhead)]);
+                    return Ok(());
+                }
+                Task::IterateRepeat(block, i) => {
+                    let overhead = if i < block.num_iterations() {
+                        self.tasks.push(Task::IterateRepeat(block, i + 1));
+                        self.tasks.push(Task::Blocks(block.body(), 0));
+                        self.decoder.wrap_loop();
+                        Overhead::Wrap
+                    } else {
+                        // the exit block consists of NOOPs only; it aligns the end of the block
+                        // with a multiple of 16 steps in the same way as the skip block of a Loop
+                        self.close_block(BaseElement::ZERO, true);
+                        self.tasks.push(Task::Span(block.exit(), 0));
+                        self.decoder.break_loop();
+                        Overhead::Break
+                    };
+                    self.stack.execute(OpCode::Noop, OpHint::None)?;
+                    self.record_cycle(&[Frame::Overhead(overhead)]);
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Adds tasks for executing the specified block to the task list; if the block is a Loop
+    /// which is entered or a Repeat block, the first cycle of the loop is executed right away.
+    /// Returns true if a cycle has been executed.
+    ///
+    /// A Span block which is not the first block in a sequence of blocks needs to be pre-padded
+    /// with a NOOP to make sure its first instruction starts executing on a step which is a
+    /// multiple of 16.
+    fn start_block(
+        &mut self,
+        block: &'a ProgramBlock,
+        is_first: bool,
+    ) -> Result<bool, ExecutionError> {
+        match block {
+            ProgramBlock::Span(block) => {
+                self.tasks.push(Task::Span(block, 0));
+                if !is_first {
+                    self.tasks.push(Task::Noop);
+                }
+            }
+            ProgramBlock::Group(block) => self.open_block(block.body(), BaseElement::ZERO, true),
+            ProgramBlock::Switch(block) => match self.stack.get_stack_top() {
+                BaseElement::ZERO => {
+                    self.open_block(block.false_branch(), block.true_branch_hash(), false)
+                }
+                BaseElement::ONE => {
+                    self.open_block(block.true_branch(), block.false_branch_hash(), true)
+                }
+                condition => {
+                    let kind = ExecutionErrorKind::NonBinaryCondition(condition);
+                    return Err(self.flow_error(FlowOps::Begin, kind));
+                }
+            },
+            ProgramBlock::Loop(block) => match self.stack.get_stack_top() {
+                BaseElement::ZERO => self.open_block(block.skip(), block.body_hash(), false),
+                BaseElement::ONE => {
+                    // mark the beginning of the loop block, and then execute blocks in the
+                    // loop body until the top of the stack becomes 0
+                    if let Err(kind) = self.decoder.start_loop(block.image(), false) {
+                        return Err(self.flow_error(FlowOps::Loop, kind));
+                    }
+                    self.tasks.push(Task::IterateLoop(block));
+                    self.tasks.push(Task::Blocks(block.body(), 0));
+                    self.stack.execute(OpCode::Noop, OpHint::None)?;
+                    self.record_cycle(&[Frame::Overhead(Overhead::Loop)]);
+                    return Ok(true);
+                }
+                condition => {
+                    let kind = ExecutionErrorKind::NonBinaryCondition(condition);
+                    return Err(self.flow_error(FlowOps::Loop, kind));
+                }
+            },
+            ProgramBlock::Repeat(block) => {
+                // a Repeat block is executed as a loop which is always entered; the number of
+                // executed iterations is tracked by the loop counter of the decoder
+                if let Err(kind) = self.decoder.start_loop(block.image(), true) {
+                    return Err(self.flow_error(FlowOps::Loop, kind));
+                }
+                self.tasks.push(Task::IterateRepeat(block, 1));
+                self.tasks.push(Task::Blocks(block.body(), 0));
+                self.stack.execute(OpCode::Noop, OpHint::None)?;
+                self.record_cycle(&[Frame::Overhead(Overhead::Loop)]);
+                return Ok(true);
+            }
+            ProgramBlock::Call(block) => {
+                // called blocks are executed in the same way as Group blocks, and thus, hash of
+                // the callee is merged into the hash of the program
+                let callee = match self.library.and_then(|lib| lib.get(block.callee_hash())) {
+                    Some(callee) => callee,
+                    None => {
+                        let kind = ExecutionErrorKind::UndefinedCallee(block.callee_hash());
+                        return Err(self.flow_error(FlowOps::Begin, kind));
+                    }
+                };
+                self.open_block(callee.body(), BaseElement::ZERO, true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Adds tasks for starting a new block, executing the `body` of the block, and closing the
+    /// block to the task list.
+    fn open_block(&mut self, body: &'a [ProgramBlock], sibling_hash: BaseElement, is_true: bool) {
+        self.close_block(sibling_hash, is_true);
+        self.tasks.push(Task::Blocks(body, 0));
+        self.tasks.push(Task::StartBlock);
+    }
+
+    /// Adds tasks for closing the currently executing block to the task list.
+    ///
+    /// A sequence of blocks always ends on a step which is one less than a multiple of 16; so,
+    /// it is padded with a single NOOP to ensure proper alignment. Ending the block prepares
+    /// decoder registers for merging the block hash into the program hash, which is done by
+    /// executing NOOPs for the required number of rounds.
+    fn close_block(&mut self, sibling_hash: BaseElement, is_true_branch: bool) {
+        for _ in 0..HACC_NUM_ROUNDS {
+            self.tasks.push(Task::Noop);
+        }
+        self.tasks
+            .push(Task::EndBlock(sibling_hash, is_true_branch));
+        self.tasks.push(Task::Noop);
+    }
+
+    /// Records the cycle which has just been executed in the profile, if profiling is enabled.
+    ///
+    /// The cycle is attributed to the stack of blocks which are currently being executed,
+    /// followed by the specified `frames`. The blocks are determined from the task list: every
+    /// sequence of blocks on the task list is executing the block preceding its next block.
+    /// Span blocks are not included, as cycles of Span blocks are recorded with a Span frame
+    /// explicitly.
+    fn record_cycle(&mut self, frames: &[Frame]) {
+        let profile = match &mut self.profile {
+            Some(profile) => profile,
+            None => return,
+        };
+
+        let mut stack = vec![Frame::Program];
+        for task in self.tasks.iter() {
+            match task {
+                Task::Blocks(blocks, i) if *i > 0 => match &blocks[i - 1] {
+                    ProgramBlock::Group(_) => stack.push(Frame::Group),
+                    ProgramBlock::Switch(_) => stack.push(Frame::Switch),
+                    ProgramBlock::Loop(_) => stack.push(Frame::Loop),
+                    ProgramBlock::Repeat(_) => stack.push(Frame::Repeat),
+                    ProgramBlock::Call(_) => stack.push(Frame::Call),
+                    ProgramBlock::Span(_) => (),
+                },
+                _ => (),
+            }
+        }
+        stack.extend_from_slice(frames);
+        profile.record_cycle(stack);
+    }
+
+    /// Records the cycle in which the operation at the specified index of the Span `block` has
+    /// been executed; the operation is attributed to the instruction to which it belongs.
+    fn record_span_cycle(&mut self, block: &Span, op_index: usize, op_code: OpCode) {
+        if self.profile.is_none() {
+            return;
+        }
+
+        let mut frames = vec![Frame::Span];
+        if let Some(location) = block.get_instruction_location(op_index) {
+            frames.push(Frame::Instruction(location));
+        }
+        if op_code == OpCode::Noop {
+            frames.push(Frame::Overhead(Overhead::Noop));
+        }
+        self.record_cycle(&frames);
+    }
+
+    /// Removes tasks which have nothing left to do from the top of the task list.
+    fn remove_finished_tasks(&mut self) {
+        while let Some(task) = self.tasks.last() {
+            let is_finished = match task {
+                Task::Blocks(blocks, i) => *i == blocks.len(),
+                Task::Span(block, i) => *i == block.length(),
+                _ => false,
+            };
+            if !is_finished {
+                break;
+            }
+            self.tasks.pop();
+        }
+    }
+
+    /// Builds an error for a failure of the specified flow control operation; the failure is
+    /// attributed to the step which follows the last step executed by the stack.
+    fn flow_error(&self, op: FlowOps, kind: ExecutionErrorKind) -> ExecutionError {
+        ExecutionError::new(
+            self.stack.current_step() + 1,
+            Operation::Flow(op),
+            kind,
+            self.stack.snapshot(),
+        )
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the first block of the specified sequence of blocks, which must be a Span block.
+fn first_span(blocks: &[ProgramBlock]) -> Result<&Span, ExecutionErrorKind> {
+    match blocks.first() {
+        Some(ProgramBlock::Span(block)) => Ok(block),
+        _ => Err(ExecutionErrorKind::InvalidBlockSequence),
+    }
+}
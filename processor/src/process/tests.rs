@@ -0,0 +1,193 @@
+use super::{
+    BaseElement, ExecutionErrorKind, FieldElement, FlowOps, OpCode, Operation, Process, Program,
+    ProgramBlock, ProgramInputs, SourceLocation, Span,
+};
+use std::collections::BTreeMap;
+use vm_core::{
+    program::blocks::{Group, Loop, Repeat},
+    TraceState,
+};
+
+#[test]
+fn step_through_program() {
+    let program = build_program();
+    let inputs = ProgramInputs::new(&[1], &[1, 0], &[]);
+    let mut process = Process::new(&program, &inputs);
+
+    // the process starts right before the first instruction of the program
+    assert_eq!(0, process.current_step());
+    assert_eq!(
+        Some(Operation::User(OpCode::Begin)),
+        process.next_operation()
+    );
+    assert_eq!(Some(SourceLocation::new(1, 1)), process.next_location());
+    assert_eq!(vec![BaseElement::ZERO], process.ctx_stack());
+
+    // enter the loop
+    step_until(&mut process, Operation::Flow(FlowOps::Loop));
+    assert_eq!(15, process.current_step());
+    process.step().unwrap();
+    assert_eq!(2, process.ctx_stack().len());
+    assert_eq!(1, process.loop_stack().len());
+
+    // read values from tape A in two iterations of the loop
+    step_until(&mut process, Operation::User(OpCode::Read));
+    assert_eq!(Some(SourceLocation::new(3, 5)), process.next_location());
+    assert_eq!(vec![BaseElement::ONE, BaseElement::ZERO], process.tape_a());
+    process.step().unwrap();
+    assert_eq!(vec![BaseElement::ZERO], process.tape_a());
+    assert_eq!(vec![BaseElement::ONE], process.stack_state());
+
+    step_until(&mut process, Operation::Flow(FlowOps::Wrap));
+    process.step().unwrap();
+    step_until(&mut process, Operation::Flow(FlowOps::Break));
+    assert!(process.tape_a().is_empty());
+    process.step().unwrap();
+    assert!(process.loop_stack().is_empty());
+
+    // stepping through the program produces the same trace as executing it at once
+    process.run().unwrap();
+    assert!(process.is_finished());
+    assert_eq!(None, process.next_operation());
+    assert!(process.ctx_stack().is_empty());
+
+    let trace = process.into_trace();
+    let expected = crate::execute(&program, &inputs).unwrap();
+    assert_eq!(expected.width(), trace.width());
+    assert_eq!(expected.length(), trace.length());
+    for i in 0..trace.width() {
+        for step in 0..trace.length() {
+            assert_eq!(expected.get(i, step), trace.get(i, step));
+        }
+    }
+}
+
+#[test]
+fn failed_step() {
+    // the loop is entered on a non-binary condition
+    let program = build_program();
+    let inputs = ProgramInputs::new(&[2], &[], &[]);
+    let mut process = Process::new(&program, &inputs);
+    step_until(&mut process, Operation::Flow(FlowOps::Loop));
+
+    let err = process.step().unwrap_err();
+    assert_eq!(
+        &ExecutionErrorKind::NonBinaryCondition(BaseElement::new(2)),
+        err.kind()
+    );
+    assert_eq!(Operation::Flow(FlowOps::Loop), err.operation());
+    assert_eq!(Some(&err), process.error());
+
+    // the process cannot advance past the error, but its state can still be inspected
+    assert_eq!(Err(err), process.step());
+    assert_eq!(vec![BaseElement::new(2)], process.stack_state());
+    assert!(!process.is_finished());
+}
+
+#[test]
+fn repeat_blocks() {
+    let mut instructions = vec![OpCode::Noop; 15];
+    instructions[0] = OpCode::Read;
+    instructions[1] = OpCode::Add;
+    let read = Span::from_instructions(instructions);
+    let inputs = ProgramInputs::new(&[1], &[2, 3, 4, 5], &[]);
+
+    // a Repeat block is executed as a loop which is always entered
+    let program =
+        build_repeat_program(Repeat::new_block(vec![ProgramBlock::Span(read.clone())], 4));
+    let mut process = Process::new(&program, &inputs);
+    step_until(&mut process, Operation::Flow(FlowOps::Loop));
+    assert_eq!(15, process.current_step());
+    process.step().unwrap();
+    assert_eq!(2, process.ctx_stack().len());
+    assert_eq!(1, process.loop_stack().len());
+    assert_eq!(
+        Some(Operation::User(OpCode::Read)),
+        process.next_operation()
+    );
+
+    // every iteration but the last one is followed by a WRAP operation, and the last one is
+    // followed by a BREAK operation; the stack is not used to decide whether to exit the loop
+    for _ in 0..3 {
+        step_until(&mut process, Operation::Flow(FlowOps::Wrap));
+        process.step().unwrap();
+    }
+    step_until(&mut process, Operation::Flow(FlowOps::Break));
+    assert_eq!(79, process.current_step());
+    assert!(process.tape_a().is_empty());
+    process.step().unwrap();
+    assert!(process.loop_stack().is_empty());
+
+    process.run().unwrap();
+    assert_eq!(BaseElement::new(15), process.stack_state()[0]);
+    assert_eq!(127, process.current_step());
+
+    // stepping through the program produces the same trace as executing it at once
+    let trace = process.into_trace();
+    let expected = crate::execute(&program, &inputs).unwrap();
+    assert_eq!(expected.width(), trace.width());
+    assert_eq!(expected.length(), trace.length());
+    for i in 0..trace.width() {
+        for step in 0..trace.length() {
+            assert_eq!(expected.get(i, step), trace.get(i, step));
+        }
+    }
+
+    // decoder registers do not depend on the contents of the stack
+    let body = vec![
+        ProgramBlock::Span(read),
+        Group::new_block(vec![Span::new_block(vec![OpCode::Noop; 15])]),
+    ];
+    let program = build_repeat_program(Repeat::new_block(body, 2));
+    let trace = crate::execute(&program, &inputs).unwrap();
+    let inputs = ProgramInputs::new(&[0], &[0, 0], &[]);
+    let expected = crate::execute(&program, &inputs).unwrap();
+    assert_eq!(expected.width(), trace.width());
+    assert_eq!(expected.length(), trace.length());
+    let decoder_width = TraceState::<BaseElement>::compute_decoder_width(2, 1);
+    for i in 0..decoder_width {
+        for step in 0..trace.length() {
+            assert_eq!(expected.get(i, step), trace.get(i, step));
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Builds a program which reads values from tape A in a loop until a 0 is read.
+fn build_program() -> Program {
+    let mut instructions = vec![OpCode::Noop; 15];
+    instructions[0] = OpCode::Begin;
+    let mut locations = BTreeMap::new();
+    locations.insert(0, SourceLocation::new(1, 1));
+    let first = Span::new(instructions, BTreeMap::new()).with_locations(locations);
+
+    let mut instructions = vec![OpCode::Noop; 15];
+    instructions[0] = OpCode::Assert;
+    instructions[1] = OpCode::Read;
+    let mut locations = BTreeMap::new();
+    locations.insert(0, SourceLocation::new(2, 5));
+    locations.insert(1, SourceLocation::new(3, 5));
+    let body = Span::new(instructions, BTreeMap::new()).with_locations(locations);
+
+    let root = Group::new(vec![
+        ProgramBlock::Span(first),
+        Loop::new_block(vec![ProgramBlock::Span(body)]),
+    ]);
+    Program::new(root)
+}
+
+/// Executes cycles until the specified operation is the next operation to be executed.
+fn step_until(process: &mut Process, op: Operation) {
+    while process.next_operation() != Some(op) {
+        process.step().unwrap();
+    }
+}
+
+/// Builds a program which executes the specified block after the first span.
+fn build_repeat_program(block: ProgramBlock) -> Program {
+    let mut instructions = vec![OpCode::Noop; 15];
+    instructions[0] = OpCode::Begin;
+    Program::new(Group::new(vec![Span::new_block(instructions), block]))
+}
@@ -0,0 +1,218 @@
+use crate::SourceLocation;
+use std::{collections::BTreeMap, fmt};
+
+#[cfg(test)]
+mod tests;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A single frame of a call stack to which VM cycles are attributed by a [Profile].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Frame {
+    /// The program block; this is always the outermost frame.
+    Program,
+    /// A Group block.
+    Group,
+    /// A Switch block; cycles of both branches are attributed to the same frame, but they can
+    /// be told apart by locations of the instructions executed within the branches.
+    Switch,
+    /// A Loop block; cycles of all iterations are attributed to the same frame.
+    Loop,
+    /// A Repeat block; cycles of all iterations are attributed to the same frame.
+    Repeat,
+    /// A Call block.
+    Call,
+    /// A Span block.
+    Span,
+    /// An instruction located at the specified location in the source code; operations of a
+    /// Span block are attributed to instructions only if the span contains source locations.
+    Instruction(SourceLocation),
+    /// Cycles which do not do any useful work, but are needed to maintain the structure of the
+    /// execution trace.
+    Overhead(Overhead),
+}
+
+/// Kinds of VM cycles which are spent on maintaining the structure of the execution trace.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Overhead {
+    /// A BEGIN operation which starts a Group, Switch, Repeat, or Call block.
+    Begin,
+    /// A TEND or FEND operation which ends a block.
+    End,
+    /// A NOOP executed to merge the hash of a block into the program hash; every block is
+    /// closed with HACC_NUM_ROUNDS of such NOOPs.
+    Hash,
+    /// A NOOP inserted before a Span block, or before the end of a block, to align the
+    /// subsequent operation on a cycle boundary.
+    Padding,
+    /// A NOOP within a Span block; these are inserted by the assembler to satisfy the
+    /// alignment requirements of PUSH and hash operations, and to pad Span blocks to a multiple
+    /// of the cycle length.
+    Noop,
+    /// A LOOP operation which enters a loop.
+    Loop,
+    /// A WRAP operation which starts another iteration of a loop.
+    Wrap,
+    /// A BREAK operation which exits a loop.
+    Break,
+}
+
+/// Number of VM cycles spent in every stack of frames reached while executing a program.
+///
+/// A profile is collected by a [Process](crate::Process) created with profiling enabled, and can
+/// be written in the folded stack format which is accepted by flamegraph tools.
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    cycles: BTreeMap<Vec<Frame>, usize>,
+    num_cycles: usize,
+    trace_length: Option<usize>,
+}
+
+// PROFILE IMPLEMENTATION
+// ================================================================================================
+impl Profile {
+    // CONSTRUCTOR
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a new profile with no recorded cycles.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the total number of recorded cycles.
+    pub fn num_cycles(&self) -> usize {
+        self.num_cycles
+    }
+
+    /// Returns the length of the execution trace built for the program, if it is known; the
+    /// difference between the trace length and the number of cycles is spent on padding the
+    /// trace to a power of two.
+    pub fn trace_length(&self) -> Option<usize> {
+        self.trace_length
+    }
+
+    /// Returns the number of cycles recorded within the specified frame, including cycles of
+    /// all frames nested in it.
+    pub fn cycles_in(&self, frame: Frame) -> usize {
+        self.cycles
+            .iter()
+            .filter(|(frames, _)| frames.contains(&frame))
+            .map(|(_, &cycles)| cycles)
+            .sum()
+    }
+
+    /// Returns an iterator over all recorded stacks of frames together with the number of cycles
+    /// spent in each of them; the outermost frame is the first frame of a stack.
+    pub fn iter(&self) -> impl Iterator<Item = (&[Frame], usize)> {
+        self.cycles
+            .iter()
+            .map(|(frames, &cycles)| (frames.as_slice(), cycles))
+    }
+
+    // FOLDED STACKS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns this profile in the folded stack format: every line contains a stack of frames
+    /// separated by semicolons followed by the number of cycles spent in this stack.
+    ///
+    /// If the `source` code of the program is provided, instruction frames are labeled with the
+    /// instructions themselves (e.g. `push.3@4:5`); otherwise, they are labeled with locations
+    /// only. If the trace length is known, cycles spent on padding the trace are reported in a
+    /// separate `[trace padding]` frame.
+    pub fn to_folded(&self, source: Option<&str>) -> String {
+        let lines: Vec<&str> = source
+            .map(|source| source.lines().collect())
+            .unwrap_or_default();
+
+        let mut result = String::new();
+        for (frames, cycles) in self.iter() {
+            let labels: Vec<String> = frames
+                .iter()
+                .map(|frame| match frame {
+                    Frame::Instruction(location) => match get_instruction(&lines, *location) {
+                        Some(instruction) => format!("{}@{}", instruction, location),
+                        None => location.to_string(),
+                    },
+                    _ => frame.to_string(),
+                })
+                .collect();
+            result.push_str(&format!("{} {}\n", labels.join(";"), cycles));
+        }
+
+        if let Some(trace_length) = self.trace_length {
+            if trace_length > self.num_cycles {
+                let padding = trace_length - self.num_cycles;
+                result.push_str(&format!("{};[trace padding] {}\n", Frame::Program, padding));
+            }
+        }
+        result
+    }
+
+    // RECORDING
+    // --------------------------------------------------------------------------------------------
+
+    /// Records a single cycle spent in the specified stack of frames.
+    pub(crate) fn record_cycle(&mut self, frames: Vec<Frame>) {
+        *self.cycles.entry(frames).or_insert(0) += 1;
+        self.num_cycles += 1;
+    }
+
+    /// Sets the length of the execution trace built for the program.
+    pub(crate) fn set_trace_length(&mut self, trace_length: usize) {
+        self.trace_length = Some(trace_length);
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Frame::Program => write!(f, "program"),
+            Frame::Group => write!(f, "block"),
+            Frame::Switch => write!(f, "if"),
+            Frame::Loop => write!(f, "while"),
+            Frame::Repeat => write!(f, "repeat"),
+            Frame::Call => write!(f, "call"),
+            Frame::Span => write!(f, "span"),
+            Frame::Instruction(location) => write!(f, "{}", location),
+            Frame::Overhead(overhead) => write!(f, "[{}]", overhead),
+        }
+    }
+}
+
+impl fmt::Display for Overhead {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Overhead::Begin => write!(f, "begin"),
+            Overhead::End => write!(f, "end"),
+            Overhead::Hash => write!(f, "hash"),
+            Overhead::Padding => write!(f, "padding"),
+            Overhead::Noop => write!(f, "noop"),
+            Overhead::Loop => write!(f, "loop"),
+            Overhead::Wrap => write!(f, "wrap"),
+            Overhead::Break => write!(f, "break"),
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the instruction located at the specified location in the source code split into
+/// `lines`, or None if there is no instruction at this location.
+fn get_instruction(lines: &[&str], location: SourceLocation) -> Option<String> {
+    let line = lines.get(location.line().checked_sub(1)?)?;
+    let instruction: String = line
+        .chars()
+        .skip(location.column().checked_sub(1)?)
+        .take_while(|c| !c.is_whitespace() && *c != '#')
+        .collect();
+    if instruction.is_empty() {
+        None
+    } else {
+        Some(instruction)
+    }
+}
@@ -0,0 +1,97 @@
+use super::{Frame, Overhead};
+use crate::{
+    execute_with_profile, OpCode, Process, Program, ProgramInputs, ProgramLibrary, SourceLocation,
+    HACC_NUM_ROUNDS,
+};
+use std::collections::BTreeMap;
+use vm_core::program::blocks::{Group, Loop, ProgramBlock, Span};
+
+#[test]
+fn profile_cycles() {
+    let program = build_program();
+    let inputs = ProgramInputs::new(&[1], &[1, 0], &[]);
+    let mut process = Process::new(&program, &inputs).with_profiling();
+    process.run().unwrap();
+    let profile = process.profile().unwrap();
+    assert_eq!(process.current_step(), profile.num_cycles());
+
+    // the loop is entered once, iterated twice, and then exited
+    let overhead = |overhead| profile.cycles_in(Frame::Overhead(overhead));
+    assert_eq!(1, overhead(Overhead::Loop));
+    assert_eq!(1, overhead(Overhead::Wrap));
+    assert_eq!(1, overhead(Overhead::Break));
+    assert_eq!(0, overhead(Overhead::Begin));
+
+    // both the loop and the program block are closed
+    assert_eq!(2, overhead(Overhead::End));
+    assert_eq!(2 * HACC_NUM_ROUNDS, overhead(Overhead::Hash));
+
+    // operations are attributed to the instructions to which they belong; NOOPs which pad the
+    // body of the loop are attributed to the READ instruction
+    let assert = Frame::Instruction(SourceLocation::new(2, 5));
+    let read = Frame::Instruction(SourceLocation::new(3, 5));
+    assert_eq!(2, profile.cycles_in(assert));
+    assert_eq!(28, profile.cycles_in(read));
+
+    let folded = profile.to_folded(None);
+    assert!(folded.contains("program;while;span;2:5 2\n"));
+    assert!(folded.contains("program;while;span;3:5 2\n"));
+    assert!(folded.contains("program;while;span;3:5;[noop] 26\n"));
+    assert!(folded.contains("program;while;[wrap] 1\n"));
+    assert!(folded.contains("program;span;[padding] 1\n"));
+    assert!(folded.contains(&format!("program;while;[hash] {}\n", HACC_NUM_ROUNDS)));
+
+    // if the source code is provided, instructions are labeled with their names
+    let source = "begin\n    assert\n    read\n    pad2\nend";
+    let folded = profile.to_folded(Some(source));
+    assert!(folded.contains("program;while;span;read@3:5;[noop] 26\n"));
+    assert!(folded.contains("program;span;pad2@4:5 1\n"));
+}
+
+#[test]
+fn profile_trace_padding() {
+    let program = build_program();
+    let inputs = ProgramInputs::new(&[1], &[1, 0], &[]);
+    let (trace, profile) = execute_with_profile(&program, &inputs, &ProgramLibrary::new()).unwrap();
+    assert_eq!(Some(trace.length()), profile.trace_length());
+
+    let padding = trace.length() - profile.num_cycles();
+    let folded = profile.to_folded(None);
+    assert!(folded.ends_with(&format!("program;[trace padding] {}\n", padding)));
+
+    // profiling is disabled by default
+    let process = Process::new(&program, &inputs);
+    assert!(process.profile().is_none());
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Builds a program which reads values from tape A in a loop until a 0 is read, and then pads
+/// the stack with two zeros.
+fn build_program() -> Program {
+    let mut instructions = vec![OpCode::Noop; 15];
+    instructions[0] = OpCode::Begin;
+    let first = Span::new(instructions, BTreeMap::new());
+
+    let mut instructions = vec![OpCode::Noop; 15];
+    instructions[0] = OpCode::Assert;
+    instructions[1] = OpCode::Read;
+    let mut locations = BTreeMap::new();
+    locations.insert(0, SourceLocation::new(2, 5));
+    locations.insert(1, SourceLocation::new(3, 5));
+    let body = Span::new(instructions, BTreeMap::new()).with_locations(locations);
+
+    let mut instructions = vec![OpCode::Noop; 15];
+    instructions[0] = OpCode::Pad2;
+    let mut locations = BTreeMap::new();
+    locations.insert(0, SourceLocation::new(4, 5));
+    let last = Span::new(instructions, BTreeMap::new()).with_locations(locations);
+
+    let root = Group::new(vec![
+        ProgramBlock::Span(first),
+        Loop::new_block(vec![ProgramBlock::Span(body)]),
+        ProgramBlock::Span(last),
+    ]);
+    Program::new(root)
+}
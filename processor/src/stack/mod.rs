@@ -1,6 +1,7 @@
 use crate::{
-    hasher, BaseElement, FieldElement, OpCode, OpHint, ProgramInputs, StarkField, MAX_STACK_DEPTH,
-    MIN_STACK_DEPTH,
+    errors::{ExecutionError, ExecutionErrorKind, Operation},
+    hasher, BaseElement, FieldElement, Memory, OpCode, OpHint, ProgramInputs, StarkField,
+    StorageNode, StorageState, U32Table, MAX_STACK_DEPTH, MIN_STACK_DEPTH,
 };
 use core::cmp;
 
@@ -13,6 +14,9 @@ pub struct Stack {
     registers: Vec<Vec<BaseElement>>,
     tape_a: Vec<BaseElement>,
     tape_b: Vec<BaseElement>,
+    memory: Memory,
+    storage: StorageState,
+    u32_table: U32Table,
     max_depth: usize,
     depth: usize,
     step: usize,
@@ -48,6 +52,9 @@ impl Stack {
             registers,
             tape_a,
             tape_b,
+            memory: Memory::new(),
+            storage: StorageState::new(inputs.storage().clone()),
+            u32_table: U32Table::new(),
             max_depth: public_inputs.len(),
             depth: public_inputs.len(),
             step: 0,
@@ -55,12 +62,19 @@ impl Stack {
     }
 
     /// Executes `opcode` against the current state of the stack.
-    pub fn execute(&mut self, op_code: OpCode, op_hint: OpHint) {
+    ///
+    /// # Errors
+    /// Returns an error if the operation cannot be executed against the current state of the
+    /// stack; the error contains the state of the stack right before the operation was executed.
+    pub fn execute(&mut self, op_code: OpCode, op_hint: OpHint) -> Result<(), ExecutionError> {
+        // remember stack depth so that stack state can be reported if the operation fails
+        let depth = self.depth;
+
         // increment step pointer and make sure there is enough memory allocated to hold the trace
         self.advance_step();
 
         // execute the appropriate action against the current state of the stack
-        match op_code {
+        let result = match op_code {
             OpCode::Begin => self.op_noop(),
             OpCode::Noop => self.op_noop(),
 
@@ -103,7 +117,17 @@ impl Stack {
             OpCode::BinAcc => self.op_binacc(op_hint),
 
             OpCode::RescR => self.op_rescr(),
-        }
+
+            OpCode::MRoot => self.op_mroot(),
+            OpCode::SRoot => self.op_sroot(),
+
+            OpCode::U32 => self.op_u32(op_hint),
+        };
+
+        result.map_err(|kind| {
+            let stack = self.get_state(self.step - 1, depth);
+            ExecutionError::new(self.step, Operation::User(op_code), kind, stack)
+        })
     }
 
     /// Returns trace length of register traces in the decoder.
@@ -112,16 +136,62 @@ impl Stack {
     }
 
     /// Returns value of the current step pointer.
-    #[cfg(test)]
     pub fn current_step(&self) -> usize {
         self.step
     }
 
+    /// Returns the values currently on the stack; the top of the stack is the first value.
+    pub fn snapshot(&self) -> Vec<BaseElement> {
+        self.get_state(self.step, self.depth)
+    }
+
+    /// Returns the values remaining on secret input tape A in the order in which they will be
+    /// read.
+    pub fn tape_a(&self) -> Vec<BaseElement> {
+        self.tape_a.iter().rev().copied().collect()
+    }
+
+    /// Returns the values remaining on secret input tape B in the order in which they will be
+    /// read.
+    pub fn tape_b(&self) -> Vec<BaseElement> {
+        self.tape_b.iter().rev().copied().collect()
+    }
+
     /// Returns the value at the top of the stack at the current step.
     pub fn get_stack_top(&self) -> BaseElement {
         self.registers[0][self.step]
     }
 
+    /// Builds register traces of the memory root; the traces are empty if the program did not
+    /// access memory.
+    pub fn build_memory_traces(&self) -> Vec<Vec<BaseElement>> {
+        if self.memory.is_empty() {
+            Vec::new()
+        } else {
+            self.memory.build_register_traces(self.trace_length())
+        }
+    }
+
+    /// Builds register traces of the storage root; the traces are empty if the program did not
+    /// access the storage.
+    pub fn build_storage_traces(&self) -> Vec<Vec<BaseElement>> {
+        if self.storage.is_empty() {
+            Vec::new()
+        } else {
+            self.storage.build_register_traces(self.trace_length())
+        }
+    }
+
+    /// Builds register traces of the u32 operations table; the traces are empty if the program
+    /// did not execute any u32 operations.
+    pub fn build_u32_traces(&self) -> Vec<Vec<BaseElement>> {
+        if self.u32_table.is_empty() {
+            Vec::new()
+        } else {
+            self.u32_table.build_register_traces(self.trace_length())
+        }
+    }
+
     /// Populate all register traces with values for steps between the current step
     /// and the end of the trace.
     pub fn finalize_trace(&mut self) {
@@ -143,48 +213,52 @@ impl Stack {
 
     // FLOW CONTROL OPERATIONS
     // --------------------------------------------------------------------------------------------
-    fn op_noop(&mut self) {
+    fn op_noop(&mut self) -> Result<(), ExecutionErrorKind> {
         self.copy_state(0);
+        Ok(())
     }
 
-    fn op_assert(&mut self) {
-        assert!(self.depth >= 1, "stack underflow at step {}", self.step);
+    fn op_assert(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(1)?;
         let value = self.registers[0][self.step - 1];
-        assert!(
-            value == BaseElement::ONE,
-            "ASSERT failed at step {}",
-            self.step
-        );
+        if value != BaseElement::ONE {
+            return Err(ExecutionErrorKind::FailedAssertion);
+        }
         self.shift_left(1, 1);
+        Ok(())
     }
 
-    fn op_asserteq(&mut self) {
-        assert!(self.depth >= 2, "stack underflow at step {}", self.step);
+    fn op_asserteq(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(2)?;
         let x = self.registers[0][self.step - 1];
         let y = self.registers[1][self.step - 1];
-        assert!(x == y, "ASSERTEQ failed at step {}", self.step);
+        if x != y {
+            return Err(ExecutionErrorKind::FailedAssertion);
+        }
         self.shift_left(2, 2);
+        Ok(())
     }
 
     // INPUT OPERATIONS
     // --------------------------------------------------------------------------------------------
-    fn op_push(&mut self, hint: OpHint) {
-        self.shift_right(0, 1);
+    fn op_push(&mut self, hint: OpHint) -> Result<(), ExecutionErrorKind> {
         let op_value = match hint {
             OpHint::PushValue(value) => value,
-            _ => panic!("invalid value for PUSH operation at step {}", self.step),
+            _ => return Err(ExecutionErrorKind::InvalidHint(hint)),
         };
+        self.shift_right(0, 1)?;
         self.registers[0][self.step] = op_value;
+        Ok(())
     }
 
-    fn op_read(&mut self, hint: OpHint) {
+    fn op_read(&mut self, hint: OpHint) -> Result<(), ExecutionErrorKind> {
         // process execution hint
         match hint {
             OpHint::EqStart => {
                 // if we are about to equality comparison sequence, push inverse of the difference
                 // between top two stack values onto secret tape A, if they are equal; otherwise
                 // push value 1
-                assert!(self.depth >= 2, "stack underflow at step {}", self.step);
+                self.check_depth(2)?;
                 let x = self.registers[0][self.step - 1];
                 let y = self.registers[1][self.step - 1];
                 if x == y {
@@ -193,36 +267,69 @@ impl Stack {
                     self.tape_a.push((x - y).inv());
                 }
             }
+            OpHint::StorageRead => {
+                // if we are about to read a value from the storage, push the value and the
+                // authentication path of its leaf onto the tapes
+                self.check_depth(1)?;
+                let key = self.registers[0][self.step - 1];
+                let (value, path) = self.storage.read(key)?;
+                self.push_storage_path(key, &path, false);
+                self.tape_a.push(value);
+            }
+            OpHint::StorageWrite => {
+                // if we are about to write a value into the storage, update the storage, and push
+                // the old value and the authentication path of its leaf onto the tapes
+                self.check_depth(2)?;
+                let key = self.registers[0][self.step - 1];
+                let value = self.registers[1][self.step - 1];
+                let (old_value, path) = self.storage.write(key, value)?;
+                self.push_storage_path(key, &path, true);
+                self.tape_a.push(old_value);
+            }
+            OpHint::MemoryRead => {
+                // memory is read in the same way as the storage
+                self.check_depth(1)?;
+                let addr = self.registers[0][self.step - 1];
+                let (value, path) = self.memory.read(addr)?;
+                self.push_storage_path(addr, &path, false);
+                self.tape_a.push(value);
+            }
+            OpHint::MemoryWrite => {
+                // memory is written in the same way as the storage
+                self.check_depth(2)?;
+                let addr = self.registers[0][self.step - 1];
+                let value = self.registers[1][self.step - 1];
+                let (old_value, path) = self.memory.write(addr, value)?;
+                self.push_storage_path(addr, &path, true);
+                self.tape_a.push(old_value);
+            }
             OpHint::None => {
-                assert!(
-                    !self.tape_a.is_empty(),
-                    "attempt to read from empty tape A at step {}",
-                    self.step
-                );
+                if self.tape_a.is_empty() {
+                    return Err(ExecutionErrorKind::EmptyTapeA);
+                }
             }
-            _ => panic!("execution hint {:?} is not valid for READ operation", hint),
+            _ => return Err(ExecutionErrorKind::InvalidHint(hint)),
         }
 
-        self.shift_right(0, 1);
-        let value = self.tape_a.pop().unwrap();
+        self.shift_right(0, 1)?;
+        let value = self.tape_a.pop().expect("tape A is empty");
         self.registers[0][self.step] = value;
+        Ok(())
     }
 
-    fn op_read2(&mut self, hint: OpHint) {
+    fn op_read2(&mut self, hint: OpHint) -> Result<(), ExecutionErrorKind> {
         // process execution hint
         match hint {
             OpHint::PmpathStart(n) => {
-                assert!(self.depth >= 3, "stack underflow at step {}", self.step);
+                self.check_depth(3)?;
 
                 let n = (n - 1) as usize;
-                assert!(
-                    self.tape_a.len() >= n,
-                    "too few items on tape A for pmpath macro"
-                );
-                assert!(
-                    self.tape_b.len() >= n,
-                    "too few items on tape B for pmpath macro"
-                );
+                if self.tape_a.len() < n {
+                    return Err(ExecutionErrorKind::EmptyTapeA);
+                }
+                if self.tape_b.len() < n {
+                    return Err(ExecutionErrorKind::EmptyTapeB);
+                }
 
                 let idx = self.registers[2][self.step - 1];
 
@@ -240,85 +347,90 @@ impl Stack {
                 }
             }
             OpHint::None => {
-                assert!(
-                    !self.tape_a.is_empty(),
-                    "attempt to read from empty tape A at step {}",
-                    self.step
-                );
-                assert!(
-                    !self.tape_b.is_empty(),
-                    "attempt to read from empty tape B at step {}",
-                    self.step
-                );
+                if self.tape_a.is_empty() {
+                    return Err(ExecutionErrorKind::EmptyTapeA);
+                }
+                if self.tape_b.is_empty() {
+                    return Err(ExecutionErrorKind::EmptyTapeB);
+                }
             }
-            _ => panic!("execution hint {:?} is not valid for READ2 operation", hint),
+            _ => return Err(ExecutionErrorKind::InvalidHint(hint)),
         }
 
-        self.shift_right(0, 2);
-        let value_a = self.tape_a.pop().unwrap();
-        let value_b = self.tape_b.pop().unwrap();
+        self.shift_right(0, 2)?;
+        let value_a = self.tape_a.pop().expect("tape A is empty");
+        let value_b = self.tape_b.pop().expect("tape B is empty");
         self.registers[0][self.step] = value_b;
         self.registers[1][self.step] = value_a;
+        Ok(())
     }
 
     // STACK MANIPULATION OPERATIONS
     // --------------------------------------------------------------------------------------------
-    fn op_dup(&mut self) {
-        assert!(self.depth >= 1, "stack underflow at step {}", self.step);
-        self.shift_right(0, 1);
+    fn op_dup(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(1)?;
+        self.shift_right(0, 1)?;
         self.registers[0][self.step] = self.registers[0][self.step - 1];
+        Ok(())
     }
 
-    fn op_dup2(&mut self) {
-        assert!(self.depth >= 2, "stack underflow at step {}", self.step);
-        self.shift_right(0, 2);
+    fn op_dup2(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(2)?;
+        self.shift_right(0, 2)?;
         self.registers[0][self.step] = self.registers[0][self.step - 1];
         self.registers[1][self.step] = self.registers[1][self.step - 1];
+        Ok(())
     }
 
-    fn op_dup4(&mut self) {
-        assert!(self.depth >= 4, "stack underflow at step {}", self.step);
-        self.shift_right(0, 4);
+    fn op_dup4(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(4)?;
+        self.shift_right(0, 4)?;
         self.registers[0][self.step] = self.registers[0][self.step - 1];
         self.registers[1][self.step] = self.registers[1][self.step - 1];
         self.registers[2][self.step] = self.registers[2][self.step - 1];
         self.registers[3][self.step] = self.registers[3][self.step - 1];
+        Ok(())
     }
 
-    fn op_pad2(&mut self) {
-        self.shift_right(0, 2);
+    fn op_pad2(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.shift_right(0, 2)?;
         self.registers[0][self.step] = BaseElement::ZERO;
         self.registers[1][self.step] = BaseElement::ZERO;
+        Ok(())
     }
 
-    fn op_drop(&mut self) {
-        assert!(self.depth >= 1, "stack underflow at step {}", self.step);
+    fn op_drop(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(1)?;
         self.shift_left(1, 1);
+        Ok(())
     }
 
-    fn op_drop4(&mut self) {
-        assert!(self.depth >= 4, "stack underflow at step {}", self.step);
+    fn op_drop4(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(4)?;
         self.shift_left(4, 4);
+        Ok(())
     }
 
-    fn op_swap(&mut self) {
-        assert!(self.depth >= 2, "stack underflow at step {}", self.step);
+    fn op_swap(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(2)?;
         self.registers[0][self.step] = self.registers[1][self.step - 1];
         self.registers[1][self.step] = self.registers[0][self.step - 1];
         self.copy_state(2);
+        Ok(())
     }
 
-    fn op_swap2(&mut self) {
-        assert!(self.depth >= 4, "stack underflow at step {}", self.step);
+    fn op_swap2(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(4)?;
         self.registers[0][self.step] = self.registers[2][self.step - 1];
         self.registers[1][self.step] = self.registers[3][self.step - 1];
         self.registers[2][self.step] = self.registers[0][self.step - 1];
         self.registers[3][self.step] = self.registers[1][self.step - 1];
         self.copy_state(4);
+        Ok(())
     }
 
-    fn op_swap4(&mut self) {
-        assert!(self.depth >= 8, "stack underflow at step {}", self.step);
+    fn op_swap4(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(8)?;
         self.registers[0][self.step] = self.registers[4][self.step - 1];
         self.registers[1][self.step] = self.registers[5][self.step - 1];
         self.registers[2][self.step] = self.registers[6][self.step - 1];
@@ -328,19 +440,21 @@ impl Stack {
         self.registers[6][self.step] = self.registers[2][self.step - 1];
         self.registers[7][self.step] = self.registers[3][self.step - 1];
         self.copy_state(8);
+        Ok(())
     }
 
-    fn op_roll4(&mut self) {
-        assert!(self.depth >= 4, "stack underflow at step {}", self.step);
+    fn op_roll4(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(4)?;
         self.registers[0][self.step] = self.registers[3][self.step - 1];
         self.registers[1][self.step] = self.registers[0][self.step - 1];
         self.registers[2][self.step] = self.registers[1][self.step - 1];
         self.registers[3][self.step] = self.registers[2][self.step - 1];
         self.copy_state(4);
+        Ok(())
     }
 
-    fn op_roll8(&mut self) {
-        assert!(self.depth >= 8, "stack underflow at step {}", self.step);
+    fn op_roll8(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(8)?;
         self.registers[0][self.step] = self.registers[7][self.step - 1];
         self.registers[1][self.step] = self.registers[0][self.step - 1];
         self.registers[2][self.step] = self.registers[1][self.step - 1];
@@ -350,25 +464,27 @@ impl Stack {
         self.registers[6][self.step] = self.registers[5][self.step - 1];
         self.registers[7][self.step] = self.registers[6][self.step - 1];
         self.copy_state(8);
+        Ok(())
     }
 
     // SELECTION OPERATIONS
     // --------------------------------------------------------------------------------------------
-    fn op_choose(&mut self) {
-        assert!(self.depth >= 3, "stack underflow at step {}", self.step);
+    fn op_choose(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(3)?;
         let condition = self.registers[2][self.step - 1];
         if condition == BaseElement::ONE {
             self.registers[0][self.step] = self.registers[0][self.step - 1];
         } else if condition == BaseElement::ZERO {
             self.registers[0][self.step] = self.registers[1][self.step - 1];
         } else {
-            panic!("CHOOSE on a non-binary condition at step {}", self.step);
+            return Err(ExecutionErrorKind::NonBinaryValue(condition));
         }
         self.shift_left(3, 2);
+        Ok(())
     }
 
-    fn op_choose2(&mut self) {
-        assert!(self.depth >= 6, "stack underflow at step {}", self.step);
+    fn op_choose2(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(6)?;
         let condition = self.registers[4][self.step - 1];
         if condition == BaseElement::ONE {
             self.registers[0][self.step] = self.registers[0][self.step - 1];
@@ -377,13 +493,14 @@ impl Stack {
             self.registers[0][self.step] = self.registers[2][self.step - 1];
             self.registers[1][self.step] = self.registers[3][self.step - 1];
         } else {
-            panic!("CHOOSE2 on a non-binary condition at step {}", self.step);
+            return Err(ExecutionErrorKind::NonBinaryValue(condition));
         }
         self.shift_left(6, 4);
+        Ok(())
     }
 
-    fn op_cswap2(&mut self) {
-        assert!(self.depth >= 6, "stack underflow at step {}", self.step);
+    fn op_cswap2(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(6)?;
         let condition = self.registers[4][self.step - 1];
         if condition == BaseElement::ZERO {
             self.registers[0][self.step] = self.registers[0][self.step - 1];
@@ -396,75 +513,66 @@ impl Stack {
             self.registers[2][self.step] = self.registers[0][self.step - 1];
             self.registers[3][self.step] = self.registers[1][self.step - 1];
         } else {
-            panic!("CSWAP2 on a non-binary condition at step {}", self.step);
+            return Err(ExecutionErrorKind::NonBinaryValue(condition));
         }
         self.shift_left(6, 2);
+        Ok(())
     }
 
     // ARITHMETIC AND BOOLEAN OPERATIONS
     // --------------------------------------------------------------------------------------------
-    fn op_add(&mut self) {
-        assert!(self.depth >= 2, "stack underflow at step {}", self.step);
+    fn op_add(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(2)?;
         let x = self.registers[0][self.step - 1];
         let y = self.registers[1][self.step - 1];
         self.registers[0][self.step] = x + y;
         self.shift_left(2, 1);
+        Ok(())
     }
 
-    fn op_mul(&mut self) {
-        assert!(self.depth >= 2, "stack underflow at step {}", self.step);
+    fn op_mul(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(2)?;
         let x = self.registers[0][self.step - 1];
         let y = self.registers[1][self.step - 1];
         self.registers[0][self.step] = x * y;
         self.shift_left(2, 1);
+        Ok(())
     }
 
-    fn op_inv(&mut self) {
-        assert!(self.depth >= 1, "stack underflow at step {}", self.step);
+    fn op_inv(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(1)?;
         let x = self.registers[0][self.step - 1];
-        assert!(
-            x != BaseElement::ZERO,
-            "cannot compute INV of {} at step {}",
-            BaseElement::ZERO,
-            self.step
-        );
+        if x == BaseElement::ZERO {
+            return Err(ExecutionErrorKind::DivisionByZero);
+        }
         self.registers[0][self.step] = x.inv();
         self.copy_state(1);
+        Ok(())
     }
 
-    fn op_neg(&mut self) {
-        assert!(self.depth >= 1, "stack underflow at step {}", self.step);
+    fn op_neg(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(1)?;
         let x = self.registers[0][self.step - 1];
         self.registers[0][self.step] = -x;
         self.copy_state(1);
+        Ok(())
     }
 
-    fn op_not(&mut self) {
-        assert!(self.depth >= 1, "stack underflow at step {}", self.step);
+    fn op_not(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(1)?;
         let x = self.registers[0][self.step - 1];
-        assert!(
-            is_binary(x),
-            "cannot compute NOT of a non-binary value at step {}",
-            self.step
-        );
+        check_binary(x)?;
         self.registers[0][self.step] = BaseElement::ONE - x;
         self.copy_state(1);
+        Ok(())
     }
 
-    fn op_and(&mut self) {
-        assert!(self.depth >= 2, "stack underflow at step {}", self.step);
+    fn op_and(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(2)?;
         let x = self.registers[0][self.step - 1];
         let y = self.registers[1][self.step - 1];
-        assert!(
-            is_binary(x),
-            "cannot compute AND for a non-binary value at step {}",
-            self.step
-        );
-        assert!(
-            is_binary(y),
-            "cannot compute AND for a non-binary value at step {}",
-            self.step
-        );
+        check_binary(x)?;
+        check_binary(y)?;
 
         self.registers[0][self.step] = if x == BaseElement::ONE && y == BaseElement::ONE {
             BaseElement::ONE
@@ -472,22 +580,15 @@ impl Stack {
             BaseElement::ZERO
         };
         self.shift_left(2, 1);
+        Ok(())
     }
 
-    fn op_or(&mut self) {
-        assert!(self.depth >= 2, "stack underflow at step {}", self.step);
+    fn op_or(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(2)?;
         let x = self.registers[0][self.step - 1];
         let y = self.registers[1][self.step - 1];
-        assert!(
-            is_binary(x),
-            "cannot compute OR for a non-binary value at step {}",
-            self.step
-        );
-        assert!(
-            is_binary(y),
-            "cannot compute OR for a non-binary value at step {}",
-            self.step
-        );
+        check_binary(x)?;
+        check_binary(y)?;
 
         self.registers[0][self.step] = if x == BaseElement::ONE || y == BaseElement::ONE {
             BaseElement::ONE
@@ -495,12 +596,13 @@ impl Stack {
             BaseElement::ZERO
         };
         self.shift_left(2, 1);
+        Ok(())
     }
 
     // COMPARISON OPERATIONS
     // --------------------------------------------------------------------------------------------
-    fn op_eq(&mut self) {
-        assert!(self.depth >= 3, "stack underflow at step {}", self.step);
+    fn op_eq(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(3)?;
         let aux = self.registers[0][self.step - 1];
         let x = self.registers[1][self.step - 1];
         let y = self.registers[2][self.step - 1];
@@ -508,23 +610,22 @@ impl Stack {
             self.registers[0][self.step] = BaseElement::ONE;
         } else {
             let diff = x - y;
-            assert!(
-                aux == diff.inv(),
-                "invalid AUX value for EQ operation at step {}",
-                self.step
-            );
+            if aux != diff.inv() {
+                return Err(ExecutionErrorKind::InvalidAuxValue);
+            }
             self.registers[0][self.step] = BaseElement::ZERO;
         }
         self.shift_left(3, 2);
+        Ok(())
     }
 
-    fn op_cmp(&mut self, hint: OpHint) {
+    fn op_cmp(&mut self, hint: OpHint) -> Result<(), ExecutionErrorKind> {
         // process execution hint
         match hint {
             OpHint::CmpStart(n) => {
                 // if we are about to start comparison sequence, push binary decompositions
                 // of a and b values onto the tapes
-                assert!(self.depth >= 10, "stack underflow at step {}", self.step);
+                self.check_depth(10)?;
                 let a_val = self.registers[8][self.step - 1];
                 let b_val = self.registers[9][self.step - 1];
                 for i in 0..n {
@@ -535,36 +636,22 @@ impl Stack {
                 }
             }
             OpHint::None => {
-                assert!(self.depth >= 8, "stack underflow at step {}", self.step);
-                assert!(
-                    !self.tape_a.is_empty(),
-                    "attempt to read from empty tape A at step {}",
-                    self.step
-                );
-                assert!(
-                    !self.tape_b.is_empty(),
-                    "attempt to read from empty tape B at step {}",
-                    self.step
-                );
+                self.check_depth(8)?;
+                if self.tape_a.is_empty() {
+                    return Err(ExecutionErrorKind::EmptyTapeA);
+                }
+                if self.tape_b.is_empty() {
+                    return Err(ExecutionErrorKind::EmptyTapeB);
+                }
             }
-            _ => panic!("execution hint {:?} is not valid for CMP operation", hint),
+            _ => return Err(ExecutionErrorKind::InvalidHint(hint)),
         }
 
         // get next bits of a and b values from the tapes
-        let a_bit = self.tape_a.pop().unwrap();
-        assert!(
-            a_bit == BaseElement::ZERO || a_bit == BaseElement::ONE,
-            "expected binary input at step {} but received: {}",
-            self.step,
-            a_bit
-        );
-        let b_bit = self.tape_b.pop().unwrap();
-        assert!(
-            b_bit == BaseElement::ZERO || b_bit == BaseElement::ONE,
-            "expected binary input at step {} but received: {}",
-            self.step,
-            b_bit
-        );
+        let a_bit = self.tape_a.pop().expect("tape A is empty");
+        check_binary(a_bit)?;
+        let b_bit = self.tape_b.pop().expect("tape B is empty");
+        check_binary(b_bit)?;
 
         // determine which bit is greater
         let bit_gt = a_bit * (BaseElement::ONE - b_bit);
@@ -572,12 +659,9 @@ impl Stack {
 
         // compute current power of 2 for binary decomposition
         let power_of_two = self.registers[0][self.step - 1];
-        assert!(
-            power_of_two.as_int().is_power_of_two(),
-            "expected top of the stack at step {} to be a power of 2, but received {}",
-            self.step,
-            power_of_two
-        );
+        if !power_of_two.as_int().is_power_of_two() {
+            return Err(ExecutionErrorKind::NotPowerOfTwo(power_of_two));
+        }
         let next_power_of_two = if power_of_two == BaseElement::ONE {
             power_of_two / BaseElement::new(2)
         } else {
@@ -600,15 +684,16 @@ impl Stack {
         self.registers[7][self.step] = self.registers[7][self.step - 1] + a_bit * power_of_two;
 
         self.copy_state(8);
+        Ok(())
     }
 
-    fn op_binacc(&mut self, hint: OpHint) {
+    fn op_binacc(&mut self, hint: OpHint) -> Result<(), ExecutionErrorKind> {
         // process execution hint
         match hint {
             OpHint::RcStart(n) => {
                 // if we are about to start range check sequence, push binary decompositions
                 // of the value onto tape A
-                assert!(self.depth >= 5, "stack underflow at step {}", self.step);
+                self.check_depth(5)?;
                 let val = self.registers[4][self.step - 1];
                 for i in 0..n {
                     // most significant bit is pushed first
@@ -617,33 +702,23 @@ impl Stack {
                 }
             }
             OpHint::None => {
-                assert!(self.depth >= 4, "stack underflow at step {}", self.step);
-                assert!(
-                    !self.tape_a.is_empty(),
-                    "attempt to read from empty tape A at step {}",
-                    self.step
-                );
+                self.check_depth(4)?;
+                if self.tape_a.is_empty() {
+                    return Err(ExecutionErrorKind::EmptyTapeA);
+                }
             }
-            _ => panic!(
-                "execution hint {:?} is not valid for BINACC operation",
-                hint
-            ),
+            _ => return Err(ExecutionErrorKind::InvalidHint(hint)),
         }
 
         // get the next bit of the value from tape A
-        let bit = self.tape_a.pop().unwrap();
-        assert!(
-            bit == BaseElement::ZERO || bit == BaseElement::ONE,
-            "expected binary input at step {} but received: {}",
-            self.step,
-            bit
-        );
+        let bit = self.tape_a.pop().expect("tape A is empty");
+        check_binary(bit)?;
 
         // compute current power of 2 for binary decomposition
         let power_of_two = self.registers[2][self.step - 1];
-        assert!(power_of_two.as_int().is_power_of_two(),
-            "expected 3rd value from the top of the stack at step {} to be a power of 2, but received {}",
-            self.step, power_of_two);
+        if !power_of_two.as_int().is_power_of_two() {
+            return Err(ExecutionErrorKind::NotPowerOfTwo(power_of_two));
+        }
         let next_power_of_two = power_of_two * BaseElement::new(2);
 
         let acc = self.registers[3][self.step - 1];
@@ -655,16 +730,13 @@ impl Stack {
         self.registers[3][self.step] = acc + bit * power_of_two;
 
         self.copy_state(4);
+        Ok(())
     }
 
     // CRYPTOGRAPHIC OPERATIONS
     // --------------------------------------------------------------------------------------------
-    fn op_rescr(&mut self) {
-        assert!(
-            self.depth >= hasher::STATE_WIDTH,
-            "stack underflow at step {}",
-            self.step
-        );
+    fn op_rescr(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.check_depth(hasher::STATE_WIDTH)?;
         let mut state = [
             self.registers[0][self.step - 1],
             self.registers[1][self.step - 1],
@@ -684,11 +756,98 @@ impl Stack {
         self.registers[5][self.step] = state[5];
 
         self.copy_state(hasher::STATE_WIDTH);
+        Ok(())
+    }
+
+    // MEMORY AND STORAGE OPERATIONS
+    // --------------------------------------------------------------------------------------------
+    fn op_mroot(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.swap_root(|stack, root, step| stack.memory.swap_root(root, step))
+    }
+
+    fn op_sroot(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.swap_root(|stack, root, step| stack.storage.swap_root(root, step))
+    }
+
+    /// Swaps the top two items of the stack with a Merkle root; `swap` sets the root of memory
+    /// or of the storage to the specified value starting with the specified step, and returns
+    /// the previous root.
+    fn swap_root<F>(&mut self, swap: F) -> Result<(), ExecutionErrorKind>
+    where
+        F: FnOnce(&mut Self, StorageNode, usize) -> StorageNode,
+    {
+        self.check_depth(2)?;
+        let root = [
+            self.registers[0][self.step - 1],
+            self.registers[1][self.step - 1],
+        ];
+        let old_root = swap(self, root, self.step);
+        self.registers[0][self.step] = old_root[0];
+        self.registers[1][self.step] = old_root[1];
+        self.copy_state(2);
+        Ok(())
+    }
+
+    // U32 OPERATIONS
+    // --------------------------------------------------------------------------------------------
+    fn op_u32(&mut self, hint: OpHint) -> Result<(), ExecutionErrorKind> {
+        let op = match hint {
+            OpHint::U32Op(op) => op,
+            _ => return Err(ExecutionErrorKind::InvalidHint(hint)),
+        };
+        self.check_depth(2)?;
+        let a = to_u32(self.registers[0][self.step - 1])?;
+        let b = to_u32(self.registers[1][self.step - 1])?;
+        let result = self.u32_table.execute(op, a, b, self.step - 1)?;
+        self.registers[0][self.step] = BaseElement::from(result);
+        self.shift_left(2, 1);
+        Ok(())
     }
 
     // HELPER METHODS
     // --------------------------------------------------------------------------------------------
 
+    /// Returns an error if there are fewer than `min_depth` items on the stack.
+    fn check_depth(&self, min_depth: usize) -> Result<(), ExecutionErrorKind> {
+        if self.depth < min_depth {
+            return Err(ExecutionErrorKind::StackUnderflow);
+        }
+        Ok(())
+    }
+
+    /// Returns the top `depth` values of the stack at the specified step.
+    fn get_state(&self, step: usize, depth: usize) -> Vec<BaseElement> {
+        self.registers
+            .iter()
+            .take(depth)
+            .map(|register| register[step])
+            .collect()
+    }
+
+    /// Pushes the authentication path of the leaf with the specified key onto the tapes in the
+    /// order in which `storage.read` and `storage.write` (as well as `mem.load` and `mem.store`)
+    /// instructions consume it.
+    ///
+    /// For every node on the path, the first element of the sibling is read from tape B, and the
+    /// second element from tape A together with the key bit which defines the position of the
+    /// node; write instructions read the bit before the sibling, and read instructions after it.
+    /// Since values are popped from the ends of the tapes, the path is pushed starting with the
+    /// root.
+    fn push_storage_path(&mut self, key: BaseElement, path: &[StorageNode], bit_first: bool) {
+        let key = key.as_int();
+        for (i, sibling) in path.iter().enumerate().rev() {
+            let bit = BaseElement::new((key >> i) & 1);
+            self.tape_b.push(sibling[0]);
+            if bit_first {
+                self.tape_a.push(sibling[1]);
+                self.tape_a.push(bit);
+            } else {
+                self.tape_a.push(bit);
+                self.tape_a.push(sibling[1]);
+            }
+        }
+    }
+
     fn copy_state(&mut self, start: usize) {
         for i in start..self.depth {
             self.registers[i][self.step] = self.registers[i][self.step - 1];
@@ -696,7 +855,7 @@ impl Stack {
     }
 
     fn shift_left(&mut self, start: usize, pos_count: usize) {
-        assert!(
+        debug_assert!(
             self.depth >= pos_count,
             "stack underflow at step {}",
             self.step
@@ -716,16 +875,14 @@ impl Stack {
         self.depth -= pos_count;
     }
 
-    fn shift_right(&mut self, start: usize, pos_count: usize) {
+    fn shift_right(&mut self, start: usize, pos_count: usize) -> Result<(), ExecutionErrorKind> {
+        if self.depth + pos_count > MAX_STACK_DEPTH {
+            return Err(ExecutionErrorKind::StackOverflow);
+        }
         self.depth += pos_count;
-        assert!(
-            self.depth <= MAX_STACK_DEPTH,
-            "stack overflow at step {}",
-            self.step
-        );
 
         if self.depth > self.max_depth {
-            self.max_depth += pos_count;
+            self.max_depth = self.depth;
             if self.max_depth > self.registers.len() {
                 self.add_registers(self.max_depth - self.registers.len());
             }
@@ -734,6 +891,7 @@ impl Stack {
         for i in start..(self.depth - pos_count) {
             self.registers[i + pos_count][self.step] = self.registers[i][self.step - 1];
         }
+        Ok(())
     }
 
     /// Extends the stack by the specified number of registers.
@@ -760,6 +918,20 @@ impl Stack {
 
 // HELPER FUNCTIONS
 // ================================================================================================
-fn is_binary(value: BaseElement) -> bool {
-    value == BaseElement::ZERO || value == BaseElement::ONE
+
+/// Converts the `value` into a 32-bit unsigned integer; returns an error if the value is not
+/// smaller than 2^32.
+fn to_u32(value: BaseElement) -> Result<u32, ExecutionErrorKind> {
+    if value.as_int() > u32::MAX as u128 {
+        return Err(ExecutionErrorKind::NotU32Value(value));
+    }
+    Ok(value.as_int() as u32)
+}
+
+/// Returns an error if the `value` is neither ZERO nor ONE.
+fn check_binary(value: BaseElement) -> Result<(), ExecutionErrorKind> {
+    if value != BaseElement::ZERO && value != BaseElement::ONE {
+        return Err(ExecutionErrorKind::NonBinaryValue(value));
+    }
+    Ok(())
 }
@@ -1,4 +1,6 @@
-use super::{get_stack_state, init_stack, OpCode, OpHint, TRACE_LENGTH};
+use super::{
+    get_stack_state, init_stack, BaseElement, ExecutionErrorKind, OpCode, OpHint, TRACE_LENGTH,
+};
 
 // CHOOSE OPERATIONS
 // ================================================================================================
@@ -7,14 +9,14 @@ use super::{get_stack_state, init_stack, OpCode, OpHint, TRACE_LENGTH};
 fn choose() {
     // choose on false
     let mut stack = init_stack(&[2, 3, 0], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Choose, OpHint::None);
+    stack.execute(OpCode::Choose, OpHint::None).unwrap();
     assert_eq!(vec![3, 0, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(1, stack.depth);
     assert_eq!(3, stack.max_depth);
 
     let mut stack = init_stack(&[2, 3, 0, 4], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Choose, OpHint::None);
+    stack.execute(OpCode::Choose, OpHint::None).unwrap();
     assert_eq!(vec![3, 4, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(2, stack.depth);
@@ -22,7 +24,7 @@ fn choose() {
 
     // choose on true
     let mut stack = init_stack(&[2, 3, 1, 4], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Choose, OpHint::None);
+    stack.execute(OpCode::Choose, OpHint::None).unwrap();
     assert_eq!(vec![2, 4, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(2, stack.depth);
@@ -30,17 +32,21 @@ fn choose() {
 }
 
 #[test]
-#[should_panic(expected = "CHOOSE on a non-binary condition at step 1")]
 fn choose_fail() {
     let mut stack = init_stack(&[2, 3, 4], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Choose, OpHint::None);
+    let err = stack.execute(OpCode::Choose, OpHint::None).unwrap_err();
+    assert_eq!(
+        &ExecutionErrorKind::NonBinaryValue(BaseElement::new(4)),
+        err.kind()
+    );
+    assert_eq!(1, err.step());
 }
 
 #[test]
 fn choose2() {
     // choose on false
     let mut stack = init_stack(&[2, 3, 4, 5, 0, 6, 7], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Choose2, OpHint::None);
+    stack.execute(OpCode::Choose2, OpHint::None).unwrap();
     assert_eq!(vec![4, 5, 7, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(3, stack.depth);
@@ -48,7 +54,7 @@ fn choose2() {
 
     // choose on true
     let mut stack = init_stack(&[2, 3, 4, 5, 1, 6, 7], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Choose2, OpHint::None);
+    stack.execute(OpCode::Choose2, OpHint::None).unwrap();
     assert_eq!(vec![2, 3, 7, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(3, stack.depth);
@@ -56,10 +62,14 @@ fn choose2() {
 }
 
 #[test]
-#[should_panic(expected = "CHOOSE2 on a non-binary condition at step 1")]
 fn choose2_fail() {
     let mut stack = init_stack(&[2, 3, 4, 5, 6, 8, 8], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Choose2, OpHint::None);
+    let err = stack.execute(OpCode::Choose2, OpHint::None).unwrap_err();
+    assert_eq!(
+        &ExecutionErrorKind::NonBinaryValue(BaseElement::new(6)),
+        err.kind()
+    );
+    assert_eq!(1, err.step());
 }
 
 // OTHER CONDITIONAL OPERATIONS
@@ -69,7 +79,7 @@ fn choose2_fail() {
 fn cswap2() {
     // don't swap on false
     let mut stack = init_stack(&[2, 3, 4, 5, 0, 6, 7], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::CSwap2, OpHint::None);
+    stack.execute(OpCode::CSwap2, OpHint::None).unwrap();
     assert_eq!(vec![2, 3, 4, 5, 7, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(5, stack.depth);
@@ -77,7 +87,7 @@ fn cswap2() {
 
     // swap on true
     let mut stack = init_stack(&[2, 3, 4, 5, 1, 6, 7], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::CSwap2, OpHint::None);
+    stack.execute(OpCode::CSwap2, OpHint::None).unwrap();
     assert_eq!(vec![4, 5, 2, 3, 7, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(5, stack.depth);
@@ -85,8 +95,12 @@ fn cswap2() {
 }
 
 #[test]
-#[should_panic(expected = "CSWAP2 on a non-binary condition at step 1")]
 fn cswap2_fail() {
     let mut stack = init_stack(&[2, 3, 4, 5, 6, 8, 8], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::CSwap2, OpHint::None);
+    let err = stack.execute(OpCode::CSwap2, OpHint::None).unwrap_err();
+    assert_eq!(
+        &ExecutionErrorKind::NonBinaryValue(BaseElement::new(6)),
+        err.kind()
+    );
+    assert_eq!(1, err.step());
 }
@@ -1,4 +1,8 @@
-use super::{hasher, BaseElement, FieldElement, OpCode, OpHint, ProgramInputs, Stack, StarkField};
+use super::{
+    hasher, BaseElement, ExecutionErrorKind, FieldElement, OpCode, OpHint, ProgramInputs, Stack,
+    StarkField, MAX_STACK_DEPTH,
+};
+use vm_core::{opcodes::U32Op, storage::Storage};
 
 mod comparisons;
 mod conditional;
@@ -11,7 +15,7 @@ const TRACE_LENGTH: usize = 16;
 #[test]
 fn noop() {
     let mut stack = init_stack(&[1, 2, 3, 4], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Noop, OpHint::None);
+    stack.execute(OpCode::Noop, OpHint::None).unwrap();
     assert_eq!(vec![1, 2, 3, 4, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(4, stack.depth);
@@ -21,7 +25,7 @@ fn noop() {
 #[test]
 fn assert() {
     let mut stack = init_stack(&[1, 2, 3, 4], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Assert, OpHint::None);
+    stack.execute(OpCode::Assert, OpHint::None).unwrap();
     assert_eq!(vec![2, 3, 4, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(3, stack.depth);
@@ -29,16 +33,25 @@ fn assert() {
 }
 
 #[test]
-#[should_panic(expected = "ASSERT failed at step 1")]
 fn assert_fail() {
     let mut stack = init_stack(&[2, 3, 4], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Assert, OpHint::None);
+    let err = stack.execute(OpCode::Assert, OpHint::None).unwrap_err();
+    assert_eq!(&ExecutionErrorKind::FailedAssertion, err.kind());
+    assert_eq!(1, err.step());
+    assert_eq!(
+        &[
+            BaseElement::new(2),
+            BaseElement::new(3),
+            BaseElement::new(4)
+        ],
+        err.stack()
+    );
 }
 
 #[test]
 fn asserteq() {
     let mut stack = init_stack(&[1, 1, 3, 4], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::AssertEq, OpHint::None);
+    stack.execute(OpCode::AssertEq, OpHint::None).unwrap();
     assert_eq!(vec![3, 4, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(2, stack.depth);
@@ -46,10 +59,11 @@ fn asserteq() {
 }
 
 #[test]
-#[should_panic(expected = "ASSERTEQ failed at step 1")]
 fn asserteq_fail() {
     let mut stack = init_stack(&[2, 3, 4], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::AssertEq, OpHint::None);
+    let err = stack.execute(OpCode::AssertEq, OpHint::None).unwrap_err();
+    assert_eq!(&ExecutionErrorKind::FailedAssertion, err.kind());
+    assert_eq!(1, err.step());
 }
 
 // INPUT OPERATIONS
@@ -58,7 +72,9 @@ fn asserteq_fail() {
 #[test]
 fn push() {
     let mut stack = init_stack(&[], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Push, OpHint::PushValue(BaseElement::new(3)));
+    stack
+        .execute(OpCode::Push, OpHint::PushValue(BaseElement::new(3)))
+        .unwrap();
     assert_eq!(vec![3, 0, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(1, stack.depth);
@@ -69,30 +85,38 @@ fn push() {
 fn read() {
     let mut stack = init_stack(&[1], &[2, 3], &[], TRACE_LENGTH);
 
-    stack.execute(OpCode::Read, OpHint::None);
+    stack.execute(OpCode::Read, OpHint::None).unwrap();
     assert_eq!(vec![2, 1, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(2, stack.depth);
     assert_eq!(2, stack.max_depth);
 
-    stack.execute(OpCode::Read, OpHint::None);
+    stack.execute(OpCode::Read, OpHint::None).unwrap();
     assert_eq!(vec![3, 2, 1, 0, 0, 0, 0, 0], get_stack_state(&stack, 2));
 
     assert_eq!(3, stack.depth);
     assert_eq!(3, stack.max_depth);
 }
 
+#[test]
+fn read_empty_tape() {
+    let mut stack = init_stack(&[1], &[], &[], TRACE_LENGTH);
+    let err = stack.execute(OpCode::Read, OpHint::None).unwrap_err();
+    assert_eq!(&ExecutionErrorKind::EmptyTapeA, err.kind());
+    assert_eq!(&[BaseElement::new(1)], err.stack());
+}
+
 #[test]
 fn read2() {
     let mut stack = init_stack(&[1], &[2, 4], &[3, 5], TRACE_LENGTH);
 
-    stack.execute(OpCode::Read2, OpHint::None);
+    stack.execute(OpCode::Read2, OpHint::None).unwrap();
     assert_eq!(vec![3, 2, 1, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(3, stack.depth);
     assert_eq!(3, stack.max_depth);
 
-    stack.execute(OpCode::Read2, OpHint::None);
+    stack.execute(OpCode::Read2, OpHint::None).unwrap();
     assert_eq!(vec![5, 4, 3, 2, 1, 0, 0, 0], get_stack_state(&stack, 2));
 
     assert_eq!(5, stack.depth);
@@ -105,7 +129,7 @@ fn read2() {
 #[test]
 fn dup() {
     let mut stack = init_stack(&[1, 2], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Dup, OpHint::None);
+    stack.execute(OpCode::Dup, OpHint::None).unwrap();
     assert_eq!(vec![1, 1, 2, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(3, stack.depth);
@@ -115,7 +139,7 @@ fn dup() {
 #[test]
 fn dup2() {
     let mut stack = init_stack(&[1, 2, 3, 4], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Dup2, OpHint::None);
+    stack.execute(OpCode::Dup2, OpHint::None).unwrap();
     assert_eq!(vec![1, 2, 1, 2, 3, 4, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(6, stack.depth);
@@ -125,7 +149,7 @@ fn dup2() {
 #[test]
 fn dup4() {
     let mut stack = init_stack(&[1, 2, 3, 4], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Dup4, OpHint::None);
+    stack.execute(OpCode::Dup4, OpHint::None).unwrap();
     assert_eq!(vec![1, 2, 3, 4, 1, 2, 3, 4], get_stack_state(&stack, 1));
 
     assert_eq!(8, stack.depth);
@@ -135,7 +159,7 @@ fn dup4() {
 #[test]
 fn pad2() {
     let mut stack = init_stack(&[1, 2], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Pad2, OpHint::None);
+    stack.execute(OpCode::Pad2, OpHint::None).unwrap();
     assert_eq!(vec![0, 0, 1, 2, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(4, stack.depth);
@@ -145,7 +169,7 @@ fn pad2() {
 #[test]
 fn drop() {
     let mut stack = init_stack(&[1, 2], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Drop, OpHint::None);
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
     assert_eq!(vec![2, 0, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(1, stack.depth);
@@ -155,17 +179,40 @@ fn drop() {
 #[test]
 fn drop4() {
     let mut stack = init_stack(&[1, 2, 3, 4, 5], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Drop4, OpHint::None);
+    stack.execute(OpCode::Drop4, OpHint::None).unwrap();
     assert_eq!(vec![5, 0, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(1, stack.depth);
     assert_eq!(5, stack.max_depth);
 }
 
+#[test]
+fn stack_underflow() {
+    let mut stack = init_stack(&[1, 2, 3], &[], &[], TRACE_LENGTH);
+    let err = stack.execute(OpCode::Drop4, OpHint::None).unwrap_err();
+    assert_eq!(&ExecutionErrorKind::StackUnderflow, err.kind());
+    assert_eq!(1, err.step());
+    assert_eq!(3, err.stack().len());
+}
+
+#[test]
+fn stack_overflow() {
+    let mut stack = init_stack(&[1, 2, 3, 4, 5, 6, 7, 8], &[], &[], TRACE_LENGTH);
+    for _ in 0..6 {
+        stack.execute(OpCode::Dup4, OpHint::None).unwrap();
+    }
+    assert_eq!(MAX_STACK_DEPTH, stack.depth);
+
+    let err = stack.execute(OpCode::Pad2, OpHint::None).unwrap_err();
+    assert_eq!(&ExecutionErrorKind::StackOverflow, err.kind());
+    assert_eq!(7, err.step());
+    assert_eq!(MAX_STACK_DEPTH, err.stack().len());
+}
+
 #[test]
 fn swap() {
     let mut stack = init_stack(&[1, 2, 3, 4], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Swap, OpHint::None);
+    stack.execute(OpCode::Swap, OpHint::None).unwrap();
     assert_eq!(vec![2, 1, 3, 4, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(4, stack.depth);
@@ -175,7 +222,7 @@ fn swap() {
 #[test]
 fn swap2() {
     let mut stack = init_stack(&[1, 2, 3, 4], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Swap2, OpHint::None);
+    stack.execute(OpCode::Swap2, OpHint::None).unwrap();
     assert_eq!(vec![3, 4, 1, 2, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(4, stack.depth);
@@ -185,7 +232,7 @@ fn swap2() {
 #[test]
 fn swap4() {
     let mut stack = init_stack(&[1, 2, 3, 4, 5, 6, 7, 8], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Swap4, OpHint::None);
+    stack.execute(OpCode::Swap4, OpHint::None).unwrap();
     assert_eq!(vec![5, 6, 7, 8, 1, 2, 3, 4], get_stack_state(&stack, 1));
 
     assert_eq!(8, stack.depth);
@@ -195,7 +242,7 @@ fn swap4() {
 #[test]
 fn roll4() {
     let mut stack = init_stack(&[1, 2, 3, 4], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Roll4, OpHint::None);
+    stack.execute(OpCode::Roll4, OpHint::None).unwrap();
     assert_eq!(vec![4, 1, 2, 3, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(4, stack.depth);
@@ -205,7 +252,7 @@ fn roll4() {
 #[test]
 fn roll8() {
     let mut stack = init_stack(&[1, 2, 3, 4, 5, 6, 7, 8], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Roll8, OpHint::None);
+    stack.execute(OpCode::Roll8, OpHint::None).unwrap();
     assert_eq!(vec![8, 1, 2, 3, 4, 5, 6, 7], get_stack_state(&stack, 1));
 
     assert_eq!(8, stack.depth);
@@ -218,7 +265,7 @@ fn roll8() {
 #[test]
 fn add() {
     let mut stack = init_stack(&[1, 2], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Add, OpHint::None);
+    stack.execute(OpCode::Add, OpHint::None).unwrap();
     assert_eq!(vec![3, 0, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(1, stack.depth);
@@ -228,7 +275,7 @@ fn add() {
 #[test]
 fn mul() {
     let mut stack = init_stack(&[2, 3], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Mul, OpHint::None);
+    stack.execute(OpCode::Mul, OpHint::None).unwrap();
     assert_eq!(vec![6, 0, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(1, stack.depth);
@@ -238,7 +285,7 @@ fn mul() {
 #[test]
 fn inv() {
     let mut stack = init_stack(&[2, 3], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Inv, OpHint::None);
+    stack.execute(OpCode::Inv, OpHint::None).unwrap();
     assert_eq!(
         vec![BaseElement::new(2).inv().as_int(), 3, 0, 0, 0, 0, 0, 0],
         get_stack_state(&stack, 1)
@@ -249,16 +296,17 @@ fn inv() {
 }
 
 #[test]
-#[should_panic(expected = "cannot compute INV of 0 at step 1")]
 fn inv_zero() {
     let mut stack = init_stack(&[0], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Inv, OpHint::None);
+    let err = stack.execute(OpCode::Inv, OpHint::None).unwrap_err();
+    assert_eq!(&ExecutionErrorKind::DivisionByZero, err.kind());
+    assert_eq!(1, err.step());
 }
 
 #[test]
 fn neg() {
     let mut stack = init_stack(&[2, 3], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Neg, OpHint::None);
+    stack.execute(OpCode::Neg, OpHint::None).unwrap();
     assert_eq!(
         vec![(-BaseElement::new(2)).as_int(), 3, 0, 0, 0, 0, 0, 0],
         get_stack_state(&stack, 1)
@@ -271,13 +319,13 @@ fn neg() {
 #[test]
 fn not() {
     let mut stack = init_stack(&[1, 2], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Not, OpHint::None);
+    stack.execute(OpCode::Not, OpHint::None).unwrap();
     assert_eq!(vec![0, 2, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(2, stack.depth);
     assert_eq!(2, stack.max_depth);
 
-    stack.execute(OpCode::Not, OpHint::None);
+    stack.execute(OpCode::Not, OpHint::None).unwrap();
     assert_eq!(vec![1, 2, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 2));
 
     assert_eq!(2, stack.depth);
@@ -285,22 +333,26 @@ fn not() {
 }
 
 #[test]
-#[should_panic(expected = "cannot compute NOT of a non-binary value at step 1")]
 fn not_fail() {
     let mut stack = init_stack(&[2, 3], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Not, OpHint::None);
+    let err = stack.execute(OpCode::Not, OpHint::None).unwrap_err();
+    assert_eq!(
+        &ExecutionErrorKind::NonBinaryValue(BaseElement::new(2)),
+        err.kind()
+    );
+    assert_eq!(1, err.step());
 }
 
 #[test]
 fn and() {
     let mut stack = init_stack(&[1, 1, 0], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::And, OpHint::None);
+    stack.execute(OpCode::And, OpHint::None).unwrap();
     assert_eq!(vec![1, 0, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(2, stack.depth);
     assert_eq!(3, stack.max_depth);
 
-    stack.execute(OpCode::And, OpHint::None);
+    stack.execute(OpCode::And, OpHint::None).unwrap();
     assert_eq!(vec![0, 0, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 2));
 
     assert_eq!(1, stack.depth);
@@ -308,22 +360,26 @@ fn and() {
 }
 
 #[test]
-#[should_panic(expected = "cannot compute AND for a non-binary value at step 1")]
 fn and_fail() {
     let mut stack = init_stack(&[1, 3], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::And, OpHint::None);
+    let err = stack.execute(OpCode::And, OpHint::None).unwrap_err();
+    assert_eq!(
+        &ExecutionErrorKind::NonBinaryValue(BaseElement::new(3)),
+        err.kind()
+    );
+    assert_eq!(1, err.step());
 }
 
 #[test]
 fn or() {
     let mut stack = init_stack(&[0, 0, 1], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Or, OpHint::None);
+    stack.execute(OpCode::Or, OpHint::None).unwrap();
     assert_eq!(vec![0, 1, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
 
     assert_eq!(2, stack.depth);
     assert_eq!(3, stack.max_depth);
 
-    stack.execute(OpCode::Or, OpHint::None);
+    stack.execute(OpCode::Or, OpHint::None).unwrap();
     assert_eq!(vec![1, 0, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 2));
 
     assert_eq!(1, stack.depth);
@@ -331,10 +387,14 @@ fn or() {
 }
 
 #[test]
-#[should_panic(expected = "cannot compute OR for a non-binary value at step 1")]
 fn and_or() {
     let mut stack = init_stack(&[1, 3], &[], &[], TRACE_LENGTH);
-    stack.execute(OpCode::Or, OpHint::None);
+    let err = stack.execute(OpCode::Or, OpHint::None).unwrap_err();
+    assert_eq!(
+        &ExecutionErrorKind::NonBinaryValue(BaseElement::new(3)),
+        err.kind()
+    );
+    assert_eq!(1, err.step());
 }
 
 // CRYPTOGRAPHIC OPERATIONS
@@ -348,7 +408,7 @@ fn rescr() {
         .map(BaseElement::new)
         .collect::<Vec<_>>();
 
-    stack.execute(OpCode::RescR, OpHint::None);
+    stack.execute(OpCode::RescR, OpHint::None).unwrap();
     hasher::apply_round(&mut expected[..hasher::STATE_WIDTH], 0);
     assert_eq!(
         expected,
@@ -358,7 +418,7 @@ fn rescr() {
             .collect::<Vec<_>>()
     );
 
-    stack.execute(OpCode::RescR, OpHint::None);
+    stack.execute(OpCode::RescR, OpHint::None).unwrap();
     hasher::apply_round(&mut expected[..hasher::STATE_WIDTH], 1);
     assert_eq!(
         expected,
@@ -372,6 +432,231 @@ fn rescr() {
     assert_eq!(6, stack.max_depth);
 }
 
+// MEMORY OPERATIONS
+// ================================================================================================
+
+#[test]
+fn mroot() {
+    let init_root = Storage::new().root_node();
+    let mut stack = init_stack(&[1, 2, 5], &[], &[], TRACE_LENGTH);
+    assert!(stack.build_memory_traces().is_empty());
+
+    // memory root starts out as the root of an empty tree
+    stack.execute(OpCode::MRoot, OpHint::None).unwrap();
+    let expected = vec![
+        init_root[0].as_int(),
+        init_root[1].as_int(),
+        5,
+        0,
+        0,
+        0,
+        0,
+        0,
+    ];
+    assert_eq!(expected, get_stack_state(&stack, 1));
+
+    assert_eq!(3, stack.depth);
+    assert_eq!(3, stack.max_depth);
+
+    // the root registers hold the new root starting with the step at which it was swapped in
+    let registers = stack.build_memory_traces();
+    assert_eq!(2, registers.len());
+    assert_eq!(init_root[0], registers[0][0]);
+    assert_eq!(BaseElement::new(1), registers[0][1]);
+    assert_eq!(BaseElement::new(2), registers[1][TRACE_LENGTH - 1]);
+    assert!(stack.build_storage_traces().is_empty());
+}
+
+#[test]
+fn memory_read_write() {
+    // writing a value pushes the old value and its authentication path onto the tapes
+    let mut stack = init_stack(&[3, 9], &[], &[], TRACE_LENGTH);
+    stack.execute(OpCode::Read, OpHint::MemoryWrite).unwrap();
+    assert_eq!(vec![0, 3, 9, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
+    assert_eq!(64, stack.tape_a.len());
+    assert_eq!(32, stack.tape_b.len());
+
+    // reading a value pushes the value and its authentication path onto the tapes
+    stack.execute(OpCode::Drop, OpHint::None).unwrap();
+    stack.execute(OpCode::Read, OpHint::MemoryRead).unwrap();
+    assert_eq!(vec![9, 3, 9, 0, 0, 0, 0, 0], get_stack_state(&stack, 3));
+
+    // memory accesses do not touch the storage
+    assert_eq!(
+        BaseElement::ZERO,
+        stack.storage.read(BaseElement::new(3)).unwrap().0
+    );
+
+    // addresses must be 32-bit integers
+    let mut stack = init_stack(&[1 << 32, 1], &[], &[], TRACE_LENGTH);
+    let err = stack.execute(OpCode::Read, OpHint::MemoryRead).unwrap_err();
+    assert_eq!(
+        &ExecutionErrorKind::InvalidMemoryAddress(BaseElement::new(1 << 32)),
+        err.kind()
+    );
+
+    let mut stack = init_stack(&[1 << 32, 1], &[], &[], TRACE_LENGTH);
+    let err = stack
+        .execute(OpCode::Read, OpHint::MemoryWrite)
+        .unwrap_err();
+    assert_eq!(
+        &ExecutionErrorKind::InvalidMemoryAddress(BaseElement::new(1 << 32)),
+        err.kind()
+    );
+
+    let mut stack = init_stack(&[1], &[], &[], TRACE_LENGTH);
+    let err = stack
+        .execute(OpCode::Read, OpHint::MemoryWrite)
+        .unwrap_err();
+    assert_eq!(&ExecutionErrorKind::StackUnderflow, err.kind());
+    assert!(stack.memory.is_empty());
+}
+
+// STORAGE OPERATIONS
+// ================================================================================================
+
+#[test]
+fn sroot() {
+    let tree = Storage::from_values(&[(3, 7)]);
+    let init_root = tree.root_node();
+    let inputs = ProgramInputs::new(&[1, 2, 5], &[], &[]).with_storage(tree);
+    let mut stack = Stack::new(&inputs, TRACE_LENGTH);
+    assert!(stack.build_storage_traces().is_empty());
+
+    stack.execute(OpCode::SRoot, OpHint::None).unwrap();
+    let expected = vec![
+        init_root[0].as_int(),
+        init_root[1].as_int(),
+        5,
+        0,
+        0,
+        0,
+        0,
+        0,
+    ];
+    assert_eq!(expected, get_stack_state(&stack, 1));
+
+    assert_eq!(3, stack.depth);
+    assert_eq!(3, stack.max_depth);
+
+    // the root registers hold the new root starting with the step at which it was swapped in
+    let registers = stack.build_storage_traces();
+    assert_eq!(2, registers.len());
+    assert_eq!(init_root[0], registers[0][0]);
+    assert_eq!(BaseElement::new(1), registers[0][1]);
+    assert_eq!(BaseElement::new(2), registers[1][TRACE_LENGTH - 1]);
+}
+
+#[test]
+fn storage_read_write() {
+    let tree = Storage::from_values(&[(3, 7)]);
+
+    // reading a value pushes the value and its authentication path onto the tapes
+    let inputs = ProgramInputs::new(&[3], &[], &[]).with_storage(tree.clone());
+    let mut stack = Stack::new(&inputs, TRACE_LENGTH);
+    stack.execute(OpCode::Read, OpHint::StorageRead).unwrap();
+    assert_eq!(vec![7, 3, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
+    assert_eq!(64, stack.tape_a.len());
+    assert_eq!(32, stack.tape_b.len());
+
+    // writing a value pushes the old value onto the stack
+    let inputs = ProgramInputs::new(&[3, 9], &[], &[]).with_storage(tree);
+    let mut stack = Stack::new(&inputs, TRACE_LENGTH);
+    stack.execute(OpCode::Read, OpHint::StorageWrite).unwrap();
+    assert_eq!(vec![7, 3, 9, 0, 0, 0, 0, 0], get_stack_state(&stack, 1));
+    assert_eq!(
+        BaseElement::new(9),
+        stack.storage.read(BaseElement::new(3)).unwrap().0
+    );
+
+    // keys must be 32-bit integers
+    let mut stack = init_stack(&[1 << 32], &[], &[], TRACE_LENGTH);
+    let err = stack
+        .execute(OpCode::Read, OpHint::StorageRead)
+        .unwrap_err();
+    assert_eq!(
+        &ExecutionErrorKind::InvalidStorageKey(BaseElement::new(1 << 32)),
+        err.kind()
+    );
+
+    let mut stack = init_stack(&[3], &[], &[], TRACE_LENGTH);
+    let err = stack
+        .execute(OpCode::Read, OpHint::StorageWrite)
+        .unwrap_err();
+    assert_eq!(&ExecutionErrorKind::StackUnderflow, err.kind());
+}
+
+// U32 OPERATIONS
+// ================================================================================================
+
+#[test]
+fn u32_ops() {
+    let max = u32::MAX as u128;
+    let cases = [
+        (U32Op::Add, [3, 5], 8),
+        (U32Op::Add, [2, max], 1),
+        (U32Op::Sub, [3, 5], 2),
+        (U32Op::Sub, [5, 3], max - 1),
+        (U32Op::Mul, [3, 5], 15),
+        (U32Op::Mul, [max, max], 1),
+        (U32Op::Div, [3, 17], 5),
+        (U32Op::And, [0b1100, 0b1010], 0b1000),
+        (U32Op::Xor, [0b1100, 0b1010], 0b0110),
+        (U32Op::Shl, [4, 3], 48),
+        (U32Op::Shl, [31, 3], 1 << 31),
+        (U32Op::Shr, [4, 50], 3),
+    ];
+
+    for &(op, inputs, expected) in cases.iter() {
+        let mut stack = init_stack(&[inputs[0], inputs[1], 7], &[], &[], TRACE_LENGTH);
+        stack.execute(OpCode::U32, OpHint::U32Op(op)).unwrap();
+        assert_eq!(
+            vec![expected, 7, 0, 0, 0, 0, 0, 0],
+            get_stack_state(&stack, 1),
+            "u32{} of {:?}",
+            op,
+            inputs
+        );
+
+        assert_eq!(2, stack.depth);
+        assert_eq!(3, stack.max_depth);
+    }
+}
+
+#[test]
+fn u32_ops_fail() {
+    // operands must be 32-bit integers
+    let mut stack = init_stack(&[1, 1 << 32], &[], &[], TRACE_LENGTH);
+    let err = stack
+        .execute(OpCode::U32, OpHint::U32Op(U32Op::Add))
+        .unwrap_err();
+    assert_eq!(
+        &ExecutionErrorKind::NotU32Value(BaseElement::new(1 << 32)),
+        err.kind()
+    );
+
+    let mut stack = init_stack(&[0, 1], &[], &[], TRACE_LENGTH);
+    let err = stack
+        .execute(OpCode::U32, OpHint::U32Op(U32Op::Div))
+        .unwrap_err();
+    assert_eq!(&ExecutionErrorKind::DivisionByZero, err.kind());
+
+    let mut stack = init_stack(&[32, 1], &[], &[], TRACE_LENGTH);
+    let err = stack
+        .execute(OpCode::U32, OpHint::U32Op(U32Op::Shr))
+        .unwrap_err();
+    assert_eq!(
+        &ExecutionErrorKind::InvalidShiftAmount(BaseElement::new(32)),
+        err.kind()
+    );
+
+    // the operation must be specified by the hint
+    let mut stack = init_stack(&[1, 2], &[], &[], TRACE_LENGTH);
+    let err = stack.execute(OpCode::U32, OpHint::None).unwrap_err();
+    assert_eq!(&ExecutionErrorKind::InvalidHint(OpHint::None), err.kind());
+    assert!(stack.u32_table.is_empty());
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 
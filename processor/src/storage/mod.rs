@@ -0,0 +1,118 @@
+use crate::{
+    errors::ExecutionErrorKind, BaseElement, StarkField, Storage, StorageNode, STORAGE_TRACE_WIDTH,
+};
+
+#[cfg(test)]
+mod tests;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Persistent storage of the VM.
+///
+/// The storage consists of a sparse Merkle tree, which is used to supply values and their
+/// authentication paths to `storage.read` and `storage.write` instructions via execution hints,
+/// and of the root of the tree as seen by the VM. The root changes only when SROOT operation is
+/// executed; all such changes are recorded so that the trace of the root can be built.
+pub struct StorageState {
+    tree: Storage,
+    init_root: StorageNode,
+    roots: Vec<(usize, StorageNode)>,
+}
+
+// STORAGE STATE IMPLEMENTATION
+// ================================================================================================
+impl StorageState {
+    /// Returns a new storage state initialized with the specified tree.
+    pub fn new(tree: Storage) -> StorageState {
+        let init_root = tree.root_node();
+        StorageState {
+            tree,
+            init_root,
+            roots: Vec::new(),
+        }
+    }
+
+    /// Returns true if SROOT operation has never been executed, and thus, the root registers
+    /// do not need to be included into the execution trace.
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Returns the root of the storage as seen by the VM.
+    pub fn root(&self) -> StorageNode {
+        match self.roots.last() {
+            Some((_, root)) => *root,
+            None => self.init_root,
+        }
+    }
+
+    /// Returns the value stored under the specified key together with the authentication path
+    /// of the leaf holding the value.
+    ///
+    /// # Errors
+    /// Returns an error if the key is not a 32-bit unsigned integer.
+    pub fn read(
+        &self,
+        key: BaseElement,
+    ) -> Result<(BaseElement, Vec<StorageNode>), ExecutionErrorKind> {
+        let key = to_storage_key(key)?;
+        Ok((self.tree.get(key), self.tree.get_path(key)))
+    }
+
+    /// Stores the `value` under the specified key, and returns the value previously stored under
+    /// this key together with the authentication path of the leaf holding the value. The path
+    /// authenticates both the old and the new values since updating a leaf does not change its
+    /// siblings.
+    ///
+    /// # Errors
+    /// Returns an error if the key is not a 32-bit unsigned integer.
+    pub fn write(
+        &mut self,
+        key: BaseElement,
+        value: BaseElement,
+    ) -> Result<(BaseElement, Vec<StorageNode>), ExecutionErrorKind> {
+        let key = to_storage_key(key)?;
+        let path = self.tree.get_path(key);
+        Ok((self.tree.set(key, value), path))
+    }
+
+    /// Sets the root of the storage as seen by the VM to `root` starting with the specified step,
+    /// and returns the previous root.
+    pub fn swap_root(&mut self, root: StorageNode, step: usize) -> StorageNode {
+        let old_root = self.root();
+        self.roots.push((step, root));
+        old_root
+    }
+
+    /// Builds register traces of the storage root for an execution trace of `trace_length`
+    /// steps; at every step, the registers hold the root as seen by the VM.
+    pub fn build_register_traces(&self, trace_length: usize) -> Vec<Vec<BaseElement>> {
+        let mut registers: Vec<Vec<BaseElement>> = (0..STORAGE_TRACE_WIDTH)
+            .map(|_| Vec::with_capacity(trace_length))
+            .collect();
+        let mut root = self.init_root;
+        let mut updates = self.roots.iter().peekable();
+        for step in 0..trace_length {
+            while let Some((_, new_root)) = updates.next_if(|(clk, _)| *clk == step) {
+                root = *new_root;
+            }
+            for (register, &value) in registers.iter_mut().zip(root.iter()) {
+                register.push(value);
+            }
+        }
+        registers
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Converts the `value` into a key of the storage; returns an error if the value is not
+/// smaller than 2^32.
+fn to_storage_key(value: BaseElement) -> Result<u32, ExecutionErrorKind> {
+    if value.as_int() > u32::MAX as u128 {
+        return Err(ExecutionErrorKind::InvalidStorageKey(value));
+    }
+    Ok(value.as_int() as u32)
+}
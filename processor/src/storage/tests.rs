@@ -0,0 +1,62 @@
+use super::{BaseElement, ExecutionErrorKind, Storage, StorageState};
+use vm_core::FieldElement;
+
+const TRACE_LENGTH: usize = 8;
+
+#[test]
+fn read_and_write() {
+    let tree = Storage::from_values(&[(3, 7)]);
+    let mut storage = StorageState::new(tree.clone());
+
+    let (value, path) = storage.read(BaseElement::new(3)).unwrap();
+    assert_eq!(BaseElement::new(7), value);
+    assert_eq!(tree.get_path(3), path);
+
+    let (old_value, path) = storage
+        .write(BaseElement::new(3), BaseElement::new(9))
+        .unwrap();
+    assert_eq!(BaseElement::new(7), old_value);
+    assert_eq!(tree.get_path(3), path);
+    assert_eq!(
+        BaseElement::new(9),
+        storage.read(BaseElement::new(3)).unwrap().0
+    );
+
+    // the root as seen by the VM changes only when it is swapped
+    assert_eq!(tree.root_node(), storage.root());
+
+    // keys must be 32-bit integers
+    let key = BaseElement::new(u32::MAX as u128 + 1);
+    assert_eq!(
+        Err(ExecutionErrorKind::InvalidStorageKey(key)),
+        storage.read(key)
+    );
+    assert_eq!(
+        Err(ExecutionErrorKind::InvalidStorageKey(key)),
+        storage.write(key, BaseElement::ONE)
+    );
+}
+
+#[test]
+fn build_register_traces() {
+    let tree = Storage::from_values(&[(3, 7)]);
+    let init_root = tree.root_node();
+    let mut storage = StorageState::new(tree);
+    assert!(storage.is_empty());
+
+    let root1 = [BaseElement::new(1), BaseElement::new(2)];
+    let root2 = [BaseElement::new(3), BaseElement::new(4)];
+    assert_eq!(init_root, storage.swap_root(root1, 2));
+    assert_eq!(root1, storage.swap_root(root2, 5));
+    assert_eq!(root2, storage.root());
+    assert!(!storage.is_empty());
+
+    let registers = storage.build_register_traces(TRACE_LENGTH);
+    let expected = [
+        init_root, init_root, root1, root1, root1, root2, root2, root2,
+    ];
+    for (i, register) in registers.iter().enumerate() {
+        let expected = expected.iter().map(|root| root[i]).collect::<Vec<_>>();
+        assert_eq!(&expected, register);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{
+    errors::ExecutionErrorKind, opcodes::U32Op, BaseElement, FieldElement, U32_A_BITS_RANGE,
+    U32_BITS, U32_B_BITS_RANGE, U32_C_BITS_RANGE, U32_D_BITS_RANGE, U32_E_BITS_RANGE,
+    U32_POWERS_RANGE, U32_SELECTORS_RANGE, U32_TRACE_WIDTH,
+};
+use core::ops::Range;
+
+#[cfg(test)]
+mod tests;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Table of operations on 32-bit unsigned integers executed by the VM.
+///
+/// Every operation is recorded together with its operands, its result, and auxiliary values
+/// which prove that the result was computed correctly (e.g., carries of additions, or remainders
+/// of divisions). In the execution trace, all of these values are decomposed into bits; this
+/// range-checks them to be 32-bit integers, and thus, every operation can be verified within a
+/// single step.
+#[derive(Default)]
+pub struct U32Table {
+    rows: Vec<U32Row>,
+}
+
+/// A single u32 operation executed at step `clk`; `a` is the operand at the top of the stack,
+/// `b` is the operand right below it, `c` is the result, and `d` and `e` are auxiliary values.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct U32Row {
+    clk: usize,
+    op: U32Op,
+    a: u32,
+    b: u32,
+    c: u32,
+    d: u32,
+    e: u32,
+}
+
+// U32 TABLE IMPLEMENTATION
+// ================================================================================================
+impl U32Table {
+    /// Returns a new empty table.
+    pub fn new() -> U32Table {
+        U32Table::default()
+    }
+
+    /// Executes the specified operation against operands `a` and `b`, records it as executed at
+    /// step `clk`, and returns the result of the operation.
+    ///
+    /// All operations are applied to `b` with `a` as the second operand: SUB computes b - a,
+    /// DIV computes b / a, and shifts shift `b` by `a` bits. ADD, SUB, MUL, and SHL discard the
+    /// bits which do not fit into 32 bits.
+    ///
+    /// # Errors
+    /// Returns an error if `a` is zero for DIV operation, or if `a` is greater than 31 for shift
+    /// operations.
+    pub fn execute(
+        &mut self,
+        op: U32Op,
+        a: u32,
+        b: u32,
+        clk: usize,
+    ) -> Result<u32, ExecutionErrorKind> {
+        let (c, d, e) = match op {
+            U32Op::Add => {
+                let (c, carry) = b.overflowing_add(a);
+                (c, carry as u32, 0)
+            }
+            U32Op::Sub => {
+                let (c, borrow) = b.overflowing_sub(a);
+                (c, borrow as u32, 0)
+            }
+            U32Op::Mul => split(a as u64 * b as u64),
+            U32Op::Div => {
+                if a == 0 {
+                    return Err(ExecutionErrorKind::DivisionByZero);
+                }
+                // the remainder is smaller than the divisor: a - d - 1 = e for some u32 e
+                let d = b % a;
+                (b / a, d, a - d - 1)
+            }
+            U32Op::And => (a & b, 0, 0),
+            U32Op::Xor => (a ^ b, 0, 0),
+            U32Op::Shl => {
+                check_shift(a)?;
+                split((b as u64) << a)
+            }
+            U32Op::Shr => {
+                check_shift(a)?;
+                // the shifted out bits are smaller than 2^a: 2^a - d - 1 = e for some u32 e
+                let d = b & ((1 << a) - 1);
+                (b >> a, d, (1 << a) - d - 1)
+            }
+        };
+
+        self.rows.push(U32Row {
+            clk,
+            op,
+            a,
+            b,
+            c,
+            d,
+            e,
+        });
+        Ok(c)
+    }
+
+    /// Returns true if no operations have been executed yet.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Builds register traces of the table for an execution trace of `trace_length` steps.
+    ///
+    /// The traces consist of the following registers:
+    /// * selectors of the executed operation; at steps which execute a u32 operation, exactly
+    ///   one of the selectors is set to one, and at all other steps, all selectors are zeros;
+    /// * binary decompositions of the operands, the result, and the auxiliary values of the
+    ///   operation, least significant bit first; these are zeros at all other steps;
+    /// * products of powers of two selected by the lower 5 bits of `a`; the last of them is
+    ///   set to 2^a for shift operations.
+    pub fn build_register_traces(&self, trace_length: usize) -> Vec<Vec<BaseElement>> {
+        let mut registers = vec![vec![BaseElement::ZERO; trace_length]; U32_TRACE_WIDTH];
+        for i in U32_POWERS_RANGE {
+            registers[i] = vec![BaseElement::ONE; trace_length];
+        }
+
+        for row in self.rows.iter() {
+            let step = row.clk;
+            registers[U32_SELECTORS_RANGE.start + row.op.selector_index()][step] = BaseElement::ONE;
+
+            set_bits(&mut registers, U32_A_BITS_RANGE, row.a, step);
+            set_bits(&mut registers, U32_B_BITS_RANGE, row.b, step);
+            set_bits(&mut registers, U32_C_BITS_RANGE, row.c, step);
+            set_bits(&mut registers, U32_D_BITS_RANGE, row.d, step);
+            set_bits(&mut registers, U32_E_BITS_RANGE, row.e, step);
+
+            let powers = get_powers(row.a);
+            for (i, &power) in U32_POWERS_RANGE.zip(powers.iter()) {
+                registers[i][step] = BaseElement::new(power as u128);
+            }
+        }
+
+        registers
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Splits a 64-bit value into its lower and upper 32 bits; the third returned value is zero.
+fn split(value: u64) -> (u32, u32, u32) {
+    (value as u32, (value >> U32_BITS) as u32, 0)
+}
+
+/// Returns an error if `a` is not a valid shift amount for a 32-bit integer.
+fn check_shift(a: u32) -> Result<(), ExecutionErrorKind> {
+    if a >= U32_BITS as u32 {
+        return Err(ExecutionErrorKind::InvalidShiftAmount(BaseElement::from(a)));
+    }
+    Ok(())
+}
+
+/// Writes binary decomposition of the `value` into the specified `registers` at the specified
+/// `step`, least significant bit first.
+fn set_bits(registers: &mut [Vec<BaseElement>], range: Range<usize>, value: u32, step: usize) {
+    for (i, register) in range.enumerate() {
+        registers[register][step] = BaseElement::from((value >> i) & 1);
+    }
+}
+
+/// Returns partial products of powers of two selected by the lower 5 bits of `a`: the first
+/// value covers bits 0 and 1, the second one bits 0 through 3, and the last one all 5 bits, and
+/// thus, is equal to 2^(a mod 32).
+fn get_powers(a: u32) -> [u64; 3] {
+    let p0 = 1u64 << (a & 0b11);
+    let p1 = p0 << (a & 0b1100);
+    let p2 = p1 << (a & 0b10000);
+    [p0, p1, p2]
+}
@@ -0,0 +1,111 @@
+use super::{
+    BaseElement, ExecutionErrorKind, FieldElement, U32Op, U32Table, U32_A_BITS_RANGE,
+    U32_B_BITS_RANGE, U32_C_BITS_RANGE, U32_D_BITS_RANGE, U32_E_BITS_RANGE, U32_POWERS_RANGE,
+    U32_SELECTORS_RANGE, U32_TRACE_WIDTH,
+};
+use core::ops::Range;
+use vm_core::StarkField;
+
+const TRACE_LENGTH: usize = 16;
+
+#[test]
+fn execute() {
+    let mut table = U32Table::new();
+    assert!(table.is_empty());
+
+    assert_eq!(Ok(1), table.execute(U32Op::Add, 2, u32::MAX, 0));
+    assert_eq!(Ok(u32::MAX), table.execute(U32Op::Sub, 1, 0, 1));
+    assert_eq!(
+        Ok(0xfffe_0001),
+        table.execute(U32Op::Mul, 0xffff, 0xffff, 2)
+    );
+    assert_eq!(Ok(3), table.execute(U32Op::Div, 7, 23, 3));
+    assert_eq!(Ok(0b0100), table.execute(U32Op::And, 0b0110, 0b1100, 4));
+    assert_eq!(Ok(0b1010), table.execute(U32Op::Xor, 0b0110, 0b1100, 5));
+    assert_eq!(Ok(0x8000_0000), table.execute(U32Op::Shl, 31, 0xff, 6));
+    assert_eq!(Ok(0xff), table.execute(U32Op::Shr, 24, 0xff12_3456, 7));
+    assert!(!table.is_empty());
+
+    // failed operations are not recorded
+    assert_eq!(
+        Err(ExecutionErrorKind::DivisionByZero),
+        table.execute(U32Op::Div, 0, 1, 8)
+    );
+    assert_eq!(
+        Err(ExecutionErrorKind::InvalidShiftAmount(BaseElement::new(32))),
+        table.execute(U32Op::Shl, 32, 1, 8)
+    );
+    assert_eq!(8, table.rows.len());
+}
+
+#[test]
+fn build_register_traces() {
+    let mut table = U32Table::new();
+    table.execute(U32Op::Add, 2, u32::MAX, 1).unwrap();
+    table.execute(U32Op::Sub, 5, 3, 2).unwrap();
+    table.execute(U32Op::Mul, u32::MAX, 3, 3).unwrap();
+    table.execute(U32Op::Div, 7, 23, 4).unwrap();
+    table.execute(U32Op::And, 0b0110, 0b1100, 5).unwrap();
+    table.execute(U32Op::Xor, 0b0110, 0b1100, 6).unwrap();
+    table.execute(U32Op::Shl, 29, 0xff, 7).unwrap();
+    table.execute(U32Op::Shr, 21, 0xff12_3456, 8).unwrap();
+
+    let registers = table.build_register_traces(TRACE_LENGTH);
+    assert_eq!(U32_TRACE_WIDTH, registers.len());
+
+    // steps which do not execute u32 operations have all selectors and bits set to zeros
+    for &step in [0, 9, TRACE_LENGTH - 1].iter() {
+        for register in registers[U32_SELECTORS_RANGE.start..U32_E_BITS_RANGE.end].iter() {
+            assert_eq!(BaseElement::ZERO, register[step]);
+        }
+        for register in registers[U32_POWERS_RANGE].iter() {
+            assert_eq!(BaseElement::ONE, register[step]);
+        }
+    }
+
+    // selectors and values of each operation satisfy the relations of the operation
+    let two_32 = 1u128 << 32;
+    for row in table.rows.iter() {
+        let step = row.clk;
+        let selector = U32_SELECTORS_RANGE.start + row.op.selector_index();
+        for register in U32_SELECTORS_RANGE {
+            let expected = (register == selector) as u128;
+            assert_eq!(expected, registers[register][step].as_int());
+        }
+
+        let a = get_value(&registers, U32_A_BITS_RANGE, step);
+        let b = get_value(&registers, U32_B_BITS_RANGE, step);
+        let c = get_value(&registers, U32_C_BITS_RANGE, step);
+        let d = get_value(&registers, U32_D_BITS_RANGE, step);
+        let e = get_value(&registers, U32_E_BITS_RANGE, step);
+        let power = registers[U32_POWERS_RANGE.end - 1][step].as_int();
+        assert_eq!(1 << (a % 32), power);
+
+        match row.op {
+            U32Op::Add => assert_eq!(a + b, c + d * two_32),
+            U32Op::Sub => assert_eq!(b + d * two_32, a + c),
+            U32Op::Mul => assert_eq!(a * b, c + d * two_32),
+            U32Op::Div => {
+                assert_eq!(b, c * a + d);
+                assert_eq!(a, d + e + 1);
+            }
+            U32Op::And => assert_eq!(a & b, c),
+            U32Op::Xor => assert_eq!(a ^ b, c),
+            U32Op::Shl => assert_eq!(b * power, c + d * two_32),
+            U32Op::Shr => {
+                assert_eq!(b, c * power + d);
+                assert_eq!(power, d + e + 1);
+            }
+        }
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the value of the binary decomposition held in the specified registers.
+fn get_value(registers: &[Vec<BaseElement>], range: Range<usize>, step: usize) -> u128 {
+    range.rev().fold(0, |value, register| {
+        (value << 1) | registers[register][step].as_int()
+    })
+}
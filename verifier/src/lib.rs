@@ -1,9 +1,12 @@
-use air::{ProcessorAir, PublicInputs};
+use air::{ProcessorAir, PublicInputs, Storage};
+
+mod proof;
 
 // EXPORTS
 // ================================================================================================
 
 pub use assembly;
+pub use proof::{ExecutionProof, ExecutionProofError, PROOF_FORMAT_VERSION};
 pub use winterfell::{StarkProof, VerifierError};
 
 // VERIFIER
@@ -13,15 +16,73 @@ pub use winterfell::{StarkProof, VerifierError};
 ///
 /// Specifically, verifies that if a program with the specified `program_hash` is executed with the
 /// provided `public_inputs` and some secret inputs, and the result is equal to the `outputs`.
+/// The program is assumed to start and to end with empty storage; use [verify_with_storage()]
+/// for programs which access the storage.
 ///
 /// # Errors
-/// Returns an error if the provided proof does not prove a correct execution of the program.
+/// Returns an error if the provided proof does not prove a correct execution of the program, or
+/// if the program hash or any of the inputs or outputs is not a valid field element.
 pub fn verify(
     program_hash: [u8; 32],
     public_inputs: &[u128],
     outputs: &[u128],
     proof: StarkProof,
 ) -> Result<(), VerifierError> {
-    let pub_inputs = PublicInputs::new(program_hash, public_inputs, outputs);
+    let empty_root = Storage::new().root();
+    verify_with_storage(
+        program_hash,
+        public_inputs,
+        outputs,
+        empty_root,
+        empty_root,
+        proof,
+    )
+}
+
+/// Returns Ok(()) if the specified program was executed correctly against the specified inputs
+/// and outputs, and updated the storage from the state with `init_storage_root` to the state
+/// with `final_storage_root`.
+///
+/// # Errors
+/// Returns an error if the provided proof does not prove a correct execution of the program, or
+/// if the program hash, any of the inputs or outputs, or either of the storage roots is not a
+/// valid field element.
+pub fn verify_with_storage(
+    program_hash: [u8; 32],
+    public_inputs: &[u128],
+    outputs: &[u128],
+    init_storage_root: [u8; 32],
+    final_storage_root: [u8; 32],
+    proof: StarkProof,
+) -> Result<(), VerifierError> {
+    let pub_inputs = PublicInputs::new(program_hash, public_inputs, outputs)
+        .and_then(|inputs| inputs.with_storage_roots(init_storage_root, final_storage_root))
+        .map_err(|err| VerifierError::ProofDeserializationError(err.to_string()))?;
     winterfell::verify::<ProcessorAir>(proof, pub_inputs)
 }
+
+/// Returns Ok(()) if the specified execution proof is valid.
+///
+/// Specifically, verifies that the program with the hash recorded in the `proof` was executed
+/// with the public inputs recorded in the `proof`, and that the execution resulted in the
+/// recorded outputs and storage roots. Note that this does not check whether the hash belongs
+/// to a specific program; the caller is responsible for checking [ExecutionProof::program_hash()]
+/// if only certain programs should be accepted.
+///
+/// # Errors
+/// Returns an error if the STARK proof does not prove a correct execution of the program.
+pub fn verify_bundle(proof: ExecutionProof) -> Result<(), VerifierError> {
+    let program_hash = *proof.program_hash();
+    let public_inputs = proof.public_inputs().to_vec();
+    let outputs = proof.outputs().to_vec();
+    let init_storage_root = *proof.init_storage_root();
+    let final_storage_root = *proof.final_storage_root();
+    verify_with_storage(
+        program_hash,
+        &public_inputs,
+        &outputs,
+        init_storage_root,
+        final_storage_root,
+        proof.into_stark_proof(),
+    )
+}
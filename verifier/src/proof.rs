@@ -0,0 +1,278 @@
+use air::{
+    BaseElement, ByteReader, ByteWriter, Deserializable, DeserializationError, ProofOptions,
+    Serializable, StarkField, Storage, MAX_OUTPUTS, MAX_PUBLIC_INPUTS,
+};
+use core::{
+    convert::{TryFrom, TryInto},
+    fmt,
+};
+use winterfell::StarkProof;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Version of the binary format used for serializing execution proofs; this must be incremented
+/// every time the format changes.
+pub const PROOF_FORMAT_VERSION: u8 = 1;
+
+// EXECUTION PROOF
+// ================================================================================================
+
+/// A proof of program execution bundled together with everything needed to verify it.
+///
+/// Besides the STARK proof itself, the bundle contains the hash of the executed program, public
+/// inputs and outputs of the program, roots of the storage before and after the execution, and
+/// the options with which the proof was generated.
+#[derive(Clone)]
+pub struct ExecutionProof {
+    program_hash: [u8; 32],
+    public_inputs: Vec<u128>,
+    outputs: Vec<u128>,
+    init_storage_root: [u8; 32],
+    final_storage_root: [u8; 32],
+    options: ProofOptions,
+    proof: StarkProof,
+}
+
+impl ExecutionProof {
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns a new execution proof for a program with the specified hash which was executed
+    /// against the specified public inputs and produced the specified outputs.
+    ///
+    /// The program is assumed to start and to end with empty storage; use
+    /// [ExecutionProof::with_storage_roots()] for programs which access the storage.
+    ///
+    /// # Errors
+    /// Returns an error if there are more than [MAX_PUBLIC_INPUTS] public inputs, or more than
+    /// [MAX_OUTPUTS] outputs.
+    pub fn new(
+        program_hash: [u8; 32],
+        public_inputs: &[u128],
+        outputs: &[u128],
+        proof: StarkProof,
+    ) -> Result<Self, ExecutionProofError> {
+        if public_inputs.len() > MAX_PUBLIC_INPUTS {
+            return Err(ExecutionProofError::TooManyPublicInputs(
+                public_inputs.len(),
+            ));
+        }
+        if outputs.len() > MAX_OUTPUTS {
+            return Err(ExecutionProofError::TooManyOutputs(outputs.len()));
+        }
+
+        let empty_root = Storage::new().root();
+        Ok(ExecutionProof {
+            program_hash,
+            public_inputs: public_inputs.to_vec(),
+            outputs: outputs.to_vec(),
+            init_storage_root: empty_root,
+            final_storage_root: empty_root,
+            options: proof.options().clone().into(),
+            proof,
+        })
+    }
+
+    /// Returns this proof with the roots of the storage before and after the execution of the
+    /// program set to the specified values.
+    pub fn with_storage_roots(mut self, init_root: [u8; 32], final_root: [u8; 32]) -> Self {
+        self.init_storage_root = init_root;
+        self.final_storage_root = final_root;
+        self
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns the hash of the executed program.
+    pub fn program_hash(&self) -> &[u8; 32] {
+        &self.program_hash
+    }
+
+    /// Returns public inputs against which the program was executed.
+    pub fn public_inputs(&self) -> &[u128] {
+        &self.public_inputs
+    }
+
+    /// Returns outputs produced by the program.
+    pub fn outputs(&self) -> &[u128] {
+        &self.outputs
+    }
+
+    /// Returns the root of the storage before the program was executed.
+    pub fn init_storage_root(&self) -> &[u8; 32] {
+        &self.init_storage_root
+    }
+
+    /// Returns the root of the storage after the program was executed.
+    pub fn final_storage_root(&self) -> &[u8; 32] {
+        &self.final_storage_root
+    }
+
+    /// Returns the options with which the proof was generated.
+    pub fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+
+    /// Returns the STARK proof of the execution.
+    pub fn stark_proof(&self) -> &StarkProof {
+        &self.proof
+    }
+
+    /// Converts this bundle into the STARK proof of the execution.
+    pub fn into_stark_proof(self) -> StarkProof {
+        self.proof
+    }
+}
+
+// EXECUTION PROOF ERROR
+// ================================================================================================
+
+/// Describes why an execution proof could not be constructed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExecutionProofError {
+    /// The number of public inputs is greater than [MAX_PUBLIC_INPUTS].
+    TooManyPublicInputs(usize),
+    /// The number of outputs is greater than [MAX_OUTPUTS].
+    TooManyOutputs(usize),
+}
+
+impl fmt::Display for ExecutionProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionProofError::TooManyPublicInputs(num_inputs) => write!(
+                f,
+                "expected no more than {} public inputs, but received {}",
+                MAX_PUBLIC_INPUTS, num_inputs
+            ),
+            ExecutionProofError::TooManyOutputs(num_outputs) => write!(
+                f,
+                "expected no more than {} outputs, but received {}",
+                MAX_OUTPUTS, num_outputs
+            ),
+        }
+    }
+}
+
+// SERIALIZATION
+// ================================================================================================
+
+impl Serializable for ExecutionProof {
+    /// Serializes this proof as the format version, followed by the program hash, public inputs,
+    /// outputs, storage roots, proof options, and then the STARK proof itself.
+    fn write_into<W: ByteWriter>(&self, target: &mut W) {
+        target.write_u8(PROOF_FORMAT_VERSION);
+        target.write_u8_slice(&self.program_hash);
+        write_values(target, &self.public_inputs);
+        write_values(target, &self.outputs);
+        target.write_u8_slice(&self.init_storage_root);
+        target.write_u8_slice(&self.final_storage_root);
+        self.options.write_into(target);
+
+        let proof_bytes = self.proof.to_bytes();
+        target.write_u32(proof_bytes.len() as u32);
+        target.write_u8_slice(&proof_bytes);
+    }
+}
+
+impl Deserializable for ExecutionProof {
+    /// Reads an execution proof from the `source`, makes sure all hashes and values are valid
+    /// field elements, and that the proof options recorded in the `source` are the same as the
+    /// options of the STARK proof.
+    fn read_from<R: ByteReader>(source: &mut R) -> Result<Self, DeserializationError> {
+        let version = source.read_u8()?;
+        if version != PROOF_FORMAT_VERSION {
+            return Err(DeserializationError::InvalidValue(format!(
+                "unsupported proof format version: expected {}, but was {}",
+                PROOF_FORMAT_VERSION, version
+            )));
+        }
+
+        let program_hash = read_digest(source)?;
+        let public_inputs = read_values(source, MAX_PUBLIC_INPUTS)?;
+        let outputs = read_values(source, MAX_OUTPUTS)?;
+        let init_storage_root = read_digest(source)?;
+        let final_storage_root = read_digest(source)?;
+        let options = ProofOptions::read_from(source)?;
+
+        let proof_len = source.read_u32()? as usize;
+        let proof = StarkProof::from_bytes(&source.read_u8_vec(proof_len)?)?;
+        if *proof.options() != *options {
+            return Err(DeserializationError::InvalidValue(
+                "proof options do not match the options of the STARK proof".to_string(),
+            ));
+        }
+
+        Ok(ExecutionProof {
+            program_hash,
+            public_inputs,
+            outputs,
+            init_storage_root,
+            final_storage_root,
+            options,
+            proof,
+        })
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Writes the number of values followed by the values themselves; each value is written as 16
+/// bytes in little-endian byte order.
+///
+/// # Panics
+/// Panics if there are more than 255 values; this cannot happen for values of an execution proof
+/// since the number of its inputs and outputs is checked on construction.
+fn write_values<W: ByteWriter>(target: &mut W, values: &[u128]) {
+    let num_values = u8::try_from(values.len()).expect("too many values to serialize");
+    target.write_u8(num_values);
+    for value in values {
+        target.write_u8_slice(&value.to_le_bytes());
+    }
+}
+
+/// Reads a list of values written by [write_values()]; returns an error if there are more than
+/// `max_values` values, or if any of the values is not a valid field element.
+fn read_values<R: ByteReader>(
+    source: &mut R,
+    max_values: usize,
+) -> Result<Vec<u128>, DeserializationError> {
+    let num_values = source.read_u8()? as usize;
+    if num_values > max_values {
+        return Err(DeserializationError::InvalidValue(format!(
+            "expected no more than {} values, but was {}",
+            max_values, num_values
+        )));
+    }
+
+    let mut values = Vec::with_capacity(num_values);
+    for _ in 0..num_values {
+        let bytes: [u8; 16] = source.read_u8_vec(16)?.try_into().unwrap();
+        let value = u128::from_le_bytes(bytes);
+        if value >= BaseElement::MODULUS {
+            return Err(DeserializationError::InvalidValue(format!(
+                "value {} is not a valid field element",
+                value
+            )));
+        }
+        values.push(value);
+    }
+    Ok(values)
+}
+
+/// Reads a 32-byte digest (a program hash or a storage root); returns an error if the digest
+/// does not consist of two valid field elements.
+fn read_digest<R: ByteReader>(source: &mut R) -> Result<[u8; 32], DeserializationError> {
+    let digest: [u8; 32] = source.read_u8_vec(32)?.try_into().unwrap();
+    for chunk in digest.chunks(16) {
+        let chunk: [u8; 16] = chunk.try_into().unwrap();
+        if u128::from_le_bytes(chunk) >= BaseElement::MODULUS {
+            return Err(DeserializationError::InvalidValue(
+                "digest is not a valid field element".to_string(),
+            ));
+        }
+    }
+    Ok(digest)
+}